@@ -0,0 +1,99 @@
+//! Zero-copy reads of uncompressed EVTS packets.
+
+use crate::{flatbuffer, Error};
+
+pub const EVENT_PACKET_IDENTIFIER: &[u8; 4] = b"EVTS";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    /// Timestamp in microseconds.
+    pub t: i64,
+    pub x: i16,
+    pub y: i16,
+    pub on: bool,
+}
+
+/// Iterates over the events of a packet without copying the buffer.
+#[derive(Debug, Clone)]
+pub struct Events<'a> {
+    chunks: core::slice::ChunksExact<'a, u8>,
+}
+
+impl Iterator for Events<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.chunks.next()?;
+        Some(Event {
+            t: i64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            x: i16::from_le_bytes(bytes[8..10].try_into().unwrap()),
+            y: i16::from_le_bytes(bytes[10..12].try_into().unwrap()),
+            on: bytes[12] != 0,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Events<'_> {}
+
+/// Reads a decompressed, size-prefixed EVTS packet.
+pub fn parse_events(buffer: &[u8]) -> Result<Events<'_>, Error> {
+    flatbuffer::expect_identifier(buffer, EVENT_PACKET_IDENTIFIER)?;
+    let root = flatbuffer::root(buffer)?;
+    let elements = match flatbuffer::field(buffer, root, 0)? {
+        Some(position) => flatbuffer::vector(buffer, position, 16)?,
+        None => &[],
+    };
+    Ok(Events {
+        chunks: elements.chunks_exact(16),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framing::{Frame, Framer};
+    use aedat::testing::Fixture;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn encoded_packets() {
+        let fixture = Fixture::new().triggers(true);
+        let mut framer = Framer::new();
+        framer.push(&fixture.to_bytes().unwrap());
+        let mut events = Vec::new();
+        while let Some(frame) = framer.next_frame() {
+            match frame.unwrap() {
+                Frame::Packet {
+                    stream_id: 0,
+                    buffer,
+                } => events.extend(parse_events(&buffer).unwrap()),
+                Frame::Packet { buffer, .. } => assert!(matches!(
+                    parse_events(&buffer),
+                    Err(Error::IdentifierMismatch { .. })
+                )),
+                Frame::IoHeader(_) => {}
+            }
+        }
+        let expected = fixture.events();
+        assert_eq!(events.len(), expected.len());
+        for (event, expected) in events.iter().zip(expected.iter()) {
+            assert_eq!(
+                (event.t, event.x, event.y, event.on),
+                (expected.t(), expected.x(), expected.y(), expected.on())
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_packet() {
+        let packet = Fixture::new().packets().remove(0);
+        assert_eq!(parse_events(&packet.buffer).unwrap().len(), 100);
+        for length in 0..packet.buffer.len() {
+            assert!(parse_events(&packet.buffer[..length]).is_err());
+        }
+    }
+}
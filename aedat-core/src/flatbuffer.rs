@@ -0,0 +1,217 @@
+//! Bounds-checked reads of size-prefixed flatbuffers.
+//!
+//! Positions are byte offsets in the buffer, including the 4-byte size prefix. Every read checks its bounds,
+//! hence hostile buffers produce errors rather than panics.
+
+use crate::Error;
+
+fn bytes<const N: usize>(buffer: &[u8], position: usize) -> Result<[u8; N], Error> {
+    match buffer.get(position..position.saturating_add(N)) {
+        Some(bytes) => Ok(bytes.try_into().unwrap()),
+        None => Err(Error::OutOfBounds {
+            position,
+            length: buffer.len(),
+        }),
+    }
+}
+
+pub fn read_u16(buffer: &[u8], position: usize) -> Result<u16, Error> {
+    bytes(buffer, position).map(u16::from_le_bytes)
+}
+
+pub fn read_u32(buffer: &[u8], position: usize) -> Result<u32, Error> {
+    bytes(buffer, position).map(u32::from_le_bytes)
+}
+
+pub fn read_i32(buffer: &[u8], position: usize) -> Result<i32, Error> {
+    bytes(buffer, position).map(i32::from_le_bytes)
+}
+
+pub fn read_i64(buffer: &[u8], position: usize) -> Result<i64, Error> {
+    bytes(buffer, position).map(i64::from_le_bytes)
+}
+
+/// Follows the unsigned offset stored at `position`.
+pub fn follow(buffer: &[u8], position: usize) -> Result<usize, Error> {
+    position
+        .checked_add(read_u32(buffer, position)? as usize)
+        .ok_or(Error::OutOfBounds {
+            position,
+            length: buffer.len(),
+        })
+}
+
+/// Returns the file identifier (bytes 8 to 12), or None if the buffer is too short.
+pub fn identifier(buffer: &[u8]) -> Option<[u8; 4]> {
+    buffer.get(8..12).map(|bytes| bytes.try_into().unwrap())
+}
+
+pub fn expect_identifier(buffer: &[u8], expected: &[u8; 4]) -> Result<(), Error> {
+    match identifier(buffer) {
+        Some(got) if got == *expected => Ok(()),
+        got => Err(Error::IdentifierMismatch {
+            expected: *expected,
+            got: got.unwrap_or([0; 4]),
+        }),
+    }
+}
+
+/// Returns the position of the root table.
+pub fn root(buffer: &[u8]) -> Result<usize, Error> {
+    follow(buffer, 4)
+}
+
+/// Returns the position of a table field, or None if the field is absent.
+pub fn field(buffer: &[u8], table: usize, field: usize) -> Result<Option<usize>, Error> {
+    let out_of_bounds = Error::OutOfBounds {
+        position: table,
+        length: buffer.len(),
+    };
+    let vtable = usize::try_from(table as i64 - read_i32(buffer, table)? as i64)
+        .map_err(|_| out_of_bounds.clone())?;
+    let vtable_length = read_u16(buffer, vtable)? as usize;
+    let entry = 4 + 2 * field;
+    if entry + 2 > vtable_length {
+        return Ok(None);
+    }
+    match read_u16(buffer, vtable.saturating_add(entry))? {
+        0 => Ok(None),
+        offset => table
+            .checked_add(offset as usize)
+            .map(Some)
+            .ok_or(out_of_bounds),
+    }
+}
+
+/// Follows the vector offset stored at `position` and returns the elements, which must fit in the buffer.
+pub fn vector(buffer: &[u8], position: usize, element_size: usize) -> Result<&[u8], Error> {
+    let vector = follow(buffer, position)?;
+    let length = read_u32(buffer, vector)? as usize;
+    let begin = vector + 4;
+    let end = length
+        .checked_mul(element_size)
+        .and_then(|size| begin.checked_add(size))
+        .ok_or(Error::OutOfBounds {
+            position: vector,
+            length: buffer.len(),
+        })?;
+    buffer.get(begin..end).ok_or(Error::OutOfBounds {
+        position: end,
+        length: buffer.len(),
+    })
+}
+
+/// Follows the string offset stored at `position`.
+pub fn string(buffer: &[u8], position: usize) -> Result<&str, Error> {
+    core::str::from_utf8(vector(buffer, position, 1)?).map_err(|_| Error::InvalidUtf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Size-prefixed `TEST` table whose first field is a vector of bytes.
+    fn table(vector_length: u32, elements: &[u8]) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(&0u32.to_le_bytes());
+        // the root table is at 18
+        buffer.extend_from_slice(&14u32.to_le_bytes());
+        buffer.extend_from_slice(b"TEST");
+        // vtable at 12: vtable length, table length, offset of field 0
+        for value in [6u16, 8, 4] {
+            buffer.extend_from_slice(&value.to_le_bytes());
+        }
+        buffer.extend_from_slice(&6i32.to_le_bytes());
+        // field 0 at 22, the vector is at 26
+        buffer.extend_from_slice(&4u32.to_le_bytes());
+        buffer.extend_from_slice(&vector_length.to_le_bytes());
+        buffer.extend_from_slice(elements);
+        let size = buffer.len() as u32 - 4;
+        buffer[0..4].copy_from_slice(&size.to_le_bytes());
+        buffer
+    }
+
+    fn text(buffer: &[u8]) -> Result<&str, Error> {
+        let root = root(buffer)?;
+        let position = field(buffer, root, 0)?.ok_or(Error::EmptyDescription)?;
+        string(buffer, position)
+    }
+
+    #[test]
+    fn valid_table() {
+        let buffer = table(3, b"abc");
+        assert_eq!(root(&buffer), Ok(18));
+        assert_eq!(field(&buffer, 18, 0), Ok(Some(22)));
+        assert_eq!(field(&buffer, 18, 1), Ok(None));
+        assert_eq!(text(&buffer), Ok("abc"));
+        assert_eq!(identifier(&buffer), Some(*b"TEST"));
+        assert_eq!(expect_identifier(&buffer, b"TEST"), Ok(()));
+    }
+
+    #[test]
+    fn truncated_buffers() {
+        let buffer = table(3, b"abc");
+        for length in 0..buffer.len() {
+            assert!(matches!(
+                text(&buffer[..length]),
+                Err(Error::OutOfBounds { .. })
+            ));
+        }
+        assert_eq!(identifier(&buffer[..11]), None);
+        assert_eq!(
+            expect_identifier(&buffer[..11], b"TEST"),
+            Err(Error::IdentifierMismatch {
+                expected: *b"TEST",
+                got: [0; 4]
+            })
+        );
+    }
+
+    #[test]
+    fn out_of_range_offsets() {
+        let mut buffer = table(3, b"abc");
+        buffer[4..8].copy_from_slice(&1000u32.to_le_bytes());
+        assert_eq!(
+            text(&buffer),
+            Err(Error::OutOfBounds {
+                position: 1004,
+                length: buffer.len()
+            })
+        );
+        let mut buffer = table(3, b"abc");
+        // the vtable would be before the start of the buffer
+        buffer[18..22].copy_from_slice(&100i32.to_le_bytes());
+        assert_eq!(
+            text(&buffer),
+            Err(Error::OutOfBounds {
+                position: 18,
+                length: buffer.len()
+            })
+        );
+        let mut buffer = table(3, b"abc");
+        buffer[22..26].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(text(&buffer), Err(Error::OutOfBounds { .. })));
+        assert!(read_u32(&buffer, usize::MAX - 1).is_err());
+    }
+
+    #[test]
+    fn vector_lengths() {
+        let buffer = table(1000, b"abc");
+        assert_eq!(
+            text(&buffer),
+            Err(Error::OutOfBounds {
+                position: 1030,
+                length: buffer.len()
+            })
+        );
+        let buffer = table(u32::MAX, b"abc");
+        assert!(matches!(
+            vector(&buffer, 22, 16),
+            Err(Error::OutOfBounds { .. })
+        ));
+        assert_eq!(vector(&buffer, 22, 0), Ok(&[][..]));
+        let buffer = table(2, &[0xff, 0xfe]);
+        assert_eq!(text(&buffer), Err(Error::InvalidUtf8));
+    }
+}
@@ -0,0 +1,373 @@
+//! Splits an AEDAT4 byte stream into the IO header and packets.
+//!
+//! `Framer` is push-based: the caller feeds whatever bytes the transport delivered (socket reads, DMA buffers...)
+//! and pulls complete frames, hence it needs no `Read` trait or blocking IO.
+
+use crate::{flatbuffer, Error, DEFAULT_MAX_PACKET_SIZE, MAGIC_NUMBER};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Compression algorithm declared by the IO header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Lz4,
+    Lz4High,
+    Zstd,
+    ZstdHigh,
+}
+
+impl TryFrom<i32> for Compression {
+    type Error = Error;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Lz4),
+            2 => Ok(Compression::Lz4High),
+            3 => Ok(Compression::Zstd),
+            4 => Ok(Compression::ZstdHigh),
+            value => Err(Error::UnknownCompression(value)),
+        }
+    }
+}
+
+/// The 8 bytes that precede every packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketHeader {
+    pub stream_id: u32,
+    /// Length of the (possibly compressed) packet, excluding the header.
+    pub length: u32,
+}
+
+impl PacketHeader {
+    pub const SIZE: usize = 8;
+
+    pub fn from_bytes(bytes: &[u8; 8]) -> Self {
+        PacketHeader {
+            stream_id: u32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+            length: u32::from_le_bytes(bytes[4..8].try_into().unwrap()),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let mut bytes = [0; 8];
+        bytes[0..4].copy_from_slice(&self.stream_id.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.length.to_le_bytes());
+        bytes
+    }
+}
+
+/// Stream-level information sent after the magic number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IoHeader {
+    pub compression: Compression,
+    /// Byte offset of the file data table, or -1 (network streams and unfinished files).
+    pub file_data_position: i64,
+    /// XML description of the streams.
+    pub description: String,
+}
+
+impl IoHeader {
+    /// Parses a size-prefixed IOHE flatbuffer.
+    pub fn parse(buffer: &[u8]) -> Result<Self, Error> {
+        let root = flatbuffer::root(buffer)?;
+        let compression = match flatbuffer::field(buffer, root, 0)? {
+            Some(position) => Compression::try_from(flatbuffer::read_i32(buffer, position)?)?,
+            None => Compression::None,
+        };
+        let file_data_position = match flatbuffer::field(buffer, root, 1)? {
+            Some(position) => flatbuffer::read_i64(buffer, position)?,
+            None => -1,
+        };
+        let description = match flatbuffer::field(buffer, root, 2)? {
+            Some(position) => flatbuffer::string(buffer, position)?.to_string(),
+            None => return Err(Error::EmptyDescription),
+        };
+        Ok(IoHeader {
+            compression,
+            file_data_position,
+            description,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    IoHeader(IoHeader),
+    /// A packet as sent, which must be decompressed if the IO header declares a compression algorithm.
+    Packet {
+        stream_id: u32,
+        buffer: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    MagicNumber,
+    IoHeader,
+    Packets,
+    /// The file data table of a file has been reached.
+    End,
+    Failed,
+}
+
+/// See the module documentation.
+pub struct Framer {
+    buffer: Vec<u8>,
+    /// Bytes before `begin` have been consumed.
+    begin: usize,
+    /// Bytes consumed since the beginning of the stream.
+    position: u64,
+    /// Position of the file data table, which ends the packets of complete files.
+    file_data_position: Option<u64>,
+    state: State,
+    max_packet_size: u32,
+}
+
+impl Default for Framer {
+    fn default() -> Self {
+        Framer::new()
+    }
+}
+
+impl Framer {
+    /// Creates a framer for a complete stream, starting with the magic number.
+    pub fn new() -> Self {
+        Framer {
+            buffer: Vec::new(),
+            begin: 0,
+            position: 0,
+            file_data_position: None,
+            state: State::MagicNumber,
+            max_packet_size: DEFAULT_MAX_PACKET_SIZE,
+        }
+    }
+
+    /// Creates a framer for a stream whose magic number and IO header have already been consumed.
+    pub fn packets_only() -> Self {
+        Framer {
+            state: State::Packets,
+            ..Framer::new()
+        }
+    }
+
+    /// Largest accepted header or packet length (256 MiB by default).
+    ///
+    /// Devices with little memory should lower the limit, since the framer buffers whole packets.
+    pub fn with_max_packet_size(mut self, max_packet_size: u32) -> Self {
+        self.max_packet_size = max_packet_size;
+        self
+    }
+
+    /// Appends received bytes.
+    pub fn push(&mut self, bytes: &[u8]) {
+        if self.begin > 0 && self.begin >= self.buffer.len() / 2 {
+            self.buffer.drain(..self.begin);
+            self.begin = 0;
+        }
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Number of received bytes that do not belong to a returned frame yet.
+    pub fn pending(&self) -> usize {
+        self.buffer.len() - self.begin
+    }
+
+    /// Returns the next complete frame, or None if more bytes are needed.
+    ///
+    /// Framers created with `new` stop before the file data table of complete files.
+    /// Errors are fatal: the framer returns None afterwards.
+    pub fn next_frame(&mut self) -> Option<Result<Frame, Error>> {
+        let result = self.read_frame();
+        if let Some(Err(_)) = result {
+            self.state = State::Failed;
+        }
+        result
+    }
+
+    fn read_frame(&mut self) -> Option<Result<Frame, Error>> {
+        loop {
+            let available = &self.buffer[self.begin..];
+            match self.state {
+                State::MagicNumber => {
+                    if available.len() < MAGIC_NUMBER.len() {
+                        return None;
+                    }
+                    if &available[..MAGIC_NUMBER.len()] != MAGIC_NUMBER {
+                        return Some(Err(Error::BadMagic));
+                    }
+                    self.consume(MAGIC_NUMBER.len());
+                    self.state = State::IoHeader;
+                }
+                State::IoHeader => {
+                    let length = match self.length(available, 0)? {
+                        Ok(length) => length,
+                        Err(error) => return Some(Err(error)),
+                    };
+                    let end = 4 + length as usize;
+                    if available.len() < end {
+                        return None;
+                    }
+                    let result = IoHeader::parse(&available[..end]);
+                    self.consume(end);
+                    self.state = State::Packets;
+                    if let Ok(header) = result.as_ref() {
+                        self.file_data_position = u64::try_from(header.file_data_position).ok();
+                    }
+                    return Some(result.map(Frame::IoHeader));
+                }
+                State::Packets => {
+                    if Some(self.position) == self.file_data_position {
+                        self.state = State::End;
+                        continue;
+                    }
+                    let length = match self.length(available, 4)? {
+                        Ok(length) => length,
+                        Err(error) => return Some(Err(error)),
+                    };
+                    let end = PacketHeader::SIZE + length as usize;
+                    if available.len() < end {
+                        return None;
+                    }
+                    let header = PacketHeader::from_bytes(available[..8].try_into().unwrap());
+                    let buffer = available[PacketHeader::SIZE..end].to_vec();
+                    self.consume(end);
+                    return Some(Ok(Frame::Packet {
+                        stream_id: header.stream_id,
+                        buffer,
+                    }));
+                }
+                State::End | State::Failed => return None,
+            }
+        }
+    }
+
+    fn consume(&mut self, length: usize) {
+        self.begin += length;
+        self.position += length as u64;
+    }
+
+    /// Reads the little-endian length at `position`, or returns None if it has not been received yet.
+    fn length(&self, available: &[u8], position: usize) -> Option<Result<u32, Error>> {
+        let length = u32::from_le_bytes(available.get(position..position + 4)?.try_into().unwrap());
+        Some(if length > self.max_packet_size {
+            Err(Error::PacketTooLarge {
+                size: length,
+                limit: self.max_packet_size,
+            })
+        } else {
+            Ok(length)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aedat::testing::Fixture;
+
+    fn frames(framer: &mut Framer) -> Vec<Frame> {
+        let mut frames = Vec::new();
+        while let Some(frame) = framer.next_frame() {
+            frames.push(frame.unwrap());
+        }
+        frames
+    }
+
+    #[test]
+    fn complete_stream() {
+        let fixture = Fixture::new().triggers(true);
+        let mut framer = Framer::new();
+        framer.push(&fixture.to_bytes().unwrap());
+        let frames = frames(&mut framer);
+        assert!(matches!(
+            &frames[0],
+            Frame::IoHeader(IoHeader {
+                compression: Compression::None,
+                ..
+            })
+        ));
+        let packets = fixture.packets();
+        assert_eq!(frames.len(), packets.len() + 1);
+        for (frame, packet) in frames[1..].iter().zip(packets.iter()) {
+            assert_eq!(
+                frame,
+                &Frame::Packet {
+                    stream_id: packet.stream_id,
+                    buffer: packet.buffer.clone(),
+                }
+            );
+        }
+        // the file data table is not a packet
+        assert!(framer.pending() > 0);
+        assert!(framer.next_frame().is_none());
+    }
+
+    #[test]
+    fn split_pushes() {
+        let bytes = Fixture::new().frames(true).to_bytes().unwrap();
+        let mut framer = Framer::new();
+        framer.push(&bytes);
+        let expected = frames(&mut framer);
+        for chunk_size in [1, 3, 7, 100] {
+            let mut framer = Framer::new();
+            let mut received = Vec::new();
+            for chunk in bytes.chunks(chunk_size) {
+                framer.push(chunk);
+                received.extend(frames(&mut framer));
+            }
+            assert_eq!(received, expected);
+        }
+    }
+
+    #[test]
+    fn partial_pushes() {
+        let mut framer = Framer::packets_only();
+        let header = PacketHeader {
+            stream_id: 2,
+            length: 4,
+        };
+        framer.push(&header.to_bytes()[..5]);
+        assert!(framer.next_frame().is_none());
+        framer.push(&header.to_bytes()[5..]);
+        framer.push(&[1, 2]);
+        assert!(framer.next_frame().is_none());
+        assert_eq!(framer.pending(), 10);
+        framer.push(&[3, 4, 5]);
+        assert_eq!(
+            framer.next_frame(),
+            Some(Ok(Frame::Packet {
+                stream_id: 2,
+                buffer: alloc::vec![1, 2, 3, 4],
+            }))
+        );
+        assert_eq!(framer.pending(), 1);
+        assert!(framer.next_frame().is_none());
+    }
+
+    #[test]
+    fn errors() {
+        let mut framer = Framer::new();
+        framer.push(b"#!AER-DAT3.1\r\n");
+        assert_eq!(framer.next_frame(), Some(Err(Error::BadMagic)));
+        let mut framer = Framer::packets_only().with_max_packet_size(10);
+        framer.push(
+            &PacketHeader {
+                stream_id: 0,
+                length: 11,
+            }
+            .to_bytes(),
+        );
+        assert_eq!(
+            framer.next_frame(),
+            Some(Err(Error::PacketTooLarge {
+                size: 11,
+                limit: 10
+            }))
+        );
+        // errors are fatal
+        framer.push(&[0; 32]);
+        assert!(framer.next_frame().is_none());
+    }
+}
@@ -0,0 +1,74 @@
+//! AEDAT4 packet framing and parsing without the standard library.
+//!
+//! This crate only requires `core` and `alloc`, which lets embedded devices (for instance ARM gateways that
+//! receive AEDAT4 over a socket) split a byte stream into packets and read uncompressed event packets.
+//! Decompression and IO live in the `aedat` crate, which builds on the helpers of this one.
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod events;
+pub mod flatbuffer;
+pub mod framing;
+
+pub use events::{parse_events, Event, Events};
+pub use framing::{Compression, Frame, Framer, IoHeader, PacketHeader};
+
+/// First bytes of every AEDAT4 file and stream.
+pub const MAGIC_NUMBER: &[u8] = b"#!AER-DAT4.0\r\n";
+
+/// Default limit of `Framer` on packet lengths (256 MiB), which matches the `aedat` decoder.
+pub const DEFAULT_MAX_PACKET_SIZE: u32 = 1 << 28;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The stream does not start with `MAGIC_NUMBER`.
+    BadMagic,
+    /// A header declares a length larger than the framer limit.
+    PacketTooLarge { size: u32, limit: u32 },
+    /// A flatbuffer offset points outside the buffer.
+    OutOfBounds { position: usize, length: usize },
+    /// The flatbuffer identifier differs from the expected one.
+    IdentifierMismatch { expected: [u8; 4], got: [u8; 4] },
+    /// The IO header declares an unknown compression algorithm.
+    UnknownCompression(i32),
+    /// A flatbuffer string is not valid UTF-8.
+    InvalidUtf8,
+    /// The IO header has no description.
+    EmptyDescription,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Error::BadMagic => write!(
+                formatter,
+                "the stream does not contain AEDAT4 data (wrong magic number)"
+            ),
+            Error::PacketTooLarge { size, limit } => write!(
+                formatter,
+                "the packet length ({} bytes) exceeds the limit ({} bytes)",
+                size, limit
+            ),
+            Error::OutOfBounds { position, length } => write!(
+                formatter,
+                "flatbuffer offset {} is out of bounds (buffer length {})",
+                position, length
+            ),
+            Error::IdentifierMismatch { expected, got } => write!(
+                formatter,
+                "expected a `{}` flatbuffer but got `{}`",
+                core::str::from_utf8(expected).unwrap_or("?"),
+                core::str::from_utf8(got).unwrap_or("?")
+            ),
+            Error::UnknownCompression(compression) => {
+                write!(formatter, "unknown compression algorithm `{}`", compression)
+            }
+            Error::InvalidUtf8 => write!(formatter, "a flatbuffer string is not valid UTF-8"),
+            Error::EmptyDescription => write!(formatter, "the description is empty"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,69 @@
+use aedat::base::{ioheader_generated::Compression, Decoder, DecoderBuilder};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn decode(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("decode");
+    for buffer_size in [0, 1 << 13, 1 << 16, 1 << 20] {
+        group.bench_with_input(
+            BenchmarkId::new("buffer_size", buffer_size),
+            &buffer_size,
+            |bencher, buffer_size| {
+                bencher.iter(|| {
+                    DecoderBuilder::file("test_data.aedat4")
+                        .buffer_size(*buffer_size)
+                        .build()
+                        .unwrap()
+                        .map(|packet| packet.unwrap().buffer.len())
+                        .sum::<usize>()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn compression(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("compression");
+    for compression in [
+        Compression::None,
+        Compression::Lz4,
+        Compression::Lz4High,
+        Compression::Zstd,
+        Compression::ZstdHigh,
+    ] {
+        let path = std::env::temp_dir().join(format!("aedat-bench-{:?}.aedat4", compression));
+        if aedat::bench::transcode("test_data.aedat4", &path, compression).is_err() {
+            continue;
+        }
+        group.bench_with_input(
+            BenchmarkId::new("file", format!("{:?}", compression)),
+            &path,
+            |bencher, path| {
+                bencher.iter(|| {
+                    Decoder::new_from_file(path)
+                        .unwrap()
+                        .map(|packet| packet.unwrap().buffer.len())
+                        .sum::<usize>()
+                })
+            },
+        );
+        let bytes = std::fs::read(&path).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("memory", format!("{:?}", compression)),
+            &bytes,
+            |bencher, bytes| {
+                bencher.iter(|| {
+                    Decoder::from_bytes(bytes)
+                        .unwrap()
+                        .map(|packet| packet.unwrap().buffer.len())
+                        .sum::<usize>()
+                })
+            },
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decode, compression);
+criterion_main!(benches);
@@ -0,0 +1,122 @@
+#ifndef AEDAT_H
+#define AEDAT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum AedatStreamContent {
+  AEDAT_STREAM_CONTENT_EVENTS = 0,
+  AEDAT_STREAM_CONTENT_FRAME = 1,
+  AEDAT_STREAM_CONTENT_IMUS = 2,
+  AEDAT_STREAM_CONTENT_TRIGGERS = 3,
+  // Stream type unknown to this library.
+  AEDAT_STREAM_CONTENT_OTHER = 4,
+  AEDAT_STREAM_CONTENT_BOUNDING_BOXES = 5,
+  AEDAT_STREAM_CONTENT_POSE = 6,
+} AedatStreamContent;
+
+typedef struct AedatDecoder AedatDecoder;
+
+typedef struct AedatPacket AedatPacket;
+
+typedef struct AedatStream {
+  uint32_t id;
+  enum AedatStreamContent content;
+  uint16_t width;
+  uint16_t height;
+} AedatStream;
+
+typedef struct AedatEvent {
+  int64_t t;
+  int16_t x;
+  int16_t y;
+  bool on;
+} AedatEvent;
+
+// `pixels` points to `pixels_length` bytes, row-major, with 1 (Gray), 3 (BGR) or 4 (BGRA) channels.
+typedef struct AedatFrame {
+  int64_t t;
+  int64_t begin_t;
+  int64_t end_t;
+  int64_t exposure_begin_t;
+  int64_t exposure_end_t;
+  // 0 (Gray), 16 (BGR) or 24 (BGRA), plus 2 for 16 bits little-endian channels.
+  int8_t format;
+  int16_t width;
+  int16_t height;
+  int16_t offset_x;
+  int16_t offset_y;
+  const uint8_t *pixels;
+  size_t pixels_length;
+} AedatFrame;
+
+typedef struct AedatImu {
+  int64_t t;
+  float temperature;
+  float accelerometer_x;
+  float accelerometer_y;
+  float accelerometer_z;
+  float gyroscope_x;
+  float gyroscope_y;
+  float gyroscope_z;
+  float magnetometer_x;
+  float magnetometer_y;
+  float magnetometer_z;
+} AedatImu;
+
+typedef struct AedatTrigger {
+  int64_t t;
+  // See `TriggerSource` in the AEDAT4 specification.
+  int8_t source;
+} AedatTrigger;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns the message of the last error on this thread, or NULL.
+const char *aedat_last_error(void);
+
+// Opens an AEDAT4 file. Returns NULL on error.
+struct AedatDecoder *aedat_decoder_open(const char *path);
+
+void aedat_decoder_free(struct AedatDecoder *decoder);
+
+// Returns the streams declared in the file, sorted by id, and writes their number to `count`.
+const struct AedatStream *aedat_decoder_streams(const struct AedatDecoder *decoder, size_t *count);
+
+// Reads the next packet. Returns NULL at the end of the file, or on error (`aedat_last_error` is then not NULL).
+struct AedatPacket *aedat_next_packet(struct AedatDecoder *decoder);
+
+void aedat_packet_free(struct AedatPacket *packet);
+
+uint32_t aedat_packet_stream_id(const struct AedatPacket *packet);
+
+enum AedatStreamContent aedat_packet_content(const struct AedatPacket *packet);
+
+// Returns the decompressed packet (a size-prefixed flatbuffer) and writes its size to `size`.
+//
+// This gives access to packets from streams of unknown type (`AEDAT_STREAM_CONTENT_OTHER`).
+const uint8_t *aedat_packet_buffer(const struct AedatPacket *packet, size_t *size);
+
+// Returns the events of an event packet and writes their number to `count` (0 for other packets).
+const struct AedatEvent *aedat_packet_events(const struct AedatPacket *packet, size_t *count);
+
+// Returns the frame of a frame packet, or NULL for other packets.
+const struct AedatFrame *aedat_packet_frame(const struct AedatPacket *packet);
+
+// Returns the samples of an IMU packet and writes their number to `count` (0 for other packets).
+const struct AedatImu *aedat_packet_imus(const struct AedatPacket *packet, size_t *count);
+
+// Returns the triggers of a trigger packet and writes their number to `count` (0 for other packets).
+const struct AedatTrigger *aedat_packet_triggers(const struct AedatPacket *packet,
+                                                 size_t *count);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* AEDAT_H */
@@ -0,0 +1,174 @@
+use crate::base::ParseError;
+use crate::chunks::Chunks;
+use crate::events::Event;
+
+/// Renders events as a gray frame: ON events are white, OFF events black, and idle pixels gray.
+pub struct Accumulator {
+    width: u16,
+    height: u16,
+    pixels: Vec<u8>,
+}
+
+impl Accumulator {
+    pub fn new(width: u16, height: u16) -> Self {
+        Accumulator {
+            width,
+            height,
+            pixels: vec![128; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Draws the event. Events outside the sensor are ignored.
+    pub fn add(&mut self, event: &Event) {
+        if event.x() >= 0
+            && event.y() >= 0
+            && (event.x() as u16) < self.width
+            && (event.y() as u16) < self.height
+        {
+            self.pixels[event.y() as usize * self.width as usize + event.x() as usize] =
+                if event.on() { 255 } else { 0 };
+        }
+    }
+
+    /// Row-major gray pixels.
+    pub fn frame(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn reset(&mut self) {
+        self.pixels.iter_mut().for_each(|pixel| *pixel = 128);
+    }
+
+    /// Renders an event stream at a fixed frame period (in microseconds), starting with the first event.
+    ///
+    /// Each frame contains the events of its period only, and periods without events yield gray frames.
+    pub fn frames<I: Iterator<Item = Result<Event, ParseError>>>(
+        self,
+        events: I,
+        period: i64,
+    ) -> Frames<I> {
+        Frames {
+            accumulator: self,
+            chunks: Chunks::by_duration(events, period),
+        }
+    }
+}
+
+pub struct Frames<I> {
+    accumulator: Accumulator,
+    chunks: Chunks<I>,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Frames<I> {
+    type Item = Result<Vec<u8>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = match self.chunks.next()? {
+            Ok(chunk) => chunk,
+            Err(error) => return Some(Err(error)),
+        };
+        self.accumulator.reset();
+        for event in chunk.iter() {
+            self.accumulator.add(event);
+        }
+        Some(Ok(self.accumulator.frame().to_vec()))
+    }
+}
+
+/// `Accumulator` with a compile-time sensor size, which stores the frame in a fixed-size array.
+///
+/// The frame is inline (`WIDTH × HEIGHT` bytes), hence `Box::new` it for large sensors on small stacks.
+pub struct FixedAccumulator<const WIDTH: usize, const HEIGHT: usize> {
+    pixels: [[u8; WIDTH]; HEIGHT],
+}
+
+/// DAVIS346 (346 × 260 pixels).
+pub type Davis346Accumulator = FixedAccumulator<346, 260>;
+
+/// DVXplorer (640 × 480 pixels).
+pub type DvXplorerAccumulator = FixedAccumulator<640, 480>;
+
+impl<const WIDTH: usize, const HEIGHT: usize> Default for FixedAccumulator<WIDTH, HEIGHT> {
+    fn default() -> Self {
+        FixedAccumulator {
+            pixels: [[128; WIDTH]; HEIGHT],
+        }
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> FixedAccumulator<WIDTH, HEIGHT> {
+    pub fn new() -> Self {
+        FixedAccumulator::default()
+    }
+
+    /// Draws the event. Events outside the sensor are ignored.
+    #[inline]
+    pub fn add(&mut self, event: &Event) {
+        if let Some(pixel) = self
+            .pixels
+            .get_mut(event.y() as u16 as usize)
+            .and_then(|row| row.get_mut(event.x() as u16 as usize))
+        {
+            *pixel = if event.on() { 255 } else { 0 };
+        }
+    }
+
+    pub fn extend<'a, I: IntoIterator<Item = &'a Event>>(&mut self, events: I) {
+        for event in events {
+            self.add(event);
+        }
+    }
+
+    /// Gray pixels, indexed by row then column.
+    pub fn frame(&self) -> &[[u8; WIDTH]; HEIGHT] {
+        &self.pixels
+    }
+
+    /// Row-major gray pixels, like `Accumulator::frame`.
+    pub fn as_slice(&self) -> &[u8] {
+        self.pixels.as_flattened()
+    }
+
+    pub fn reset(&mut self) {
+        self.pixels = [[128; WIDTH]; HEIGHT];
+    }
+
+    /// Renders an event stream at a fixed frame period (in microseconds), like `Accumulator::frames`,
+    /// and passes each frame to `handle` instead of allocating it.
+    pub fn for_each_frame<I, F>(
+        &mut self,
+        events: I,
+        period: i64,
+        mut handle: F,
+    ) -> Result<(), ParseError>
+    where
+        I: Iterator<Item = Result<Event, ParseError>>,
+        F: FnMut(&[[u8; WIDTH]; HEIGHT]),
+    {
+        let period = period.max(1);
+        let mut end_t = None;
+        for event in events {
+            let event = event?;
+            let end = *end_t.get_or_insert(event.t() + period);
+            if event.t() >= end {
+                handle(&self.pixels);
+                self.reset();
+                end_t = Some(end + period);
+            }
+            self.add(&event);
+        }
+        if end_t.is_some() {
+            handle(&self.pixels);
+            self.reset();
+        }
+        Ok(())
+    }
+}
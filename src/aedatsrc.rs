@@ -0,0 +1,341 @@
+//! GStreamer source element (`aedatsrc`) that outputs the APS frames of a recording or live stream.
+//!
+//! Register the element with `aedat::aedatsrc::register(None)` before building a pipeline, for instance
+//! `aedatsrc location=recording.aedat4 ! videoconvert ! autovideosink`.
+//!
+//! The crate also defines the `aedat` plugin. Build it with
+//! `cargo rustc --release --features gstreamer --crate-type cdylib`, then add `target/release` to
+//! `GST_PLUGIN_PATH` to use the element from `gst-launch-1.0` or any other application.
+
+use gstreamer as gst;
+use gstreamer::glib;
+use gstreamer::prelude::*;
+
+glib::wrapper! {
+    pub struct AedatSrc(ObjectSubclass<imp::AedatSrc>)
+        @extends gstreamer_base::PushSrc, gstreamer_base::BaseSrc, gst::Element, gst::Object;
+}
+
+/// Registers `aedatsrc`, statically if `plugin` is None.
+pub fn register(plugin: Option<&gst::Plugin>) -> Result<(), glib::BoolError> {
+    gst::Element::register(plugin, "aedatsrc", gst::Rank::None, AedatSrc::static_type())
+}
+
+fn plugin_init(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    register(Some(plugin))
+}
+
+gst::plugin_define!(
+    aedat,
+    env!("CARGO_PKG_DESCRIPTION"),
+    plugin_init,
+    env!("CARGO_PKG_VERSION"),
+    "MIT/X11",
+    env!("CARGO_PKG_NAME"),
+    env!("CARGO_PKG_NAME"),
+    env!("CARGO_PKG_REPOSITORY")
+);
+
+mod imp {
+    use crate::base::Decoder;
+    use crate::frame_generated;
+    use gstreamer as gst;
+    use gstreamer::glib;
+    use gstreamer::prelude::*;
+    use gstreamer::subclass::prelude::*;
+    use gstreamer_base::prelude::*;
+    use gstreamer_base::subclass::base_src::CreateSuccess;
+    use gstreamer_base::subclass::prelude::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Mutex, OnceLock};
+
+    #[derive(Default)]
+    struct Settings {
+        location: Option<String>,
+        address: Option<String>,
+    }
+
+    struct State {
+        decoder: Decoder,
+        first_t: Option<i64>,
+        caps: Option<gst::Caps>,
+        /// Layout of the negotiated caps, whose rows may be padded beyond `width * channels`.
+        info: Option<gstreamer_video::VideoInfo>,
+    }
+
+    #[derive(Default)]
+    pub struct AedatSrc {
+        settings: Mutex<Settings>,
+        /// Taken by `create` while it reads, hence the lock is never held during blocking reads.
+        state: Mutex<Option<State>>,
+        /// Socket of a live stream, shut down by `unlock` to interrupt a blocking read.
+        socket: Mutex<Option<std::net::TcpStream>>,
+        flushing: AtomicBool,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AedatSrc {
+        const NAME: &'static str = "GstAedatSrc";
+        type Type = super::AedatSrc;
+        type ParentType = gstreamer_base::PushSrc;
+    }
+
+    impl ObjectImpl for AedatSrc {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: OnceLock<Vec<glib::ParamSpec>> = OnceLock::new();
+            PROPERTIES.get_or_init(|| {
+                vec![
+                    glib::ParamSpecString::builder("location")
+                        .nick("File location")
+                        .blurb("Path of the AEDAT4 file to read")
+                        .build(),
+                    glib::ParamSpecString::builder("address")
+                        .nick("Address")
+                        .blurb(
+                            "host:port of a live AEDAT4 TCP stream, used when location is not set",
+                        )
+                        .build(),
+                ]
+            })
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let mut settings = self.settings.lock().unwrap();
+            match pspec.name() {
+                "location" => settings.location = value.get().expect("type checked upstream"),
+                "address" => settings.address = value.get().expect("type checked upstream"),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let settings = self.settings.lock().unwrap();
+            match pspec.name() {
+                "location" => settings.location.to_value(),
+                "address" => settings.address.to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().set_format(gst::Format::Time);
+        }
+    }
+
+    impl GstObjectImpl for AedatSrc {}
+
+    impl ElementImpl for AedatSrc {
+        fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+            static METADATA: OnceLock<gst::subclass::ElementMetadata> = OnceLock::new();
+            Some(METADATA.get_or_init(|| {
+                gst::subclass::ElementMetadata::new(
+                    "AEDAT4 frame source",
+                    "Source/Video",
+                    "Reads the APS frames of an AEDAT4 file or live stream",
+                    "International Centre for Neuromorphic Systems, Andrew C. Freeman",
+                )
+            }))
+        }
+
+        fn pad_templates() -> &'static [gst::PadTemplate] {
+            static PAD_TEMPLATES: OnceLock<Vec<gst::PadTemplate>> = OnceLock::new();
+            PAD_TEMPLATES.get_or_init(|| {
+                let caps = gstreamer_video::VideoCapsBuilder::new()
+                    .format_list([
+                        gstreamer_video::VideoFormat::Gray8,
+                        gstreamer_video::VideoFormat::Bgr,
+                        gstreamer_video::VideoFormat::Bgra,
+                    ])
+                    .build();
+                vec![gst::PadTemplate::new(
+                    "src",
+                    gst::PadDirection::Src,
+                    gst::PadPresence::Always,
+                    &caps,
+                )
+                .unwrap()]
+            })
+        }
+    }
+
+    impl BaseSrcImpl for AedatSrc {
+        fn start(&self) -> Result<(), gst::ErrorMessage> {
+            let settings = self.settings.lock().unwrap();
+            let decoder = match (&settings.location, &settings.address) {
+                (Some(location), _) => Decoder::new_from_file(location).map_err(|error| {
+                    gst::error_msg!(
+                        gst::ResourceError::OpenRead,
+                        ["Could not open {}: {}", location, error]
+                    )
+                })?,
+                (None, Some(address)) => {
+                    self.obj().set_live(true);
+                    let error = |error: &dyn std::fmt::Display| {
+                        gst::error_msg!(
+                            gst::ResourceError::OpenRead,
+                            ["Could not connect to {}: {}", address, error]
+                        )
+                    };
+                    let stream = std::net::TcpStream::connect(address.as_str())
+                        .map_err(|content| error(&content))?;
+                    *self.socket.lock().unwrap() =
+                        Some(stream.try_clone().map_err(|content| error(&content))?);
+                    Decoder::new_from_source(
+                        Box::new(std::io::BufReader::new(stream)),
+                        false,
+                        false,
+                    )
+                    .map_err(|content| error(&content))?
+                }
+                (None, None) => {
+                    return Err(gst::error_msg!(
+                        gst::ResourceError::Settings,
+                        ["Neither location nor address is set"]
+                    ))
+                }
+            };
+            *self.state.lock().unwrap() = Some(State {
+                decoder,
+                first_t: None,
+                caps: None,
+                info: None,
+            });
+            Ok(())
+        }
+
+        fn stop(&self) -> Result<(), gst::ErrorMessage> {
+            *self.state.lock().unwrap() = None;
+            *self.socket.lock().unwrap() = None;
+            Ok(())
+        }
+
+        /// Interrupts `create`. Live streams cannot resume after an interruption, their socket is closed.
+        fn unlock(&self) -> Result<(), gst::ErrorMessage> {
+            self.flushing.store(true, Ordering::Release);
+            if let Some(socket) = self.socket.lock().unwrap().as_ref() {
+                let _ = socket.shutdown(std::net::Shutdown::Both);
+            }
+            Ok(())
+        }
+
+        fn unlock_stop(&self) -> Result<(), gst::ErrorMessage> {
+            self.flushing.store(false, Ordering::Release);
+            Ok(())
+        }
+
+        fn is_seekable(&self) -> bool {
+            false
+        }
+    }
+
+    impl PushSrcImpl for AedatSrc {
+        fn create(
+            &self,
+            _buffer: Option<&mut gst::BufferRef>,
+        ) -> Result<CreateSuccess, gst::FlowError> {
+            let mut state = self
+                .state
+                .lock()
+                .unwrap()
+                .take()
+                .ok_or(gst::FlowError::Flushing)?;
+            let result = self.next_frame(&mut state);
+            *self.state.lock().unwrap() = Some(state);
+            result
+        }
+    }
+
+    impl AedatSrc {
+        fn next_frame(&self, state: &mut State) -> Result<CreateSuccess, gst::FlowError> {
+            loop {
+                if self.flushing.load(Ordering::Acquire) {
+                    return Err(gst::FlowError::Flushing);
+                }
+                let packet = match state.decoder.next() {
+                    Some(Ok(packet)) => packet,
+                    // the socket was shut down by unlock
+                    Some(Err(_)) | None if self.flushing.load(Ordering::Acquire) => {
+                        return Err(gst::FlowError::Flushing)
+                    }
+                    Some(Err(error)) => {
+                        gst::element_imp_error!(
+                            self,
+                            gst::StreamError::Decode,
+                            ["Could not decode packet: {}", error]
+                        );
+                        return Err(gst::FlowError::Error);
+                    }
+                    None => return Err(gst::FlowError::Eos),
+                };
+                if packet
+                    .expect_identifier(frame_generated::FRAME_IDENTIFIER)
+                    .is_err()
+                {
+                    continue;
+                }
+                let frame = frame_generated::size_prefixed_root_as_frame(&packet.buffer)
+                    .map_err(|_| gst::FlowError::Error)?;
+                let format = match frame.format() {
+                    frame_generated::FrameFormat::Gray => gstreamer_video::VideoFormat::Gray8,
+                    frame_generated::FrameFormat::Bgr => gstreamer_video::VideoFormat::Bgr,
+                    frame_generated::FrameFormat::Bgra => gstreamer_video::VideoFormat::Bgra,
+                    _ => return Err(gst::FlowError::NotSupported),
+                };
+                let caps = gstreamer_video::VideoCapsBuilder::new()
+                    .format(format)
+                    .width(frame.width() as i32)
+                    .height(frame.height() as i32)
+                    .framerate(gst::Fraction::new(0, 1))
+                    .build();
+                if state.caps.as_ref() != Some(&caps) {
+                    let info = gstreamer_video::VideoInfo::from_caps(&caps)
+                        .map_err(|_| gst::FlowError::NotNegotiated)?;
+                    self.obj()
+                        .set_caps(&caps)
+                        .map_err(|_| gst::FlowError::NotNegotiated)?;
+                    state.caps = Some(caps);
+                    state.info = Some(info);
+                }
+                let info = state.info.as_ref().ok_or(gst::FlowError::NotNegotiated)?;
+                let pixels = frame.pixels().unwrap_or(&[]);
+                let row = info.width() as usize * info.format_info().pixel_stride()[0] as usize;
+                let stride = info.stride()[0] as usize;
+                if pixels.len() != row * info.height() as usize {
+                    gst::element_imp_error!(
+                        self,
+                        gst::StreamError::Decode,
+                        [
+                            "The frame has {} bytes instead of {}",
+                            pixels.len(),
+                            row * info.height() as usize
+                        ]
+                    );
+                    return Err(gst::FlowError::Error);
+                }
+                // GStreamer rounds strides up to 4 bytes (348 for a 346 pixels wide Gray8 frame)
+                let mut data = vec![0u8; info.size()];
+                if row > 0 {
+                    for (source, target) in pixels
+                        .chunks_exact(row)
+                        .zip(data[info.offset()[0]..].chunks_mut(stride))
+                    {
+                        target[..row].copy_from_slice(source);
+                    }
+                }
+                let first_t = *state.first_t.get_or_insert(frame.t());
+                let mut buffer = gst::Buffer::from_mut_slice(data);
+                {
+                    let buffer = buffer.get_mut().unwrap();
+                    buffer.set_pts(gst::ClockTime::from_useconds(
+                        (frame.t() - first_t).max(0) as u64
+                    ));
+                    buffer.set_duration(gst::ClockTime::from_useconds(
+                        (frame.end_t() - frame.begin_t()).max(0) as u64,
+                    ));
+                }
+                return Ok(CreateSuccess::NewBuffer(buffer));
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+//! Event-based vision algorithms built on the decoder.
+
+pub mod contrast_maximization;
+#[cfg(feature = "ndarray")]
+pub mod reconstruction;
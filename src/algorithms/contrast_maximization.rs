@@ -0,0 +1,185 @@
+//! Contrast maximization (CMax) for motion estimation.
+//!
+//! Events are warped to a reference time with a motion model, and accumulated (with bilinear voting) in an
+//! image of warped events (IWE). The motion parameters that best explain the events produce the sharpest IWE,
+//! measured by its variance. `ContrastMaximization::contrast_and_gradient` computes the variance and its
+//! analytical gradient, which can be fed to any optimizer.
+//!
+//! Lens distortion is not modelled, hence events should be undistorted first (see `calibration`).
+
+use crate::events::Event;
+
+/// Motion model used to warp events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WarpModel {
+    /// Constant optic flow, with parameters `[vx, vy]` in pixels per second.
+    OpticFlow,
+    /// Pure camera rotation, with parameters `[wx, wy, wz]` (angular velocity in radians per second),
+    /// given the focal lengths and principal point of the camera in pixels.
+    Rotation { fx: f64, fy: f64, cx: f64, cy: f64 },
+}
+
+impl WarpModel {
+    pub fn parameters(&self) -> usize {
+        match self {
+            WarpModel::OpticFlow => 2,
+            WarpModel::Rotation { .. } => 3,
+        }
+    }
+
+    /// Position of an event moved back by `dt` seconds, and its derivatives with respect to the parameters
+    /// (`dx[i]` and `dy[i]` are the derivatives of x and y with respect to `parameters[i]`).
+    fn warp(&self, x: f64, y: f64, dt: f64, parameters: &[f64]) -> (f64, f64, [f64; 3], [f64; 3]) {
+        match *self {
+            WarpModel::OpticFlow => (
+                x - parameters[0] * dt,
+                y - parameters[1] * dt,
+                [-dt, 0.0, 0.0],
+                [0.0, -dt, 0.0],
+            ),
+            WarpModel::Rotation { fx, fy, cx, cy } => {
+                let (wx, wy, wz) = (parameters[0], parameters[1], parameters[2]);
+                let (xn, yn) = ((x - cx) / fx, (y - cy) / fy);
+                // first-order rotation of the ray (xn, yn, 1) by -w dt, then projection
+                let a = xn - dt * (wy - wz * yn);
+                let b = yn - dt * (wz * xn - wx);
+                let c = 1.0 - dt * (wx * yn - wy * xn);
+                let da = [0.0, -dt, dt * yn];
+                let db = [dt, 0.0, -dt * xn];
+                let dc = [-dt * yn, dt * xn, 0.0];
+                let mut dx = [0.0; 3];
+                let mut dy = [0.0; 3];
+                for index in 0..3 {
+                    dx[index] = fx * (da[index] * c - a * dc[index]) / (c * c);
+                    dy[index] = fy * (db[index] * c - b * dc[index]) / (c * c);
+                }
+                (fx * a / c + cx, fy * b / c + cy, dx, dy)
+            }
+        }
+    }
+}
+
+/// Computes images of warped events and their contrast for a sensor (see the module documentation).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastMaximization {
+    pub width: u16,
+    pub height: u16,
+    pub model: WarpModel,
+}
+
+impl ContrastMaximization {
+    pub fn new(width: u16, height: u16, model: WarpModel) -> Self {
+        ContrastMaximization {
+            width,
+            height,
+            model,
+        }
+    }
+
+    /// Calls `vote` with the pixel index, the bilinear weight and its derivatives along x and y,
+    /// for the four pixels around each warped event. Events are warped to the timestamp of the first event.
+    fn votes(
+        &self,
+        events: &[Event],
+        parameters: &[f64],
+        mut vote: impl FnMut(usize, f64, f64, f64, &[f64; 3], &[f64; 3]),
+    ) {
+        assert_eq!(
+            parameters.len(),
+            self.model.parameters(),
+            "wrong number of warp parameters"
+        );
+        let reference_t = match events.first() {
+            Some(event) => event.t(),
+            None => return,
+        };
+        let (width, height) = (self.width as i64, self.height as i64);
+        for event in events {
+            let dt = (event.t() - reference_t) as f64 * 1e-6;
+            let (x, y, dx, dy) =
+                self.model
+                    .warp(event.x() as f64, event.y() as f64, dt, parameters);
+            let (left, top) = (x.floor(), y.floor());
+            let (horizontal, vertical) = (x - left, y - top);
+            for (pixel_x, weight_x, slope_x) in [
+                (left as i64, 1.0 - horizontal, -1.0),
+                (left as i64 + 1, horizontal, 1.0),
+            ] {
+                if pixel_x < 0 || pixel_x >= width {
+                    continue;
+                }
+                for (pixel_y, weight_y, slope_y) in [
+                    (top as i64, 1.0 - vertical, -1.0),
+                    (top as i64 + 1, vertical, 1.0),
+                ] {
+                    if pixel_y < 0 || pixel_y >= height {
+                        continue;
+                    }
+                    vote(
+                        (pixel_y * width + pixel_x) as usize,
+                        weight_x * weight_y,
+                        slope_x * weight_y,
+                        weight_x * slope_y,
+                        &dx,
+                        &dy,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Image of warped events (row-major, `width` × `height`), in events per pixel.
+    pub fn image_of_warped_events(&self, events: &[Event], parameters: &[f64]) -> Vec<f64> {
+        let mut image = vec![0.0; self.width as usize * self.height as usize];
+        self.votes(events, parameters, |index, weight, _, _, _, _| {
+            image[index] += weight
+        });
+        image
+    }
+
+    /// Variance of the image of warped events.
+    pub fn contrast(&self, events: &[Event], parameters: &[f64]) -> f64 {
+        variance(&self.image_of_warped_events(events, parameters))
+    }
+
+    /// Variance of the image of warped events, and its gradient with respect to the parameters.
+    pub fn contrast_and_gradient(&self, events: &[Event], parameters: &[f64]) -> (f64, Vec<f64>) {
+        let image = self.image_of_warped_events(events, parameters);
+        if image.is_empty() {
+            return (0.0, vec![0.0; parameters.len()]);
+        }
+        let mean = image.iter().sum::<f64>() / image.len() as f64;
+        let mut gradient = [0.0; 3];
+        self.votes(
+            events,
+            parameters,
+            |index, _, weight_dx, weight_dy, dx, dy| {
+                let deviation = image[index] - mean;
+                for parameter in 0..3 {
+                    gradient[parameter] +=
+                        deviation * (weight_dx * dx[parameter] + weight_dy * dy[parameter]);
+                }
+            },
+        );
+        let scale = 2.0 / image.len() as f64;
+        (
+            variance(&image),
+            gradient[..parameters.len()]
+                .iter()
+                .map(|value| value * scale)
+                .collect(),
+        )
+    }
+}
+
+fn variance(image: &[f64]) -> f64 {
+    if image.is_empty() {
+        return 0.0;
+    }
+    let mean = image.iter().sum::<f64>() / image.len() as f64;
+    image
+        .iter()
+        .map(|value| (value - mean) * (value - mean))
+        .sum::<f64>()
+        / image.len() as f64
+}
@@ -0,0 +1,235 @@
+//! Intensity reconstruction with E2VID and FireNet models.
+//!
+//! `E2vidPreprocessor` reproduces the preprocessing of the reference implementations: a voxel grid with a
+//! fixed number of bins, normalized per grid (the non-zero cells are shifted and scaled to zero mean and unit
+//! variance), then padded or cropped to a multiple of 8 pixels (the encoder halves the resolution three times).
+//! With the `ort` feature, `Reconstructor` runs an ONNX export of the model and returns the frames.
+
+use crate::base::ParseError;
+use crate::chunks::{Chunks, Window};
+use crate::events::Event;
+use crate::representations::VoxelGrid;
+
+/// How the voxel grid is brought to a multiple of 8 pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadMode {
+    /// Adds zeros around the grid (centered), as the reference implementations do.
+    Pad,
+    /// Removes the pixels beyond the largest multiple of 8 (centered).
+    Crop,
+}
+
+/// Converts event windows into model inputs (see the module documentation).
+pub struct E2vidPreprocessor {
+    grid: VoxelGrid,
+    width: u16,
+    height: u16,
+    mode: PadMode,
+}
+
+impl E2vidPreprocessor {
+    /// The reference models use 5 bins.
+    pub fn new(bins: usize, width: u16, height: u16) -> Self {
+        E2vidPreprocessor {
+            grid: VoxelGrid::new(bins, width, height),
+            width,
+            height,
+            mode: PadMode::Pad,
+        }
+    }
+
+    pub fn mode(mut self, mode: PadMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Width and height of the model input.
+    pub fn input_size(&self) -> (usize, usize) {
+        let size = |length: u16| match self.mode {
+            PadMode::Pad => (length as usize).div_ceil(8) * 8,
+            PadMode::Crop => length as usize / 8 * 8,
+        };
+        (size(self.width), size(self.height))
+    }
+
+    /// Position of the sensor origin in the model input (negative when cropping).
+    pub fn offset(&self) -> (isize, isize) {
+        let (width, height) = self.input_size();
+        (
+            (width as isize - self.width as isize) / 2,
+            (height as isize - self.height as isize) / 2,
+        )
+    }
+
+    /// Computes a normalized (1, bins, height, width) input tensor, using the first and last event timestamps
+    /// as the window.
+    pub fn compute(&self, events: &[Event]) -> ndarray::Array4<f32> {
+        let mut grid = self.grid.compute(events);
+        normalize(&mut grid);
+        let (width, height) = self.input_size();
+        let (offset_x, offset_y) = self.offset();
+        let mut input = ndarray::Array4::zeros((1, grid.shape()[0], height, width));
+        for ((bin, y, x), value) in grid.indexed_iter() {
+            let (input_x, input_y) = (x as isize + offset_x, y as isize + offset_y);
+            if input_x >= 0
+                && input_y >= 0
+                && (input_x as usize) < width
+                && (input_y as usize) < height
+            {
+                input[[0, bin, input_y as usize, input_x as usize]] = *value;
+            }
+        }
+        input
+    }
+
+    /// Extracts the sensor area from a (height, width) model output, the inverse of the padding.
+    ///
+    /// Pixels that were cropped from the input are zero.
+    pub fn restore(&self, output: &[f32]) -> ndarray::Array2<f32> {
+        let (width, height) = self.input_size();
+        let (offset_x, offset_y) = self.offset();
+        ndarray::Array2::from_shape_fn((self.height as usize, self.width as usize), |(y, x)| {
+            let (input_x, input_y) = (x as isize + offset_x, y as isize + offset_y);
+            if input_x >= 0
+                && input_y >= 0
+                && (input_x as usize) < width
+                && (input_y as usize) < height
+            {
+                output
+                    .get(input_y as usize * width + input_x as usize)
+                    .copied()
+                    .unwrap_or(0.0)
+            } else {
+                0.0
+            }
+        })
+    }
+
+    /// Converts an event stream into one input tensor per window.
+    ///
+    /// The reference implementations use windows with a fixed number of events (for instance
+    /// `Window::Count(width * height * 0.35)`).
+    pub fn windows<I: Iterator<Item = Result<Event, ParseError>>>(
+        self,
+        events: I,
+        window: Window,
+    ) -> E2vidInputs<I> {
+        E2vidInputs {
+            preprocessor: self,
+            chunks: Chunks::new(events, window),
+        }
+    }
+}
+
+/// Shifts and scales the non-zero cells to zero mean and unit variance.
+fn normalize(grid: &mut ndarray::Array3<f32>) {
+    let (mut count, mut sum, mut squares) = (0usize, 0.0f64, 0.0f64);
+    for value in grid.iter().filter(|value| **value != 0.0) {
+        count += 1;
+        sum += *value as f64;
+        squares += *value as f64 * *value as f64;
+    }
+    if count == 0 {
+        return;
+    }
+    let mean = sum / count as f64;
+    let deviation = (squares / count as f64 - mean * mean).max(0.0).sqrt();
+    let scale = if deviation > 0.0 {
+        1.0 / deviation
+    } else {
+        1.0
+    };
+    grid.mapv_inplace(|value| {
+        if value != 0.0 {
+            ((value as f64 - mean) * scale) as f32
+        } else {
+            value
+        }
+    });
+}
+
+/// A model input and the time span of its events.
+#[derive(Debug, Clone)]
+pub struct E2vidInput {
+    pub begin_t: i64,
+    pub end_t: i64,
+    /// (1, bins, height, width) tensor.
+    pub tensor: ndarray::Array4<f32>,
+}
+
+/// Input tensors of consecutive windows (see `E2vidPreprocessor::windows`). Empty windows are skipped.
+pub struct E2vidInputs<I> {
+    preprocessor: E2vidPreprocessor,
+    chunks: Chunks<I>,
+}
+
+impl<I> E2vidInputs<I> {
+    pub fn preprocessor(&self) -> &E2vidPreprocessor {
+        &self.preprocessor
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for E2vidInputs<I> {
+    type Item = Result<E2vidInput, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let events = match self.chunks.next()? {
+                Ok(events) => events,
+                Err(error) => return Some(Err(error)),
+            };
+            if let (Some(first), Some(last)) = (events.first(), events.last()) {
+                return Some(Ok(E2vidInput {
+                    begin_t: first.t(),
+                    end_t: last.t(),
+                    tensor: self.preprocessor.compute(&events),
+                }));
+            }
+        }
+    }
+}
+
+/// Runs an ONNX export of E2VID or FireNet.
+///
+/// The model must take the voxel grid as its only input and return the image, with values in [0, 1], as its
+/// first output. Recurrent models must be exported with their state handled inside the graph.
+#[cfg(feature = "ort")]
+pub struct Reconstructor {
+    session: ort::session::Session,
+    preprocessor: E2vidPreprocessor,
+}
+
+#[cfg(feature = "ort")]
+impl Reconstructor {
+    pub fn new<P: std::convert::AsRef<std::path::Path>>(
+        model: P,
+        preprocessor: E2vidPreprocessor,
+    ) -> Result<Self, ParseError> {
+        Ok(Reconstructor {
+            session: ort::session::Session::builder()?.commit_from_file(model)?,
+            preprocessor,
+        })
+    }
+
+    /// Reconstructs a (height, width) frame with values in [0, 1] from a model input.
+    pub fn run(&mut self, input: &E2vidInput) -> Result<ndarray::Array2<f32>, ParseError> {
+        let shape = input.tensor.shape().to_vec();
+        let tensor = ort::value::Tensor::from_array((
+            shape,
+            input.tensor.iter().copied().collect::<Vec<f32>>(),
+        ))?;
+        let outputs = self.session.run(ort::inputs![tensor])?;
+        let (_, image) = outputs[0].try_extract_tensor::<f32>()?;
+        Ok(self.preprocessor.restore(image))
+    }
+
+    /// Reconstructs the frame of a window of events.
+    pub fn reconstruct(&mut self, events: &[Event]) -> Result<ndarray::Array2<f32>, ParseError> {
+        let input = E2vidInput {
+            begin_t: events.first().map_or(0, |event| event.t()),
+            end_t: events.last().map_or(0, |event| event.t()),
+            tensor: self.preprocessor.compute(events),
+        };
+        self.run(&input)
+    }
+}
@@ -0,0 +1,96 @@
+use crate::base::{Packet, ParseError};
+use crate::events::Event;
+use crate::frames::FrameMeta;
+
+/// Pairs each frame of a packet stream with the events that occurred during its exposure.
+///
+/// Yields the frame packet (see `Packet::frame`) and the events whose timestamp is in
+/// [exposure_begin_t, exposure_end_t). Events usually arrive before the frame that they belong to,
+/// hence the decoder keeps a history of recent events (one second by default).
+pub struct Aligned<I> {
+    packets: I,
+    events: std::collections::VecDeque<Event>,
+    frames: std::collections::VecDeque<(Packet, FrameMeta)>,
+    history: i64,
+    latest_t: i64,
+    done: bool,
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> Aligned<I> {
+    pub fn new(packets: I) -> Self {
+        Aligned {
+            packets,
+            events: std::collections::VecDeque::new(),
+            frames: std::collections::VecDeque::new(),
+            history: 1_000_000,
+            latest_t: i64::MIN,
+            done: false,
+        }
+    }
+
+    /// Maximum age (in microseconds) of events kept while waiting for a frame.
+    pub fn history(mut self, history: i64) -> Self {
+        self.history = history;
+        self
+    }
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> Iterator for Aligned<I> {
+    type Item = Result<(Packet, Vec<Event>), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((_, meta)) = self.frames.front() {
+                if self.done || meta.exposure_end_t <= self.latest_t {
+                    let (packet, meta) = self.frames.pop_front().unwrap();
+                    let events = self
+                        .events
+                        .iter()
+                        .filter(|event| {
+                            event.t() >= meta.exposure_begin_t && event.t() < meta.exposure_end_t
+                        })
+                        .copied()
+                        .collect();
+                    while matches!(self.events.front(), Some(event) if event.t() < meta.exposure_begin_t)
+                    {
+                        self.events.pop_front();
+                    }
+                    return Some(Ok((packet, events)));
+                }
+            } else if self.done {
+                return None;
+            }
+            let packet = match self.packets.next() {
+                Some(Ok(packet)) => packet,
+                Some(Err(error)) => return Some(Err(error)),
+                None => {
+                    self.done = true;
+                    continue;
+                }
+            };
+            if packet.has_identifier(crate::frame_generated::FRAME_IDENTIFIER) {
+                let meta = match packet.frame_meta() {
+                    Ok(meta) => meta,
+                    Err(error) => return Some(Err(error)),
+                };
+                self.frames.push_back((packet, meta));
+            } else if packet.has_identifier(crate::events_generated::EVENT_PACKET_IDENTIFIER) {
+                match packet.events() {
+                    Ok(events) => {
+                        for event in events {
+                            self.latest_t = self.latest_t.max(event.t());
+                            self.events.push_back(event);
+                        }
+                    }
+                    Err(error) => return Some(Err(error)),
+                }
+                if self.frames.is_empty() {
+                    while matches!(self.events.front(), Some(event) if event.t() < self.latest_t - self.history)
+                    {
+                        self.events.pop_front();
+                    }
+                }
+            }
+        }
+    }
+}
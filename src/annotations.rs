@@ -0,0 +1,170 @@
+//! Bounding box (BBOX) and pose (POSE) streams, as written by the DV object detection and tracking modules.
+
+use crate::base::{Packet, ParseError};
+use crate::{bounding_boxes_generated, pose_generated};
+
+/// Axis-aligned bounding box, in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub t: i64,
+    pub top_left_x: f32,
+    pub top_left_y: f32,
+    pub bottom_right_x: f32,
+    pub bottom_right_y: f32,
+    pub confidence: f32,
+    pub label: Option<String>,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> f32 {
+        self.bottom_right_x - self.top_left_x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.bottom_right_y - self.top_left_y
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.top_left_x
+            && x < self.bottom_right_x
+            && y >= self.top_left_y
+            && y < self.bottom_right_y
+    }
+}
+
+/// Position and orientation of `target_frame` in `reference_frame`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pose {
+    pub t: i64,
+    /// x, y and z.
+    pub translation: [f32; 3],
+    /// Unit quaternion w, x, y and z.
+    pub rotation: [f32; 4],
+    pub reference_frame: Option<String>,
+    pub target_frame: Option<String>,
+}
+
+impl Packet {
+    /// Copies the bounding boxes of a BBOX packet.
+    pub fn bounding_boxes(&self) -> Result<Vec<BoundingBox>, ParseError> {
+        self.expect_identifier(bounding_boxes_generated::BOUNDING_BOX_PACKET_IDENTIFIER)?;
+        let packet =
+            bounding_boxes_generated::size_prefixed_root_as_bounding_box_packet(&self.buffer)?;
+        Ok(match packet.elements() {
+            Some(elements) => elements
+                .iter()
+                .map(|element| BoundingBox {
+                    t: element.timestamp(),
+                    top_left_x: element.top_left_x(),
+                    top_left_y: element.top_left_y(),
+                    bottom_right_x: element.bottom_right_x(),
+                    bottom_right_y: element.bottom_right_y(),
+                    confidence: element.confidence(),
+                    label: element.label().map(str::to_owned),
+                })
+                .collect(),
+            None => Vec::new(),
+        })
+    }
+
+    /// Encodes bounding boxes as a BBOX packet.
+    pub fn from_bounding_boxes(stream_id: u32, bounding_boxes: &[BoundingBox]) -> Self {
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        let elements = bounding_boxes
+            .iter()
+            .map(|bounding_box| {
+                let label = bounding_box
+                    .label
+                    .as_ref()
+                    .map(|label| builder.create_string(label));
+                bounding_boxes_generated::BoundingBox::create(
+                    &mut builder,
+                    &bounding_boxes_generated::BoundingBoxArgs {
+                        timestamp: bounding_box.t,
+                        top_left_x: bounding_box.top_left_x,
+                        top_left_y: bounding_box.top_left_y,
+                        bottom_right_x: bounding_box.bottom_right_x,
+                        bottom_right_y: bounding_box.bottom_right_y,
+                        confidence: bounding_box.confidence,
+                        label,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        let elements = builder.create_vector(&elements);
+        let root = bounding_boxes_generated::BoundingBoxPacket::create(
+            &mut builder,
+            &bounding_boxes_generated::BoundingBoxPacketArgs {
+                elements: Some(elements),
+            },
+        );
+        bounding_boxes_generated::finish_size_prefixed_bounding_box_packet_buffer(
+            &mut builder,
+            root,
+        );
+        Packet {
+            buffer: builder.finished_data().to_vec(),
+            stream_id,
+            arrival_time: None,
+        }
+    }
+
+    /// Reads the pose of a POSE packet.
+    ///
+    /// A missing translation or rotation is read as the identity.
+    pub fn pose(&self) -> Result<Pose, ParseError> {
+        self.expect_identifier(pose_generated::POSE_IDENTIFIER)?;
+        let pose = pose_generated::size_prefixed_root_as_pose(&self.buffer)?;
+        Ok(Pose {
+            t: pose.timestamp(),
+            translation: pose.translation().map_or([0.0; 3], |translation| {
+                [translation.x(), translation.y(), translation.z()]
+            }),
+            rotation: pose.rotation().map_or([1.0, 0.0, 0.0, 0.0], |rotation| {
+                [rotation.w(), rotation.x(), rotation.y(), rotation.z()]
+            }),
+            reference_frame: pose.reference_frame().map(str::to_owned),
+            target_frame: pose.target_frame().map(str::to_owned),
+        })
+    }
+
+    /// Encodes a pose as a POSE packet.
+    pub fn from_pose(stream_id: u32, pose: &Pose) -> Self {
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        let reference_frame = pose
+            .reference_frame
+            .as_ref()
+            .map(|frame| builder.create_string(frame));
+        let target_frame = pose
+            .target_frame
+            .as_ref()
+            .map(|frame| builder.create_string(frame));
+        let translation = pose_generated::Vec3f::new(
+            pose.translation[0],
+            pose.translation[1],
+            pose.translation[2],
+        );
+        let rotation = pose_generated::Quaternion::new(
+            pose.rotation[0],
+            pose.rotation[1],
+            pose.rotation[2],
+            pose.rotation[3],
+        );
+        let root = pose_generated::Pose::create(
+            &mut builder,
+            &pose_generated::PoseArgs {
+                timestamp: pose.t,
+                translation: Some(&translation),
+                rotation: Some(&rotation),
+                reference_frame,
+                target_frame,
+            },
+        );
+        pose_generated::finish_size_prefixed_pose_buffer(&mut builder, root);
+        Packet {
+            buffer: builder.finished_data().to_vec(),
+            stream_id,
+            arrival_time: None,
+        }
+    }
+}
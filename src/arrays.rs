@@ -0,0 +1,141 @@
+use crate::base::{Packet, ParseError};
+use crate::events::Event;
+use crate::{frame_generated, imus_generated};
+
+/// Channels and bytes per channel of a frame format.
+fn layout(format: frame_generated::FrameFormat) -> Result<(usize, usize), ParseError> {
+    match format {
+        frame_generated::FrameFormat::Gray => Ok((1, 1)),
+        frame_generated::FrameFormat::Gray16 => Ok((1, 2)),
+        frame_generated::FrameFormat::Bgr => Ok((3, 1)),
+        frame_generated::FrameFormat::Bgr16 => Ok((3, 2)),
+        frame_generated::FrameFormat::Bgra => Ok((4, 1)),
+        frame_generated::FrameFormat::Bgra16 => Ok((4, 2)),
+        _ => Err(ParseError::UnknownFrameFormat(format.0)),
+    }
+}
+
+/// Returns the frame and its (height, width, channels) shape, checking the size of its samples.
+fn parse_frame<'a>(
+    packet: &'a Packet,
+    bytes_per_channel: usize,
+    expected: &'static str,
+) -> Result<(frame_generated::Frame<'a>, (usize, usize, usize)), ParseError> {
+    packet.expect_identifier(frame_generated::FRAME_IDENTIFIER)?;
+    let frame = frame_generated::size_prefixed_root_as_frame(&packet.buffer)?;
+    let (channels, size) = layout(frame.format())?;
+    if size != bytes_per_channel {
+        return Err(ParseError::FrameFormatMismatch {
+            expected,
+            format: format!("{:?}", frame.format()),
+        });
+    }
+    let shape = (
+        frame.height().max(0) as usize,
+        frame.width().max(0) as usize,
+        channels,
+    );
+    Ok((frame, shape))
+}
+
+/// Converts an 8-bit FRME packet into a (height, width, channels) array.
+///
+/// Gray frames have one channel, BGR frames three and BGRA frames four.
+pub fn frame(packet: &Packet) -> Result<ndarray::Array3<u8>, ParseError> {
+    let (frame, shape) = parse_frame(packet, 1, "8-bit")?;
+    Ok(ndarray::Array3::from_shape_vec(
+        shape,
+        frame.pixels().unwrap_or(&[]).to_vec(),
+    )?)
+}
+
+/// Converts a 16-bit FRME packet (Gray16, Bgr16 or Bgra16) into a (height, width, channels) array.
+pub fn frame16(packet: &Packet) -> Result<ndarray::Array3<u16>, ParseError> {
+    let (frame, shape) = parse_frame(packet, 2, "16-bit")?;
+    Ok(ndarray::Array3::from_shape_vec(
+        shape,
+        frame
+            .pixels()
+            .unwrap_or(&[])
+            .chunks_exact(2)
+            .map(|value| u16::from_le_bytes([value[0], value[1]]))
+            .collect(),
+    )?)
+}
+
+/// Converts a gray FRME packet into a (height, width) array.
+pub fn gray_frame(packet: &Packet) -> Result<ndarray::Array2<u8>, ParseError> {
+    let (frame, (height, width, channels)) = parse_frame(packet, 1, "8-bit gray")?;
+    if channels != 1 {
+        return Err(ParseError::FrameFormatMismatch {
+            expected: "8-bit gray",
+            format: format!("{:?}", frame.format()),
+        });
+    }
+    Ok(ndarray::Array2::from_shape_vec(
+        (height, width),
+        frame.pixels().unwrap_or(&[]).to_vec(),
+    )?)
+}
+
+/// Converts a Gray16 FRME packet into a (height, width) array.
+pub fn gray_frame16(packet: &Packet) -> Result<ndarray::Array2<u16>, ParseError> {
+    let (frame, (height, width, channels)) = parse_frame(packet, 2, "16-bit gray")?;
+    if channels != 1 {
+        return Err(ParseError::FrameFormatMismatch {
+            expected: "16-bit gray",
+            format: format!("{:?}", frame.format()),
+        });
+    }
+    Ok(ndarray::Array2::from_shape_vec(
+        (height, width),
+        frame
+            .pixels()
+            .unwrap_or(&[])
+            .chunks_exact(2)
+            .map(|value| u16::from_le_bytes([value[0], value[1]]))
+            .collect(),
+    )?)
+}
+
+/// Converts events into a (events, 4) array with columns t, x, y and on (0 or 1).
+pub fn events(events: &[Event]) -> ndarray::Array2<i64> {
+    ndarray::Array2::from_shape_fn((events.len(), 4), |(index, column)| {
+        let event = &events[index];
+        match column {
+            0 => event.t(),
+            1 => event.x() as i64,
+            2 => event.y() as i64,
+            _ => event.on() as i64,
+        }
+    })
+}
+
+/// Converts an IMUS packet into timestamps and a (samples, 10) array.
+///
+/// The columns are temperature, accelerometer x, y, z, gyroscope x, y, z and magnetometer x, y, z.
+pub fn imus(packet: &Packet) -> Result<(ndarray::Array1<i64>, ndarray::Array2<f32>), ParseError> {
+    packet.expect_identifier(imus_generated::IMU_PACKET_IDENTIFIER)?;
+    let imu_packet = imus_generated::size_prefixed_root_as_imu_packet(&packet.buffer)?;
+    let imus: Vec<imus_generated::Imu> = match imu_packet.elements() {
+        Some(content) => content.iter().collect(),
+        None => Vec::new(),
+    };
+    let timestamps = imus.iter().map(|imu| imu.t()).collect();
+    let values = ndarray::Array2::from_shape_fn((imus.len(), 10), |(index, column)| {
+        let imu = &imus[index];
+        match column {
+            0 => imu.temperature(),
+            1 => imu.accelerometer_x(),
+            2 => imu.accelerometer_y(),
+            3 => imu.accelerometer_z(),
+            4 => imu.gyroscope_x(),
+            5 => imu.gyroscope_y(),
+            6 => imu.gyroscope_z(),
+            7 => imu.magnetometer_x(),
+            8 => imu.magnetometer_y(),
+            _ => imu.magnetometer_z(),
+        }
+    });
+    Ok((timestamps, values))
+}
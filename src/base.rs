@@ -3,44 +3,171 @@ use std::io::{Read};
 use std::net::{TcpStream, ToSocketAddrs};
 #[cfg(target_family = "unix")]
 use std::os::unix::net::UnixStream;
-use num_derive::FromPrimitive;
+use aedat_core::flatbuffer;
 use thiserror::Error;
 
-#[allow(dead_code, unused_imports)]
+#[allow(warnings, clippy::all)]
 #[path = "./ioheader_generated.rs"]
 pub mod ioheader_generated;
 
-#[allow(dead_code, unused_imports)]
-#[path = "./events_generated.rs"]
-mod events_generated;
+use crate::compression::{CompressionBackend, DefaultBackend};
+use crate::framing::PacketFramer;
+use crate::{
+    bounding_boxes_generated, events_generated, file_data_table_generated, frame_generated, imus_generated,
+    pose_generated, triggers_generated,
+};
 
-#[allow(dead_code, unused_imports)]
-#[path = "./frame_generated.rs"]
-mod frame_generated;
+const MAGIC_NUMBER: &str = "#!AER-DAT4.0\r\n";
 
-#[allow(dead_code, unused_imports)]
-#[path = "./imus_generated.rs"]
-mod imus_generated;
+/// Default limit of `Decoder::with_max_packet_size`.
+pub const DEFAULT_MAX_PACKET_SIZE: u32 = 1 << 28;
 
-#[allow(dead_code, unused_imports)]
-#[path = "./triggers_generated.rs"]
-mod triggers_generated;
+/// Default limit of `Decoder::with_max_decompressed_size`.
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 1 << 30;
 
-const MAGIC_NUMBER: &str = "#!AER-DAT4.0\r\n";
+/// Largest buffer allocated for a length read from the source, before the bytes themselves are read.
+///
+/// Longer buffers grow as the bytes arrive, hence a corrupted length cannot allocate more memory than the source holds.
+pub(crate) const MAXIMUM_PREALLOCATION: usize = 1 << 20;
 
 
+/// Errors of the crate, sorted into IO, format, consistency and usage layers by `ParseError::kind`.
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum ParseError {
+    /// Malformed data without a dedicated variant.
     #[error("Parse error: `{0}`")]
     General(String),
 
+    /// The request is invalid for this object or source (for instance writing to a finished file).
+    #[error("{0}")]
+    Usage(String),
+
     #[error("Unsupported stream type: `{0}`")]
     UnsupportedStreamType(String),
 
+    #[error("the file does not contain AEDAT4 data (wrong magic number)")]
+    BadMagic,
+
+    #[error("the description is empty")]
+    EmptyDescription,
+
+    #[error("the description has no `{node}` node")]
+    MissingDescriptionNode { node: &'static str },
+
+    #[error("unexpected `{tag}` node tag (expected `{expected}`)")]
+    UnexpectedTag { expected: &'static str, tag: String },
+
+    #[error("missing `{attribute}` attribute")]
+    MissingAttribute { attribute: &'static str },
+
+    #[error("empty `{attribute}` attribute")]
+    EmptyAttribute { attribute: &'static str },
+
+    #[error("duplicated stream id `{0}`")]
+    DuplicatedStreamId(u32),
+
+    #[error("no stream found in the description")]
+    NoStreams,
+
+    #[error("unknown compression algorithm `{0}`")]
+    UnknownCompression(i32),
+
+    #[error("{compression} support is disabled (enable the {features} feature)")]
+    CompressionDisabled {
+        compression: &'static str,
+        features: &'static str,
+    },
+
+    #[error("decompression error: {0}")]
+    Decompression(String),
+
+    #[error("unknown stream id `{0}`")]
+    UnknownStreamId(u32),
+
+    #[error(
+        "the packet length ({size} bytes) exceeds the limit ({limit} bytes){}",
+        crate::framing::length_hint(.size, .limit)
+    )]
+    PacketTooLarge { size: u32, limit: u32 },
+
+    #[error("malformed packet header at byte {position} ({header}): {reason}")]
+    MalformedPacketHeader {
+        position: u64,
+        /// Raw header bytes in hexadecimal.
+        header: String,
+        reason: String,
+    },
+
+    #[error("the source ends in the middle of a packet ({got} of {expected} bytes)")]
+    TruncatedPacket {
+        /// Size of the packet, including its 8-byte header (8 if the header itself is truncated).
+        expected: u64,
+        got: u64,
+    },
+
+    #[error("the decompressed packet exceeds the limit ({limit} bytes)")]
+    DecompressedTooLarge { limit: usize },
+
+    #[error("event ({x}, {y}) is outside the {width}x{height} sensor (packet at byte {packet})")]
+    EventOutOfBounds {
+        x: i16,
+        y: i16,
+        width: u16,
+        height: u16,
+        /// Byte offset of the packet data in the source, as in `FileDataEntry::byte_offset`.
+        packet: i64,
+    },
+
+    #[error("stream `{stream_id}` expects `{expected}` packets but got `{got}`")]
+    IdentifierMismatch {
+        stream_id: u32,
+        expected: String,
+        got: String,
+    },
+
+    #[error("`{0}` is not a valid polarity value")]
+    InvalidPolarity(i8),
+
+    #[error("no file to decode")]
+    NoFiles,
+
+    #[error("calibration error: {0}")]
+    Calibration(String),
+
+    #[error("the source has no file data table (network stream or unfinished recording)")]
+    NoFileDataTable,
+
+    #[error("invalid checkpoint: {0}")]
+    InvalidCheckpoint(String),
+
+    #[error("the streams of `{path}` do not match the streams of `{first}`")]
+    InconsistentStreams {
+        first: std::path::PathBuf,
+        path: std::path::PathBuf,
+    },
+
+    #[error("camera error: {0}")]
+    Device(String),
+
+    #[error("unknown frame format `{0}`")]
+    UnknownFrameFormat(i8),
+
+    #[error("expected a {expected} frame, got a `{format}` frame")]
+    FrameFormatMismatch {
+        expected: &'static str,
+        format: String,
+    },
+
+    #[error("the frame has {got} bytes instead of {expected} (width x height x channels x sample size)")]
+    FrameSizeMismatch { expected: usize, got: usize },
+
     #[error("FlatBuffer error")]
     FlatBuffer(#[from] flatbuffers::InvalidFlatbuffer),
 
+    #[error("{0}")]
+    Core(#[from] aedat_core::Error),
+
     #[error("Utf8 error")]
     Utf8(#[from] std::str::Utf8Error),
 
@@ -52,309 +179,2309 @@ pub enum ParseError {
 
     #[error("IO error")]
     Io(#[from] std::io::Error),
+
+    #[cfg(feature = "ndarray")]
+    #[error("Shape error")]
+    Shape(#[from] ndarray::ShapeError),
+
+    #[cfg(feature = "ort")]
+    #[error("ONNX Runtime error: {0}")]
+    Ort(#[from] ort::Error),
+
+    #[cfg(feature = "safetensors")]
+    #[error("safetensors error: {0}")]
+    SafeTensors(#[from] safetensors::SafeTensorError),
+
+    #[cfg(feature = "flow")]
+    #[error("Zip error")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[cfg(any(
+        feature = "datasets",
+        feature = "flow-hdf5",
+        feature = "mat",
+        feature = "nwb"
+    ))]
+    #[error("HDF5 error")]
+    Hdf5(#[from] hdf5::Error),
+}
+
+/// Layer of an error (see `ParseError::kind`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Reading or writing failed (file system, network).
+    Io,
+    /// The bytes do not follow the AEDAT4 format (header, description, compression, flatbuffers).
+    Format,
+    /// The packets are well-formed, but their content contradicts the description or the sensor.
+    Consistency,
+    /// The request cannot be served by this source or this build (missing feature, no file data table...).
+    Usage,
+}
+
+/// What a service should do after an error (see `ParseError::recovery`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Recovery {
+    /// The error is transient (interrupted or timed out IO), trying again may succeed.
+    Retry,
+    /// The error only concerns the current packet, the next packets can be decoded
+    /// (this is what `DecoderBuilder::recovery` does).
+    Skip,
+    /// The source or the request is unusable.
+    Abort,
+}
+
+impl ParseError {
+    pub fn kind(&self) -> ErrorKind {
+        match self.code() / 100 {
+            1 => ErrorKind::Io,
+            2 => ErrorKind::Format,
+            3 => ErrorKind::Consistency,
+            _ => ErrorKind::Usage,
+        }
+    }
+
+    /// Stable numeric code. The hundreds digit gives the kind (1 IO, 2 format, 3 consistency, 4 usage).
+    ///
+    /// Codes are never reused, new variants get new codes.
+    pub fn code(&self) -> u32 {
+        match self {
+            ParseError::Io(_) => 100,
+            #[cfg(feature = "flow")]
+            ParseError::Zip(_) => 101,
+            #[cfg(any(
+                feature = "datasets",
+                feature = "flow-hdf5",
+                feature = "mat",
+                feature = "nwb"
+            ))]
+            ParseError::Hdf5(_) => 102,
+            #[cfg(feature = "safetensors")]
+            ParseError::SafeTensors(_) => 103,
+            ParseError::Device(_) => 104,
+            ParseError::General(_) => 200,
+            ParseError::BadMagic => 201,
+            ParseError::EmptyDescription => 202,
+            ParseError::MissingDescriptionNode { .. } => 203,
+            ParseError::UnexpectedTag { .. } => 204,
+            ParseError::MissingAttribute { .. } => 205,
+            ParseError::EmptyAttribute { .. } => 206,
+            ParseError::DuplicatedStreamId(_) => 207,
+            ParseError::NoStreams => 208,
+            ParseError::UnknownCompression(_) => 209,
+            ParseError::Decompression(_) => 210,
+            ParseError::PacketTooLarge { .. } => 211,
+            ParseError::DecompressedTooLarge { .. } => 212,
+            ParseError::FlatBuffer(_) => 213,
+            ParseError::Core(_) => 214,
+            ParseError::Utf8(_) => 215,
+            ParseError::RoxmlTree(_) => 216,
+            ParseError::ParseInt(_) => 217,
+            ParseError::MalformedPacketHeader { .. } => 218,
+            ParseError::TruncatedPacket { .. } => 219,
+            ParseError::UnknownFrameFormat(_) => 220,
+            #[cfg(feature = "ndarray")]
+            ParseError::Shape(_) => 221,
+            ParseError::FrameSizeMismatch { .. } => 222,
+            ParseError::UnknownStreamId(_) => 300,
+            ParseError::EventOutOfBounds { .. } => 301,
+            ParseError::IdentifierMismatch { .. } => 302,
+            ParseError::InvalidPolarity(_) => 303,
+            ParseError::InconsistentStreams { .. } => 304,
+            ParseError::UnsupportedStreamType(_) => 400,
+            ParseError::CompressionDisabled { .. } => 401,
+            ParseError::NoFiles => 402,
+            ParseError::Calibration(_) => 403,
+            ParseError::NoFileDataTable => 404,
+            ParseError::InvalidCheckpoint(_) => 405,
+            #[cfg(feature = "ort")]
+            ParseError::Ort(_) => 406,
+            ParseError::FrameFormatMismatch { .. } => 407,
+            ParseError::Usage(_) => 408,
+        }
+    }
+
+    pub fn recovery(&self) -> Recovery {
+        match self {
+            ParseError::Io(error) => match error.kind() {
+                std::io::ErrorKind::Interrupted
+                | std::io::ErrorKind::TimedOut
+                | std::io::ErrorKind::WouldBlock => Recovery::Retry,
+                _ => Recovery::Abort,
+            },
+            ParseError::Decompression(_)
+            | ParseError::DecompressedTooLarge { .. }
+            | ParseError::FlatBuffer(_)
+            | ParseError::Core(_)
+            | ParseError::UnknownStreamId(_)
+            | ParseError::EventOutOfBounds { .. }
+            | ParseError::IdentifierMismatch { .. }
+            | ParseError::InvalidPolarity(_)
+            | ParseError::FrameSizeMismatch { .. } => Recovery::Skip,
+            #[cfg(feature = "ndarray")]
+            ParseError::Shape(_) => Recovery::Skip,
+            _ => Recovery::Abort,
+        }
+    }
+
+    /// Whether decoding can continue after the error (by retrying or skipping the packet).
+    pub fn is_recoverable(&self) -> bool {
+        self.recovery() != Recovery::Abort
+    }
 }
 
-trait Source:std::io::Read {}
-impl Source for File {}
+pub(crate) trait Source: std::io::Read + Send {
+    /// Returns None if the source does not support random access (network streams).
+    fn seekable(&mut self) -> Option<&mut dyn std::io::Seek> {
+        None
+    }
+}
+impl Source for File {
+    fn seekable(&mut self) -> Option<&mut dyn std::io::Seek> {
+        Some(self)
+    }
+}
 #[cfg(target_family = "unix")]
 impl Source for UnixStream {}
 impl Source for TcpStream {}
+impl Source for std::io::BufReader<File> {
+    fn seekable(&mut self) -> Option<&mut dyn std::io::Seek> {
+        Some(self)
+    }
+}
+#[cfg(target_family = "unix")]
+impl Source for std::io::BufReader<UnixStream> {}
+impl Source for std::io::BufReader<TcpStream> {}
+impl Source for std::io::Cursor<Vec<u8>> {
+    fn seekable(&mut self) -> Option<&mut dyn std::io::Seek> {
+        Some(self)
+    }
+}
+#[cfg(feature = "mmap")]
+impl Source for std::io::Cursor<memmap2::Mmap> {
+    fn seekable(&mut self) -> Option<&mut dyn std::io::Seek> {
+        Some(self)
+    }
+}
 
-#[derive(FromPrimitive, Copy, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StreamContent {
     Events,
     Frame,
     Imus,
     Triggers,
+    BoundingBoxes,
+    Pose,
+    /// Stream type unknown to this library (for instance a custom DV module output), with its type identifier.
+    ///
+    /// Packets are delivered as raw buffers: their identifier is not checked and their timestamps are not offset.
+    Other(String),
 }
 
-impl StreamContent {
-    fn from(identifier: &str) -> Result<Self, ParseError> {
+impl From<&str> for StreamContent {
+    /// Parses a flatbuffer identifier (inverse of `StreamContent::identifier`).
+    fn from(identifier: &str) -> Self {
         match identifier {
-            "EVTS" => Ok(StreamContent::Events),
-            "FRME" => Ok(StreamContent::Frame),
-            "IMUS" => Ok(StreamContent::Imus),
-            "TRIG" => Ok(StreamContent::Triggers),
-            _ => Err(ParseError::UnsupportedStreamType("unsupported stream type".to_string())),
+            "EVTS" => StreamContent::Events,
+            "FRME" => StreamContent::Frame,
+            "IMUS" => StreamContent::Imus,
+            "TRIG" => StreamContent::Triggers,
+            "BBOX" => StreamContent::BoundingBoxes,
+            "POSE" => StreamContent::Pose,
+            _ => StreamContent::Other(identifier.to_string()),
+        }
+    }
+}
+
+impl StreamContent {
+    /// Flatbuffer identifier of the packets, for instance `"EVTS"`.
+    pub fn identifier(&self) -> &str {
+        match self {
+            StreamContent::Events => "EVTS",
+            StreamContent::Frame => "FRME",
+            StreamContent::Imus => "IMUS",
+            StreamContent::Triggers => "TRIG",
+            StreamContent::BoundingBoxes => "BBOX",
+            StreamContent::Pose => "POSE",
+            StreamContent::Other(identifier) => identifier,
         }
     }
 }
 
 impl std::fmt::Display for StreamContent {
     fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
-            formatter,
-            "{}",
-            match self {
-                StreamContent::Events => "EVTS",
-                StreamContent::Frame => "FRME",
-                StreamContent::Imus => "IMUS",
-                StreamContent::Triggers => "TRIG",
-            }
-        )
+        write!(formatter, "{}", self.identifier())
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stream {
     pub content: StreamContent,
     pub width: u16,
     pub height: u16,
 }
 
+impl Stream {
+    pub fn new(content: StreamContent, width: u16, height: u16) -> Self {
+        Stream {
+            content,
+            width,
+            height,
+        }
+    }
+
+    /// Sensor size (width, height), or None for streams without geometry (IMU, triggers...).
+    pub fn resolution(&self) -> Option<(u16, u16)> {
+        (self.width > 0 && self.height > 0).then_some((self.width, self.height))
+    }
+}
+
+impl std::fmt::Display for Stream {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.resolution() {
+            Some((width, height)) => write!(formatter, "{} ({}x{})", self.content, width, height),
+            None => write!(formatter, "{}", self.content),
+        }
+    }
+}
+
+/// Location and time range of a packet, read from the file data table at the end of a recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileDataEntry {
+    /// Position of the packet data in the file (after the stream id and length).
+    pub byte_offset: i64,
+    pub stream_id: u32,
+    /// Size of the compressed packet data in bytes.
+    pub size: u32,
+    pub num_elements: i64,
+    pub timestamp_start: i64,
+    pub timestamp_end: i64,
+}
+
+/// Smallest first timestamp of the non-empty packets of a file data table.
+fn table_first_timestamp(entries: &[FileDataEntry]) -> Option<i64> {
+    entries
+        .iter()
+        .filter(|entry| entry.num_elements > 0)
+        .map(|entry| entry.timestamp_start)
+        .min()
+}
+
+/// Compressed packet (see `Decoder::read_raw`).
+pub(crate) struct RawPacket {
+    pub(crate) stream_id: u32,
+    byte_offset: i64,
+    data: Vec<u8>,
+    arrival_time: Option<std::time::SystemTime>,
+    /// The source ended within the packet, and the decoder salvages truncated packets.
+    truncated: bool,
+}
+
+/// Decompresses raw packets outside the decoder (see `Decoder::decompressor`).
+pub(crate) struct Decompressor {
+    backend: std::sync::Arc<dyn CompressionBackend>,
+    compression: ioheader_generated::Compression,
+    limit: usize,
+}
+
+impl Decompressor {
+    /// Returns the decompressed buffer, which may be partial if decompression failed.
+    pub(crate) fn decompress(&self, packet: &mut RawPacket) -> (Vec<u8>, Result<(), ParseError>) {
+        if self.compression == ioheader_generated::Compression::None {
+            return (std::mem::take(&mut packet.data), Ok(()));
+        }
+        let mut buffer = Vec::new();
+        let result = self
+            .backend
+            .decompress_with_limit(self.compression, &mut &packet.data[..], &mut buffer, self.limit);
+        (buffer, result)
+    }
+}
+
 pub struct Decoder {
     pub id_to_stream: std::collections::HashMap<u32, Stream>,
     file: Box<dyn Source>,
     position: i64,
     compression: ioheader_generated::Compression,
+    description: String,
+    /// Length of the magic number and the IOHeader, in bytes.
+    header_length: i64,
     file_data_position: i64,
+    verify: bool,
+    recover: bool,
+    skipped_packets: usize,
+    max_packet_size: u32,
+    max_decompressed_size: usize,
+    bounds_check: BoundsCheck,
+    out_of_bounds_events: usize,
+    identifier_mismatch: MismatchPolicy,
+    stream_ids: Option<std::collections::HashSet<u32>>,
+    timestamp_offset: i64,
+    zero_timestamps: bool,
+    first_timestamp: Option<i64>,
+    latency: Option<crate::latency::LatencyHistogram>,
+    runtime_config: Option<crate::runtime::RuntimeConfig>,
+    backend: std::sync::Arc<dyn CompressionBackend>,
+    capacities: std::collections::HashMap<u32, usize>,
+    salvage_truncated: bool,
+    truncated: bool,
 }
 
-unsafe impl Send for Decoder {}
+/// What the decoder does with events outside the sensor geometry advertised by their stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundsCheck {
+    /// Events are not checked.
+    Off,
+    /// Returns `ParseError::EventOutOfBounds` (the packet is skipped in recovery mode).
+    Error,
+    /// Keeps the events and counts them (see `Decoder::out_of_bounds_events`).
+    Count,
+}
 
-impl Decoder {
-    pub fn new_from_file<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, ParseError> {
-        let mut decoder = Decoder {
-            id_to_stream: std::collections::HashMap::new(),
-            file: Box::new(File::open(path)?),
-            position: 0i64,
-            file_data_position: 0,
-            compression: ioheader_generated::Compression::None,
-        };
-        {
-            let mut magic_number_buffer = [0; MAGIC_NUMBER.len()];
-            decoder.file.read_exact(&mut magic_number_buffer)?;
-            if std::str::from_utf8(&magic_number_buffer)? != MAGIC_NUMBER {
-                return Err(ParseError::General(
-                    "the file does not contain AEDAT4 data (wrong magic number)".to_string(),
-                ));
-            }
-            decoder.position += MAGIC_NUMBER.len() as i64;
+/// What the decoder does with packets whose flatbuffer identifier does not match the type of their stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MismatchPolicy {
+    /// Returns `ParseError::IdentifierMismatch`.
+    Error,
+    /// Skips the packet (see `Decoder::skipped_packets`).
+    Skip,
+    /// Decodes the packet as the type of its stream, and overwrites its identifier accordingly.
+    TrustStreamId,
+    /// Decodes the packet as the type given by its identifier, regardless of its stream.
+    TrustIdentifier,
+}
+
+enum Input {
+    File(std::path::PathBuf),
+    #[cfg(target_family = "unix")]
+    UnixStream(std::path::PathBuf),
+    TcpStream(std::io::Result<Vec<std::net::SocketAddr>>),
+}
+
+/// Configures and creates a `Decoder`.
+pub struct DecoderBuilder {
+    input: Input,
+    buffer_size: usize,
+    verify: bool,
+    recover: bool,
+    salvage_truncated: bool,
+    lenient: bool,
+    max_packet_size: u32,
+    max_decompressed_size: usize,
+    bounds_check: BoundsCheck,
+    identifier_mismatch: MismatchPolicy,
+    stream_ids: Option<std::collections::HashSet<u32>>,
+    contents: Option<Vec<StreamContent>>,
+    timestamp_offset: i64,
+    zero_timestamps: bool,
+    latency_window: Option<usize>,
+    runtime_config: Option<std::io::Result<Vec<std::net::SocketAddr>>>,
+    backend: Box<dyn CompressionBackend>,
+}
+
+impl DecoderBuilder {
+    pub fn file<P: std::convert::AsRef<std::path::Path>>(path: P) -> Self {
+        DecoderBuilder::new(Input::File(path.as_ref().to_path_buf()))
+    }
+
+    #[cfg(target_family = "unix")]
+    pub fn unix_stream<P: std::convert::AsRef<std::path::Path>>(path: P) -> Self {
+        DecoderBuilder::new(Input::UnixStream(path.as_ref().to_path_buf()))
+    }
+
+    pub fn tcp_stream<A: ToSocketAddrs>(address: A) -> Self {
+        DecoderBuilder::new(Input::TcpStream(
+            address.to_socket_addrs().map(|addresses| addresses.collect()),
+        ))
+    }
+
+    fn new(input: Input) -> Self {
+        DecoderBuilder {
+            input,
+            buffer_size: 1 << 16,
+            verify: false,
+            recover: false,
+            salvage_truncated: false,
+            lenient: false,
+            max_packet_size: DEFAULT_MAX_PACKET_SIZE,
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
+            bounds_check: BoundsCheck::Off,
+            identifier_mismatch: MismatchPolicy::Error,
+            stream_ids: None,
+            contents: None,
+            timestamp_offset: 0,
+            zero_timestamps: false,
+            latency_window: None,
+            runtime_config: None,
+            backend: Box::new(DefaultBackend),
         }
-        decoder = read_io_header(decoder)?;
+    }
+
+    /// Size of the read buffer in bytes (64 KiB by default). Zero disables buffering.
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// See `Decoder::with_verification`.
+    pub fn verification(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Skips packets that cannot be decompressed, belong to an unknown stream, or fail verification,
+    /// instead of returning an error. Read errors are still returned.
+    pub fn recovery(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    /// See `Decoder::with_max_packet_size`. The IOHeader is always checked against the default limit.
+    pub fn max_packet_size(mut self, max_packet_size: u32) -> Self {
+        self.max_packet_size = max_packet_size;
+        self
+    }
+
+    /// See `Decoder::with_max_decompressed_size`.
+    pub fn max_decompressed_size(mut self, max_decompressed_size: usize) -> Self {
+        self.max_decompressed_size = max_decompressed_size;
+        self
+    }
+
+    /// Checks that event coordinates fall within the width and height of their stream (off by default).
+    ///
+    /// Streams without geometry are not checked.
+    pub fn bounds_check(mut self, bounds_check: BoundsCheck) -> Self {
+        self.bounds_check = bounds_check;
+        self
+    }
+
+    /// Handling of packets whose identifier does not match their stream (`MismatchPolicy::Error` by default).
+    ///
+    /// Some recordings made with older DV versions have benign mismatches.
+    pub fn identifier_mismatch(mut self, policy: MismatchPolicy) -> Self {
+        self.identifier_mismatch = policy;
+        self
+    }
+
+    /// Ends decoding without error if the source stops in the middle of a packet, and returns the complete
+    /// events of a truncated event packet instead of `ParseError::TruncatedPacket`.
+    ///
+    /// Compressed packets only keep the events of the blocks that could be decompressed, and other packet types
+    /// are dropped. `Decoder::is_truncated` tells whether the source was truncated.
+    pub fn salvage_truncated(mut self, salvage_truncated: bool) -> Self {
+        self.salvage_truncated = salvage_truncated;
+        self
+    }
+
+    /// Tolerates nonconforming IOHeader descriptions instead of failing.
+    ///
+    /// The `outInfo` node may be nested anywhere, malformed stream nodes are skipped, the first of duplicated
+    /// stream ids is kept, and streams without geometry have zero size.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Only decodes packets from the given streams. Other packets are skipped before decompression.
+    pub fn stream_ids(mut self, stream_ids: &[u32]) -> Self {
+        self.stream_ids = Some(stream_ids.iter().copied().collect());
+        self
+    }
+
+    /// Only decodes packets from streams with the given content types.
+    /// Other packets are skipped before decompression.
+    pub fn only_streams(mut self, contents: &[StreamContent]) -> Self {
+        self.contents = Some(contents.to_vec());
+        self
+    }
+
+    /// Adds an offset (in microseconds) to the timestamps of every decoded packet.
+    pub fn timestamp_offset(mut self, timestamp_offset: i64) -> Self {
+        self.timestamp_offset = timestamp_offset;
+        self
+    }
+
+    /// Subtracts the first timestamp of the recording from every timestamp, so that it starts at zero.
+    ///
+    /// The first timestamp is the minimum over all the streams, read from the file data table.
+    /// Without a table (live sources, unfinished files), it is the first timestamp of the first
+    /// non-empty decoded packet, which may be larger than the first timestamp of another stream.
+    /// The first timestamp is shared by all the content types (events, frames, IMU samples and triggers),
+    /// and the offset set with `timestamp_offset` is applied afterwards.
+    pub fn zero_timestamps(mut self, zero_timestamps: bool) -> Self {
+        self.zero_timestamps = zero_timestamps;
+        self
+    }
+
+    /// Records the wall-clock arrival time of each packet (`Packet::arrival_time`),
+    /// and the latency of the last `window` packets (see `Decoder::latency`).
+    ///
+    /// This is meant for live network sources.
+    pub fn record_arrival(mut self, window: usize) -> Self {
+        self.latency_window = Some(window);
+        self
+    }
+
+    /// Downloads the configuration tree of the DV runtime from its config server
+    /// (usually the host of the TCP stream, on port `runtime::DEFAULT_CONFIG_PORT`).
+    ///
+    /// The configuration is optional: if the server cannot be reached within one second, or does not complete
+    /// the handshake, `Decoder::runtime_config` returns None.
+    pub fn runtime_config<A: ToSocketAddrs>(mut self, address: A) -> Self {
+        self.runtime_config = Some(address.to_socket_addrs().map(|addresses| addresses.collect()));
+        self
+    }
+
+    /// Replaces the compression backend selected by cargo features.
+    pub fn compression_backend(mut self, backend: Box<dyn CompressionBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
 
+    pub fn build(self) -> Result<Decoder, ParseError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("build_decoder").entered();
+        let mut decoder = match self.input {
+            Input::File(path) => Decoder::new_from_source(
+                Box::new(std::io::BufReader::with_capacity(self.buffer_size, File::open(path)?)),
+                true,
+                self.lenient,
+            ),
+            #[cfg(target_family = "unix")]
+            Input::UnixStream(path) => Decoder::new_from_source(
+                Box::new(std::io::BufReader::with_capacity(
+                    self.buffer_size,
+                    UnixStream::connect(path)?,
+                )),
+                false,
+                self.lenient,
+            ),
+            Input::TcpStream(addresses) => Decoder::new_from_source(
+                Box::new(std::io::BufReader::with_capacity(
+                    self.buffer_size,
+                    TcpStream::connect(&addresses?[..])?,
+                )),
+                false,
+                self.lenient,
+            ),
+        }?;
+        decoder.verify = self.verify;
+        decoder.recover = self.recover;
+        decoder.salvage_truncated = self.salvage_truncated;
+        decoder.max_packet_size = self.max_packet_size;
+        decoder.max_decompressed_size = self.max_decompressed_size;
+        decoder.bounds_check = self.bounds_check;
+        decoder.identifier_mismatch = self.identifier_mismatch;
+        decoder.stream_ids = self.stream_ids;
+        if let Some(contents) = self.contents {
+            let selected = decoder
+                .id_to_stream
+                .iter()
+                .filter(|(_, stream)| contents.contains(&stream.content))
+                .map(|(id, _)| *id);
+            decoder.stream_ids = Some(match decoder.stream_ids {
+                Some(stream_ids) => selected.filter(|id| stream_ids.contains(id)).collect(),
+                None => selected.collect(),
+            });
+        }
+        decoder.timestamp_offset = self.timestamp_offset;
+        decoder.zero_timestamps = self.zero_timestamps;
+        if decoder.zero_timestamps {
+            // a missing or damaged table falls back to the first decoded packet
+            if let Ok(entries) = decoder.file_data_table() {
+                decoder.first_timestamp = table_first_timestamp(&entries);
+            }
+        }
+        decoder.latency = self.latency_window.map(crate::latency::LatencyHistogram::new);
+        decoder.runtime_config = match self.runtime_config {
+            Some(Ok(addresses)) => {
+                let result = crate::runtime::RuntimeConfig::fetch(
+                    &addresses[..],
+                    std::time::Duration::from_secs(1),
+                );
+                #[cfg(feature = "tracing")]
+                if let Err(error) = result.as_ref() {
+                    tracing::warn!(%error, "the DV runtime configuration is not available");
+                }
+                result.ok()
+            }
+            _ => None,
+        };
+        decoder.backend = self.backend.into();
         Ok(decoder)
     }
+}
+
+impl Decoder {
+    pub fn new_from_file<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, ParseError> {
+        DecoderBuilder::file(path).build()
+    }
 
+    /// Maps the file in memory instead of issuing a read syscall per packet field.
+    ///
+    /// The file must not be modified while the decoder is alive.
+    #[cfg(feature = "mmap")]
+    pub fn new_from_file_mmap<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, ParseError> {
+        let file = File::open(path)?;
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Decoder::new_from_source(Box::new(std::io::Cursor::new(map)), true, false)
+    }
+
+    /// Decodes a recording held in memory.
+    ///
+    /// The bytes are copied. Buffers are allocated as their bytes are read, hence corrupted lengths fail without
+    /// allocating more than the input size (plus 1 MiB), which makes this entry point suitable
+    /// for fuzzing harnesses. Decompressed packets are limited by `with_max_decompressed_size`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        Decoder::new_from_source(Box::new(std::io::Cursor::new(bytes.to_vec())), true, false)
+    }
 
     #[cfg(target_family = "unix")]
     pub fn new_from_unix_stream<P: std::convert::AsRef<std::path::Path> + Clone>(
         path: P) -> Result<Self, ParseError> {
-        let mut decoder = Decoder {
-            id_to_stream: std::collections::HashMap::new(),
-            file: Box::new(UnixStream::connect(path)?),
-            position: 0i64,
-            file_data_position: -1,
-            compression: ioheader_generated::Compression::None,
-        };
-        decoder = read_io_header(decoder)?;
-        Ok(decoder)
+        DecoderBuilder::unix_stream(path).build()
     }
 
     pub fn new_from_tcp_stream<P: ToSocketAddrs + Clone>(
         path: P,
+    ) -> Result<Self, ParseError> {
+        DecoderBuilder::tcp_stream(path).build()
+    }
+
+    pub(crate) fn new_from_source(
+        file: Box<dyn Source>,
+        is_file: bool,
+        lenient: bool,
     ) -> Result<Self, ParseError> {
         let mut decoder = Decoder {
             id_to_stream: std::collections::HashMap::new(),
-            file: Box::new(TcpStream::connect(path)?),
+            file,
             position: 0i64,
-            file_data_position: -1,
+            file_data_position: if is_file { 0 } else { -1 },
             compression: ioheader_generated::Compression::None,
+            description: String::new(),
+            header_length: 0,
+            verify: false,
+            recover: false,
+            skipped_packets: 0,
+            max_packet_size: DEFAULT_MAX_PACKET_SIZE,
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
+            bounds_check: BoundsCheck::Off,
+            out_of_bounds_events: 0,
+            identifier_mismatch: MismatchPolicy::Error,
+            stream_ids: None,
+            timestamp_offset: 0,
+            zero_timestamps: false,
+            first_timestamp: None,
+            latency: None,
+            runtime_config: None,
+            backend: std::sync::Arc::new(DefaultBackend),
+            capacities: std::collections::HashMap::new(),
+            salvage_truncated: false,
+            truncated: false,
         };
-        decoder = read_io_header(decoder)?;
+        if is_file {
+            decoder = read_magic_number(decoder)?;
+        }
+        decoder = read_io_header(decoder, lenient)?;
         Ok(decoder)
     }
+
+    /// Maximum length of a packet in the file (`DEFAULT_MAX_PACKET_SIZE` by default).
+    ///
+    /// Longer packets return `ParseError::PacketTooLarge` instead of allocating memory for a corrupted length.
+    pub fn with_max_packet_size(mut self, max_packet_size: u32) -> Self {
+        self.max_packet_size = max_packet_size;
+        self
+    }
+
+    /// Maximum size of a decompressed packet (`DEFAULT_MAX_DECOMPRESSED_SIZE` by default).
+    ///
+    /// Decompression stops at the limit and returns `ParseError::DecompressedTooLarge`, which protects
+    /// against compression bombs.
+    pub fn with_max_decompressed_size(mut self, max_decompressed_size: usize) -> Self {
+        self.max_decompressed_size = max_decompressed_size;
+        self
+    }
+
+    /// Runs the flatbuffers verifier on every packet, and returns `ParseError::FlatBuffer` for malformed packets.
+    ///
+    /// The IOHeader is always verified.
+    pub fn with_verification(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Replaces the compression backend (see `DecoderBuilder::compression_backend`).
+    pub fn with_compression_backend(mut self, backend: Box<dyn CompressionBackend>) -> Self {
+        self.backend = backend.into();
+        self
+    }
+
+    /// Moves decoding to a background thread.
+    ///
+    /// The thread blocks once `channel_capacity` packets are waiting, and stops when the receiver is dropped.
+    pub fn spawn(self, channel_capacity: usize) -> std::sync::mpsc::Receiver<Result<Packet, ParseError>> {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(channel_capacity);
+        std::thread::spawn(move || {
+            for result in self {
+                if sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+        receiver
+    }
+}
+
+fn read_magic_number(mut decoder: Decoder) -> Result<Decoder, ParseError> {
+    let mut magic_number_buffer = [0; MAGIC_NUMBER.len()];
+    decoder.file.read_exact(&mut magic_number_buffer)?;
+    if std::str::from_utf8(&magic_number_buffer)? != MAGIC_NUMBER {
+        return Err(ParseError::BadMagic);
+    }
+    decoder.position += MAGIC_NUMBER.len() as i64;
+    Ok(decoder)
 }
 
-fn read_io_header(mut decoder: Decoder) -> Result<Decoder, ParseError> {
+fn read_io_header(mut decoder: Decoder, lenient: bool) -> Result<Decoder, ParseError> {
     let length = {
         let mut bytes = [0; 4];
         decoder.file.read_exact(&mut bytes)?;
         u32::from_le_bytes(bytes)
     };
+    if length > decoder.max_packet_size {
+        return Err(ParseError::PacketTooLarge {
+            size: length,
+            limit: decoder.max_packet_size,
+        });
+    }
     decoder.position += 4i64 + length as i64;
     {
-        let mut buffer = std::vec![0; length as usize];
-        decoder.file.read_exact(&mut buffer)?;
-        let ioheader = unsafe { ioheader_generated::root_as_ioheader_unchecked(&buffer) };
+        let mut buffer = length.to_le_bytes().to_vec();
+        read_length(&mut decoder.file, &mut buffer, length)?;
+        let ioheader = ioheader_generated::size_prefixed_root_as_ioheader(&buffer)?;
         decoder.compression = ioheader.compression();
         decoder.file_data_position = ioheader.file_data_position();
         let description = match ioheader.description() {
             Some(content) => content,
-            None => return Err(ParseError::General("the description is empty".to_string())),
+            None => return Err(ParseError::EmptyDescription),
+        };
+        decoder.id_to_stream = parse_description(description, lenient)?;
+        decoder.description = description.to_owned();
+    }
+    decoder.header_length = decoder.position;
+    if decoder.id_to_stream.is_empty() {
+        return Err(ParseError::NoStreams);
+    }
+    Ok(decoder)
+}
+
+fn child_element<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    tag: &str,
+    attribute: &str,
+    value: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|child| {
+        child.is_element() && child.has_tag_name(tag) && child.attribute(attribute) == Some(value)
+    })
+}
+
+fn attr_text<'a>(node: roxmltree::Node<'a, '_>, key: &'static str) -> Result<&'a str, ParseError> {
+    match child_element(node, "attr", "key", key) {
+        Some(content) => match content.text() {
+            Some(content) => Ok(content),
+            None => Err(ParseError::EmptyAttribute { attribute: key }),
+        },
+        None => Err(ParseError::MissingAttribute { attribute: key }),
+    }
+}
+
+fn parse_stream(stream_node: roxmltree::Node, lenient: bool) -> Result<(u32, Stream), ParseError> {
+    let stream_id = match stream_node.attribute("name") {
+        Some(content) => content,
+        None => return Err(ParseError::MissingAttribute { attribute: "name" }),
+    }
+    .trim()
+    .parse::<u32>()?;
+    let identifier = attr_text(stream_node, "typeIdentifier")?.trim();
+    let content = StreamContent::from(identifier);
+    let mut width = 0u16;
+    let mut height = 0u16;
+    if let StreamContent::Events | StreamContent::Frame = content {
+        let size = match child_element(stream_node, "node", "name", "info") {
+            Some(info_node) => attr_text(info_node, "sizeX").and_then(|width| {
+                attr_text(info_node, "sizeY").and_then(|height| {
+                    Ok((width.trim().parse::<u16>()?, height.trim().parse::<u16>()?))
+                })
+            }),
+            None => Err(ParseError::MissingDescriptionNode { node: "info" }),
         };
-        let document = roxmltree::Document::parse(description)?;
+        match size {
+            Ok(size) => (width, height) = size,
+            Err(_) if lenient => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok((
+        stream_id,
+        Stream {
+            content,
+            width,
+            height,
+        },
+    ))
+}
+
+/// Parses the streams declared in an IOHeader description (see `DecoderBuilder::lenient`).
+fn parse_description(
+    description: &str,
+    lenient: bool,
+) -> Result<std::collections::HashMap<u32, Stream>, ParseError> {
+    let document = roxmltree::Document::parse(description)?;
+    let output_node = if lenient {
+        document.descendants().find(|node| {
+            node.is_element() && node.has_tag_name("node") && node.attribute("name") == Some("outInfo")
+        })
+    } else {
         let dv_node = match document.root().first_child() {
             Some(content) => content,
-            None => return Err(ParseError::General("the description has no dv node".to_string())),
+            None => return Err(ParseError::MissingDescriptionNode { node: "dv" }),
         };
         if !dv_node.has_tag_name("dv") {
-            return Err(ParseError::General("unexpected dv node tag".to_string()));
+            return Err(ParseError::UnexpectedTag {
+                expected: "dv",
+                tag: dv_node.tag_name().name().to_string(),
+            });
         }
-        let output_node = match dv_node.children().find(|node| {
-            node.is_element()
-                && node.has_tag_name("node")
-                && node.attribute("name") == Some("outInfo")
-        }) {
-            Some(content) => content,
-            None => return Err(ParseError::General("the description has no output node".to_string())),
-        };
-        for stream_node in output_node.children() {
-            if stream_node.is_element() && stream_node.has_tag_name("node") {
-                if !stream_node.has_tag_name("node") {
-                    return Err(ParseError::General("unexpected stream node tag".to_string()));
-                }
-                let stream_id = match stream_node.attribute("name") {
-                    Some(content) => content,
-                    None => return Err(ParseError::General("missing stream node id".to_string())),
-                }
-                    .parse::<u32>()?;
-                let identifier = match stream_node.children().find(|node| {
-                    node.is_element()
-                        && node.has_tag_name("attr")
-                        && node.attribute("key") == Some("typeIdentifier")
-                }) {
-                    Some(content) => match content.text() {
-                        Some(content) => content,
-                        None => {
-                            return Err(ParseError::General("empty stream node type identifier".to_string()))
-                        }
-                    },
-                    None => return Err(ParseError::General("missing stream node type identifier".to_string())),
-                }
-                    .to_string();
-                let mut width = 0u16;
-                let mut height = 0u16;
-                if identifier == "EVTS" || identifier == "FRME" {
-                    let info_node = match stream_node.children().find(|node| {
-                        node.is_element()
-                            && node.has_tag_name("node")
-                            && node.attribute("name") == Some("info")
-                    }) {
-                        Some(content) => content,
-                        None => return Err(ParseError::General("missing info node".to_string())),
-                    };
-                    width = match info_node.children().find(|node| {
-                        node.is_element()
-                            && node.has_tag_name("attr")
-                            && node.attribute("key") == Some("sizeX")
-                    }) {
-                        Some(content) => match content.text() {
-                            Some(content) => content,
-                            None => return Err(ParseError::General("empty sizeX attribute".to_string())),
-                        },
-                        None => return Err(ParseError::General("missing sizeX attribute".to_string())),
-                    }
-                        .parse::<u16>()?;
-                    height = match info_node.children().find(|node| {
-                        node.is_element()
-                            && node.has_tag_name("attr")
-                            && node.attribute("key") == Some("sizeY")
-                    }) {
-                        Some(content) => match content.text() {
-                            Some(content) => content,
-                            None => return Err(ParseError::General("empty sizeX attribute".to_string())),
-                        },
-                        None => return Err(ParseError::General("missing sizeX attribute".to_string())),
-                    }
-                        .parse::<u16>()?;
-                }
-                if decoder
-                    .id_to_stream
-                    .insert(
-                        stream_id,
-                        Stream {
-                            content: StreamContent::from(&identifier)?,
-                            width,
-                            height,
-                        },
-                    )
-                    .is_some()
-                {
-                    return Err(ParseError::General("duplicated stream id".to_string()));
+        child_element(dv_node, "node", "name", "outInfo")
+    };
+    let output_node = match output_node {
+        Some(content) => content,
+        None => return Err(ParseError::MissingDescriptionNode { node: "outInfo" }),
+    };
+    let mut id_to_stream = std::collections::HashMap::new();
+    for stream_node in output_node.children() {
+        if stream_node.is_element() && stream_node.has_tag_name("node") {
+            let (stream_id, stream) = match parse_stream(stream_node, lenient) {
+                Ok(content) => content,
+                Err(_) if lenient => continue,
+                Err(error) => return Err(error),
+            };
+            if id_to_stream.contains_key(&stream_id) {
+                if lenient {
+                    continue;
                 }
+                return Err(ParseError::DuplicatedStreamId(stream_id));
             }
+            id_to_stream.insert(stream_id, stream);
         }
     }
-    if decoder.id_to_stream.is_empty() {
-        return Err(ParseError::General("no stream found in the description".to_string()));
-    }
-    Ok(decoder)
-
+    Ok(id_to_stream)
 }
 
 #[derive(Debug, Clone)]
 pub struct Packet {
     pub buffer: Vec<u8>,
     pub stream_id: u32,
+    /// Wall-clock time at which the packet header was read, if the decoder records arrival times.
+    pub arrival_time: Option<std::time::SystemTime>,
 }
 
-impl Iterator for Decoder {
-    type Item = Result<Packet, ParseError>;
+impl Packet {
+    /// Copies the events of an EVTS packet.
+    pub fn events(&self) -> Result<Vec<events_generated::Event>, ParseError> {
+        self.expect_identifier(events_generated::EVENT_PACKET_IDENTIFIER)?;
+        let event_packet = events_generated::size_prefixed_root_as_event_packet(&self.buffer)?;
+        Ok(match event_packet.elements() {
+            Some(content) => content.to_vec(),
+            None => Vec::new(),
+        })
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.file_data_position > -1 && self.position == self.file_data_position {
-            return None;
+    /// Encodes events as an EVTS packet.
+    pub fn from_events(stream_id: u32, events: &[events_generated::Event]) -> Self {
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        let elements = builder.create_vector(events);
+        let root = events_generated::EventPacket::create(
+            &mut builder,
+            &events_generated::EventPacketArgs {
+                elements: Some(elements),
+            },
+        );
+        events_generated::finish_size_prefixed_event_packet_buffer(&mut builder, root);
+        Packet {
+            buffer: builder.finished_data().to_vec(),
+            stream_id,
+            arrival_time: None,
         }
-        let mut packet = Packet {
-            buffer: Vec::new(),
-            stream_id: {
-                let mut bytes = [0; 4];
-                match self.file.read_exact(&mut bytes) {
-                    Ok(()) => (),
-                    Err(_) => return None,
-                }
-                u32::from_le_bytes(bytes)
+    }
+
+    /// Returns the timestamps of the first and last events of an EVTS packet, without copying the events.
+    ///
+    /// Returns None if the packet is empty.
+    pub fn time_range(&self) -> Result<Option<(i64, i64)>, ParseError> {
+        self.expect_identifier(events_generated::EVENT_PACKET_IDENTIFIER)?;
+        let event_packet = events_generated::size_prefixed_root_as_event_packet(&self.buffer)?;
+        Ok(match event_packet.elements() {
+            Some(content) => match (content.first(), content.last()) {
+                (Some(first), Some(last)) => Some((first.t(), last.t())),
+                _ => None,
             },
-        };
-        let length = {
-            let mut bytes = [0; 4];
-            if let Err(error) = self.file.read_exact(&mut bytes) {
-                return Some(Err(ParseError::from(error)));
+            None => None,
+        })
+    }
+
+    /// Returns the number of elements and the first and last timestamps of the packet.
+    pub(crate) fn summary(&self, content: &StreamContent) -> Result<(i64, i64, i64), ParseError> {
+        Ok(match content {
+            StreamContent::Events => {
+                let elements = events_generated::size_prefixed_root_as_event_packet(&self.buffer)?
+                    .elements()
+                    .map_or(0, |elements| elements.len());
+                let (begin, end) = self.time_range()?.unwrap_or((0, 0));
+                (elements as i64, begin, end)
             }
-            u32::from_le_bytes(bytes)
-        };
-        self.position += 8i64 + length as i64;
-        let mut raw_buffer = std::vec![0; length as usize];
-        if let Err(error) = self.file.read_exact(&mut raw_buffer) {
-            return Some(Err(ParseError::from(error)));
-        }
-        match self.compression {
-            ioheader_generated::Compression::None => {
-                std::mem::swap(&mut raw_buffer, &mut packet.buffer)
-            }
-            ioheader_generated::Compression::Lz4 | ioheader_generated::Compression::Lz4High => {
-                match lz4::Decoder::new(&raw_buffer[..]) {
-                    Ok(mut result) => {
-                        if let Err(error) = result.read_to_end(&mut packet.buffer) {
-                            return Some(Err(ParseError::from(error)));
-                        }
-                    }
-                    Err(error) => return Some(Err(ParseError::from(error))),
+            StreamContent::Frame => {
+                let t = self.frame()?.t();
+                (1, t, t)
+            }
+            StreamContent::Imus => {
+                match imus_generated::size_prefixed_root_as_imu_packet(&self.buffer)?.elements() {
+                    Some(elements) if !elements.is_empty() => (
+                        elements.len() as i64,
+                        elements.get(0).t(),
+                        elements.get(elements.len() - 1).t(),
+                    ),
+                    _ => (0, 0, 0),
                 }
             }
-            ioheader_generated::Compression::Zstd | ioheader_generated::Compression::ZstdHigh => {
-                match zstd::stream::Decoder::new(&raw_buffer[..]) {
-                    Ok(mut result) => {
-                        if let Err(error) = result.read_to_end(&mut packet.buffer) {
-                            return Some(Err(ParseError::from(error)));
-                        }
-                    }
-                    Err(error) => return Some(Err(ParseError::from(error))),
+            StreamContent::Triggers => {
+                match triggers_generated::size_prefixed_root_as_trigger_packet(&self.buffer)?
+                    .elements()
+                {
+                    Some(elements) if !elements.is_empty() => (
+                        elements.len() as i64,
+                        elements.get(0).t(),
+                        elements.get(elements.len() - 1).t(),
+                    ),
+                    _ => (0, 0, 0),
                 }
             }
-            _ => return Some(Err(ParseError::General("unknown compression algorithm".to_string()))),
-        }
-        let expected_content = &(match self.id_to_stream.get(&packet.stream_id) {
-            Some(content) => content,
-            None => return Some(Err(ParseError::General("unknown stream id".to_string()))),
-        }
-            .content);
-        if !flatbuffers::buffer_has_identifier(&packet.buffer, &expected_content.to_string(), true)
-        {
-            return Some(Err(ParseError::General(
-                "the stream id and the identifier do not match".to_string(),
-            )));
-        }
-        Some(Ok(packet))
+            StreamContent::BoundingBoxes => {
+                match bounding_boxes_generated::size_prefixed_root_as_bounding_box_packet(&self.buffer)?
+                    .elements()
+                {
+                    Some(elements) if !elements.is_empty() => (
+                        elements.len() as i64,
+                        elements.get(0).timestamp(),
+                        elements.get(elements.len() - 1).timestamp(),
+                    ),
+                    _ => (0, 0, 0),
+                }
+            }
+            StreamContent::Pose => {
+                let t = pose_generated::size_prefixed_root_as_pose(&self.buffer)?.timestamp();
+                (1, t, t)
+            }
+            // the layout of unknown packets is unknown
+            StreamContent::Other(_) => (0, 0, 0),
+        })
+    }
+
+    /// Type given by the flatbuffer identifier, or `declared` for custom streams and unknown identifiers.
+    ///
+    /// The two differ only for packets decoded with `MismatchPolicy::TrustIdentifier`.
+    pub(crate) fn content(&self, declared: &StreamContent) -> StreamContent {
+        if let (false, Some(identifier)) = (matches!(declared, StreamContent::Other(_)), self.buffer.get(8..12)) {
+            if let Ok(identifier) = std::str::from_utf8(identifier) {
+                let content = StreamContent::from(identifier);
+                if !matches!(content, StreamContent::Other(_)) {
+                    return content;
+                }
+            }
+        }
+        declared.clone()
+    }
+
+    /// Type of the packet, given by its flatbuffer identifier or, for custom streams, by `stream`.
+    pub fn content_kind(&self, stream: &Stream) -> StreamContent {
+        self.content(&stream.content)
+    }
+
+    /// Number of elements (events, IMU samples, triggers or bounding boxes), without copying them.
+    ///
+    /// Frame and pose packets have one element, and packets of custom streams have none.
+    pub fn event_count(&self, stream: &Stream) -> Result<usize, ParseError> {
+        Ok(self.summary(&self.content_kind(stream))?.0 as usize)
+    }
+
+    pub fn is_empty(&self, stream: &Stream) -> Result<bool, ParseError> {
+        Ok(self.event_count(stream)? == 0)
+    }
+
+    /// Timestamp of the first element, or None if the packet has no elements.
+    pub fn first_timestamp(&self, stream: &Stream) -> Result<Option<i64>, ParseError> {
+        let (elements, begin_t, _) = self.summary(&self.content_kind(stream))?;
+        Ok((elements > 0).then_some(begin_t))
+    }
+
+    /// Timestamp of the last element, or None if the packet has no elements.
+    pub fn last_timestamp(&self, stream: &Stream) -> Result<Option<i64>, ParseError> {
+        let (elements, _, end_t) = self.summary(&self.content_kind(stream))?;
+        Ok((elements > 0).then_some(end_t))
+    }
+
+    /// Checks the flatbuffer identifier of the packet, for instance `"EVTS"`.
+    ///
+    /// Unlike `flatbuffers::buffer_has_identifier`, returns false for buffers too short to have an identifier.
+    pub fn has_identifier(&self, identifier: &str) -> bool {
+        self.buffer.get(8..12) == Some(identifier.as_bytes())
+    }
+
+    pub(crate) fn expect_identifier(&self, identifier: &str) -> Result<(), ParseError> {
+        if self.has_identifier(identifier) {
+            Ok(())
+        } else {
+            Err(ParseError::IdentifierMismatch {
+                stream_id: self.stream_id,
+                expected: identifier.to_string(),
+                got: String::from_utf8_lossy(self.buffer.get(8..12).unwrap_or(&[])).to_string(),
+            })
+        }
+    }
+}
+
+impl Decoder {
+    /// Reads the next packet header, skipping the packets of filtered out streams.
+    fn read_packet_header(&mut self) -> Option<Result<(u32, u32), ParseError>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("read_packet_header", position = self.position).entered();
+        loop {
+            if self.file_data_position > -1 && self.position == self.file_data_position {
+                return None;
+            }
+            let header = PacketFramer::new(&mut self.file)
+                .max_packet_size(self.max_packet_size)
+                .offset(self.position as u64)
+                .read_header();
+            let (stream_id, length) = match header {
+                Ok(Some(header)) => (header.stream_id, header.length),
+                // the source ends between two packets
+                Ok(None) => return None,
+                Err(error @ ParseError::TruncatedPacket { .. }) => {
+                    self.truncated = true;
+                    if self.salvage_truncated {
+                        return None;
+                    }
+                    return Some(Err(error));
+                }
+                Err(error) => return Some(Err(error)),
+            };
+            self.position += 8i64 + length as i64;
+            if let Some(stream_ids) = &self.stream_ids {
+                if !stream_ids.contains(&stream_id) {
+                    if let Err(error) = drain(&mut Read::by_ref(&mut self.file).take(length as u64), length) {
+                        if let ParseError::TruncatedPacket { .. } = error {
+                            self.truncated = true;
+                            if self.salvage_truncated {
+                                return None;
+                            }
+                        }
+                        return Some(Err(error));
+                    }
+                    continue;
+                }
+            }
+            return Some(Ok((stream_id, length)));
+        }
+    }
+
+    /// Decompresses a packet directly from the source, without an intermediate compressed buffer.
+    ///
+    /// The outer error is fatal (the source cannot be read), the inner error only concerns this packet.
+    fn read_packet(
+        &mut self,
+        stream_id: u32,
+        length: u32,
+        byte_offset: i64,
+    ) -> Result<Result<Packet, ParseError>, ParseError> {
+        let capacity = if self.compression == ioheader_generated::Compression::None {
+            (length as usize).min(MAXIMUM_PREALLOCATION)
+        } else {
+            self.capacities.get(&stream_id).copied().unwrap_or(0)
+        };
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("decode_packet", stream_id, length, byte_offset).entered();
+        let mut packet = Packet {
+            buffer: Vec::with_capacity(capacity),
+            stream_id,
+            arrival_time: None,
+        };
+        let mut reader = Read::by_ref(&mut self.file).take(length as u64);
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+        let result = if self.compression == ioheader_generated::Compression::None {
+            reader.read_to_end(&mut packet.buffer).map(|_| ()).map_err(ParseError::from)
+        } else {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::trace_span!("decompress", compression = ?self.compression).entered();
+            self.backend.decompress_with_limit(
+                self.compression,
+                &mut reader,
+                &mut packet.buffer,
+                self.max_decompressed_size,
+            )
+        };
+        if let Err(error) = drain(&mut reader, length) {
+            if let ParseError::TruncatedPacket { .. } = error {
+                self.truncated = true;
+                if self.salvage_truncated
+                    && self.id_to_stream.get(&stream_id).map(|stream| &stream.content)
+                        == Some(&StreamContent::Events)
+                {
+                    let events = salvage_events(&packet.buffer);
+                    if !events.is_empty() {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(stream_id, byte_offset, events = events.len(), "salvaged a truncated packet");
+                        return Ok(self.check_packet(Packet::from_events(stream_id, &events), byte_offset));
+                    }
+                }
+            }
+            return Err(error);
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_read(length, start.elapsed());
+        if let Err(error) = result {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(stream_id, byte_offset, %error, "packet decompression failed");
+            return Ok(Err(error));
+        }
+        self.capacities.insert(stream_id, packet.buffer.len());
+        Ok(self.check_packet(packet, byte_offset))
+    }
+
+    fn check_packet(&mut self, mut packet: Packet, byte_offset: i64) -> Result<Packet, ParseError> {
+        if crate::packed::is_packed(&packet.buffer) {
+            let events = crate::packed::unpack(&packet.buffer)?;
+            packet.buffer = Packet::from_events(packet.stream_id, &events).buffer;
+        }
+        let (mut expected_content, width, height) = match self.id_to_stream.get(&packet.stream_id) {
+            Some(stream) => (stream.content.clone(), stream.width, stream.height),
+            None => return Err(ParseError::UnknownStreamId(packet.stream_id)),
+        };
+        // custom modules do not necessarily set a flatbuffers identifier
+        if !matches!(expected_content, StreamContent::Other(_)) {
+            if let Err(error) = packet.expect_identifier(&expected_content.to_string()) {
+                match (self.identifier_mismatch, packet.buffer.get_mut(8..12)) {
+                    (MismatchPolicy::TrustStreamId, Some(identifier)) => {
+                        identifier.copy_from_slice(expected_content.to_string().as_bytes());
+                    }
+                    (MismatchPolicy::TrustIdentifier, Some(identifier)) => {
+                        expected_content = StreamContent::from(String::from_utf8_lossy(identifier).as_ref());
+                    }
+                    _ => return Err(error),
+                }
+            }
+        }
+        if self.verify {
+            match expected_content {
+                StreamContent::Events => {
+                    events_generated::size_prefixed_root_as_event_packet(&packet.buffer).map(|_| ())
+                }
+                StreamContent::Frame => {
+                    frame_generated::size_prefixed_root_as_frame(&packet.buffer).map(|_| ())
+                }
+                StreamContent::Imus => {
+                    imus_generated::size_prefixed_root_as_imu_packet(&packet.buffer).map(|_| ())
+                }
+                StreamContent::Triggers => {
+                    triggers_generated::size_prefixed_root_as_trigger_packet(&packet.buffer).map(|_| ())
+                }
+                StreamContent::BoundingBoxes => {
+                    bounding_boxes_generated::size_prefixed_root_as_bounding_box_packet(&packet.buffer)
+                        .map(|_| ())
+                }
+                StreamContent::Pose => {
+                    pose_generated::size_prefixed_root_as_pose(&packet.buffer).map(|_| ())
+                }
+                StreamContent::Other(_) => Ok(()),
+            }?;
+        }
+        if self.bounds_check != BoundsCheck::Off
+            && expected_content == StreamContent::Events
+            && width > 0
+            && height > 0
+        {
+            self.check_bounds(&packet, width, height, byte_offset)?;
+        }
+        let mut offset = self.timestamp_offset;
+        if self.zero_timestamps {
+            if self.first_timestamp.is_none() {
+                let (elements, first_timestamp, _) = packet.summary(&expected_content)?;
+                if elements > 0 {
+                    self.first_timestamp = Some(first_timestamp);
+                }
+            }
+            offset -= self.first_timestamp.unwrap_or(0);
+        }
+        if offset != 0 {
+            offset_timestamps(&mut packet.buffer, &expected_content, offset)?;
+        }
+        Ok(packet)
+    }
+
+    pub fn compression(&self) -> ioheader_generated::Compression {
+        self.compression
+    }
+
+    /// Reads the file data table written at the end of AEDAT4 files.
+    ///
+    /// The decoder can still be used afterwards, it resumes where it stopped.
+    pub fn file_data_table(&mut self) -> Result<Vec<FileDataEntry>, ParseError> {
+        if self.file_data_position <= 0 {
+            return Err(ParseError::NoFileDataTable);
+        }
+        let seekable = self.file.seekable().ok_or(ParseError::NoFileDataTable)?;
+        seekable.seek(std::io::SeekFrom::Start(self.file_data_position as u64))?;
+        let mut raw_buffer = Vec::new();
+        let result = self.file.read_to_end(&mut raw_buffer);
+        self.file
+            .seekable()
+            .ok_or(ParseError::NoFileDataTable)?
+            .seek(std::io::SeekFrom::Start(self.position as u64))?;
+        result?;
+        let mut buffer = Vec::new();
+        if self.compression == ioheader_generated::Compression::None {
+            buffer = raw_buffer;
+        } else {
+            self.backend.decompress_with_limit(
+                self.compression,
+                &mut &raw_buffer[..],
+                &mut buffer,
+                self.max_decompressed_size,
+            )?;
+        }
+        let options = flatbuffers::VerifierOptions {
+            max_tables: buffer.len(),
+            ..Default::default()
+        };
+        let table = file_data_table_generated::size_prefixed_root_as_file_data_table_with_opts(
+            &options, &buffer,
+        )?;
+        Ok(match table.table() {
+            Some(definitions) => definitions
+                .iter()
+                .map(|definition| {
+                    let packet_info = definition.packet_info().copied().unwrap_or_default();
+                    FileDataEntry {
+                        byte_offset: definition.byte_offset(),
+                        stream_id: packet_info.stream_id() as u32,
+                        size: packet_info.size() as u32,
+                        num_elements: definition.num_elements(),
+                        timestamp_start: definition.timestamp_start(),
+                        timestamp_end: definition.timestamp_end(),
+                    }
+                })
+                .collect(),
+            None => Vec::new(),
+        })
+    }
+
+    /// First and last timestamps of each stream in microseconds, without decoding the file. Streams without
+    /// elements are omitted.
+    ///
+    /// The statistics embedded by the encoder are used if available, then the file data table. Otherwise (unfinished
+    /// recordings), packet headers are scanned without decompressing the packets, and only the first and last
+    /// non-empty packets of each stream are decoded. Timestamps are those stored in the file, without the offsets
+    /// of `DecoderBuilder::timestamp_offset` and `DecoderBuilder::zero_timestamps`.
+    ///
+    /// The decoder can still be used afterwards, it resumes where it stopped.
+    pub fn time_extent(&mut self) -> Result<std::collections::HashMap<u32, (i64, i64)>, ParseError> {
+        let id_to_statistics = match self.statistics()? {
+            Some(id_to_statistics) => Some(id_to_statistics),
+            None if self.file_data_position > 0 => {
+                let entries = self.file_data_table()?;
+                Some(crate::statistics::summarize(&self.id_to_stream, &entries))
+            }
+            None => None,
+        };
+        if let Some(id_to_statistics) = id_to_statistics {
+            return Ok(id_to_statistics
+                .into_iter()
+                .filter(|(_, statistics)| statistics.elements > 0)
+                .map(|(stream_id, statistics)| (stream_id, (statistics.begin_t, statistics.end_t)))
+                .collect());
+        }
+        if self.file.seekable().is_none() {
+            return Err(ParseError::Usage("time extents require a file source".to_owned()));
+        }
+        let result = self.scan_time_extent();
+        self.file
+            .seekable()
+            .expect("the source is seekable")
+            .seek(std::io::SeekFrom::Start(self.position as u64))?;
+        result
+    }
+
+    /// Time between the first and last timestamps of all the streams in microseconds (see `time_extent`),
+    /// or None if the file has no elements.
+    pub fn duration(&mut self) -> Result<Option<i64>, ParseError> {
+        Ok(self
+            .time_extent()?
+            .into_values()
+            .reduce(|(begin_t, end_t), (stream_begin_t, stream_end_t)| {
+                (begin_t.min(stream_begin_t), end_t.max(stream_end_t))
+            })
+            .map(|(begin_t, end_t)| end_t - begin_t))
+    }
+
+    fn scan_time_extent(&mut self) -> Result<std::collections::HashMap<u32, (i64, i64)>, ParseError> {
+        let seekable = self.file.seekable().expect("the source is seekable");
+        let file_length = seekable.seek(std::io::SeekFrom::End(0))?;
+        seekable.seek(std::io::SeekFrom::Start(MAGIC_NUMBER.len() as u64))?;
+        let mut length = [0; 4];
+        self.file.read_exact(&mut length)?;
+        let mut position = MAGIC_NUMBER.len() as u64 + 4 + u32::from_le_bytes(length) as u64;
+        self.file
+            .seekable()
+            .expect("the source is seekable")
+            .seek(std::io::SeekFrom::Start(position))?;
+        let mut id_to_packets: std::collections::HashMap<u32, Vec<(i64, u32)>> =
+            std::collections::HashMap::new();
+        loop {
+            let header = PacketFramer::new(&mut self.file)
+                .max_packet_size(self.max_packet_size)
+                .offset(position)
+                .read_header();
+            let (stream_id, length) = match header {
+                Ok(Some(header)) => (header.stream_id, header.length),
+                Ok(None)
+                | Err(ParseError::TruncatedPacket { .. })
+                | Err(ParseError::PacketTooLarge { .. }) => break,
+                Err(error) => return Err(error),
+            };
+            // the last packet of an interrupted recording may be incomplete
+            if !self.id_to_stream.contains_key(&stream_id) || position + 8 + length as u64 > file_length {
+                break;
+            }
+            id_to_packets
+                .entry(stream_id)
+                .or_default()
+                .push((position as i64 + 8, length));
+            drain(&mut Read::by_ref(&mut self.file).take(length as u64), length)?;
+            position += 8 + length as u64;
+        }
+        let mut id_to_extent = std::collections::HashMap::new();
+        for (stream_id, packets) in id_to_packets {
+            let mut begin_t = None;
+            for (byte_offset, length) in packets.iter() {
+                begin_t = self.read_raw_summary(stream_id, *byte_offset, *length)?.map(|(begin_t, _)| begin_t);
+                if begin_t.is_some() {
+                    break;
+                }
+            }
+            let mut end_t = None;
+            for (byte_offset, length) in packets.iter().rev() {
+                end_t = self.read_raw_summary(stream_id, *byte_offset, *length)?.map(|(_, end_t)| end_t);
+                if end_t.is_some() {
+                    break;
+                }
+            }
+            if let (Some(begin_t), Some(end_t)) = (begin_t, end_t) {
+                id_to_extent.insert(stream_id, (begin_t, end_t));
+            }
+        }
+        Ok(id_to_extent)
+    }
+
+    /// Decodes the packet at `byte_offset` without checks or timestamp offsets, and returns its first and last
+    /// timestamps (None if it is empty).
+    fn read_raw_summary(&mut self, stream_id: u32, byte_offset: i64, length: u32) -> Result<Option<(i64, i64)>, ParseError> {
+        self.file
+            .seekable()
+            .expect("the source is seekable")
+            .seek(std::io::SeekFrom::Start(byte_offset as u64))?;
+        let mut packet = Packet {
+            buffer: Vec::new(),
+            stream_id,
+            arrival_time: None,
+        };
+        let mut reader = Read::by_ref(&mut self.file).take(length as u64);
+        if self.compression == ioheader_generated::Compression::None {
+            reader.read_to_end(&mut packet.buffer)?;
+        } else {
+            self.backend.decompress_with_limit(
+                self.compression,
+                &mut reader,
+                &mut packet.buffer,
+                self.max_decompressed_size,
+            )?;
+        }
+        if crate::packed::is_packed(&packet.buffer) {
+            let events = crate::packed::unpack(&packet.buffer)?;
+            packet.buffer = Packet::from_events(stream_id, &events).buffer;
+        }
+        let (elements, begin_t, end_t) = packet.summary(&self.id_to_stream[&stream_id].content)?;
+        Ok((elements > 0).then_some((begin_t, end_t)))
+    }
+
+    /// Compares the packets of the file with the checksums written by `Encoder::with_checksums`.
+    ///
+    /// The decoder can still be used afterwards, it resumes where it stopped.
+    pub fn verify_integrity<P: std::convert::AsRef<std::path::Path>>(
+        &mut self,
+        checksums: P,
+    ) -> Result<crate::integrity::IntegrityReport, ParseError> {
+        let (checksum, packets) = crate::integrity::read_sidecar(checksums)?;
+        let mut corrupted = Vec::new();
+        let mut bytes = Vec::new();
+        let mut result = Ok(());
+        for packet in packets.iter() {
+            let seekable = self.file.seekable().ok_or_else(|| {
+                ParseError::Usage("integrity checks require a seekable source".to_owned())
+            })?;
+            result = seekable
+                .seek(std::io::SeekFrom::Start((packet.byte_offset - 8).max(0) as u64))
+                .map(|_| ());
+            if result.is_err() {
+                break;
+            }
+            bytes.clear();
+            match (&mut self.file)
+                .take(packet.size as u64 + 8)
+                .read_to_end(&mut bytes)
+            {
+                Ok(_) => {
+                    if bytes.len() != packet.size as usize + 8
+                        || checksum.compute(&bytes) != packet.value
+                    {
+                        corrupted.push(*packet);
+                    }
+                }
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+        if let Some(seekable) = self.file.seekable() {
+            seekable.seek(std::io::SeekFrom::Start(self.position as u64))?;
+        }
+        result?;
+        Ok(crate::integrity::IntegrityReport {
+            packets: packets.len(),
+            corrupted,
+        })
+    }
+
+    /// Iterates over the packets from the end of the file, using the file data table.
+    pub fn iter_rev(mut self) -> Result<ReversePackets, ParseError> {
+        let entries = self.file_data_table()?;
+        if self.zero_timestamps && self.first_timestamp.is_none() {
+            self.first_timestamp = table_first_timestamp(&entries);
+        }
+        Ok(ReversePackets {
+            decoder: self,
+            entries,
+        })
+    }
+
+    /// Iterates over the packets of the frame streams, seeking from one to the next with the file data table.
+    ///
+    /// Event packets are never read, hence frames can be extracted from large recordings quickly.
+    pub fn frames_only(mut self) -> Result<FramePackets, ParseError> {
+        let entries = self.file_data_table()?;
+        if self.zero_timestamps && self.first_timestamp.is_none() {
+            self.first_timestamp = table_first_timestamp(&entries);
+        }
+        let entries: Vec<FileDataEntry> = entries
+            .into_iter()
+            .filter(|entry| {
+                self.id_to_stream
+                    .get(&entry.stream_id)
+                    .is_some_and(|stream| stream.content == StreamContent::Frame)
+                    && self
+                        .stream_ids
+                        .as_ref()
+                        .is_none_or(|stream_ids| stream_ids.contains(&entry.stream_id))
+            })
+            .collect();
+        Ok(FramePackets {
+            decoder: self,
+            entries: entries.into_iter(),
+        })
+    }
+
+    /// Returns the last `count` packets in file order, without reading the rest of the file.
+    pub fn last_packets(self, count: usize) -> Result<Vec<Packet>, ParseError> {
+        let mut packets = self
+            .iter_rev()?
+            .take(count)
+            .collect::<Result<Vec<Packet>, ParseError>>()?;
+        packets.reverse();
+        Ok(packets)
+    }
+
+    fn check_bounds(&mut self, packet: &Packet, width: u16, height: u16, byte_offset: i64) -> Result<(), ParseError> {
+        let elements = match events_generated::size_prefixed_root_as_event_packet(&packet.buffer)?.elements() {
+            Some(elements) => elements,
+            None => return Ok(()),
+        };
+        for event in elements {
+            if event.x() < 0 || event.y() < 0 || event.x() as u16 >= width || event.y() as u16 >= height {
+                if self.bounds_check == BoundsCheck::Error {
+                    return Err(ParseError::EventOutOfBounds {
+                        x: event.x(),
+                        y: event.y(),
+                        width,
+                        height,
+                        packet: byte_offset,
+                    });
+                }
+                self.out_of_bounds_events += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps reading a file that is still being written (for instance by a camera), instead of stopping at its end.
+    ///
+    /// At the end of the available data, the iterator checks for new data every `poll_interval`.
+    /// It stops once the writer finalizes the file (writes its file data table), or after `Follow::idle_timeout`.
+    /// Memory-mapped decoders cannot see new data.
+    pub fn follow(mut self, poll_interval: std::time::Duration) -> Result<Follow, ParseError> {
+        if self.file.seekable().is_none() {
+            return Err(ParseError::Usage(
+                "following requires a file source".to_owned(),
+            ));
+        }
+        Ok(Follow {
+            decoder: self,
+            poll_interval,
+            idle_timeout: None,
+            file_length: 0,
+            last_data: std::time::Instant::now(),
+        })
+    }
+
+    /// Applies the recovery policy and records latency, returns None if the packet is skipped.
+    fn deliver(
+        &mut self,
+        result: Result<Packet, ParseError>,
+        arrival_time: Option<std::time::SystemTime>,
+    ) -> Option<Result<Packet, ParseError>> {
+        #[cfg(feature = "metrics")]
+        match &result {
+            Ok(packet) => {
+                if let Some(stream) = self.id_to_stream.get(&packet.stream_id) {
+                    crate::metrics::record_packet(packet, &packet.content(&stream.content));
+                }
+            }
+            Err(error) => crate::metrics::record_error(error),
+        }
+        match result {
+            Err(ParseError::IdentifierMismatch { .. }) if self.identifier_mismatch == MismatchPolicy::Skip => {
+                self.skipped_packets += 1;
+                None
+            }
+            Err(_error) if self.recover => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(error = %_error, "skipped a malformed packet");
+                self.skipped_packets += 1;
+                None
+            }
+            Ok(mut packet) => {
+                packet.arrival_time = arrival_time;
+                if let Some(latency) = self.latency.as_mut() {
+                    latency.record_packet(&packet);
+                }
+                Some(Ok(packet))
+            }
+            result => Some(result),
+        }
+    }
+
+    /// Reads the next packet without decompressing it, so that it can be decompressed by another thread.
+    ///
+    /// The packet is checked by `finish_raw`.
+    pub(crate) fn read_raw(&mut self) -> Option<Result<RawPacket, ParseError>> {
+        let (stream_id, length) = match self.read_packet_header()? {
+            Ok(header) => header,
+            Err(error) => return Some(Err(error)),
+        };
+        let arrival_time = self.latency.as_ref().map(|_| std::time::SystemTime::now());
+        let mut packet = RawPacket {
+            stream_id,
+            byte_offset: self.position - length as i64,
+            data: Vec::with_capacity((length as usize).min(MAXIMUM_PREALLOCATION)),
+            arrival_time,
+            truncated: false,
+        };
+        let mut reader = Read::by_ref(&mut self.file).take(length as u64);
+        let result = reader
+            .read_to_end(&mut packet.data)
+            .map_err(ParseError::from)
+            .and_then(|_| drain(&mut reader, length));
+        if let Err(error) = result {
+            if let ParseError::TruncatedPacket { .. } = error {
+                self.truncated = true;
+                if self.salvage_truncated {
+                    packet.truncated = true;
+                    return Some(Ok(packet));
+                }
+            }
+            return Some(Err(error));
+        }
+        Some(Ok(packet))
+    }
+
+    /// Decompresses the packets returned by `read_raw`, and can be moved to another thread.
+    pub(crate) fn decompressor(&self) -> Decompressor {
+        Decompressor {
+            backend: self.backend.clone(),
+            compression: self.compression,
+            limit: self.max_decompressed_size,
+        }
+    }
+
+    /// Checks a packet returned by `read_raw` and decompressed by `decompressor`, returns None if it is skipped.
+    pub(crate) fn finish_raw(
+        &mut self,
+        packet: RawPacket,
+        buffer: Vec<u8>,
+        result: Result<(), ParseError>,
+    ) -> Option<Result<Packet, ParseError>> {
+        if packet.truncated {
+            if self.id_to_stream.get(&packet.stream_id).map(|stream| &stream.content) == Some(&StreamContent::Events) {
+                let events = salvage_events(&buffer);
+                if !events.is_empty() {
+                    let result = self.check_packet(Packet::from_events(packet.stream_id, &events), packet.byte_offset);
+                    return self.deliver(result, packet.arrival_time);
+                }
+            }
+            return None;
+        }
+        let result = result.and_then(|()| {
+            self.check_packet(
+                Packet {
+                    buffer,
+                    stream_id: packet.stream_id,
+                    arrival_time: None,
+                },
+                packet.byte_offset,
+            )
+        });
+        self.deliver(result, packet.arrival_time)
+    }
+
+    /// Position in the file of the end of the last packet read.
+    pub(crate) fn position(&self) -> i64 {
+        self.position
+    }
+
+    /// Resumes decoding at the packet whose data starts at `byte_offset` (see `FileDataEntry::byte_offset`).
+    pub(crate) fn seek_to_packet(&mut self, byte_offset: i64) -> Result<(), ParseError> {
+        let seekable = self.file.seekable().ok_or(ParseError::NoFileDataTable)?;
+        seekable.seek(std::io::SeekFrom::Start((byte_offset - 8) as u64))?;
+        self.position = byte_offset - 8;
+        Ok(())
+    }
+
+    /// Returns the position of the decoder, before the packet that the next call to `next` returns.
+    ///
+    /// Storing a checkpoint after each processed packet gives exactly-once processing across restarts.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            position: self.position,
+            header_length: self.header_length,
+            description_hash: crate::integrity::Checksum::Crc32.compute(self.description.as_bytes()),
+            first_timestamp: self.first_timestamp,
+            skipped_packets: self.skipped_packets,
+            out_of_bounds_events: self.out_of_bounds_events,
+        }
+    }
+
+    /// Moves a decoder of the same file to a checkpoint. The source must be seekable.
+    ///
+    /// The file is identified by the length of its header and a hash of its description, hence
+    /// recordings made with the same configuration cannot be told apart.
+    ///
+    /// Options (stream selection, timestamp offsets...) come from the decoder, and should match those of the
+    /// decoder that made the checkpoint. The shift of `DecoderBuilder::zero_timestamps` is restored.
+    pub fn resume(&mut self, checkpoint: &Checkpoint) -> Result<(), ParseError> {
+        if checkpoint.header_length != self.header_length
+            || checkpoint.description_hash
+                != crate::integrity::Checksum::Crc32.compute(self.description.as_bytes())
+        {
+            return Err(ParseError::InvalidCheckpoint(
+                "the checkpoint belongs to another file".to_owned(),
+            ));
+        }
+        let seekable = self.file.seekable().ok_or_else(|| {
+            ParseError::InvalidCheckpoint("the source is not seekable".to_owned())
+        })?;
+        seekable.seek(std::io::SeekFrom::Start(checkpoint.position as u64))?;
+        self.position = checkpoint.position;
+        self.first_timestamp = checkpoint.first_timestamp;
+        self.skipped_packets = checkpoint.skipped_packets;
+        self.out_of_bounds_events = checkpoint.out_of_bounds_events;
+        Ok(())
+    }
+
+    /// Number of events outside their sensor with `BoundsCheck::Count`.
+    pub fn out_of_bounds_events(&self) -> usize {
+        self.out_of_bounds_events
+    }
+
+    /// Whether the source ended in the middle of a packet.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Number of malformed packets skipped in recovery mode, or with `MismatchPolicy::Skip`.
+    pub fn skipped_packets(&self) -> usize {
+        self.skipped_packets
+    }
+
+    /// Original timestamp subtracted from every packet when `DecoderBuilder::zero_timestamps` is set.
+    ///
+    /// Returns None until a packet with at least one element has been decoded.
+    pub fn first_timestamp(&self) -> Option<i64> {
+        self.first_timestamp
+    }
+
+    /// Latency statistics, if arrival times are recorded (see `DecoderBuilder::record_arrival`).
+    pub fn latency(&self) -> Option<&crate::latency::LatencyHistogram> {
+        self.latency.as_ref()
+    }
+
+    /// Configuration of the DV runtime (camera biases, device information...), if it was downloaded
+    /// (see `DecoderBuilder::runtime_config`).
+    pub fn runtime_config(&self) -> Option<&crate::runtime::RuntimeConfig> {
+        self.runtime_config.as_ref()
+    }
+
+    /// XML description of the streams, as found in the IOHeader.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// Position of a decoder between two packets, with the state needed to resume (see `Decoder::checkpoint`).
+///
+/// The string representation (`Display` and `FromStr`) can be stored in a database or a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    position: i64,
+    /// Identifies the file, together with `description_hash`.
+    header_length: i64,
+    /// CRC-32 of the description.
+    description_hash: u32,
+    first_timestamp: Option<i64>,
+    skipped_packets: usize,
+    out_of_bounds_events: usize,
+}
+
+impl Checkpoint {
+    /// Byte offset of the next packet header.
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+}
+
+impl std::fmt::Display for Checkpoint {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            formatter,
+            "aedat-checkpoint-2:{}:{}:{:08x}:{}:{}:{}",
+            self.position,
+            self.header_length,
+            self.description_hash,
+            match self.first_timestamp {
+                Some(first_timestamp) => first_timestamp.to_string(),
+                None => "-".to_owned(),
+            },
+            self.skipped_packets,
+            self.out_of_bounds_events
+        )
+    }
+}
+
+impl std::str::FromStr for Checkpoint {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = string.trim().split(':').collect();
+        if fields.len() != 7 || fields[0] != "aedat-checkpoint-2" {
+            return Err(ParseError::InvalidCheckpoint(string.to_owned()));
+        }
+        Ok(Checkpoint {
+            position: fields[1].parse()?,
+            header_length: fields[2].parse()?,
+            description_hash: u32::from_str_radix(fields[3], 16)
+                .map_err(|_| ParseError::InvalidCheckpoint(string.to_owned()))?,
+            first_timestamp: match fields[4] {
+                "-" => None,
+                first_timestamp => Some(first_timestamp.parse()?),
+            },
+            skipped_packets: fields[5].parse()?,
+            out_of_bounds_events: fields[6].parse()?,
+        })
+    }
+}
+
+impl Iterator for Decoder {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (stream_id, length) = match self.read_packet_header()? {
+                Ok(content) => content,
+                Err(error) => {
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::record_error(&error);
+                    return Some(Err(error));
+                }
+            };
+            // decompression time is not part of the transport latency
+            let arrival_time = self.latency.as_ref().map(|_| std::time::SystemTime::now());
+            let result = match self.read_packet(stream_id, length, self.position - length as i64) {
+                Ok(result) => result,
+                Err(ParseError::TruncatedPacket { .. }) if self.salvage_truncated => return None,
+                Err(error) => {
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::record_error(&error);
+                    return Some(Err(error));
+                }
+            };
+            if let Some(result) = self.deliver(result, arrival_time) {
+                return Some(result);
+            }
+        }
+    }
+}
+
+/// Formats an error with its sources (for instance the IO error behind `ParseError::Io`).
+pub(crate) fn describe(error: &dyn std::error::Error) -> String {
+    let mut description = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        description.push_str(&format!(": {}", error));
+        source = error.source();
+    }
+    description
+}
+
+/// Appends exactly `length` bytes to `buffer`, or fails like `Read::read_exact` if the source ends first.
+///
+/// Unlike `read_exact`, the buffer grows as the bytes arrive (see `MAXIMUM_PREALLOCATION`).
+pub(crate) fn read_length<R: Read + ?Sized>(
+    source: &mut R,
+    buffer: &mut Vec<u8>,
+    length: u32,
+) -> std::io::Result<()> {
+    buffer.reserve((length as usize).min(MAXIMUM_PREALLOCATION));
+    let read = (&mut *source).take(length as u64).read_to_end(buffer)?;
+    if read < length as usize {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "failed to fill whole buffer",
+        ));
+    }
+    Ok(())
+}
+
+/// Consumes the rest of a packet of `length` bytes, and fails if the source ends before the packet does.
+fn drain<R: Read>(reader: &mut std::io::Take<R>, length: u32) -> Result<(), ParseError> {
+    std::io::copy(reader, &mut std::io::sink())?;
+    if reader.limit() > 0 {
+        return Err(ParseError::TruncatedPacket {
+            expected: 8 + length as u64,
+            got: 8 + length as u64 - reader.limit(),
+        });
+    }
+    Ok(())
+}
+
+/// Returns the complete events at the beginning of a truncated event packet.
+///
+/// The flatbuffers builder writes the events vector first, hence at the end of the buffer, after the root table.
+fn salvage_events(buffer: &[u8]) -> Vec<events_generated::Event> {
+    let read_u32 = |offset: usize| -> Option<usize> {
+        Some(u32::from_le_bytes(buffer.get(offset..offset + 4)?.try_into().ok()?) as usize)
+    };
+    let vector = (|| {
+        if crate::packed::is_packed(buffer) {
+            return None;
+        }
+        let table = 4 + read_u32(4)?;
+        let vtable = usize::try_from(table as i64 - read_u32(table)? as i32 as i64).ok()?;
+        let vtable_length = u16::from_le_bytes(buffer.get(vtable..vtable + 2)?.try_into().ok()?);
+        if vtable_length < 6 {
+            return None;
+        }
+        let field = u16::from_le_bytes(buffer.get(vtable + 4..vtable + 6)?.try_into().ok()?) as usize;
+        if field == 0 {
+            return None;
+        }
+        let vector = table + field + read_u32(table + field)?;
+        Some((vector + 4, read_u32(vector)?))
+    })();
+    match vector {
+        Some((begin, length)) if begin <= buffer.len() => buffer[begin..]
+            .chunks_exact(16)
+            .take(length)
+            .map(|bytes| events_generated::Event(bytes.try_into().unwrap()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Packets in reverse file order (see `Decoder::iter_rev`).
+pub struct ReversePackets {
+    decoder: Decoder,
+    entries: Vec<FileDataEntry>,
+}
+
+impl Iterator for ReversePackets {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = self.entries.pop()?;
+            if let Some(stream_ids) = &self.decoder.stream_ids {
+                if !stream_ids.contains(&entry.stream_id) {
+                    continue;
+                }
+            }
+            let result = match self.decoder.file.seekable() {
+                Some(seekable) => seekable
+                    .seek(std::io::SeekFrom::Start(entry.byte_offset as u64))
+                    .map_err(ParseError::from)
+                    .and_then(|_| self.decoder.read_packet(entry.stream_id, entry.size, entry.byte_offset)),
+                None => Err(ParseError::NoFileDataTable),
+            };
+            match result {
+                Ok(Err(ParseError::IdentifierMismatch { .. }))
+                    if self.decoder.identifier_mismatch == MismatchPolicy::Skip =>
+                {
+                    self.decoder.skipped_packets += 1
+                }
+                Ok(Err(_)) if self.decoder.recover => self.decoder.skipped_packets += 1,
+                Ok(result) => return Some(result),
+                Err(error) => {
+                    self.entries.clear();
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
+
+/// Packets of the frame streams in file order (see `Decoder::frames_only`).
+pub struct FramePackets {
+    decoder: Decoder,
+    entries: std::vec::IntoIter<FileDataEntry>,
+}
+
+impl FramePackets {
+    /// Number of remaining frames.
+    pub fn remaining(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl Iterator for FramePackets {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = self.entries.next()?;
+            let result = match self.decoder.file.seekable() {
+                Some(seekable) => seekable
+                    .seek(std::io::SeekFrom::Start(entry.byte_offset as u64))
+                    .map_err(ParseError::from)
+                    .and_then(|_| self.decoder.read_packet(entry.stream_id, entry.size, entry.byte_offset)),
+                None => Err(ParseError::NoFileDataTable),
+            };
+            match result {
+                Ok(Err(_)) if self.decoder.recover => self.decoder.skipped_packets += 1,
+                Ok(result) => return Some(result),
+                Err(error) => {
+                    self.entries = Vec::new().into_iter();
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
+
+/// Packets of a file that is still being written (see `Decoder::follow`).
+pub struct Follow {
+    decoder: Decoder,
+    poll_interval: std::time::Duration,
+    idle_timeout: Option<std::time::Duration>,
+    file_length: u64,
+    last_data: std::time::Instant,
+}
+
+enum FollowState {
+    Packet(u32, u32),
+    Wait,
+    End,
+}
+
+impl Follow {
+    /// Stops after `timeout` without new data, even if the file has not been finalized.
+    pub fn idle_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    pub fn decoder(&self) -> &Decoder {
+        &self.decoder
+    }
+
+    fn finished(&self) -> bool {
+        self.decoder.file_data_position > -1 && self.decoder.position >= self.decoder.file_data_position
+    }
+
+    /// Updates the file length and the file data table position, and seeks back to the next packet.
+    fn refresh(&mut self) -> Result<(), ParseError> {
+        let seekable = self.decoder.file.seekable().expect("follow checks that the source is seekable");
+        self.file_length = seekable.seek(std::io::SeekFrom::End(0))?;
+        seekable.seek(std::io::SeekFrom::Start(MAGIC_NUMBER.len() as u64))?;
+        let mut length = [0; 4];
+        self.decoder.file.read_exact(&mut length)?;
+        if u32::from_le_bytes(length) > self.decoder.max_packet_size {
+            return Err(ParseError::PacketTooLarge {
+                size: u32::from_le_bytes(length),
+                limit: self.decoder.max_packet_size,
+            });
+        }
+        let mut buffer = length.to_vec();
+        read_length(&mut self.decoder.file, &mut buffer, u32::from_le_bytes(length))?;
+        // the writer may be rewriting the header, in which case the next refresh reads it
+        if let Ok(ioheader) = ioheader_generated::size_prefixed_root_as_ioheader(&buffer) {
+            self.decoder.file_data_position = ioheader.file_data_position();
+        }
+        self.decoder
+            .file
+            .seekable()
+            .expect("follow checks that the source is seekable")
+            .seek(std::io::SeekFrom::Start(self.decoder.position as u64))?;
+        Ok(())
+    }
+
+    /// Reads the next packet header if the whole packet is available.
+    fn poll(&mut self) -> Result<FollowState, ParseError> {
+        if self.finished() {
+            return Ok(FollowState::End);
+        }
+        let position = self.decoder.position as u64;
+        if position + 8 > self.file_length {
+            self.refresh()?;
+            if self.finished() {
+                return Ok(FollowState::End);
+            }
+            if position + 8 > self.file_length {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(position, file_length = self.file_length, "waiting for data");
+                return Ok(FollowState::Wait);
+            }
+        }
+        let (stream_id, length) = match PacketFramer::new(&mut self.decoder.file)
+            .max_packet_size(self.decoder.max_packet_size)
+            .offset(position)
+            .read_header()?
+        {
+            Some(header) => (header.stream_id, header.length),
+            // the file was truncated since the last refresh
+            None => return Ok(FollowState::Wait),
+        };
+        // an unknown stream id may be the start of the file data table, before the header points to it
+        if !self.decoder.id_to_stream.contains_key(&stream_id) || position + 8 + length as u64 > self.file_length {
+            self.refresh()?;
+            return Ok(if self.finished() { FollowState::End } else { FollowState::Wait });
+        }
+        self.decoder.position += 8i64 + length as i64;
+        Ok(FollowState::Packet(stream_id, length))
+    }
+}
+
+impl Iterator for Follow {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.poll() {
+                Ok(FollowState::Packet(stream_id, length)) => {
+                    self.last_data = std::time::Instant::now();
+                    let arrival_time = self.decoder.latency.as_ref().map(|_| std::time::SystemTime::now());
+                    if let Some(stream_ids) = &self.decoder.stream_ids {
+                        if !stream_ids.contains(&stream_id) {
+                            if let Err(error) = drain(&mut Read::by_ref(&mut self.decoder.file).take(length as u64), length) {
+                                return Some(Err(error));
+                            }
+                            continue;
+                        }
+                    }
+                    let byte_offset = self.decoder.position - length as i64;
+                    let result = match self.decoder.read_packet(stream_id, length, byte_offset) {
+                        Ok(result) => result,
+                        Err(error) => return Some(Err(error)),
+                    };
+                    if let Some(result) = self.decoder.deliver(result, arrival_time) {
+                        return Some(result);
+                    }
+                }
+                Ok(FollowState::Wait) => {
+                    if let Some(idle_timeout) = self.idle_timeout {
+                        if self.last_data.elapsed() >= idle_timeout {
+                            return None;
+                        }
+                    }
+                    std::thread::sleep(self.poll_interval);
+                }
+                Ok(FollowState::End) => return None,
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+fn offset_timestamp(buffer: &mut [u8], position: usize, offset: i64) -> Result<(), ParseError> {
+    match buffer.get_mut(position..position.saturating_add(8)) {
+        Some(bytes) => {
+            let t = i64::from_le_bytes((&*bytes).try_into().unwrap()) + offset;
+            bytes.copy_from_slice(&t.to_le_bytes());
+            Ok(())
+        }
+        None => Err(ParseError::General("flatbuffer timestamp out of bounds".to_string())),
+    }
+}
+
+/// Adds an offset to the timestamps of a size-prefixed packet, in place.
+///
+/// Timestamps absent from the buffer (flatbuffers omit default values) and timestamps of unknown packets
+/// are left untouched.
+fn offset_timestamps(buffer: &mut [u8], content: &StreamContent, offset: i64) -> Result<(), ParseError> {
+    let root = flatbuffer::root(buffer)?;
+    match content {
+        StreamContent::Frame => {
+            for field in 0..5 {
+                if let Some(position) = flatbuffer::field(buffer, root, field)? {
+                    offset_timestamp(buffer, position, offset)?;
+                }
+            }
+        }
+        StreamContent::Pose => {
+            if let Some(position) = flatbuffer::field(buffer, root, 0)? {
+                offset_timestamp(buffer, position, offset)?;
+            }
+        }
+        StreamContent::Events
+        | StreamContent::Imus
+        | StreamContent::Triggers
+        | StreamContent::BoundingBoxes => {
+            let elements = match flatbuffer::field(buffer, root, 0)? {
+                Some(position) => flatbuffer::follow(buffer, position)?,
+                None => return Ok(()),
+            };
+            for index in 0..flatbuffer::read_u32(buffer, elements)? as usize {
+                let element = elements + 4 + index * if *content == StreamContent::Events { 16 } else { 4 };
+                if *content == StreamContent::Events {
+                    offset_timestamp(buffer, element, offset)?;
+                } else {
+                    let table = flatbuffer::follow(buffer, element)?;
+                    if let Some(position) = flatbuffer::field(buffer, table, 0)? {
+                        offset_timestamp(buffer, position, offset)?;
+                    }
+                }
+            }
+        }
+        StreamContent::Other(_) => {}
+    }
+    Ok(())
+}
+
+/// Decodes a recording that DV split into several files as a single packet stream.
+///
+/// Packet timestamps are absolute in AEDAT4 files, hence packets from consecutive files
+/// need no rebasing.
+pub struct ChainedDecoder {
+    pub id_to_stream: std::collections::HashMap<u32, Stream>,
+    first: std::path::PathBuf,
+    /// Decoder of the file being read, None once its packets are exhausted.
+    decoder: Option<Decoder>,
+    /// Files that have not been opened yet, which are opened one at a time.
+    paths: std::collections::VecDeque<std::path::PathBuf>,
+}
+
+impl ChainedDecoder {
+    /// Checks that every file has the same streams as the first one, but only keeps the first one open.
+    pub fn new_from_files<P: std::convert::AsRef<std::path::Path>>(paths: &[P]) -> Result<Self, ParseError> {
+        let first = match paths.first() {
+            Some(content) => content.as_ref().to_path_buf(),
+            None => return Err(ParseError::NoFiles),
+        };
+        let decoder = Decoder::new_from_file(&first)?;
+        let mut chained = ChainedDecoder {
+            id_to_stream: decoder.id_to_stream.clone(),
+            first,
+            decoder: Some(decoder),
+            paths: std::collections::VecDeque::with_capacity(paths.len() - 1),
+        };
+        for path in paths[1..].iter().map(|path| path.as_ref()) {
+            chained.open(path)?;
+            chained.paths.push_back(path.to_path_buf());
+        }
+        Ok(chained)
+    }
+
+    fn open(&self, path: &std::path::Path) -> Result<Decoder, ParseError> {
+        let decoder = Decoder::new_from_file(path)?;
+        if decoder.id_to_stream.len() != self.id_to_stream.len()
+            || decoder.id_to_stream.iter().any(|(id, stream)| {
+                match self.id_to_stream.get(id) {
+                    Some(content) => {
+                        content.content != stream.content
+                            || content.width != stream.width
+                            || content.height != stream.height
+                    }
+                    None => true,
+                }
+            })
+        {
+            return Err(ParseError::InconsistentStreams {
+                first: self.first.clone(),
+                path: path.to_path_buf(),
+            });
+        }
+        Ok(decoder)
+    }
+}
+
+impl Iterator for ChainedDecoder {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.decoder.as_mut().and_then(|decoder| decoder.next()) {
+                return Some(result);
+            }
+            self.decoder = None;
+            let path = self.paths.pop_front()?;
+            // the file may have changed since new_from_files checked it
+            match self.open(&path) {
+                Ok(decoder) => self.decoder = Some(decoder),
+                Err(error) => return Some(Err(error)),
+            }
+        }
     }
 }
@@ -0,0 +1,164 @@
+//! Decoding throughput measurements, to compare codecs and sources on the local hardware.
+
+use crate::base::{ioheader_generated::Compression, Decoder, Packet, ParseError, StreamContent};
+use crate::encoder::Encoder;
+
+/// How the benchmarked decoder reads the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    /// Buffered file reads (`Decoder::new_from_file`).
+    File,
+    /// Memory-mapped file (`Decoder::new_from_file_mmap`).
+    #[cfg(feature = "mmap")]
+    Mmap,
+    /// Bytes already in memory (`Decoder::from_bytes`), which excludes IO.
+    Memory,
+}
+
+/// Decoding throughput for one codec and one source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkResult {
+    pub compression: Compression,
+    pub source: SourceKind,
+    /// Size of the file with this codec.
+    pub file_bytes: u64,
+    pub packets: u64,
+    pub events: u64,
+    /// Time taken to decode every packet and unpack the events.
+    pub duration: std::time::Duration,
+}
+
+impl BenchmarkResult {
+    pub fn packets_per_second(&self) -> f64 {
+        self.packets as f64 / self.duration.as_secs_f64()
+    }
+
+    pub fn events_per_second(&self) -> f64 {
+        self.events as f64 / self.duration.as_secs_f64()
+    }
+}
+
+/// See `benchmark_file`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkReport {
+    pub results: Vec<BenchmarkResult>,
+}
+
+impl std::fmt::Display for BenchmarkReport {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for result in self.results.iter() {
+            writeln!(
+                formatter,
+                "{:?} from {:?} ({} bytes): {:.0} packets/s, {:.3} Mev/s",
+                result.compression,
+                result.source,
+                result.file_bytes,
+                result.packets_per_second(),
+                result.events_per_second() / 1e6
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Rewrites a recording with another codec.
+pub fn transcode<
+    P: std::convert::AsRef<std::path::Path>,
+    Q: std::convert::AsRef<std::path::Path>,
+>(
+    input: P,
+    output: Q,
+    compression: Compression,
+) -> Result<(), ParseError> {
+    let decoder = Decoder::new_from_file(input)?;
+    let mut encoder = Encoder::new_to_file(output, decoder.id_to_stream.clone(), compression)?;
+    for packet in decoder {
+        encoder.write(&packet?)?;
+    }
+    encoder.finish()
+}
+
+/// Transcodes the recording with every codec enabled by cargo features (in the temporary directory),
+/// and measures the decoding throughput of each codec from each source type.
+pub fn benchmark_file<P: std::convert::AsRef<std::path::Path>>(
+    path: P,
+) -> Result<BenchmarkReport, ParseError> {
+    let mut results = Vec::new();
+    for compression in [
+        Compression::None,
+        Compression::Lz4,
+        Compression::Lz4High,
+        Compression::Zstd,
+        Compression::ZstdHigh,
+    ] {
+        let transcoded = std::env::temp_dir().join(format!(
+            "aedat-benchmark-{}-{:?}.aedat4",
+            std::process::id(),
+            compression
+        ));
+        match transcode(&path, &transcoded, compression) {
+            Ok(()) => (),
+            Err(ParseError::CompressionDisabled { .. }) => {
+                let _ = std::fs::remove_file(&transcoded);
+                continue;
+            }
+            Err(error) => {
+                let _ = std::fs::remove_file(&transcoded);
+                return Err(error);
+            }
+        }
+        let result = benchmark_sources(&transcoded, compression, &mut results);
+        let _ = std::fs::remove_file(&transcoded);
+        result?;
+    }
+    Ok(BenchmarkReport { results })
+}
+
+fn benchmark_sources(
+    path: &std::path::Path,
+    compression: Compression,
+    results: &mut Vec<BenchmarkResult>,
+) -> Result<(), ParseError> {
+    let file_bytes = std::fs::metadata(path)?.len();
+    // the timer starts after the decoder has read the header (and copied the bytes for `SourceKind::Memory`)
+    let mut run = |source: SourceKind, decoder: Decoder| -> Result<(), ParseError> {
+        let begin = std::time::Instant::now();
+        let (packets, events) = decode(decoder)?;
+        results.push(BenchmarkResult {
+            compression,
+            source,
+            file_bytes,
+            packets,
+            events,
+            duration: begin.elapsed(),
+        });
+        Ok(())
+    };
+    run(SourceKind::File, Decoder::new_from_file(path)?)?;
+    #[cfg(feature = "mmap")]
+    run(SourceKind::Mmap, Decoder::new_from_file_mmap(path)?)?;
+    run(
+        SourceKind::Memory,
+        Decoder::from_bytes(&std::fs::read(path)?)?,
+    )
+}
+
+/// Returns the number of packets and events.
+fn decode(decoder: Decoder) -> Result<(u64, u64), ParseError> {
+    let events_streams: std::collections::HashSet<u32> = decoder
+        .id_to_stream
+        .iter()
+        .filter(|(_, stream)| stream.content == StreamContent::Events)
+        .map(|(id, _)| *id)
+        .collect();
+    let mut packets = 0;
+    let mut events = 0;
+    for packet in decoder {
+        let packet: Packet = packet?;
+        packets += 1;
+        if events_streams.contains(&packet.stream_id) {
+            events += packet.events()?.len() as u64;
+        }
+    }
+    Ok((packets, events))
+}
@@ -0,0 +1,231 @@
+use clap::{Parser, Subcommand};
+
+/// Tools for AEDAT4 recordings.
+#[derive(Parser)]
+#[command(version)]
+struct Arguments {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the streams of a recording with their packet and element counts, durations and rates
+    Info { input: std::path::PathBuf },
+
+    /// Salvages the packets of a damaged recording and writes a well-formed file
+    Repair {
+        input: std::path::PathBuf,
+        output: std::path::PathBuf,
+    },
+
+    /// Checks the structure of a recording and lists its problems
+    Validate {
+        input: std::path::PathBuf,
+        /// Prints the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Reports the compression ratio of each stream and estimates the size with other codecs
+    Compression { input: std::path::PathBuf },
+
+    /// Splits a recording or a live TCP stream into files by duration, size, or activity
+    Split {
+        /// Path of a file, or address of a DV TCP output with --tcp
+        input: String,
+        /// Directory of the output files
+        output: std::path::PathBuf,
+        /// Reads from a TCP address instead of a file
+        #[arg(long)]
+        tcp: bool,
+        /// Maximum duration of a file in seconds
+        #[arg(long)]
+        duration: Option<f64>,
+        /// Maximum size of a file in megabytes
+        #[arg(long)]
+        size: Option<u64>,
+        /// Only records while the event rate exceeds this value (events per second)
+        #[arg(long)]
+        min_rate: Option<f64>,
+    },
+
+    /// Records a live TCP stream and re-serves it to downstream clients
+    Proxy {
+        /// Address of the DV TCP output
+        input: String,
+        /// Address on which downstream clients connect
+        listen: String,
+        /// Writes the stream to this file
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Exports a recording to a MATLAB (v7.3) file
+    #[cfg(feature = "mat")]
+    Mat {
+        input: std::path::PathBuf,
+        output: std::path::PathBuf,
+    },
+
+    /// Exports the events of a recording to a Neurodata Without Borders file
+    #[cfg(feature = "nwb")]
+    Nwb {
+        input: std::path::PathBuf,
+        output: std::path::PathBuf,
+        /// Session description stored in the NWB file
+        #[arg(long, default_value = "DVS recording")]
+        description: String,
+    },
+
+    /// Plays a recording in a window (space pauses, arrows seek)
+    #[cfg(feature = "viewer")]
+    View {
+        input: std::path::PathBuf,
+        /// Playback speed
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+    },
+}
+
+fn main() -> Result<(), aedat::base::ParseError> {
+    match Arguments::parse().command {
+        Command::Info { input } => {
+            let mut decoder = aedat::base::Decoder::new_from_file(input)?;
+            // files written by other tools have no embedded statistics
+            let id_to_statistics = match decoder.statistics()? {
+                Some(id_to_statistics) => id_to_statistics,
+                None => {
+                    let entries = decoder.file_data_table()?;
+                    aedat::statistics::summarize(&decoder.id_to_stream, &entries)
+                }
+            };
+            let mut ids: Vec<&u32> = decoder.id_to_stream.keys().collect();
+            ids.sort();
+            for id in ids {
+                let statistics = &id_to_statistics[id];
+                println!(
+                    "{}: {}, {} packets, {} elements, {:.3} s, {:.1} elements/s",
+                    id,
+                    decoder.id_to_stream[id],
+                    statistics.packets,
+                    statistics.elements,
+                    statistics.duration() as f64 / 1e6,
+                    statistics.mean_rate
+                );
+            }
+        }
+        Command::Repair { input, output } => {
+            let report = aedat::repair::repair(input, output)?;
+            println!(
+                "wrote {} packets, dropped {} malformed packets{}",
+                report.packets,
+                report.skipped_packets,
+                if report.truncated {
+                    ", the input is truncated"
+                } else {
+                    ""
+                }
+            );
+        }
+        Command::Validate { input, json } => {
+            let report = aedat::validate::validate(input)?;
+            if json {
+                println!("{}", report.to_json());
+            } else {
+                for issue in report.issues.iter() {
+                    println!("{}", issue);
+                }
+                println!(
+                    "{} packets, {}",
+                    report.packets,
+                    if report.is_valid() {
+                        "valid"
+                    } else {
+                        "invalid"
+                    }
+                );
+            }
+            if !report.is_valid() {
+                std::process::exit(1);
+            }
+        }
+        Command::Compression { input } => {
+            print!("{}", aedat::compression::analyze_compression(input)?);
+        }
+        Command::Split {
+            input,
+            output,
+            tcp,
+            duration,
+            size,
+            min_rate,
+        } => {
+            let decoder = if tcp {
+                aedat::base::Decoder::new_from_tcp_stream(input)?
+            } else {
+                aedat::base::Decoder::new_from_file(input)?
+            };
+            std::fs::create_dir_all(&output)?;
+            let mut splitter = aedat::splitter::Splitter::new(
+                output,
+                decoder.id_to_stream.clone(),
+                aedat::base::ioheader_generated::Compression::Lz4,
+            );
+            if let Some(duration) = duration {
+                splitter = splitter.max_duration((duration * 1e6) as i64);
+            }
+            if let Some(size) = size {
+                splitter = splitter.max_size(size * 1_000_000);
+            }
+            if let Some(min_rate) = min_rate {
+                splitter = splitter.activity(min_rate, 100_000, 1_000_000);
+            }
+            for packet in decoder {
+                splitter.write(&packet?)?;
+            }
+            for path in splitter.finish()? {
+                println!("{}", path.display());
+            }
+        }
+        Command::Proxy {
+            input,
+            listen,
+            output,
+        } => {
+            let decoder = aedat::base::Decoder::new_from_tcp_stream(input)?;
+            let mut proxy = aedat::proxy::Proxy::new(decoder, listen)?;
+            if let Some(output) = output {
+                proxy = proxy.record(output, aedat::base::ioheader_generated::Compression::Lz4)?;
+            }
+            println!("forwarded {} packets", proxy.run()?);
+        }
+        #[cfg(feature = "mat")]
+        Command::Mat { input, output } => {
+            aedat::mat::export(aedat::base::Decoder::new_from_file(input)?, output)?;
+        }
+        #[cfg(feature = "nwb")]
+        Command::Nwb {
+            input,
+            output,
+            description,
+        } => {
+            use aedat::events::PacketIterator;
+            let events = aedat::nwb::export(
+                aedat::base::Decoder::new_from_file(input)?.events(),
+                output,
+                &description,
+            )?;
+            println!("wrote {} events", events);
+        }
+        #[cfg(feature = "viewer")]
+        Command::View { input, speed } => {
+            let title = input.display().to_string();
+            aedat::viewer::Viewer::new(aedat::base::Decoder::new_from_file(input)?)
+                .speed(speed)
+                .title(&title)
+                .run()?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,427 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+
+use std::cmp::Ordering;
+use std::mem;
+
+extern crate flatbuffers;
+use self::flatbuffers::{EndianScalar, Follow};
+
+pub enum BoundingBoxOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct BoundingBox<'a> {
+    pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for BoundingBox<'a> {
+    type Inner = BoundingBox<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf, loc },
+        }
+    }
+}
+
+impl<'a> BoundingBox<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        BoundingBox { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args BoundingBoxArgs<'args>,
+    ) -> flatbuffers::WIPOffset<BoundingBox<'bldr>> {
+        let mut builder = BoundingBoxBuilder::new(_fbb);
+        builder.add_timestamp(args.timestamp);
+        if let Some(x) = args.label {
+            builder.add_label(x);
+        }
+        builder.add_confidence(args.confidence);
+        builder.add_bottom_right_y(args.bottom_right_y);
+        builder.add_bottom_right_x(args.bottom_right_x);
+        builder.add_top_left_y(args.top_left_y);
+        builder.add_top_left_x(args.top_left_x);
+        builder.finish()
+    }
+
+    pub const VT_TIMESTAMP: flatbuffers::VOffsetT = 4;
+    pub const VT_TOP_LEFT_X: flatbuffers::VOffsetT = 6;
+    pub const VT_TOP_LEFT_Y: flatbuffers::VOffsetT = 8;
+    pub const VT_BOTTOM_RIGHT_X: flatbuffers::VOffsetT = 10;
+    pub const VT_BOTTOM_RIGHT_Y: flatbuffers::VOffsetT = 12;
+    pub const VT_CONFIDENCE: flatbuffers::VOffsetT = 14;
+    pub const VT_LABEL: flatbuffers::VOffsetT = 16;
+
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self._tab
+            .get::<i64>(BoundingBox::VT_TIMESTAMP, Some(0))
+            .unwrap()
+    }
+    #[inline]
+    pub fn top_left_x(&self) -> f32 {
+        self._tab
+            .get::<f32>(BoundingBox::VT_TOP_LEFT_X, Some(0.0))
+            .unwrap()
+    }
+    #[inline]
+    pub fn top_left_y(&self) -> f32 {
+        self._tab
+            .get::<f32>(BoundingBox::VT_TOP_LEFT_Y, Some(0.0))
+            .unwrap()
+    }
+    #[inline]
+    pub fn bottom_right_x(&self) -> f32 {
+        self._tab
+            .get::<f32>(BoundingBox::VT_BOTTOM_RIGHT_X, Some(0.0))
+            .unwrap()
+    }
+    #[inline]
+    pub fn bottom_right_y(&self) -> f32 {
+        self._tab
+            .get::<f32>(BoundingBox::VT_BOTTOM_RIGHT_Y, Some(0.0))
+            .unwrap()
+    }
+    #[inline]
+    pub fn confidence(&self) -> f32 {
+        self._tab
+            .get::<f32>(BoundingBox::VT_CONFIDENCE, Some(0.0))
+            .unwrap()
+    }
+    #[inline]
+    pub fn label(&self) -> Option<&'a str> {
+        self._tab
+            .get::<flatbuffers::ForwardsUOffset<&str>>(BoundingBox::VT_LABEL, None)
+    }
+}
+
+impl flatbuffers::Verifiable for BoundingBox<'_> {
+    #[inline]
+    fn run_verifier(
+        v: &mut flatbuffers::Verifier,
+        pos: usize,
+    ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+        use self::flatbuffers::Verifiable;
+        v.visit_table(pos)?
+            .visit_field::<i64>(&"timestamp", Self::VT_TIMESTAMP, false)?
+            .visit_field::<f32>(&"top_left_x", Self::VT_TOP_LEFT_X, false)?
+            .visit_field::<f32>(&"top_left_y", Self::VT_TOP_LEFT_Y, false)?
+            .visit_field::<f32>(&"bottom_right_x", Self::VT_BOTTOM_RIGHT_X, false)?
+            .visit_field::<f32>(&"bottom_right_y", Self::VT_BOTTOM_RIGHT_Y, false)?
+            .visit_field::<f32>(&"confidence", Self::VT_CONFIDENCE, false)?
+            .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"label", Self::VT_LABEL, false)?
+            .finish();
+        Ok(())
+    }
+}
+pub struct BoundingBoxArgs<'a> {
+    pub timestamp: i64,
+    pub top_left_x: f32,
+    pub top_left_y: f32,
+    pub bottom_right_x: f32,
+    pub bottom_right_y: f32,
+    pub confidence: f32,
+    pub label: Option<flatbuffers::WIPOffset<&'a str>>,
+}
+impl<'a> Default for BoundingBoxArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        BoundingBoxArgs {
+            timestamp: 0,
+            top_left_x: 0.0,
+            top_left_y: 0.0,
+            bottom_right_x: 0.0,
+            bottom_right_y: 0.0,
+            confidence: 0.0,
+            label: None,
+        }
+    }
+}
+pub struct BoundingBoxBuilder<'a: 'b, 'b> {
+    fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> BoundingBoxBuilder<'a, 'b> {
+    #[inline]
+    pub fn add_timestamp(&mut self, timestamp: i64) {
+        self.fbb_
+            .push_slot::<i64>(BoundingBox::VT_TIMESTAMP, timestamp, 0);
+    }
+    #[inline]
+    pub fn add_top_left_x(&mut self, top_left_x: f32) {
+        self.fbb_
+            .push_slot::<f32>(BoundingBox::VT_TOP_LEFT_X, top_left_x, 0.0);
+    }
+    #[inline]
+    pub fn add_top_left_y(&mut self, top_left_y: f32) {
+        self.fbb_
+            .push_slot::<f32>(BoundingBox::VT_TOP_LEFT_Y, top_left_y, 0.0);
+    }
+    #[inline]
+    pub fn add_bottom_right_x(&mut self, bottom_right_x: f32) {
+        self.fbb_
+            .push_slot::<f32>(BoundingBox::VT_BOTTOM_RIGHT_X, bottom_right_x, 0.0);
+    }
+    #[inline]
+    pub fn add_bottom_right_y(&mut self, bottom_right_y: f32) {
+        self.fbb_
+            .push_slot::<f32>(BoundingBox::VT_BOTTOM_RIGHT_Y, bottom_right_y, 0.0);
+    }
+    #[inline]
+    pub fn add_confidence(&mut self, confidence: f32) {
+        self.fbb_
+            .push_slot::<f32>(BoundingBox::VT_CONFIDENCE, confidence, 0.0);
+    }
+    #[inline]
+    pub fn add_label(&mut self, label: flatbuffers::WIPOffset<&'b str>) {
+        self.fbb_
+            .push_slot_always::<flatbuffers::WIPOffset<_>>(BoundingBox::VT_LABEL, label);
+    }
+    #[inline]
+    pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> BoundingBoxBuilder<'a, 'b> {
+        let start = _fbb.start_table();
+        BoundingBoxBuilder {
+            fbb_: _fbb,
+            start_: start,
+        }
+    }
+    #[inline]
+    pub fn finish(self) -> flatbuffers::WIPOffset<BoundingBox<'a>> {
+        let o = self.fbb_.end_table(self.start_);
+        flatbuffers::WIPOffset::new(o.value())
+    }
+}
+
+impl std::fmt::Debug for BoundingBox<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("BoundingBox");
+        ds.field("timestamp", &self.timestamp());
+        ds.field("top_left_x", &self.top_left_x());
+        ds.field("top_left_y", &self.top_left_y());
+        ds.field("bottom_right_x", &self.bottom_right_x());
+        ds.field("bottom_right_y", &self.bottom_right_y());
+        ds.field("confidence", &self.confidence());
+        ds.field("label", &self.label());
+        ds.finish()
+    }
+}
+pub enum BoundingBoxPacketOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct BoundingBoxPacket<'a> {
+    pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for BoundingBoxPacket<'a> {
+    type Inner = BoundingBoxPacket<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf, loc },
+        }
+    }
+}
+
+impl<'a> BoundingBoxPacket<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        BoundingBoxPacket { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args BoundingBoxPacketArgs<'args>,
+    ) -> flatbuffers::WIPOffset<BoundingBoxPacket<'bldr>> {
+        let mut builder = BoundingBoxPacketBuilder::new(_fbb);
+        if let Some(x) = args.elements {
+            builder.add_elements(x);
+        }
+        builder.finish()
+    }
+
+    pub const VT_ELEMENTS: flatbuffers::VOffsetT = 4;
+
+    #[inline]
+    pub fn elements(
+        &self,
+    ) -> Option<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<BoundingBox<'a>>>> {
+        self._tab.get::<flatbuffers::ForwardsUOffset<
+            flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<BoundingBox>>,
+        >>(BoundingBoxPacket::VT_ELEMENTS, None)
+    }
+}
+
+impl flatbuffers::Verifiable for BoundingBoxPacket<'_> {
+    #[inline]
+    fn run_verifier(
+        v: &mut flatbuffers::Verifier,
+        pos: usize,
+    ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+        use self::flatbuffers::Verifiable;
+        v.visit_table(pos)?
+            .visit_field::<flatbuffers::ForwardsUOffset<
+                flatbuffers::Vector<'_, flatbuffers::ForwardsUOffset<BoundingBox>>,
+            >>(&"elements", Self::VT_ELEMENTS, false)?
+            .finish();
+        Ok(())
+    }
+}
+pub struct BoundingBoxPacketArgs<'a> {
+    pub elements: Option<
+        flatbuffers::WIPOffset<
+            flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<BoundingBox<'a>>>,
+        >,
+    >,
+}
+impl<'a> Default for BoundingBoxPacketArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        BoundingBoxPacketArgs { elements: None }
+    }
+}
+pub struct BoundingBoxPacketBuilder<'a: 'b, 'b> {
+    fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> BoundingBoxPacketBuilder<'a, 'b> {
+    #[inline]
+    pub fn add_elements(
+        &mut self,
+        elements: flatbuffers::WIPOffset<
+            flatbuffers::Vector<'b, flatbuffers::ForwardsUOffset<BoundingBox<'b>>>,
+        >,
+    ) {
+        self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(
+            BoundingBoxPacket::VT_ELEMENTS,
+            elements,
+        );
+    }
+    #[inline]
+    pub fn new(
+        _fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    ) -> BoundingBoxPacketBuilder<'a, 'b> {
+        let start = _fbb.start_table();
+        BoundingBoxPacketBuilder {
+            fbb_: _fbb,
+            start_: start,
+        }
+    }
+    #[inline]
+    pub fn finish(self) -> flatbuffers::WIPOffset<BoundingBoxPacket<'a>> {
+        let o = self.fbb_.end_table(self.start_);
+        flatbuffers::WIPOffset::new(o.value())
+    }
+}
+
+impl std::fmt::Debug for BoundingBoxPacket<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("BoundingBoxPacket");
+        ds.field("elements", &self.elements());
+        ds.finish()
+    }
+}
+#[inline]
+#[deprecated(since = "2.0.0", note = "Deprecated in favor of `root_as...` methods.")]
+pub fn get_root_as_bounding_box_packet<'a>(buf: &'a [u8]) -> BoundingBoxPacket<'a> {
+    unsafe { flatbuffers::root_unchecked::<BoundingBoxPacket<'a>>(buf) }
+}
+
+#[inline]
+#[deprecated(since = "2.0.0", note = "Deprecated in favor of `root_as...` methods.")]
+pub fn get_size_prefixed_root_as_bounding_box_packet<'a>(buf: &'a [u8]) -> BoundingBoxPacket<'a> {
+    unsafe { flatbuffers::size_prefixed_root_unchecked::<BoundingBoxPacket<'a>>(buf) }
+}
+
+#[inline]
+/// Verifies that a buffer of bytes contains a `BoundingBoxPacket`
+/// and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_bounding_box_packet_unchecked`.
+pub fn root_as_bounding_box_packet(
+    buf: &[u8],
+) -> Result<BoundingBoxPacket, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::root::<BoundingBoxPacket>(buf)
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a size prefixed
+/// `BoundingBoxPacket` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `size_prefixed_root_as_bounding_box_packet_unchecked`.
+pub fn size_prefixed_root_as_bounding_box_packet(
+    buf: &[u8],
+) -> Result<BoundingBoxPacket, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::size_prefixed_root::<BoundingBoxPacket>(buf)
+}
+#[inline]
+/// Verifies, with the given options, that a buffer of bytes
+/// contains a `BoundingBoxPacket` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_bounding_box_packet_unchecked`.
+pub fn root_as_bounding_box_packet_with_opts<'b, 'o>(
+    opts: &'o flatbuffers::VerifierOptions,
+    buf: &'b [u8],
+) -> Result<BoundingBoxPacket<'b>, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::root_with_opts::<BoundingBoxPacket<'b>>(opts, buf)
+}
+#[inline]
+/// Verifies, with the given verifier options, that a buffer of
+/// bytes contains a size prefixed `BoundingBoxPacket` and returns
+/// it. Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_bounding_box_packet_unchecked`.
+pub fn size_prefixed_root_as_bounding_box_packet_with_opts<'b, 'o>(
+    opts: &'o flatbuffers::VerifierOptions,
+    buf: &'b [u8],
+) -> Result<BoundingBoxPacket<'b>, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::size_prefixed_root_with_opts::<BoundingBoxPacket<'b>>(opts, buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a BoundingBoxPacket and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid `BoundingBoxPacket`.
+pub unsafe fn root_as_bounding_box_packet_unchecked(buf: &[u8]) -> BoundingBoxPacket {
+    flatbuffers::root_unchecked::<BoundingBoxPacket>(buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a size prefixed BoundingBoxPacket and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid size prefixed `BoundingBoxPacket`.
+pub unsafe fn size_prefixed_root_as_bounding_box_packet_unchecked(buf: &[u8]) -> BoundingBoxPacket {
+    flatbuffers::size_prefixed_root_unchecked::<BoundingBoxPacket>(buf)
+}
+pub const BOUNDING_BOX_PACKET_IDENTIFIER: &str = "BBOX";
+
+#[inline]
+pub fn bounding_box_packet_buffer_has_identifier(buf: &[u8]) -> bool {
+    flatbuffers::buffer_has_identifier(buf, BOUNDING_BOX_PACKET_IDENTIFIER, false)
+}
+
+#[inline]
+pub fn bounding_box_packet_size_prefixed_buffer_has_identifier(buf: &[u8]) -> bool {
+    flatbuffers::buffer_has_identifier(buf, BOUNDING_BOX_PACKET_IDENTIFIER, true)
+}
+
+#[inline]
+pub fn finish_bounding_box_packet_buffer<'a, 'b>(
+    fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    root: flatbuffers::WIPOffset<BoundingBoxPacket<'a>>,
+) {
+    fbb.finish(root, Some(BOUNDING_BOX_PACKET_IDENTIFIER));
+}
+
+#[inline]
+pub fn finish_size_prefixed_bounding_box_packet_buffer<'a, 'b>(
+    fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    root: flatbuffers::WIPOffset<BoundingBoxPacket<'a>>,
+) {
+    fbb.finish_size_prefixed(root, Some(BOUNDING_BOX_PACKET_IDENTIFIER));
+}
@@ -0,0 +1,452 @@
+//! Lens undistortion with the OpenCV pinhole model (k1, k2, p1, p2, k3, k4, k5, k6).
+//!
+//! Events can also be projected to normalized camera coordinates (see `Projector`), the input of most SLAM and
+//! visual odometry pipelines.
+
+use crate::base::{Packet, ParseError, StreamContent};
+use crate::events::Event;
+use crate::{events_generated, frame_generated};
+
+/// Intrinsic parameters and distortion coefficients of a camera.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calibration {
+    pub width: u16,
+    pub height: u16,
+    /// Row-major 3 × 3 camera matrix.
+    pub camera_matrix: [f64; 9],
+    /// k1, k2, p1, p2 and optionally k3, k4, k5, k6 (missing coefficients are zero).
+    pub distortion: Vec<f64>,
+}
+
+impl Calibration {
+    /// Reads a DV or OpenCV calibration file, in XML or YAML format.
+    ///
+    /// The file must contain `camera_matrix`, `distortion_coefficients`, `image_width` and `image_height`,
+    /// possibly nested in a node named after the camera.
+    pub fn from_file<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, ParseError> {
+        let text = std::fs::read_to_string(path)?;
+        if text.trim_start().starts_with("%YAML") {
+            Calibration::from_yaml(&text)
+        } else {
+            Calibration::from_xml(&text)
+        }
+    }
+
+    /// Parses an OpenCV FileStorage XML document.
+    pub fn from_xml(text: &str) -> Result<Self, ParseError> {
+        let document = roxmltree::Document::parse(text)?;
+        let node_text = |name: &'static str| {
+            document
+                .descendants()
+                .find(|node| node.has_tag_name(name))
+                .map(|node| {
+                    node.children()
+                        .find(|child| child.has_tag_name("data"))
+                        .unwrap_or(node)
+                        .text()
+                        .unwrap_or("")
+                        .to_owned()
+                })
+                .ok_or_else(|| ParseError::Calibration(format!("missing `{}`", name)))
+        };
+        Calibration::new_checked(
+            parse_integer(&node_text("image_width")?)?,
+            parse_integer(&node_text("image_height")?)?,
+            parse_numbers(&node_text("camera_matrix")?)?,
+            parse_numbers(&node_text("distortion_coefficients")?)?,
+        )
+    }
+
+    /// Parses an OpenCV FileStorage YAML document.
+    pub fn from_yaml(text: &str) -> Result<Self, ParseError> {
+        let value = |name: &'static str| -> Result<String, ParseError> {
+            let start = text
+                .find(&format!("{}:", name))
+                .ok_or_else(|| ParseError::Calibration(format!("missing `{}`", name)))?
+                + name.len()
+                + 1;
+            let rest = &text[start..];
+            if rest.trim_start().starts_with("!!opencv-matrix") {
+                let data = rest
+                    .find("data:")
+                    .and_then(|begin| {
+                        let begin = begin + rest[begin..].find('[')? + 1;
+                        Some(&rest[begin..begin + rest[begin..].find(']')?])
+                    })
+                    .ok_or_else(|| ParseError::Calibration(format!("`{}` has no data", name)))?;
+                Ok(data.to_owned())
+            } else {
+                Ok(rest.lines().next().unwrap_or("").to_owned())
+            }
+        };
+        Calibration::new_checked(
+            parse_integer(&value("image_width")?)?,
+            parse_integer(&value("image_height")?)?,
+            parse_numbers(&value("camera_matrix")?)?,
+            parse_numbers(&value("distortion_coefficients")?)?,
+        )
+    }
+
+    fn new_checked(
+        width: u16,
+        height: u16,
+        camera_matrix: Vec<f64>,
+        distortion: Vec<f64>,
+    ) -> Result<Self, ParseError> {
+        if camera_matrix.len() != 9 {
+            return Err(ParseError::Calibration(format!(
+                "the camera matrix has {} coefficients instead of 9",
+                camera_matrix.len()
+            )));
+        }
+        if distortion.len() > 8 {
+            return Err(ParseError::Calibration(format!(
+                "unsupported distortion model with {} coefficients",
+                distortion.len()
+            )));
+        }
+        let mut matrix = [0.0; 9];
+        matrix.copy_from_slice(&camera_matrix);
+        Ok(Calibration {
+            width,
+            height,
+            camera_matrix: matrix,
+            distortion,
+        })
+    }
+
+    fn coefficients(&self) -> [f64; 8] {
+        let mut coefficients = [0.0; 8];
+        for (coefficient, value) in coefficients.iter_mut().zip(self.distortion.iter()) {
+            *coefficient = *value;
+        }
+        coefficients
+    }
+
+    /// Maps an undistorted pixel position to the position where the lens projects it.
+    pub fn distort_point(&self, x: f64, y: f64) -> (f64, f64) {
+        let [fx, _, cx, _, fy, cy, ..] = self.camera_matrix;
+        let [k1, k2, p1, p2, k3, k4, k5, k6] = self.coefficients();
+        let (x, y) = ((x - cx) / fx, (y - cy) / fy);
+        let r2 = x * x + y * y;
+        let radial =
+            (1.0 + r2 * (k1 + r2 * (k2 + r2 * k3))) / (1.0 + r2 * (k4 + r2 * (k5 + r2 * k6)));
+        let distorted_x = x * radial + 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x);
+        let distorted_y = y * radial + p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y;
+        (distorted_x * fx + cx, distorted_y * fy + cy)
+    }
+
+    /// Maps a distorted pixel position to the undistorted image plane (iterative inverse of `distort_point`).
+    pub fn undistort_point(&self, x: f64, y: f64) -> (f64, f64) {
+        let [fx, _, cx, _, fy, cy, ..] = self.camera_matrix;
+        let [k1, k2, p1, p2, k3, k4, k5, k6] = self.coefficients();
+        let (distorted_x, distorted_y) = ((x - cx) / fx, (y - cy) / fy);
+        let (mut x, mut y) = (distorted_x, distorted_y);
+        for _ in 0..20 {
+            let r2 = x * x + y * y;
+            let inverse_radial =
+                (1.0 + r2 * (k4 + r2 * (k5 + r2 * k6))) / (1.0 + r2 * (k1 + r2 * (k2 + r2 * k3)));
+            let delta_x = 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x);
+            let delta_y = p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y;
+            x = (distorted_x - delta_x) * inverse_radial;
+            y = (distorted_y - delta_y) * inverse_radial;
+        }
+        (x * fx + cx, y * fy + cy)
+    }
+
+    /// Maps a distorted pixel position to normalized camera coordinates, that is, to the undistorted point
+    /// on the plane z = 1.
+    pub fn normalize_point(&self, x: f64, y: f64) -> (f64, f64) {
+        let [fx, _, cx, _, fy, cy, ..] = self.camera_matrix;
+        let (x, y) = self.undistort_point(x, y);
+        ((x - cx) / fx, (y - cy) / fy)
+    }
+
+    /// Precomputes the normalized coordinates of every pixel.
+    pub fn projector(&self) -> Projector {
+        let mut points = Vec::with_capacity(self.width as usize * self.height as usize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                points.push(self.normalize_point(x as f64, y as f64));
+            }
+        }
+        Projector {
+            width: self.width,
+            height: self.height,
+            points,
+        }
+    }
+
+    /// Precomputes the lookup tables used to undistort events and frames.
+    pub fn undistorter(&self) -> Undistorter {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut events = Vec::with_capacity(width * height);
+        let mut sources = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (undistorted_x, undistorted_y) = self.undistort_point(x as f64, y as f64);
+                let (undistorted_x, undistorted_y) = (undistorted_x.round(), undistorted_y.round());
+                events.push(
+                    if undistorted_x >= 0.0
+                        && undistorted_y >= 0.0
+                        && undistorted_x < width as f64
+                        && undistorted_y < height as f64
+                    {
+                        Some((undistorted_x as i16, undistorted_y as i16))
+                    } else {
+                        None
+                    },
+                );
+                let (source_x, source_y) = self.distort_point(x as f64, y as f64);
+                sources.push((source_x as f32, source_y as f32));
+            }
+        }
+        Undistorter {
+            width: self.width,
+            height: self.height,
+            events,
+            sources,
+        }
+    }
+}
+
+fn parse_numbers(text: &str) -> Result<Vec<f64>, ParseError> {
+    text.split(|character: char| character.is_whitespace() || character == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .map_err(|_| ParseError::Calibration(format!("`{}` is not a number", token)))
+        })
+        .collect()
+}
+
+fn parse_integer(text: &str) -> Result<u16, ParseError> {
+    Ok(text.trim().parse::<u16>()?)
+}
+
+/// Lookup tables computed by `Calibration::undistorter`.
+#[derive(Debug, Clone)]
+pub struct Undistorter {
+    width: u16,
+    height: u16,
+    /// Undistorted position of each pixel, None if it falls outside the sensor.
+    events: Vec<Option<(i16, i16)>>,
+    /// Distorted position of each undistorted pixel.
+    sources: Vec<(f32, f32)>,
+}
+
+impl Undistorter {
+    /// Moves an event to its undistorted position (rounded), or returns None if it falls outside the sensor.
+    pub fn event(&self, event: &Event) -> Option<Event> {
+        let (x, y) = (event.x(), event.y());
+        if x < 0 || y < 0 || x as u16 >= self.width || y as u16 >= self.height {
+            return None;
+        }
+        self.events[y as usize * self.width as usize + x as usize]
+            .map(|(x, y)| Event::new(event.t(), x, y, event.on()))
+    }
+
+    /// Undistorts row-major pixels with bilinear interpolation. Pixels that map outside the sensor are black.
+    pub fn pixels(&self, pixels: &[u8], channels: usize) -> Vec<u8> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut result = vec![0u8; width * height * channels];
+        for (index, (source_x, source_y)) in self.sources.iter().enumerate() {
+            if *source_x < 0.0
+                || *source_y < 0.0
+                || *source_x > (width - 1) as f32
+                || *source_y > (height - 1) as f32
+            {
+                continue;
+            }
+            let (left, top) = (source_x.floor() as usize, source_y.floor() as usize);
+            let (right, bottom) = ((left + 1).min(width - 1), (top + 1).min(height - 1));
+            let (horizontal, vertical) = (source_x - left as f32, source_y - top as f32);
+            for channel in 0..channels {
+                let value =
+                    |x: usize, y: usize| pixels[(y * width + x) * channels + channel] as f32;
+                let interpolated = (value(left, top) * (1.0 - horizontal)
+                    + value(right, top) * horizontal)
+                    * (1.0 - vertical)
+                    + (value(left, bottom) * (1.0 - horizontal)
+                        + value(right, bottom) * horizontal)
+                        * vertical;
+                result[index * channels + channel] = interpolated.round() as u8;
+            }
+        }
+        result
+    }
+
+    /// Undistorts the events or the frame of a packet. IMU and trigger packets are returned unchanged.
+    ///
+    /// Frames must cover the whole sensor.
+    pub fn packet(&self, packet: Packet, content: &StreamContent) -> Result<Packet, ParseError> {
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        match content {
+            StreamContent::Events => {
+                let events = packet
+                    .events()?
+                    .iter()
+                    .filter_map(|event| self.event(event))
+                    .collect::<Vec<Event>>();
+                let elements = builder.create_vector(&events);
+                let root = events_generated::EventPacket::create(
+                    &mut builder,
+                    &events_generated::EventPacketArgs {
+                        elements: Some(elements),
+                    },
+                );
+                events_generated::finish_size_prefixed_event_packet_buffer(&mut builder, root);
+            }
+            StreamContent::Frame => {
+                let frame = packet.frame()?;
+                let channels = match frame.format() {
+                    frame_generated::FrameFormat::Bgr => 3,
+                    frame_generated::FrameFormat::Bgra => 4,
+                    _ => 1,
+                };
+                let pixels = frame.pixels().unwrap_or(&[]);
+                if frame.width() as u16 != self.width
+                    || frame.height() as u16 != self.height
+                    || pixels.len() != self.width as usize * self.height as usize * channels
+                {
+                    return Err(ParseError::Calibration(format!(
+                        "the frame is {}x{} but the calibration is {}x{}",
+                        frame.width(),
+                        frame.height(),
+                        self.width,
+                        self.height
+                    )));
+                }
+                let args = frame_generated::FrameArgs {
+                    t: frame.t(),
+                    begin_t: frame.begin_t(),
+                    end_t: frame.end_t(),
+                    exposure_begin_t: frame.exposure_begin_t(),
+                    exposure_end_t: frame.exposure_end_t(),
+                    format: frame.format(),
+                    width: frame.width(),
+                    height: frame.height(),
+                    offset_x: frame.offset_x(),
+                    offset_y: frame.offset_y(),
+                    pixels: Some(builder.create_vector(&self.pixels(pixels, channels))),
+                };
+                let root = frame_generated::Frame::create(&mut builder, &args);
+                frame_generated::finish_size_prefixed_frame_buffer(&mut builder, root);
+            }
+            StreamContent::Imus
+            | StreamContent::Triggers
+            | StreamContent::BoundingBoxes
+            | StreamContent::Pose
+            | StreamContent::Other(_) => return Ok(packet),
+        }
+        Ok(Packet {
+            buffer: builder.finished_data().to_vec(),
+            stream_id: packet.stream_id,
+            arrival_time: packet.arrival_time,
+        })
+    }
+}
+
+/// Moves the events of a stream to the undistorted image plane (see `Undistorter::event`).
+pub struct Undistorted<I> {
+    events: I,
+    undistorter: Undistorter,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Undistorted<I> {
+    pub fn new(events: I, undistorter: Undistorter) -> Self {
+        Undistorted {
+            events,
+            undistorter,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Undistorted<I> {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(event) => {
+                    if let Some(event) = self.undistorter.event(&event) {
+                        return Some(Ok(event));
+                    }
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// An event in normalized camera coordinates (see `Projector`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalizedEvent {
+    pub t: i64,
+    pub x: f64,
+    pub y: f64,
+    pub on: bool,
+}
+
+impl NormalizedEvent {
+    /// Unit vector of the ray through the event, in the camera frame (z points forward).
+    pub fn ray(&self) -> [f64; 3] {
+        let norm = (self.x * self.x + self.y * self.y + 1.0).sqrt();
+        [self.x / norm, self.y / norm, 1.0 / norm]
+    }
+}
+
+/// Lookup table computed by `Calibration::projector`.
+#[derive(Debug, Clone)]
+pub struct Projector {
+    width: u16,
+    height: u16,
+    points: Vec<(f64, f64)>,
+}
+
+impl Projector {
+    /// Projects an event, or returns None if it is outside the sensor.
+    pub fn event(&self, event: &Event) -> Option<NormalizedEvent> {
+        let (x, y) = (event.x(), event.y());
+        if x < 0 || y < 0 || x as u16 >= self.width || y as u16 >= self.height {
+            return None;
+        }
+        let (x, y) = self.points[y as usize * self.width as usize + x as usize];
+        Some(NormalizedEvent {
+            t: event.t(),
+            x,
+            y,
+            on: event.on(),
+        })
+    }
+}
+
+/// Projects the events of a stream to normalized camera coordinates, skipping events outside the sensor.
+pub struct Projected<I> {
+    events: I,
+    projector: Projector,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Projected<I> {
+    pub fn new(events: I, projector: Projector) -> Self {
+        Projected { events, projector }
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Projected<I> {
+    type Item = Result<NormalizedEvent, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(event) => {
+                    if let Some(event) = self.projector.event(&event) {
+                        return Some(Ok(event));
+                    }
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
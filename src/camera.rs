@@ -0,0 +1,145 @@
+//! Camera settings embedded in recordings.
+//!
+//! DV writes the bias settings and the device information of the capture module to the XML description
+//! of the IOHeader when they are part of the recorded configuration, and always exposes them through the
+//! runtime config server (see `runtime`). `CameraConfig` gathers them with typed values.
+
+use crate::base::Decoder;
+use crate::runtime::RuntimeConfig;
+use std::collections::BTreeMap;
+
+/// Value of a configuration attribute, typed from its text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl ConfigValue {
+    pub fn parse(text: &str) -> Self {
+        if let Ok(value) = text.parse::<bool>() {
+            ConfigValue::Bool(value)
+        } else if let Ok(value) = text.parse::<i64>() {
+            ConfigValue::Int(value)
+        } else if let Ok(value) = text.parse::<f64>() {
+            ConfigValue::Float(value)
+        } else {
+            ConfigValue::String(text.to_owned())
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ConfigValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ConfigValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Integers are converted.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ConfigValue::Int(value) => Some(*value as f64),
+            ConfigValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigValue {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigValue::Bool(value) => write!(formatter, "{}", value),
+            ConfigValue::Int(value) => write!(formatter, "{}", value),
+            ConfigValue::Float(value) => write!(formatter, "{}", value),
+            ConfigValue::String(value) => write!(formatter, "{}", value),
+        }
+    }
+}
+
+/// Biases and device information of the camera that produced a recording.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CameraConfig {
+    /// Camera model, for instance `DAVIS346`.
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+    /// Offset between the camera clock and the Unix epoch, in microseconds (`tsOffset`).
+    pub timestamp_offset: Option<i64>,
+    /// Bias settings, by name relative to the bias node (for instance `DiffBn/coarseValue` on DAVIS cameras).
+    pub biases: BTreeMap<String, ConfigValue>,
+    /// Attributes of the `sourceInfo` node (firmware and logic versions, chip id...).
+    pub device: BTreeMap<String, ConfigValue>,
+}
+
+impl CameraConfig {
+    pub fn from_runtime_config(config: &RuntimeConfig) -> Self {
+        let source = config
+            .paths()
+            .filter_map(|path| config.get(path, "source"))
+            .next();
+        let mut device = BTreeMap::new();
+        for path in config.paths().filter(|path| path.ends_with("/sourceInfo/")) {
+            if let Some(attributes) = config.node(path) {
+                for (key, value) in attributes.iter() {
+                    device.insert(key.clone(), ConfigValue::parse(value));
+                }
+            }
+        }
+        // serial numbers are read as text, since they may have leading zeros
+        let serial_number = config
+            .paths()
+            .filter(|path| path.ends_with("/sourceInfo/"))
+            .find_map(|path| config.get(path, "deviceSerialNumber"))
+            .or_else(|| {
+                source
+                    .and_then(|source| source.rsplit_once('_'))
+                    .map(|(_, serial_number)| serial_number)
+            })
+            .map(|serial_number| serial_number.to_owned());
+        CameraConfig {
+            model: config.sensor_model().map(|model| model.to_owned()),
+            serial_number,
+            timestamp_offset: config
+                .paths()
+                .filter_map(|path| config.get(path, "tsOffset"))
+                .find_map(|offset| offset.parse().ok()),
+            biases: config
+                .biases()
+                .into_iter()
+                .map(|(name, value)| (name, ConfigValue::parse(&value)))
+                .collect(),
+            device,
+        }
+    }
+
+    /// Reads a DV XML description or configuration file.
+    pub fn from_xml(xml: &str) -> Result<Self, crate::base::ParseError> {
+        Ok(CameraConfig::from_runtime_config(&RuntimeConfig::from_xml(
+            xml,
+        )?))
+    }
+
+    pub fn bias(&self, name: &str) -> Option<&ConfigValue> {
+        self.biases.get(name)
+    }
+}
+
+impl Decoder {
+    /// Camera settings found in the IOHeader description, completed with the runtime configuration
+    /// if it was downloaded (see `DecoderBuilder::runtime_config`), which takes precedence.
+    pub fn camera_config(&self) -> CameraConfig {
+        let mut config = RuntimeConfig::from_xml(self.description()).unwrap_or_default();
+        if let Some(runtime_config) = self.runtime_config() {
+            config.merge(runtime_config.clone());
+        }
+        CameraConfig::from_runtime_config(&config)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::base::ParseError;
+use crate::events::Event;
+
+/// Window used to group events.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Window {
+    /// Duration in microseconds.
+    Duration(i64),
+    Count(usize),
+}
+
+/// Groups an event stream into owned batches, regardless of packet boundaries.
+///
+/// Duration windows are aligned on the first event and empty windows yield empty batches.
+/// The last batch may be shorter than the window.
+pub struct Chunks<I> {
+    events: I,
+    window: Window,
+    end_t: Option<i64>,
+    pending: Option<Event>,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Chunks<I> {
+    pub fn new(events: I, window: Window) -> Self {
+        Chunks {
+            events,
+            window: match window {
+                Window::Duration(duration) => Window::Duration(duration.max(1)),
+                Window::Count(count) => Window::Count(count.max(1)),
+            },
+            end_t: None,
+            pending: None,
+        }
+    }
+
+    pub fn by_count(events: I, count: usize) -> Self {
+        Chunks::new(events, Window::Count(count))
+    }
+
+    /// `duration` is in microseconds.
+    pub fn by_duration(events: I, duration: i64) -> Self {
+        Chunks::new(events, Window::Duration(duration))
+    }
+
+    /// Same as `by_duration`.
+    pub fn by_time(events: I, duration: i64) -> Self {
+        Chunks::by_duration(events, duration)
+    }
+
+    /// Yields the chunks as structure-of-arrays batches.
+    pub fn batches(self) -> crate::soa::Batched<Self> {
+        crate::soa::Batched::new(self)
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Chunks<I> {
+    type Item = Result<Vec<Event>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.window {
+            Window::Count(count) => {
+                let mut chunk = Vec::with_capacity(count);
+                while chunk.len() < count {
+                    match self.events.next() {
+                        Some(Ok(event)) => chunk.push(event),
+                        Some(Err(error)) => return Some(Err(error)),
+                        None if chunk.is_empty() => return None,
+                        None => break,
+                    }
+                }
+                Some(Ok(chunk))
+            }
+            Window::Duration(duration) => {
+                let mut chunk = Vec::new();
+                loop {
+                    let event = match self.pending.take() {
+                        Some(event) => event,
+                        None => match self.events.next() {
+                            Some(Ok(event)) => event,
+                            Some(Err(error)) => return Some(Err(error)),
+                            None => {
+                                self.end_t?;
+                                self.end_t = None;
+                                return Some(Ok(chunk));
+                            }
+                        },
+                    };
+                    let end_t = *self.end_t.get_or_insert(event.t() + duration);
+                    if event.t() >= end_t {
+                        self.pending = Some(event);
+                        self.end_t = Some(end_t + duration);
+                        return Some(Ok(chunk));
+                    }
+                    chunk.push(event);
+                }
+            }
+        }
+    }
+}
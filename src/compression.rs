@@ -0,0 +1,607 @@
+//! Compression backends.
+//!
+//! The default backend uses the C libraries (`lz4` and `zstd` features, enabled by default),
+//! or the pure-Rust implementations (`lz4_flex` and `ruzstd` features) if the former are disabled.
+//! Building with `--no-default-features --features lz4_flex,ruzstd` does not require a C compiler.
+
+use crate::base::{ioheader_generated::Compression, Decoder, ParseError, StreamContent};
+#[allow(unused_imports)]
+use std::io::{Read, Write};
+
+/// Compresses and decompresses packet buffers.
+pub trait CompressionBackend: Send + Sync {
+    /// Decompresses `input` and appends the result to `output`.
+    fn decompress(
+        &self,
+        compression: Compression,
+        input: &mut dyn Read,
+        output: &mut Vec<u8>,
+    ) -> Result<(), ParseError>;
+
+    /// Decompresses `input` and appends the result to `output`, which may grow by at most `limit` bytes.
+    ///
+    /// Returns `ParseError::DecompressedTooLarge` otherwise. The default implementation checks the size
+    /// after decompressing; backends should override it to stop as soon as the limit is reached.
+    fn decompress_with_limit(
+        &self,
+        compression: Compression,
+        input: &mut dyn Read,
+        output: &mut Vec<u8>,
+        limit: usize,
+    ) -> Result<(), ParseError> {
+        let start = output.len();
+        self.decompress(compression, input, output)?;
+        if output.len() - start > limit {
+            return Err(ParseError::DecompressedTooLarge { limit });
+        }
+        Ok(())
+    }
+
+    /// Compresses `input` and appends the result to `output`.
+    fn compress(
+        &self,
+        compression: Compression,
+        input: &[u8],
+        output: &mut Vec<u8>,
+    ) -> Result<(), ParseError>;
+}
+
+/// Uses the implementations selected by cargo features.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultBackend;
+
+impl CompressionBackend for DefaultBackend {
+    fn decompress(
+        &self,
+        compression: Compression,
+        input: &mut dyn Read,
+        output: &mut Vec<u8>,
+    ) -> Result<(), ParseError> {
+        self.decompress_with_limit(compression, input, output, usize::MAX)
+    }
+
+    fn decompress_with_limit(
+        &self,
+        compression: Compression,
+        input: &mut dyn Read,
+        output: &mut Vec<u8>,
+        limit: usize,
+    ) -> Result<(), ParseError> {
+        let start = output.len();
+        let bound = (limit as u64).saturating_add(1);
+        match compression {
+            Compression::None => {
+                input.take(bound).read_to_end(output)?;
+            }
+            Compression::Lz4 | Compression::Lz4High => decompress_lz4(input, output, bound)?,
+            Compression::Zstd | Compression::ZstdHigh => decompress_zstd(input, output, bound)?,
+            _ => return Err(ParseError::UnknownCompression(compression.0)),
+        }
+        if output.len() - start > limit {
+            return Err(ParseError::DecompressedTooLarge { limit });
+        }
+        Ok(())
+    }
+
+    fn compress(
+        &self,
+        compression: Compression,
+        input: &[u8],
+        output: &mut Vec<u8>,
+    ) -> Result<(), ParseError> {
+        match compression {
+            Compression::None => {
+                output.extend_from_slice(input);
+                Ok(())
+            }
+            Compression::Lz4 | Compression::Lz4High => {
+                compress_lz4(input, output, default_lz4_level(compression))
+            }
+            Compression::Zstd | Compression::ZstdHigh => {
+                compress_zstd(input, output, default_zstd_level(compression))
+            }
+            _ => Err(ParseError::UnknownCompression(compression.0)),
+        }
+    }
+}
+
+fn default_lz4_level(compression: Compression) -> u32 {
+    if compression == Compression::Lz4High {
+        9
+    } else {
+        0
+    }
+}
+
+fn default_zstd_level(compression: Compression) -> i32 {
+    if compression == Compression::ZstdHigh {
+        9
+    } else {
+        0
+    }
+}
+
+/// Default backend with custom compression levels and an optional zstd dictionary.
+///
+/// Levels are only used by the C libraries (`lz4` and `zstd` features), and dictionaries require the `zstd`
+/// feature. Files compressed with a dictionary can only be read with the same dictionary:
+/// `Encoder::with_tuned_compression` embeds it in the file description, and `Decoder::with_embedded_dictionary`
+/// reads it back.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TunedBackend {
+    zstd_level: Option<i32>,
+    lz4_level: Option<u32>,
+    dictionary: Option<Vec<u8>>,
+}
+
+impl TunedBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// zstd level for both ZSTD and ZSTD_HIGH (by default 0, the library default, and 9).
+    pub fn zstd_level(mut self, level: i32) -> Self {
+        self.zstd_level = Some(level);
+        self
+    }
+
+    /// LZ4 HC level for both LZ4 and LZ4_HIGH (by default 0, fast mode, and 9).
+    pub fn lz4_level(mut self, level: u32) -> Self {
+        self.lz4_level = Some(level);
+        self
+    }
+
+    /// Dictionary used to compress and decompress zstd packets (see `train_zstd_dictionary`).
+    pub fn dictionary(mut self, dictionary: Vec<u8>) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    pub fn get_dictionary(&self) -> Option<&[u8]> {
+        self.dictionary.as_deref()
+    }
+}
+
+impl CompressionBackend for TunedBackend {
+    fn decompress(
+        &self,
+        compression: Compression,
+        input: &mut dyn Read,
+        output: &mut Vec<u8>,
+    ) -> Result<(), ParseError> {
+        self.decompress_with_limit(compression, input, output, usize::MAX)
+    }
+
+    fn decompress_with_limit(
+        &self,
+        compression: Compression,
+        input: &mut dyn Read,
+        output: &mut Vec<u8>,
+        limit: usize,
+    ) -> Result<(), ParseError> {
+        let dictionary = match (&self.dictionary, compression) {
+            (Some(dictionary), Compression::Zstd | Compression::ZstdHigh) => dictionary,
+            _ => return DefaultBackend.decompress_with_limit(compression, input, output, limit),
+        };
+        let start = output.len();
+        decompress_zstd_with_dictionary(
+            input,
+            output,
+            (limit as u64).saturating_add(1),
+            dictionary,
+        )?;
+        if output.len() - start > limit {
+            return Err(ParseError::DecompressedTooLarge { limit });
+        }
+        Ok(())
+    }
+
+    fn compress(
+        &self,
+        compression: Compression,
+        input: &[u8],
+        output: &mut Vec<u8>,
+    ) -> Result<(), ParseError> {
+        match compression {
+            Compression::Lz4 | Compression::Lz4High => compress_lz4(
+                input,
+                output,
+                self.lz4_level
+                    .unwrap_or_else(|| default_lz4_level(compression)),
+            ),
+            Compression::Zstd | Compression::ZstdHigh => {
+                let level = self
+                    .zstd_level
+                    .unwrap_or_else(|| default_zstd_level(compression));
+                match &self.dictionary {
+                    Some(dictionary) => {
+                        compress_zstd_with_dictionary(input, output, level, dictionary)
+                    }
+                    None => compress_zstd(input, output, level),
+                }
+            }
+            _ => DefaultBackend.compress(compression, input, output),
+        }
+    }
+}
+
+/// Trains a zstd dictionary on packet buffers (typically a few hundred event packets of the same camera).
+///
+/// Small packets compress much better with a dictionary, since each packet is compressed independently.
+#[cfg(feature = "zstd")]
+pub fn train_zstd_dictionary(
+    packets: &[crate::base::Packet],
+    max_size: usize,
+) -> Result<Vec<u8>, ParseError> {
+    let samples: Vec<&[u8]> = packets.iter().map(|packet| &packet.buffer[..]).collect();
+    Ok(zstd::dict::from_samples(&samples, max_size)?)
+}
+
+impl Decoder {
+    /// zstd dictionary embedded in the description by `Encoder::with_tuned_compression`, if any.
+    pub fn zstd_dictionary(&self) -> Result<Option<Vec<u8>>, ParseError> {
+        let document = roxmltree::Document::parse(self.description())?;
+        match document
+            .descendants()
+            .find(|node| node.attribute("key") == Some(DICTIONARY_KEY))
+            .and_then(|node| node.text())
+        {
+            Some(text) => Ok(Some(decode_base64(text.trim()).ok_or_else(|| {
+                ParseError::General("the embedded zstd dictionary is not valid base64".to_owned())
+            })?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Decompresses the packets with the zstd dictionary embedded in the file, if any.
+    pub fn with_embedded_dictionary(self) -> Result<Self, ParseError> {
+        Ok(match self.zstd_dictionary()? {
+            Some(dictionary) => {
+                self.with_compression_backend(Box::new(TunedBackend::new().dictionary(dictionary)))
+            }
+            None => self,
+        })
+    }
+}
+
+/// Key of the description attribute that holds the base64-encoded zstd dictionary.
+pub(crate) const DICTIONARY_KEY: &str = "zstdDictionary";
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub(crate) fn encode_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let value = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                text.push(BASE64_ALPHABET[(value >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut value = 0u32;
+    let mut bits = 0;
+    for character in text.bytes() {
+        value = value << 6
+            | BASE64_ALPHABET
+                .iter()
+                .position(|symbol| *symbol == character)? as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((value >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Size of a stream with another codec.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodecEstimate {
+    pub compression: Compression,
+    pub bytes: u64,
+    /// Time spent compressing the stream.
+    pub duration: std::time::Duration,
+}
+
+/// Compression statistics of one stream (see `analyze_compression`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamCompression {
+    pub stream_id: u32,
+    pub content: StreamContent,
+    pub packets: usize,
+    pub uncompressed_bytes: u64,
+    /// Size in the file, without the 8 bytes packet headers.
+    pub compressed_bytes: u64,
+    /// Codecs disabled at build time are missing.
+    pub estimates: Vec<CodecEstimate>,
+}
+
+impl StreamCompression {
+    /// Uncompressed size divided by compressed size.
+    pub fn ratio(&self) -> f64 {
+        self.uncompressed_bytes as f64 / self.compressed_bytes.max(1) as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionReport {
+    /// Codec of the file.
+    pub compression: Compression,
+    /// Sorted by stream id.
+    pub streams: Vec<StreamCompression>,
+}
+
+impl std::fmt::Display for CompressionReport {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(formatter, "file compression: {:?}", self.compression)?;
+        for stream in self.streams.iter() {
+            writeln!(
+                formatter,
+                "stream {} ({}): {} packets, {} bytes -> {} bytes (ratio {:.2})",
+                stream.stream_id,
+                stream.content,
+                stream.packets,
+                stream.uncompressed_bytes,
+                stream.compressed_bytes,
+                stream.ratio()
+            )?;
+            for estimate in stream.estimates.iter() {
+                writeln!(
+                    formatter,
+                    "    {:?}: {} bytes (ratio {:.2}, {:.1} ms)",
+                    estimate.compression,
+                    estimate.bytes,
+                    stream.uncompressed_bytes as f64 / estimate.bytes.max(1) as f64,
+                    estimate.duration.as_secs_f64() * 1e3
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reports the compressed and uncompressed sizes of each stream of a recording,
+/// and estimates its size with every codec by recompressing its packets.
+pub fn analyze_compression<P: std::convert::AsRef<std::path::Path>>(
+    path: P,
+) -> Result<CompressionReport, ParseError> {
+    let mut decoder = Decoder::new_from_file(path)?;
+    let codecs = [
+        Compression::None,
+        Compression::Lz4,
+        Compression::Lz4High,
+        Compression::Zstd,
+        Compression::ZstdHigh,
+    ];
+    let mut streams = decoder
+        .id_to_stream
+        .iter()
+        .map(|(stream_id, stream)| {
+            (
+                *stream_id,
+                StreamCompression {
+                    stream_id: *stream_id,
+                    content: stream.content.clone(),
+                    packets: 0,
+                    uncompressed_bytes: 0,
+                    compressed_bytes: 0,
+                    estimates: codecs
+                        .iter()
+                        .map(|compression| CodecEstimate {
+                            compression: *compression,
+                            bytes: 0,
+                            duration: std::time::Duration::ZERO,
+                        })
+                        .collect(),
+                },
+            )
+        })
+        .collect::<std::collections::HashMap<u32, StreamCompression>>();
+    let mut disabled = [false; 5];
+    let mut position = decoder.position();
+    let mut output = Vec::new();
+    while let Some(packet) = decoder.next() {
+        let packet = packet?;
+        let stream = streams
+            .get_mut(&packet.stream_id)
+            .ok_or(ParseError::UnknownStreamId(packet.stream_id))?;
+        stream.packets += 1;
+        stream.uncompressed_bytes += packet.buffer.len() as u64;
+        stream.compressed_bytes += (decoder.position() - position - 8) as u64;
+        position = decoder.position();
+        for (estimate, disabled) in stream.estimates.iter_mut().zip(disabled.iter_mut()) {
+            if *disabled {
+                continue;
+            }
+            output.clear();
+            let begin = std::time::Instant::now();
+            match DefaultBackend.compress(estimate.compression, &packet.buffer, &mut output) {
+                Ok(()) => {
+                    estimate.duration += begin.elapsed();
+                    estimate.bytes += output.len() as u64;
+                }
+                Err(ParseError::CompressionDisabled { .. }) => *disabled = true,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+    let mut streams = streams
+        .into_values()
+        .map(|mut stream| {
+            stream.estimates = stream
+                .estimates
+                .into_iter()
+                .zip(disabled.iter())
+                .filter(|(_, disabled)| !**disabled)
+                .map(|(estimate, _)| estimate)
+                .collect();
+            stream
+        })
+        .collect::<Vec<StreamCompression>>();
+    streams.sort_by_key(|stream| stream.stream_id);
+    Ok(CompressionReport {
+        compression: decoder.compression(),
+        streams,
+    })
+}
+
+#[cfg(feature = "lz4")]
+fn decompress_lz4(input: &mut dyn Read, output: &mut Vec<u8>, bound: u64) -> Result<(), ParseError> {
+    lz4::Decoder::new(input)?.take(bound).read_to_end(output)?;
+    Ok(())
+}
+
+#[cfg(all(not(feature = "lz4"), feature = "lz4_flex"))]
+fn decompress_lz4(input: &mut dyn Read, output: &mut Vec<u8>, bound: u64) -> Result<(), ParseError> {
+    lz4_flex::frame::FrameDecoder::new(input)
+        .take(bound)
+        .read_to_end(output)?;
+    Ok(())
+}
+
+#[cfg(not(any(feature = "lz4", feature = "lz4_flex")))]
+fn decompress_lz4(_input: &mut dyn Read, _output: &mut Vec<u8>, _bound: u64) -> Result<(), ParseError> {
+    Err(ParseError::CompressionDisabled {
+        compression: "LZ4",
+        features: "lz4 or lz4_flex",
+    })
+}
+
+#[cfg(feature = "lz4")]
+fn compress_lz4(input: &[u8], output: &mut Vec<u8>, level: u32) -> Result<(), ParseError> {
+    let mut encoder = lz4::EncoderBuilder::new()
+        .level(level)
+        .build(std::mem::take(output))?;
+    encoder.write_all(input)?;
+    let (result, error) = encoder.finish();
+    *output = result;
+    error?;
+    Ok(())
+}
+
+#[cfg(all(not(feature = "lz4"), feature = "lz4_flex"))]
+fn compress_lz4(input: &[u8], output: &mut Vec<u8>, _level: u32) -> Result<(), ParseError> {
+    let mut encoder = lz4_flex::frame::FrameEncoder::new(std::mem::take(output));
+    encoder.write_all(input)?;
+    *output = encoder.finish().map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+#[cfg(not(any(feature = "lz4", feature = "lz4_flex")))]
+fn compress_lz4(_input: &[u8], _output: &mut Vec<u8>, _level: u32) -> Result<(), ParseError> {
+    Err(ParseError::CompressionDisabled {
+        compression: "LZ4",
+        features: "lz4 or lz4_flex",
+    })
+}
+
+#[cfg(feature = "zstd")]
+fn decompress_zstd(input: &mut dyn Read, output: &mut Vec<u8>, bound: u64) -> Result<(), ParseError> {
+    zstd::stream::Decoder::new(input)?.take(bound).read_to_end(output)?;
+    Ok(())
+}
+
+#[cfg(all(not(feature = "zstd"), feature = "ruzstd"))]
+fn decompress_zstd(input: &mut dyn Read, output: &mut Vec<u8>, bound: u64) -> Result<(), ParseError> {
+    ruzstd::decoding::StreamingDecoder::new(input)
+        .map_err(|error| ParseError::Decompression(error.to_string()))?
+        .take(bound)
+        .read_to_end(output)?;
+    Ok(())
+}
+
+#[cfg(not(any(feature = "zstd", feature = "ruzstd")))]
+fn decompress_zstd(_input: &mut dyn Read, _output: &mut Vec<u8>, _bound: u64) -> Result<(), ParseError> {
+    Err(ParseError::CompressionDisabled {
+        compression: "ZSTD",
+        features: "zstd or ruzstd",
+    })
+}
+
+#[cfg(feature = "zstd")]
+fn compress_zstd(input: &[u8], output: &mut Vec<u8>, level: i32) -> Result<(), ParseError> {
+    let mut encoder = zstd::stream::Encoder::new(std::mem::take(output), level)?;
+    encoder.write_all(input)?;
+    *output = encoder.finish()?;
+    Ok(())
+}
+
+/// ruzstd only implements its fastest level, which is used for both ZSTD and ZSTD_HIGH.
+#[cfg(all(not(feature = "zstd"), feature = "ruzstd"))]
+fn compress_zstd(input: &[u8], output: &mut Vec<u8>, _level: i32) -> Result<(), ParseError> {
+    ruzstd::encoding::compress(input, output, ruzstd::encoding::CompressionLevel::Fastest);
+    Ok(())
+}
+
+#[cfg(not(any(feature = "zstd", feature = "ruzstd")))]
+fn compress_zstd(_input: &[u8], _output: &mut Vec<u8>, _level: i32) -> Result<(), ParseError> {
+    Err(ParseError::CompressionDisabled {
+        compression: "ZSTD",
+        features: "zstd or ruzstd",
+    })
+}
+
+#[cfg(feature = "zstd")]
+fn compress_zstd_with_dictionary(
+    input: &[u8],
+    output: &mut Vec<u8>,
+    level: i32,
+    dictionary: &[u8],
+) -> Result<(), ParseError> {
+    let mut encoder =
+        zstd::stream::Encoder::with_dictionary(std::mem::take(output), level, dictionary)?;
+    encoder.write_all(input)?;
+    *output = encoder.finish()?;
+    Ok(())
+}
+
+#[cfg(feature = "zstd")]
+fn decompress_zstd_with_dictionary(
+    input: &mut dyn Read,
+    output: &mut Vec<u8>,
+    bound: u64,
+    dictionary: &[u8],
+) -> Result<(), ParseError> {
+    zstd::stream::Decoder::with_dictionary(std::io::BufReader::new(input), dictionary)?
+        .take(bound)
+        .read_to_end(output)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn compress_zstd_with_dictionary(
+    _input: &[u8],
+    _output: &mut Vec<u8>,
+    _level: i32,
+    _dictionary: &[u8],
+) -> Result<(), ParseError> {
+    Err(ParseError::CompressionDisabled {
+        compression: "ZSTD with a dictionary",
+        features: "zstd",
+    })
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress_zstd_with_dictionary(
+    _input: &mut dyn Read,
+    _output: &mut Vec<u8>,
+    _bound: u64,
+    _dictionary: &[u8],
+) -> Result<(), ParseError> {
+    Err(ParseError::CompressionDisabled {
+        compression: "ZSTD with a dictionary",
+        features: "zstd",
+    })
+}
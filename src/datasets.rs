@@ -0,0 +1,180 @@
+//! Readers for public event datasets, enabled by the `datasets` feature.
+//!
+//! - DSEC: `events.h5` files with the datasets `events/t` (microseconds since `t_offset`), `events/x`, `events/y`,
+//!   `events/p` and `t_offset`. DSEC compresses the events with Blosc, hence HDF5 must find the Blosc filter plugin
+//!   (see `HDF5_PLUGIN_PATH`).
+//! - MVSEC: `*_data.hdf5` files with the dataset `{group}/events` (N × 4: x, y, t in seconds, polarity in {-1, 1}),
+//!   where `group` is `davis/left` or `davis/right`.
+//!
+//! Readers yield the same events as `Decoder::events`, hence all the `EventIterator` combinators apply.
+
+use crate::base::{ParseError, Stream, StreamContent};
+use crate::events::Event;
+
+/// Number of events read from the file at once.
+const CHUNK_LENGTH: usize = 1 << 16;
+
+enum Layout {
+    Dsec {
+        t: hdf5::Dataset,
+        x: hdf5::Dataset,
+        y: hdf5::Dataset,
+        p: hdf5::Dataset,
+        t_offset: i64,
+    },
+    Mvsec {
+        events: hdf5::Dataset,
+    },
+}
+
+/// Iterates over the events of a dataset file.
+pub struct DatasetReader {
+    pub stream: Stream,
+    layout: Layout,
+    length: usize,
+    position: usize,
+    chunk: std::vec::IntoIter<Event>,
+}
+
+impl DatasetReader {
+    /// Opens a DSEC `events.h5` file (640 × 480 Prophesee Gen3.1 sensor).
+    pub fn dsec<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, ParseError> {
+        let file = hdf5::File::open(path)?;
+        let t = file.dataset("events/t")?;
+        let length = t.size();
+        let x = file.dataset("events/x")?;
+        let y = file.dataset("events/y")?;
+        let p = file.dataset("events/p")?;
+        for dataset in [&x, &y, &p] {
+            if dataset.size() != length {
+                return Err(ParseError::General(format!(
+                    "{} has {} events instead of {}",
+                    dataset.name(),
+                    dataset.size(),
+                    length
+                )));
+            }
+        }
+        let t_offset = match file.dataset("t_offset") {
+            Ok(dataset) => dataset.read_scalar::<i64>()?,
+            Err(_) => 0,
+        };
+        Ok(DatasetReader::new(
+            640,
+            480,
+            Layout::Dsec {
+                t,
+                x,
+                y,
+                p,
+                t_offset,
+            },
+            length,
+        ))
+    }
+
+    /// Opens the events of one camera (`group` is typically `davis/left`) of an MVSEC file (346 × 260 DAVIS sensor).
+    pub fn mvsec<P: std::convert::AsRef<std::path::Path>>(
+        path: P,
+        group: &str,
+    ) -> Result<Self, ParseError> {
+        let file = hdf5::File::open(path)?;
+        let events = file.dataset(&format!("{}/events", group))?;
+        let shape = events.shape();
+        if shape.len() != 2 || shape[1] != 4 {
+            return Err(ParseError::General(format!(
+                "unexpected MVSEC events shape {:?}",
+                shape
+            )));
+        }
+        Ok(DatasetReader::new(
+            346,
+            260,
+            Layout::Mvsec { events },
+            shape[0],
+        ))
+    }
+
+    fn new(width: u16, height: u16, layout: Layout, length: usize) -> Self {
+        DatasetReader {
+            stream: Stream {
+                content: StreamContent::Events,
+                width,
+                height,
+            },
+            layout,
+            length,
+            position: 0,
+            chunk: Vec::new().into_iter(),
+        }
+    }
+
+    /// Total number of events in the file.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    fn read_chunk(&mut self) -> Result<Vec<Event>, ParseError> {
+        let range = self.position..(self.position + CHUNK_LENGTH).min(self.length);
+        self.position = range.end;
+        Ok(match &self.layout {
+            Layout::Dsec {
+                t,
+                x,
+                y,
+                p,
+                t_offset,
+            } => {
+                let t = t.read_slice_1d::<u64, _>(range.clone())?;
+                let x = x.read_slice_1d::<u16, _>(range.clone())?;
+                let y = y.read_slice_1d::<u16, _>(range.clone())?;
+                let p = p.read_slice_1d::<u8, _>(range)?;
+                t.iter()
+                    .zip(x.iter())
+                    .zip(y.iter().zip(p.iter()))
+                    .map(|((t, x), (y, p))| {
+                        Event::new(*t as i64 + t_offset, *x as i16, *y as i16, *p > 0)
+                    })
+                    .collect()
+            }
+            Layout::Mvsec { events } => events
+                .read_slice_2d::<f64, _>((range, ..))?
+                .outer_iter()
+                .map(|row| {
+                    Event::new(
+                        (row[2] * 1e6).round() as i64,
+                        row[0] as i16,
+                        row[1] as i16,
+                        row[3] > 0.0,
+                    )
+                })
+                .collect(),
+        })
+    }
+}
+
+impl Iterator for DatasetReader {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.chunk.next() {
+                return Some(Ok(event));
+            }
+            if self.position >= self.length {
+                return None;
+            }
+            match self.read_chunk() {
+                Ok(chunk) => self.chunk = chunk.into_iter(),
+                Err(error) => {
+                    self.position = self.length;
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
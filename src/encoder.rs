@@ -0,0 +1,668 @@
+use crate::base::{ioheader_generated, FileDataEntry, Packet, ParseError, Stream, StreamContent};
+use crate::compression::{
+    encode_base64, CompressionBackend, DefaultBackend, TunedBackend, DICTIONARY_KEY,
+};
+use crate::events::Event;
+use crate::file_data_table_generated;
+use crate::integrity::{Checksum, SidecarWriter};
+use crate::statistics::StreamStatistics;
+use std::io::{Seek, Write};
+use std::net::{TcpListener, ToSocketAddrs};
+#[cfg(target_family = "unix")]
+use std::os::unix::net::UnixListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+trait Sink: std::io::Write + Send {
+    fn configure(&self) -> std::io::Result<()>;
+}
+
+impl Sink for std::net::TcpStream {
+    fn configure(&self) -> std::io::Result<()> {
+        self.set_nonblocking(false)?;
+        self.set_write_timeout(Some(CLIENT_TIMEOUT))
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl Sink for std::os::unix::net::UnixStream {
+    fn configure(&self) -> std::io::Result<()> {
+        self.set_nonblocking(false)?;
+        self.set_write_timeout(Some(CLIENT_TIMEOUT))
+    }
+}
+
+type Clients = Arc<Mutex<Vec<Box<dyn Sink>>>>;
+
+/// Clients that do not read a packet within this duration are dropped, so that they do not stall the encoder.
+const CLIENT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Period at which the acceptor checks that the encoder was not dropped.
+const ACCEPT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+const MAGIC_NUMBER: &str = "#!AER-DAT4.0\r\n";
+
+struct FileOutput {
+    file: std::io::BufWriter<std::fs::File>,
+    position: i64,
+    entries: Vec<FileDataEntry>,
+}
+
+/// Packetization of the events written by an encoder (see `Encoder::with_rechunk`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rechunk {
+    /// Packets span at most the given duration in microseconds.
+    Duration(i64),
+    /// Packets contain the given number of events (except the last one).
+    Count(usize),
+}
+
+enum Output {
+    Clients(Clients),
+    File(Option<FileOutput>),
+}
+
+/// Writes packets to an AEDAT4 file, or using the AEDAT4 network protocol (IOHeader, then packets).
+///
+/// Every network client that connects receives the IOHeader, followed by the packets written after its connection.
+/// Network encoders stop listening (and remove their Unix socket) when they are dropped.
+/// Files are completed with a file data table by `finish` (or when the encoder is dropped).
+pub struct Encoder {
+    pub id_to_stream: std::collections::HashMap<u32, Stream>,
+    compression: ioheader_generated::Compression,
+    output: Output,
+    backend: Box<dyn CompressionBackend>,
+    rechunk: Option<Rechunk>,
+    pending: std::collections::BTreeMap<u32, Vec<Event>>,
+    checksums: Option<SidecarWriter>,
+    dictionary: Option<Vec<u8>>,
+    packed_events: bool,
+    acceptor: Option<Acceptor>,
+}
+
+impl Encoder {
+    pub fn new_to_tcp<A: ToSocketAddrs>(
+        address: A,
+        id_to_stream: std::collections::HashMap<u32, Stream>,
+        compression: ioheader_generated::Compression,
+    ) -> Result<Self, ParseError> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let clients = Clients::default();
+        let mut encoder = Encoder::new(id_to_stream, compression, Output::Clients(clients.clone()))?;
+        let header = encode_io_header(&encoder.id_to_stream, compression, -1, None, None)?;
+        encoder.acceptor = Some(Acceptor::spawn(
+            move || listener.accept().map(|(stream, _)| stream),
+            header,
+            clients,
+            None,
+        ));
+        Ok(encoder)
+    }
+
+    #[cfg(target_family = "unix")]
+    pub fn new_to_unix<P: std::convert::AsRef<std::path::Path>>(
+        path: P,
+        id_to_stream: std::collections::HashMap<u32, Stream>,
+        compression: ioheader_generated::Compression,
+    ) -> Result<Self, ParseError> {
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+        let clients = Clients::default();
+        let mut encoder = Encoder::new(id_to_stream, compression, Output::Clients(clients.clone()))?;
+        let header = encode_io_header(&encoder.id_to_stream, compression, -1, None, None)?;
+        encoder.acceptor = Some(Acceptor::spawn(
+            move || listener.accept().map(|(stream, _)| stream),
+            header,
+            clients,
+            Some(path.as_ref().to_path_buf()),
+        ));
+        Ok(encoder)
+    }
+
+    pub fn new_to_file<P: std::convert::AsRef<std::path::Path>>(
+        path: P,
+        id_to_stream: std::collections::HashMap<u32, Stream>,
+        compression: ioheader_generated::Compression,
+    ) -> Result<Self, ParseError> {
+        let mut encoder = Encoder::new(id_to_stream, compression, Output::File(None))?;
+        let header = encode_io_header(
+            &encoder.id_to_stream,
+            compression,
+            -1,
+            None,
+            Some(&std::collections::HashMap::new()),
+        )?;
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        file.write_all(MAGIC_NUMBER.as_bytes())?;
+        file.write_all(&header)?;
+        encoder.output = Output::File(Some(FileOutput {
+            file,
+            position: (MAGIC_NUMBER.len() + header.len()) as i64,
+            entries: Vec::new(),
+        }));
+        Ok(encoder)
+    }
+
+    fn new(
+        id_to_stream: std::collections::HashMap<u32, Stream>,
+        compression: ioheader_generated::Compression,
+        output: Output,
+    ) -> Result<Self, ParseError> {
+        if id_to_stream.is_empty() {
+            return Err(ParseError::NoStreams);
+        }
+        compression_name(compression)?;
+        Ok(Encoder {
+            id_to_stream,
+            compression,
+            output,
+            backend: Box::new(DefaultBackend),
+            rechunk: None,
+            pending: std::collections::BTreeMap::new(),
+            checksums: None,
+            dictionary: None,
+            packed_events: false,
+            acceptor: None,
+        })
+    }
+
+    /// Replaces the compression backend selected by cargo features.
+    pub fn with_compression_backend(mut self, backend: Box<dyn CompressionBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Compresses packets with custom levels, and with a zstd dictionary if the backend has one.
+    ///
+    /// The dictionary is embedded in the file description (see `Decoder::with_embedded_dictionary`), hence
+    /// it must be set on a file output before writing packets.
+    pub fn with_tuned_compression(mut self, backend: TunedBackend) -> Result<Self, ParseError> {
+        if let Some(dictionary) = backend.get_dictionary() {
+            let output = match &mut self.output {
+                Output::File(Some(output)) if output.entries.is_empty() => output,
+                _ => {
+                    return Err(ParseError::Usage(
+                        "zstd dictionaries require a file output without packets".to_owned(),
+                    ))
+                }
+            };
+            let header = encode_io_header(
+                &self.id_to_stream,
+                self.compression,
+                -1,
+                Some(dictionary),
+                Some(&std::collections::HashMap::new()),
+            )?;
+            output
+                .file
+                .seek(std::io::SeekFrom::Start(MAGIC_NUMBER.len() as u64))?;
+            output.file.write_all(&header)?;
+            output.file.flush()?;
+            output.position = (MAGIC_NUMBER.len() + header.len()) as i64;
+            output.file.get_ref().set_len(output.position as u64)?;
+            self.dictionary = Some(dictionary.to_vec());
+        }
+        self.backend = Box::new(backend);
+        Ok(self)
+    }
+
+    /// Stores event packets with the packed encoding of this crate (see `packed`), before compression.
+    ///
+    /// Files and streams with packed events can only be read by this crate.
+    pub fn with_packed_events(mut self, packed_events: bool) -> Self {
+        self.packed_events = packed_events;
+        self
+    }
+
+    /// Re-packetizes events independently of the input packets.
+    ///
+    /// Events are buffered until a packet is complete, hence event packets may be written after
+    /// packets from other streams that they precede. `flush` writes the buffered events.
+    pub fn with_rechunk(mut self, rechunk: Rechunk) -> Self {
+        self.rechunk = Some(rechunk);
+        self
+    }
+
+    /// Writes a checksum of every packet to a sidecar file (see `integrity`), checked by `Decoder::verify_integrity`.
+    ///
+    /// Only file outputs support checksums.
+    pub fn with_checksums<P: std::convert::AsRef<std::path::Path>>(
+        mut self,
+        path: P,
+        checksum: Checksum,
+    ) -> Result<Self, ParseError> {
+        if let Output::Clients(_) = self.output {
+            return Err(ParseError::Usage(
+                "checksums require a file output".to_owned(),
+            ));
+        }
+        self.checksums = Some(SidecarWriter::new(path, checksum)?);
+        Ok(self)
+    }
+
+    /// Writes a packet to the file, or sends it to every connected client.
+    ///
+    /// Clients whose connection failed, or that did not read the packet within a second, are dropped.
+    pub fn write(&mut self, packet: &Packet) -> Result<(), ParseError> {
+        let rechunk = match self.rechunk {
+            Some(rechunk) => rechunk,
+            None => return self.write_packet(packet),
+        };
+        match self.id_to_stream.get(&packet.stream_id) {
+            Some(stream) if stream.content == StreamContent::Events => {}
+            _ => return self.write_packet(packet),
+        }
+        let mut pending = self.pending.remove(&packet.stream_id).unwrap_or_default();
+        for event in packet.events()? {
+            let complete = match (rechunk, pending.first()) {
+                (Rechunk::Duration(duration), Some(first)) => event.t() >= first.t() + duration,
+                (Rechunk::Count(count), _) => pending.len() >= count.max(1),
+                _ => false,
+            };
+            if complete {
+                self.write_packet(&Packet::from_events(packet.stream_id, &pending))?;
+                pending.clear();
+            }
+            pending.push(event);
+        }
+        self.pending.insert(packet.stream_id, pending);
+        Ok(())
+    }
+
+    /// Writes the events buffered by the re-packetization policy.
+    pub fn flush(&mut self) -> Result<(), ParseError> {
+        for (stream_id, events) in std::mem::take(&mut self.pending) {
+            if !events.is_empty() {
+                self.write_packet(&Packet::from_events(stream_id, &events))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_packet(&mut self, packet: &Packet) -> Result<(), ParseError> {
+        let bytes = encode_packet(
+            &self.id_to_stream,
+            self.compression,
+            self.backend.as_ref(),
+            packet,
+            self.packed_events,
+        )?;
+        match &mut self.output {
+            Output::Clients(clients) => {
+                // clients are written without the lock, which would block the acceptor
+                let mut connected = std::mem::take(&mut *clients.lock().unwrap());
+                connected.retain_mut(|client| client.write_all(&bytes).is_ok());
+                let mut clients = clients.lock().unwrap();
+                // clients accepted during the write receive the next packet
+                connected.append(&mut clients);
+                *clients = connected;
+            }
+            Output::File(Some(output)) => {
+                let (num_elements, timestamp_start, timestamp_end) =
+                    packet.summary(&self.id_to_stream[&packet.stream_id].content)?;
+                output.file.write_all(&bytes)?;
+                if let Some(checksums) = &mut self.checksums {
+                    checksums.write(output.position + 8, &bytes)?;
+                }
+                output.entries.push(FileDataEntry {
+                    byte_offset: output.position + 8,
+                    stream_id: packet.stream_id,
+                    size: (bytes.len() - 8) as u32,
+                    num_elements,
+                    timestamp_start,
+                    timestamp_end,
+                });
+                output.position += bytes.len() as i64;
+            }
+            Output::File(None) => {
+                return Err(ParseError::Usage("the file is finished".to_string()))
+            }
+        }
+        Ok(())
+    }
+
+    /// Size of the file written so far, excluding the file data table (zero for network outputs).
+    pub fn bytes_written(&self) -> u64 {
+        match &self.output {
+            Output::File(Some(output)) => output.position as u64,
+            _ => 0,
+        }
+    }
+
+    /// Number of connected clients (zero for file outputs).
+    pub fn clients(&self) -> usize {
+        match &self.output {
+            Output::Clients(clients) => clients.lock().unwrap().len(),
+            Output::File(_) => 0,
+        }
+    }
+
+    /// Writes the buffered events, then the file data table, and updates the header.
+    ///
+    /// Only the buffered events are written for network outputs.
+    pub fn finish(mut self) -> Result<(), ParseError> {
+        self.finish_file()
+    }
+
+    fn finish_file(&mut self) -> Result<(), ParseError> {
+        if let Output::File(None) = self.output {
+            return Ok(());
+        }
+        self.flush()?;
+        let mut output = match &mut self.output {
+            Output::File(output) => match output.take() {
+                Some(output) => output,
+                None => return Ok(()),
+            },
+            Output::Clients(_) => return Ok(()),
+        };
+        let table = encode_file_data_table(&output.entries)?;
+        let mut bytes = Vec::new();
+        self.backend
+            .compress(self.compression, &table, &mut bytes)?;
+        output.file.write_all(&bytes)?;
+        let header = encode_io_header(
+            &self.id_to_stream,
+            self.compression,
+            output.position,
+            self.dictionary.as_deref(),
+            Some(&crate::statistics::summarize(
+                &self.id_to_stream,
+                &output.entries,
+            )),
+        )?;
+        output
+            .file
+            .seek(std::io::SeekFrom::Start(MAGIC_NUMBER.len() as u64))?;
+        output.file.write_all(&header)?;
+        output.file.flush()?;
+        if let Some(checksums) = &mut self.checksums {
+            checksums.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Encoder {
+    fn drop(&mut self) {
+        let _ = self.finish_file();
+    }
+}
+
+/// Thread that accepts network clients, stopped when the encoder is dropped.
+struct Acceptor {
+    running: Arc<AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+    socket_path: Option<std::path::PathBuf>,
+}
+
+impl Acceptor {
+    /// `accept` must not block (the listener is non-blocking), and `socket_path` is removed on drop.
+    fn spawn<S: Sink + 'static>(
+        mut accept: impl FnMut() -> std::io::Result<S> + Send + 'static,
+        header: Vec<u8>,
+        clients: Clients,
+        socket_path: Option<std::path::PathBuf>,
+    ) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let running = running.clone();
+            std::thread::spawn(move || {
+                while running.load(Ordering::Acquire) {
+                    match accept() {
+                        Ok(mut stream) => {
+                            if stream.configure().is_ok() && stream.write_all(&header).is_ok() {
+                                clients.lock().unwrap().push(Box::new(stream));
+                            }
+                        }
+                        // no pending connection, or a client that disconnected before it was accepted
+                        Err(_) => std::thread::sleep(ACCEPT_INTERVAL),
+                    }
+                }
+            })
+        };
+        Acceptor {
+            running,
+            thread: Some(thread),
+            socket_path,
+        }
+    }
+}
+
+impl Drop for Acceptor {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        // the listener is closed when the thread returns
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        if let Some(socket_path) = &self.socket_path {
+            let _ = std::fs::remove_file(socket_path);
+        }
+    }
+}
+
+fn compression_name(compression: ioheader_generated::Compression) -> Result<&'static str, ParseError> {
+    match compression {
+        ioheader_generated::Compression::None => Ok("NONE"),
+        ioheader_generated::Compression::Lz4 => Ok("LZ4"),
+        ioheader_generated::Compression::Lz4High => Ok("LZ4_HIGH"),
+        ioheader_generated::Compression::Zstd => Ok("ZSTD"),
+        ioheader_generated::Compression::ZstdHigh => Ok("ZSTD_HIGH"),
+        _ => Err(ParseError::UnknownCompression(compression.0)),
+    }
+}
+
+fn type_description(content: &StreamContent) -> &'static str {
+    match content {
+        StreamContent::Events => "Array of events (polarity ON/OFF).",
+        StreamContent::Frame => "Standard frame (8-bit image).",
+        StreamContent::Imus => "Inertial Measurement Unit data samples.",
+        StreamContent::Triggers => "External triggers and special signals.",
+        StreamContent::BoundingBoxes => "Bounding boxes (object detection).",
+        StreamContent::Pose => "Pose (translation and rotation).",
+        StreamContent::Other(_) => "Custom data.",
+    }
+}
+
+fn encode_description(
+    id_to_stream: &std::collections::HashMap<u32, Stream>,
+    compression: ioheader_generated::Compression,
+    dictionary: Option<&[u8]>,
+    statistics: Option<&std::collections::HashMap<u32, StreamStatistics>>,
+) -> Result<String, ParseError> {
+    let compression = compression_name(compression)?;
+    let mut ids: Vec<&u32> = id_to_stream.keys().collect();
+    ids.sort();
+    let mut description = String::from(
+        "<dv version=\"2.0\">\n    <node name=\"outInfo\" path=\"/mainloop/Recorder/outInfo/\">\n",
+    );
+    if let Some(dictionary) = dictionary {
+        description.push_str(&format!(
+            "        <attr key=\"{}\" type=\"string\">{}</attr>\n",
+            DICTIONARY_KEY,
+            encode_base64(dictionary)
+        ));
+    }
+    for id in ids {
+        let stream = &id_to_stream[id];
+        let path = format!("/mainloop/Recorder/outInfo/{}/", id);
+        description.push_str(&format!("        <node name=\"{}\" path=\"{}\">\n", id, path));
+        description.push_str(&format!(
+            "            <attr key=\"compression\" type=\"string\">{}</attr>\n",
+            compression
+        ));
+        description.push_str(&format!(
+            "            <attr key=\"typeDescription\" type=\"string\">{}</attr>\n",
+            type_description(&stream.content)
+        ));
+        description.push_str(&format!(
+            "            <attr key=\"typeIdentifier\" type=\"string\">{}</attr>\n",
+            stream.content
+        ));
+        if let StreamContent::Events | StreamContent::Frame = stream.content {
+            description.push_str(&format!("            <node name=\"info\" path=\"{}info/\">\n", path));
+            description.push_str(&format!(
+                "                <attr key=\"sizeX\" type=\"int\">{}</attr>\n",
+                stream.width
+            ));
+            description.push_str(&format!(
+                "                <attr key=\"sizeY\" type=\"int\">{}</attr>\n",
+                stream.height
+            ));
+            description.push_str("            </node>\n");
+        }
+        if let Some(statistics) = statistics {
+            description.push_str(&crate::statistics::encode(&path, statistics.get(id)));
+        }
+        description.push_str("        </node>\n");
+    }
+    description.push_str("    </node>\n</dv>\n");
+    Ok(description)
+}
+
+/// Encodes packets as a complete file (header, packets and file data table) in memory.
+pub(crate) fn encode_to_bytes(
+    id_to_stream: &std::collections::HashMap<u32, Stream>,
+    compression: ioheader_generated::Compression,
+    backend: &dyn CompressionBackend,
+    packets: &[Packet],
+) -> Result<Vec<u8>, ParseError> {
+    compression_name(compression)?;
+    let header_length = encode_io_header(
+        id_to_stream,
+        compression,
+        -1,
+        None,
+        Some(&std::collections::HashMap::new()),
+    )?
+    .len();
+    let mut body = Vec::new();
+    let mut entries = Vec::with_capacity(packets.len());
+    for packet in packets {
+        let bytes = encode_packet(id_to_stream, compression, backend, packet, false)?;
+        let (num_elements, timestamp_start, timestamp_end) =
+            packet.summary(&id_to_stream[&packet.stream_id].content)?;
+        entries.push(FileDataEntry {
+            byte_offset: (MAGIC_NUMBER.len() + header_length + body.len() + 8) as i64,
+            stream_id: packet.stream_id,
+            size: (bytes.len() - 8) as u32,
+            num_elements,
+            timestamp_start,
+            timestamp_end,
+        });
+        body.extend_from_slice(&bytes);
+    }
+    let mut bytes = Vec::with_capacity(MAGIC_NUMBER.len() + header_length + body.len());
+    bytes.extend_from_slice(MAGIC_NUMBER.as_bytes());
+    bytes.extend_from_slice(&encode_io_header(
+        id_to_stream,
+        compression,
+        (MAGIC_NUMBER.len() + header_length + body.len()) as i64,
+        None,
+        Some(&crate::statistics::summarize(id_to_stream, &entries)),
+    )?);
+    bytes.extend_from_slice(&body);
+    backend.compress(
+        compression,
+        &encode_file_data_table(&entries)?,
+        &mut bytes,
+    )?;
+    Ok(bytes)
+}
+
+/// Encodes an IOHeader. Streams missing from `statistics` get placeholders (unfinished files), and network
+/// headers have no statistics (`None`).
+pub(crate) fn encode_io_header(
+    id_to_stream: &std::collections::HashMap<u32, Stream>,
+    compression: ioheader_generated::Compression,
+    file_data_position: i64,
+    dictionary: Option<&[u8]>,
+    statistics: Option<&std::collections::HashMap<u32, StreamStatistics>>,
+) -> Result<Vec<u8>, ParseError> {
+    let description = encode_description(id_to_stream, compression, dictionary, statistics)?;
+    let mut builder = flatbuffers::FlatBufferBuilder::new();
+    // file_data_position is rewritten when a file is finished, the header size must not change
+    builder.force_defaults(true);
+    let description = builder.create_string(&description);
+    let ioheader = ioheader_generated::Ioheader::create(
+        &mut builder,
+        &ioheader_generated::IoheaderArgs {
+            compression,
+            file_data_position,
+            description: Some(description),
+        },
+    );
+    ioheader_generated::finish_size_prefixed_ioheader_buffer(&mut builder, ioheader);
+    Ok(builder.finished_data().to_vec())
+}
+
+pub(crate) fn encode_packet(
+    id_to_stream: &std::collections::HashMap<u32, Stream>,
+    compression: ioheader_generated::Compression,
+    backend: &dyn CompressionBackend,
+    packet: &Packet,
+    packed_events: bool,
+) -> Result<Vec<u8>, ParseError> {
+    let expected_content = match id_to_stream.get(&packet.stream_id) {
+        Some(content) => &content.content,
+        None => return Err(ParseError::UnknownStreamId(packet.stream_id)),
+    };
+    if !matches!(expected_content, StreamContent::Other(_))
+        && !packet.has_identifier(&expected_content.to_string())
+    {
+        return Err(ParseError::IdentifierMismatch {
+            stream_id: packet.stream_id,
+            expected: expected_content.to_string(),
+            got: String::from_utf8_lossy(packet.buffer.get(8..12).unwrap_or(&[])).to_string(),
+        });
+    }
+    let mut bytes = Vec::with_capacity(8 + packet.buffer.len());
+    bytes.extend_from_slice(&packet.stream_id.to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    if packed_events && *expected_content == StreamContent::Events {
+        backend.compress(
+            compression,
+            &crate::packed::pack(&packet.events()?),
+            &mut bytes,
+        )?;
+    } else {
+        backend.compress(compression, &packet.buffer, &mut bytes)?;
+    }
+    let length = (bytes.len() - 8) as u32;
+    bytes[4..8].copy_from_slice(&length.to_le_bytes());
+    Ok(bytes)
+}
+
+fn encode_file_data_table(entries: &[FileDataEntry]) -> Result<Vec<u8>, ParseError> {
+    let mut builder = flatbuffers::FlatBufferBuilder::new();
+    let definitions: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            let packet_info = file_data_table_generated::PacketHeader::new(
+                entry.stream_id as i32,
+                entry.size as i32,
+            );
+            file_data_table_generated::FileDataDefinition::create(
+                &mut builder,
+                &file_data_table_generated::FileDataDefinitionArgs {
+                    byte_offset: entry.byte_offset,
+                    packet_info: Some(&packet_info),
+                    num_elements: entry.num_elements,
+                    timestamp_start: entry.timestamp_start,
+                    timestamp_end: entry.timestamp_end,
+                },
+            )
+        })
+        .collect();
+    let definitions = builder.create_vector(&definitions);
+    let table = file_data_table_generated::FileDataTable::create(
+        &mut builder,
+        &file_data_table_generated::FileDataTableArgs {
+            table: Some(definitions),
+        },
+    );
+    file_data_table_generated::finish_size_prefixed_file_data_table_buffer(&mut builder, table);
+    Ok(builder.finished_data().to_vec())
+}
@@ -0,0 +1,158 @@
+use crate::accumulator::{Accumulator, Frames};
+use crate::base::{Packet, ParseError};
+use crate::calibration::{Projected, Projector, Undistorted, Undistorter};
+use crate::chunks::Chunks;
+use crate::features::{CornerDetector, Corners};
+pub use crate::events_generated::Event;
+use crate::filters::{Crop, Denoiser, NoiseFilter, RandomDrop, Transform, Transformed};
+#[cfg(feature = "flow")]
+use crate::flow::{FlowGroundTruth, FlowWindows};
+use crate::polarity::Polarity;
+use crate::soa::EventBatches;
+use crate::throttle::{Throttle, ThrottlePolicy};
+use crate::tracking::{ClusterTracker, Tracked};
+
+/// Iterates over the events of a packet stream (for instance a `Decoder`).
+///
+/// Packets that do not belong to an event stream are skipped.
+pub struct Events<I> {
+    packets: I,
+    events: std::vec::IntoIter<Event>,
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> Events<I> {
+    pub fn new(packets: I) -> Self {
+        Events {
+            packets,
+            events: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> Iterator for Events<I> {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.events.next() {
+                return Some(Ok(event));
+            }
+            let packet = match self.packets.next()? {
+                Ok(content) => content,
+                Err(error) => return Some(Err(error)),
+            };
+            if !packet.has_identifier(crate::events_generated::EVENT_PACKET_IDENTIFIER) {
+                continue;
+            }
+            match packet.events() {
+                Ok(events) => self.events = events.into_iter(),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// Converts a packet stream into an event stream.
+pub trait PacketIterator: Iterator<Item = Result<Packet, ParseError>> + Sized {
+    fn events(self) -> Events<Self> {
+        Events::new(self)
+    }
+
+    /// See `EventBatches`.
+    fn event_batches(self) -> EventBatches<Self> {
+        EventBatches::new(self)
+    }
+
+    /// See `Throttle`.
+    fn throttle(self, max_lag: i64, policy: ThrottlePolicy) -> Throttle<Self> {
+        Throttle::new(self, max_lag, policy)
+    }
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> PacketIterator for I {}
+
+/// Combinators for event streams, for instance `decoder.events().crop(0, 0, 64, 64).chunks_by_time(10000)`.
+pub trait EventIterator: Iterator<Item = Result<Event, ParseError>> + Sized {
+    /// See `Polarity::on`.
+    fn on(self) -> Polarity<Self> {
+        Polarity::on(self)
+    }
+
+    /// See `Polarity::off`.
+    fn off(self) -> Polarity<Self> {
+        Polarity::off(self)
+    }
+
+    /// See `NoiseFilter`.
+    fn filter_noise(self, width: u16, height: u16, tau: i64) -> NoiseFilter<Self> {
+        NoiseFilter::new(self, width, height, tau)
+    }
+
+    /// See `NoiseFilter::denoiser`.
+    fn denoise(self, denoiser: Denoiser, width: u16, height: u16, tau: i64) -> NoiseFilter<Self> {
+        NoiseFilter::new(self, width, height, tau).denoiser(denoiser)
+    }
+
+    /// See `Corners`.
+    fn corners(self, detector: CornerDetector, width: u16, height: u16) -> Corners<Self> {
+        Corners::new(self, detector, width, height)
+    }
+
+    /// See `Crop`.
+    fn crop(self, x: i16, y: i16, width: i16, height: i16) -> Crop<Self> {
+        Crop::new(self, x, y, width, height)
+    }
+
+    /// See `Transformed`.
+    fn transform(self, transform: Transform, width: u16, height: u16) -> Transformed<Self> {
+        Transformed::new(self, transform, width, height)
+    }
+
+    /// See `Undistorted`.
+    fn undistort(self, undistorter: Undistorter) -> Undistorted<Self> {
+        Undistorted::new(self, undistorter)
+    }
+
+    /// See `Projected`.
+    fn project(self, projector: Projector) -> Projected<Self> {
+        Projected::new(self, projector)
+    }
+
+    /// See `RandomDrop`.
+    fn random_drop(self, keep_probability: f64, seed: u64) -> RandomDrop<Self> {
+        RandomDrop::new(self, keep_probability, seed)
+    }
+
+    /// See `FlowGroundTruth::windows`.
+    #[cfg(feature = "flow")]
+    fn flow_windows(self, ground_truth: FlowGroundTruth) -> FlowWindows<Self> {
+        ground_truth.windows(self)
+    }
+
+    /// See `Tracked`.
+    fn track_clusters(self, tracker: ClusterTracker) -> Tracked<Self> {
+        Tracked::new(self, tracker)
+    }
+
+    /// See `Chunks::by_count`.
+    fn chunks_by_count(self, count: usize) -> Chunks<Self> {
+        Chunks::by_count(self, count)
+    }
+
+    /// See `Chunks::by_duration`.
+    fn chunks_by_time(self, duration: i64) -> Chunks<Self> {
+        Chunks::by_duration(self, duration)
+    }
+
+    /// See `Chunks::by_duration`, same as `chunks_by_time`.
+    fn chunks_by_duration(self, duration: i64) -> Chunks<Self> {
+        Chunks::by_duration(self, duration)
+    }
+
+    /// See `Accumulator::frames`.
+    fn accumulate(self, accumulator: Accumulator, period: i64) -> Frames<Self> {
+        accumulator.frames(self, period)
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> EventIterator for I {}
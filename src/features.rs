@@ -0,0 +1,195 @@
+//! Event-based corner detectors.
+//!
+//! Both detectors look at two circles (radius 3 and 4) of the surface of active events (SAE) around each event,
+//! and keep the event if the most recent pixels of each circle form a contiguous arc of the right length.
+//!
+//! - eFAST (Mueggler et al., 2017) tests every arc of the right length.
+//! - Arc* (Alzugaray and Chli, 2018) grows a single arc from the most recent pixel, which is faster
+//!   and more robust to noise.
+
+use crate::base::ParseError;
+use crate::events::Event;
+
+const INNER_CIRCLE: [(i64, i64); 16] = [
+    (0, 3),
+    (1, 3),
+    (2, 2),
+    (3, 1),
+    (3, 0),
+    (3, -1),
+    (2, -2),
+    (1, -3),
+    (0, -3),
+    (-1, -3),
+    (-2, -2),
+    (-3, -1),
+    (-3, 0),
+    (-3, 1),
+    (-2, 2),
+    (-1, 3),
+];
+
+const OUTER_CIRCLE: [(i64, i64); 20] = [
+    (0, 4),
+    (1, 4),
+    (2, 3),
+    (3, 2),
+    (4, 1),
+    (4, 0),
+    (4, -1),
+    (3, -2),
+    (2, -3),
+    (1, -4),
+    (0, -4),
+    (-1, -4),
+    (-2, -3),
+    (-3, -2),
+    (-4, -1),
+    (-4, 0),
+    (-4, 1),
+    (-3, 2),
+    (-2, 3),
+    (-1, 4),
+];
+
+/// Minimum and maximum arc lengths on the inner and outer circles.
+const INNER_ARC: (usize, usize) = (3, 6);
+const OUTER_ARC: (usize, usize) = (4, 8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CornerDetector {
+    Fast,
+    Arc,
+}
+
+/// Yields the events detected as corners (see the module documentation).
+///
+/// Events less than four pixels away from the border are never corners. By default, an event is tested only if
+/// its pixel did not produce an event with the same polarity during the last 50 ms (see `Corners::refractory_period`),
+/// which removes most of the redundant detections along edges.
+pub struct Corners<I> {
+    events: I,
+    detector: CornerDetector,
+    width: u16,
+    height: u16,
+    refractory_period: i64,
+    /// One SAE per polarity (OFF, then ON).
+    surfaces: [Vec<i64>; 2],
+    /// Timestamp of the last tested event of each pixel and polarity.
+    tested: [Vec<i64>; 2],
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Corners<I> {
+    pub fn new(events: I, detector: CornerDetector, width: u16, height: u16) -> Self {
+        let pixels = width as usize * height as usize;
+        Corners {
+            events,
+            detector,
+            width,
+            height,
+            refractory_period: 50_000,
+            surfaces: [vec![i64::MIN; pixels], vec![i64::MIN; pixels]],
+            tested: [vec![i64::MIN; pixels], vec![i64::MIN; pixels]],
+        }
+    }
+
+    /// Minimum delay (in microseconds) between two tested events of the same pixel and polarity. Zero tests every event.
+    pub fn refractory_period(mut self, refractory_period: i64) -> Self {
+        self.refractory_period = refractory_period;
+        self
+    }
+
+    fn is_corner(&mut self, event: &Event) -> bool {
+        let (x, y) = (event.x() as i64, event.y() as i64);
+        let (width, height) = (self.width as i64, self.height as i64);
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return false;
+        }
+        let index = (y * width + x) as usize;
+        let polarity = event.on() as usize;
+        self.surfaces[polarity][index] = event.t();
+        if event.t().saturating_sub(self.tested[polarity][index]) <= self.refractory_period {
+            return false;
+        }
+        self.tested[polarity][index] = event.t();
+        if x < 4 || y < 4 || x >= width - 4 || y >= height - 4 {
+            return false;
+        }
+        let surface = &self.surfaces[polarity];
+        let mut inner = [0i64; 16];
+        for (timestamp, (dx, dy)) in inner.iter_mut().zip(INNER_CIRCLE.iter()) {
+            *timestamp = surface[((y + dy) * width + x + dx) as usize];
+        }
+        let mut outer = [0i64; 20];
+        for (timestamp, (dx, dy)) in outer.iter_mut().zip(OUTER_CIRCLE.iter()) {
+            *timestamp = surface[((y + dy) * width + x + dx) as usize];
+        }
+        match self.detector {
+            CornerDetector::Fast => fast(&inner, INNER_ARC) && fast(&outer, OUTER_ARC),
+            CornerDetector::Arc => arc(&inner, INNER_ARC) && arc(&outer, OUTER_ARC),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Corners<I> {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(event) if !self.is_corner(&event) => continue,
+                result => return Some(result),
+            }
+        }
+    }
+}
+
+/// Whether an arc of length in `lengths` has only timestamps more recent than the rest of the circle.
+///
+/// The arc may also be the complement (the most recent pixels go around the corner).
+fn fast(circle: &[i64], lengths: (usize, usize)) -> bool {
+    let size = circle.len();
+    let is_newest = |start: usize, length: usize| {
+        let oldest_in_arc = (0..length)
+            .map(|offset| circle[(start + offset) % size])
+            .min()
+            .unwrap_or(i64::MAX);
+        let newest_outside = (length..size)
+            .map(|offset| circle[(start + offset) % size])
+            .max()
+            .unwrap_or(i64::MIN);
+        oldest_in_arc > newest_outside
+    };
+    (lengths.0..=lengths.1).any(|length| {
+        (0..size).any(|start| is_newest(start, length) || is_newest(start, size - length))
+    })
+}
+
+/// Grows an arc from the most recent pixel of the circle, adding the most recent of its two neighbours
+/// as long as it is more recent than the arc's oldest pixel (or the arc is shorter than the minimum length).
+fn arc(circle: &[i64], lengths: (usize, usize)) -> bool {
+    let size = circle.len();
+    let newest = (0..size).max_by_key(|index| circle[*index]).unwrap_or(0);
+    let mut oldest_in_arc = circle[newest];
+    let mut clockwise = (newest + 1) % size;
+    let mut counterclockwise = (newest + size - 1) % size;
+    let mut length = 1;
+    while length < size {
+        let candidate = if circle[clockwise] >= circle[counterclockwise] {
+            clockwise
+        } else {
+            counterclockwise
+        };
+        if circle[candidate] < oldest_in_arc && length >= lengths.0 {
+            break;
+        }
+        oldest_in_arc = oldest_in_arc.min(circle[candidate]);
+        if candidate == clockwise {
+            clockwise = (clockwise + 1) % size;
+        } else {
+            counterclockwise = (counterclockwise + size - 1) % size;
+        }
+        length += 1;
+    }
+    (lengths.0..=lengths.1).contains(&length) || (lengths.0..=lengths.1).contains(&(size - length))
+}
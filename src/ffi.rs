@@ -0,0 +1,406 @@
+//! C API, enabled by the `ffi` feature.
+//!
+//! Build the shared library with `cargo rustc --release --features ffi --crate-type cdylib`.
+//! The header `include/aedat.h` is generated with `cbindgen --config cbindgen.toml --output include/aedat.h`.
+//!
+//! Pointers returned by a function remain valid until the object they belong to is freed.
+//! Functions that return NULL on failure set an error message that can be read with `aedat_last_error`.
+
+#![allow(clippy::missing_safety_doc)]
+
+use crate::base::{describe, Decoder, Packet, ParseError, StreamContent};
+use crate::{imus_generated, triggers_generated};
+use std::os::raw::c_char;
+
+thread_local! {
+    static LAST_ERROR: std::cell::RefCell<Option<std::ffi::CString>> = const { std::cell::RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|last_error| {
+        *last_error.borrow_mut() =
+            Some(std::ffi::CString::new(message.replace('\0', " ")).unwrap_or_default())
+    });
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = None);
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AedatStreamContent {
+    Events = 0,
+    Frame = 1,
+    Imus = 2,
+    Triggers = 3,
+    /// Stream type unknown to this library.
+    Other = 4,
+    BoundingBoxes = 5,
+    Pose = 6,
+}
+
+impl From<&StreamContent> for AedatStreamContent {
+    fn from(content: &StreamContent) -> Self {
+        match content {
+            StreamContent::Events => AedatStreamContent::Events,
+            StreamContent::Frame => AedatStreamContent::Frame,
+            StreamContent::Imus => AedatStreamContent::Imus,
+            StreamContent::Triggers => AedatStreamContent::Triggers,
+            StreamContent::BoundingBoxes => AedatStreamContent::BoundingBoxes,
+            StreamContent::Pose => AedatStreamContent::Pose,
+            StreamContent::Other(_) => AedatStreamContent::Other,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AedatStream {
+    pub id: u32,
+    pub content: AedatStreamContent,
+    pub width: u16,
+    pub height: u16,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AedatEvent {
+    pub t: i64,
+    pub x: i16,
+    pub y: i16,
+    pub on: bool,
+}
+
+/// `pixels` points to `pixels_length` bytes, row-major, with 1 (Gray), 3 (BGR) or 4 (BGRA) channels.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AedatFrame {
+    pub t: i64,
+    pub begin_t: i64,
+    pub end_t: i64,
+    pub exposure_begin_t: i64,
+    pub exposure_end_t: i64,
+    /// 0 (Gray), 16 (BGR) or 24 (BGRA), plus 2 for 16 bits little-endian channels.
+    pub format: i8,
+    pub width: i16,
+    pub height: i16,
+    pub offset_x: i16,
+    pub offset_y: i16,
+    pub pixels: *const u8,
+    pub pixels_length: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AedatImu {
+    pub t: i64,
+    pub temperature: f32,
+    pub accelerometer_x: f32,
+    pub accelerometer_y: f32,
+    pub accelerometer_z: f32,
+    pub gyroscope_x: f32,
+    pub gyroscope_y: f32,
+    pub gyroscope_z: f32,
+    pub magnetometer_x: f32,
+    pub magnetometer_y: f32,
+    pub magnetometer_z: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AedatTrigger {
+    pub t: i64,
+    /// See `TriggerSource` in the AEDAT4 specification.
+    pub source: i8,
+}
+
+pub struct AedatDecoder {
+    decoder: Decoder,
+    streams: Vec<AedatStream>,
+}
+
+pub struct AedatPacket {
+    stream_id: u32,
+    content: AedatStreamContent,
+    events: Vec<AedatEvent>,
+    frame: Option<AedatFrame>,
+    imus: Vec<AedatImu>,
+    triggers: Vec<AedatTrigger>,
+    // owns the frame pixels
+    packet: Packet,
+}
+
+impl AedatPacket {
+    fn new(packet: Packet, content: &StreamContent) -> Result<Self, ParseError> {
+        let mut events = Vec::new();
+        let mut frame = None;
+        let mut imus = Vec::new();
+        let mut triggers = Vec::new();
+        match content {
+            StreamContent::Events => {
+                events = packet
+                    .events()?
+                    .iter()
+                    .map(|event| AedatEvent {
+                        t: event.t(),
+                        x: event.x(),
+                        y: event.y(),
+                        on: event.on(),
+                    })
+                    .collect();
+            }
+            StreamContent::Frame => {
+                let packet_frame = packet.frame()?;
+                let pixels = packet_frame.pixels().unwrap_or(&[]);
+                frame = Some(AedatFrame {
+                    t: packet_frame.t(),
+                    begin_t: packet_frame.begin_t(),
+                    end_t: packet_frame.end_t(),
+                    exposure_begin_t: packet_frame.exposure_begin_t(),
+                    exposure_end_t: packet_frame.exposure_end_t(),
+                    format: packet_frame.format().0,
+                    width: packet_frame.width(),
+                    height: packet_frame.height(),
+                    offset_x: packet_frame.offset_x(),
+                    offset_y: packet_frame.offset_y(),
+                    pixels: pixels.as_ptr(),
+                    pixels_length: pixels.len(),
+                });
+            }
+            StreamContent::Imus => {
+                if let Some(elements) =
+                    imus_generated::size_prefixed_root_as_imu_packet(&packet.buffer)?.elements()
+                {
+                    imus = elements
+                        .iter()
+                        .map(|imu| AedatImu {
+                            t: imu.t(),
+                            temperature: imu.temperature(),
+                            accelerometer_x: imu.accelerometer_x(),
+                            accelerometer_y: imu.accelerometer_y(),
+                            accelerometer_z: imu.accelerometer_z(),
+                            gyroscope_x: imu.gyroscope_x(),
+                            gyroscope_y: imu.gyroscope_y(),
+                            gyroscope_z: imu.gyroscope_z(),
+                            magnetometer_x: imu.magnetometer_x(),
+                            magnetometer_y: imu.magnetometer_y(),
+                            magnetometer_z: imu.magnetometer_z(),
+                        })
+                        .collect();
+                }
+            }
+            StreamContent::Triggers => {
+                if let Some(elements) =
+                    triggers_generated::size_prefixed_root_as_trigger_packet(&packet.buffer)?
+                        .elements()
+                {
+                    triggers = elements
+                        .iter()
+                        .map(|trigger| AedatTrigger {
+                            t: trigger.t(),
+                            source: trigger.source().0,
+                        })
+                        .collect();
+                }
+            }
+            // read with aedat_packet_buffer
+            StreamContent::BoundingBoxes | StreamContent::Pose | StreamContent::Other(_) => {}
+        }
+        // moving the packet does not move its buffer, hence the frame pixels pointer remains valid
+        Ok(AedatPacket {
+            stream_id: packet.stream_id,
+            content: content.into(),
+            events,
+            frame,
+            imus,
+            triggers,
+            packet,
+        })
+    }
+}
+
+/// Returns the message of the last error on this thread, or NULL.
+#[no_mangle]
+pub extern "C" fn aedat_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Opens an AEDAT4 file. Returns NULL on error.
+#[no_mangle]
+pub unsafe extern "C" fn aedat_decoder_open(path: *const c_char) -> *mut AedatDecoder {
+    clear_last_error();
+    if path.is_null() {
+        set_last_error("the path is NULL".to_owned());
+        return std::ptr::null_mut();
+    }
+    let path = match std::ffi::CStr::from_ptr(path).to_str() {
+        Ok(path) => path,
+        Err(error) => {
+            set_last_error(describe(&error));
+            return std::ptr::null_mut();
+        }
+    };
+    match Decoder::new_from_file(path) {
+        Ok(decoder) => {
+            let mut streams = decoder
+                .id_to_stream
+                .iter()
+                .map(|(id, stream)| AedatStream {
+                    id: *id,
+                    content: (&stream.content).into(),
+                    width: stream.width,
+                    height: stream.height,
+                })
+                .collect::<Vec<AedatStream>>();
+            streams.sort_by_key(|stream| stream.id);
+            Box::into_raw(Box::new(AedatDecoder { decoder, streams }))
+        }
+        Err(error) => {
+            set_last_error(describe(&error));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn aedat_decoder_free(decoder: *mut AedatDecoder) {
+    if !decoder.is_null() {
+        drop(Box::from_raw(decoder));
+    }
+}
+
+/// Returns the streams declared in the file, sorted by id, and writes their number to `count`.
+#[no_mangle]
+pub unsafe extern "C" fn aedat_decoder_streams(
+    decoder: *const AedatDecoder,
+    count: *mut usize,
+) -> *const AedatStream {
+    match decoder.as_ref() {
+        Some(decoder) => {
+            if !count.is_null() {
+                *count = decoder.streams.len();
+            }
+            decoder.streams.as_ptr()
+        }
+        None => {
+            if !count.is_null() {
+                *count = 0;
+            }
+            std::ptr::null()
+        }
+    }
+}
+
+/// Reads the next packet. Returns NULL at the end of the file, or on error (`aedat_last_error` is then not NULL).
+#[no_mangle]
+pub unsafe extern "C" fn aedat_next_packet(decoder: *mut AedatDecoder) -> *mut AedatPacket {
+    clear_last_error();
+    let decoder = match decoder.as_mut() {
+        Some(decoder) => decoder,
+        None => {
+            set_last_error("the decoder is NULL".to_owned());
+            return std::ptr::null_mut();
+        }
+    };
+    let result = match decoder.decoder.next() {
+        Some(result) => result,
+        None => return std::ptr::null_mut(),
+    };
+    match result.and_then(|packet| {
+        let content = &decoder.decoder.id_to_stream[&packet.stream_id].content;
+        AedatPacket::new(packet, content)
+    }) {
+        Ok(packet) => Box::into_raw(Box::new(packet)),
+        Err(error) => {
+            set_last_error(describe(&error));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn aedat_packet_free(packet: *mut AedatPacket) {
+    if !packet.is_null() {
+        drop(Box::from_raw(packet));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn aedat_packet_stream_id(packet: *const AedatPacket) -> u32 {
+    packet.as_ref().map_or(0, |packet| packet.stream_id)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn aedat_packet_content(packet: *const AedatPacket) -> AedatStreamContent {
+    packet
+        .as_ref()
+        .map_or(AedatStreamContent::Events, |packet| packet.content)
+}
+
+/// Returns the decompressed packet (a size-prefixed flatbuffer) and writes its size to `size`.
+///
+/// This gives access to packets from streams of unknown type (`AEDAT_STREAM_CONTENT_OTHER`).
+#[no_mangle]
+pub unsafe extern "C" fn aedat_packet_buffer(
+    packet: *const AedatPacket,
+    size: *mut usize,
+) -> *const u8 {
+    elements(
+        packet.as_ref().map(|packet| &packet.packet.buffer[..]),
+        size,
+    )
+}
+
+/// Returns the events of an event packet and writes their number to `count` (0 for other packets).
+#[no_mangle]
+pub unsafe extern "C" fn aedat_packet_events(
+    packet: *const AedatPacket,
+    count: *mut usize,
+) -> *const AedatEvent {
+    elements(packet.as_ref().map(|packet| &packet.events[..]), count)
+}
+
+/// Returns the frame of a frame packet, or NULL for other packets.
+#[no_mangle]
+pub unsafe extern "C" fn aedat_packet_frame(packet: *const AedatPacket) -> *const AedatFrame {
+    packet
+        .as_ref()
+        .and_then(|packet| packet.frame.as_ref())
+        .map_or(std::ptr::null(), |frame| frame as *const AedatFrame)
+}
+
+/// Returns the samples of an IMU packet and writes their number to `count` (0 for other packets).
+#[no_mangle]
+pub unsafe extern "C" fn aedat_packet_imus(
+    packet: *const AedatPacket,
+    count: *mut usize,
+) -> *const AedatImu {
+    elements(packet.as_ref().map(|packet| &packet.imus[..]), count)
+}
+
+/// Returns the triggers of a trigger packet and writes their number to `count` (0 for other packets).
+#[no_mangle]
+pub unsafe extern "C" fn aedat_packet_triggers(
+    packet: *const AedatPacket,
+    count: *mut usize,
+) -> *const AedatTrigger {
+    elements(packet.as_ref().map(|packet| &packet.triggers[..]), count)
+}
+
+unsafe fn elements<T>(elements: Option<&[T]>, count: *mut usize) -> *const T {
+    let elements = elements.unwrap_or(&[]);
+    if !count.is_null() {
+        *count = elements.len();
+    }
+    if elements.is_empty() {
+        std::ptr::null()
+    } else {
+        elements.as_ptr()
+    }
+}
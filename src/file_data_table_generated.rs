@@ -0,0 +1,515 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+
+use std::cmp::Ordering;
+use std::mem;
+
+extern crate flatbuffers;
+use self::flatbuffers::{EndianScalar, Follow};
+
+// struct PacketHeader, aligned to 4
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct PacketHeader(pub [u8; 8]);
+impl Default for PacketHeader {
+    fn default() -> Self {
+        Self([0; 8])
+    }
+}
+impl std::fmt::Debug for PacketHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PacketHeader")
+            .field("stream_id", &self.stream_id())
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+impl flatbuffers::SimpleToVerifyInSlice for PacketHeader {}
+impl flatbuffers::SafeSliceAccess for PacketHeader {}
+impl<'a> flatbuffers::Follow<'a> for PacketHeader {
+    type Inner = &'a PacketHeader;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        <&'a PacketHeader>::follow(buf, loc)
+    }
+}
+impl<'a> flatbuffers::Follow<'a> for &'a PacketHeader {
+    type Inner = &'a PacketHeader;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        flatbuffers::follow_cast_ref::<PacketHeader>(buf, loc)
+    }
+}
+impl<'b> flatbuffers::Push for PacketHeader {
+    type Output = PacketHeader;
+    #[inline]
+    fn push(&self, dst: &mut [u8], _rest: &[u8]) {
+        let src = unsafe {
+            ::std::slice::from_raw_parts(self as *const PacketHeader as *const u8, ::core::mem::size_of::<PacketHeader>())
+        };
+        dst.copy_from_slice(src);
+    }
+}
+impl<'b> flatbuffers::Push for &'b PacketHeader {
+    type Output = PacketHeader;
+
+    #[inline]
+    fn push(&self, dst: &mut [u8], _rest: &[u8]) {
+        let src = unsafe {
+            ::std::slice::from_raw_parts(*self as *const PacketHeader as *const u8, ::core::mem::size_of::<PacketHeader>())
+        };
+        dst.copy_from_slice(src);
+    }
+}
+
+impl<'a> flatbuffers::Verifiable for PacketHeader {
+    #[inline]
+    fn run_verifier(
+        v: &mut flatbuffers::Verifier,
+        pos: usize,
+    ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+        use self::flatbuffers::Verifiable;
+        v.in_buffer::<Self>(pos)
+    }
+}
+impl<'a> PacketHeader {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(stream_id: i32, size: i32) -> Self {
+        let mut s = Self([0; 8]);
+        s.set_stream_id(stream_id);
+        s.set_size(size);
+        s
+    }
+
+    pub fn stream_id(&self) -> i32 {
+        let mut mem = core::mem::MaybeUninit::<i32>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.0[0..].as_ptr(),
+                mem.as_mut_ptr() as *mut u8,
+                core::mem::size_of::<i32>(),
+            );
+            mem.assume_init()
+        }
+        .from_little_endian()
+    }
+
+    pub fn set_stream_id(&mut self, x: i32) {
+        let x_le = x.to_little_endian();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                &x_le as *const i32 as *const u8,
+                self.0[0..].as_mut_ptr(),
+                core::mem::size_of::<i32>(),
+            );
+        }
+    }
+
+    pub fn size(&self) -> i32 {
+        let mut mem = core::mem::MaybeUninit::<i32>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.0[4..].as_ptr(),
+                mem.as_mut_ptr() as *mut u8,
+                core::mem::size_of::<i32>(),
+            );
+            mem.assume_init()
+        }
+        .from_little_endian()
+    }
+
+    pub fn set_size(&mut self, x: i32) {
+        let x_le = x.to_little_endian();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                &x_le as *const i32 as *const u8,
+                self.0[4..].as_mut_ptr(),
+                core::mem::size_of::<i32>(),
+            );
+        }
+    }
+}
+
+pub enum FileDataDefinitionOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct FileDataDefinition<'a> {
+    pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for FileDataDefinition<'a> {
+    type Inner = FileDataDefinition<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf, loc },
+        }
+    }
+}
+
+impl<'a> FileDataDefinition<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        FileDataDefinition { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args FileDataDefinitionArgs<'args>,
+    ) -> flatbuffers::WIPOffset<FileDataDefinition<'bldr>> {
+        let mut builder = FileDataDefinitionBuilder::new(_fbb);
+        builder.add_timestamp_end(args.timestamp_end);
+        builder.add_timestamp_start(args.timestamp_start);
+        builder.add_num_elements(args.num_elements);
+        builder.add_byte_offset(args.byte_offset);
+        if let Some(x) = args.packet_info {
+            builder.add_packet_info(x);
+        }
+        builder.finish()
+    }
+
+    pub const VT_BYTE_OFFSET: flatbuffers::VOffsetT = 4;
+    pub const VT_PACKET_INFO: flatbuffers::VOffsetT = 6;
+    pub const VT_NUM_ELEMENTS: flatbuffers::VOffsetT = 8;
+    pub const VT_TIMESTAMP_START: flatbuffers::VOffsetT = 10;
+    pub const VT_TIMESTAMP_END: flatbuffers::VOffsetT = 12;
+
+    #[inline]
+    pub fn byte_offset(&self) -> i64 {
+        self._tab
+            .get::<i64>(FileDataDefinition::VT_BYTE_OFFSET, Some(0))
+            .unwrap()
+    }
+    #[inline]
+    pub fn packet_info(&self) -> Option<&'a PacketHeader> {
+        self._tab
+            .get::<PacketHeader>(FileDataDefinition::VT_PACKET_INFO, None)
+    }
+    #[inline]
+    pub fn num_elements(&self) -> i64 {
+        self._tab
+            .get::<i64>(FileDataDefinition::VT_NUM_ELEMENTS, Some(0))
+            .unwrap()
+    }
+    #[inline]
+    pub fn timestamp_start(&self) -> i64 {
+        self._tab
+            .get::<i64>(FileDataDefinition::VT_TIMESTAMP_START, Some(0))
+            .unwrap()
+    }
+    #[inline]
+    pub fn timestamp_end(&self) -> i64 {
+        self._tab
+            .get::<i64>(FileDataDefinition::VT_TIMESTAMP_END, Some(0))
+            .unwrap()
+    }
+}
+
+impl flatbuffers::Verifiable for FileDataDefinition<'_> {
+    #[inline]
+    fn run_verifier(
+        v: &mut flatbuffers::Verifier,
+        pos: usize,
+    ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+        use self::flatbuffers::Verifiable;
+        v.visit_table(pos)?
+            .visit_field::<i64>(&"byte_offset", Self::VT_BYTE_OFFSET, false)?
+            .visit_field::<PacketHeader>(&"packet_info", Self::VT_PACKET_INFO, false)?
+            .visit_field::<i64>(&"num_elements", Self::VT_NUM_ELEMENTS, false)?
+            .visit_field::<i64>(&"timestamp_start", Self::VT_TIMESTAMP_START, false)?
+            .visit_field::<i64>(&"timestamp_end", Self::VT_TIMESTAMP_END, false)?
+            .finish();
+        Ok(())
+    }
+}
+pub struct FileDataDefinitionArgs<'a> {
+    pub byte_offset: i64,
+    pub packet_info: Option<&'a PacketHeader>,
+    pub num_elements: i64,
+    pub timestamp_start: i64,
+    pub timestamp_end: i64,
+}
+impl<'a> Default for FileDataDefinitionArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        FileDataDefinitionArgs {
+            byte_offset: 0,
+            packet_info: None,
+            num_elements: 0,
+            timestamp_start: 0,
+            timestamp_end: 0,
+        }
+    }
+}
+pub struct FileDataDefinitionBuilder<'a: 'b, 'b> {
+    fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> FileDataDefinitionBuilder<'a, 'b> {
+    #[inline]
+    pub fn add_byte_offset(&mut self, byte_offset: i64) {
+        self.fbb_
+            .push_slot::<i64>(FileDataDefinition::VT_BYTE_OFFSET, byte_offset, 0);
+    }
+    #[inline]
+    pub fn add_packet_info(&mut self, packet_info: &PacketHeader) {
+        self.fbb_
+            .push_slot_always::<&PacketHeader>(FileDataDefinition::VT_PACKET_INFO, packet_info);
+    }
+    #[inline]
+    pub fn add_num_elements(&mut self, num_elements: i64) {
+        self.fbb_
+            .push_slot::<i64>(FileDataDefinition::VT_NUM_ELEMENTS, num_elements, 0);
+    }
+    #[inline]
+    pub fn add_timestamp_start(&mut self, timestamp_start: i64) {
+        self.fbb_
+            .push_slot::<i64>(FileDataDefinition::VT_TIMESTAMP_START, timestamp_start, 0);
+    }
+    #[inline]
+    pub fn add_timestamp_end(&mut self, timestamp_end: i64) {
+        self.fbb_
+            .push_slot::<i64>(FileDataDefinition::VT_TIMESTAMP_END, timestamp_end, 0);
+    }
+    #[inline]
+    pub fn new(
+        _fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    ) -> FileDataDefinitionBuilder<'a, 'b> {
+        let start = _fbb.start_table();
+        FileDataDefinitionBuilder {
+            fbb_: _fbb,
+            start_: start,
+        }
+    }
+    #[inline]
+    pub fn finish(self) -> flatbuffers::WIPOffset<FileDataDefinition<'a>> {
+        let o = self.fbb_.end_table(self.start_);
+        flatbuffers::WIPOffset::new(o.value())
+    }
+}
+
+impl std::fmt::Debug for FileDataDefinition<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("FileDataDefinition");
+        ds.field("byte_offset", &self.byte_offset());
+        ds.field("packet_info", &self.packet_info());
+        ds.field("num_elements", &self.num_elements());
+        ds.field("timestamp_start", &self.timestamp_start());
+        ds.field("timestamp_end", &self.timestamp_end());
+        ds.finish()
+    }
+}
+pub enum FileDataTableOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct FileDataTable<'a> {
+    pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for FileDataTable<'a> {
+    type Inner = FileDataTable<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf, loc },
+        }
+    }
+}
+
+impl<'a> FileDataTable<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        FileDataTable { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args FileDataTableArgs<'args>,
+    ) -> flatbuffers::WIPOffset<FileDataTable<'bldr>> {
+        let mut builder = FileDataTableBuilder::new(_fbb);
+        if let Some(x) = args.table {
+            builder.add_table(x);
+        }
+        builder.finish()
+    }
+
+    pub const VT_TABLE: flatbuffers::VOffsetT = 4;
+
+    #[inline]
+    pub fn table(
+        &self,
+    ) -> Option<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<FileDataDefinition<'a>>>> {
+        self._tab.get::<flatbuffers::ForwardsUOffset<
+            flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<FileDataDefinition>>,
+        >>(FileDataTable::VT_TABLE, None)
+    }
+}
+
+impl flatbuffers::Verifiable for FileDataTable<'_> {
+    #[inline]
+    fn run_verifier(
+        v: &mut flatbuffers::Verifier,
+        pos: usize,
+    ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+        use self::flatbuffers::Verifiable;
+        v.visit_table(pos)?
+            .visit_field::<flatbuffers::ForwardsUOffset<
+                flatbuffers::Vector<'_, flatbuffers::ForwardsUOffset<FileDataDefinition>>,
+            >>(&"table", Self::VT_TABLE, false)?
+            .finish();
+        Ok(())
+    }
+}
+pub struct FileDataTableArgs<'a> {
+    pub table: Option<
+        flatbuffers::WIPOffset<
+            flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<FileDataDefinition<'a>>>,
+        >,
+    >,
+}
+impl<'a> Default for FileDataTableArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        FileDataTableArgs { table: None }
+    }
+}
+pub struct FileDataTableBuilder<'a: 'b, 'b> {
+    fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> FileDataTableBuilder<'a, 'b> {
+    #[inline]
+    pub fn add_table(
+        &mut self,
+        table: flatbuffers::WIPOffset<
+            flatbuffers::Vector<'b, flatbuffers::ForwardsUOffset<FileDataDefinition<'b>>>,
+        >,
+    ) {
+        self.fbb_
+            .push_slot_always::<flatbuffers::WIPOffset<_>>(FileDataTable::VT_TABLE, table);
+    }
+    #[inline]
+    pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> FileDataTableBuilder<'a, 'b> {
+        let start = _fbb.start_table();
+        FileDataTableBuilder {
+            fbb_: _fbb,
+            start_: start,
+        }
+    }
+    #[inline]
+    pub fn finish(self) -> flatbuffers::WIPOffset<FileDataTable<'a>> {
+        let o = self.fbb_.end_table(self.start_);
+        flatbuffers::WIPOffset::new(o.value())
+    }
+}
+
+impl std::fmt::Debug for FileDataTable<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("FileDataTable");
+        ds.field("table", &self.table());
+        ds.finish()
+    }
+}
+#[inline]
+#[deprecated(since = "2.0.0", note = "Deprecated in favor of `root_as...` methods.")]
+pub fn get_root_as_file_data_table<'a>(buf: &'a [u8]) -> FileDataTable<'a> {
+    unsafe { flatbuffers::root_unchecked::<FileDataTable<'a>>(buf) }
+}
+
+#[inline]
+#[deprecated(since = "2.0.0", note = "Deprecated in favor of `root_as...` methods.")]
+pub fn get_size_prefixed_root_as_file_data_table<'a>(buf: &'a [u8]) -> FileDataTable<'a> {
+    unsafe { flatbuffers::size_prefixed_root_unchecked::<FileDataTable<'a>>(buf) }
+}
+
+#[inline]
+/// Verifies that a buffer of bytes contains a `FileDataTable`
+/// and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_file_data_table_unchecked`.
+pub fn root_as_file_data_table(
+    buf: &[u8],
+) -> Result<FileDataTable, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::root::<FileDataTable>(buf)
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a size prefixed
+/// `FileDataTable` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `size_prefixed_root_as_file_data_table_unchecked`.
+pub fn size_prefixed_root_as_file_data_table(
+    buf: &[u8],
+) -> Result<FileDataTable, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::size_prefixed_root::<FileDataTable>(buf)
+}
+#[inline]
+/// Verifies, with the given options, that a buffer of bytes
+/// contains a `FileDataTable` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_file_data_table_unchecked`.
+pub fn root_as_file_data_table_with_opts<'b, 'o>(
+    opts: &'o flatbuffers::VerifierOptions,
+    buf: &'b [u8],
+) -> Result<FileDataTable<'b>, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::root_with_opts::<FileDataTable<'b>>(opts, buf)
+}
+#[inline]
+/// Verifies, with the given verifier options, that a buffer of
+/// bytes contains a size prefixed `FileDataTable` and returns
+/// it. Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_file_data_table_unchecked`.
+pub fn size_prefixed_root_as_file_data_table_with_opts<'b, 'o>(
+    opts: &'o flatbuffers::VerifierOptions,
+    buf: &'b [u8],
+) -> Result<FileDataTable<'b>, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::size_prefixed_root_with_opts::<FileDataTable<'b>>(opts, buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a FileDataTable and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid `FileDataTable`.
+pub unsafe fn root_as_file_data_table_unchecked(buf: &[u8]) -> FileDataTable {
+    flatbuffers::root_unchecked::<FileDataTable>(buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a size prefixed FileDataTable and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid size prefixed `FileDataTable`.
+pub unsafe fn size_prefixed_root_as_file_data_table_unchecked(buf: &[u8]) -> FileDataTable {
+    flatbuffers::size_prefixed_root_unchecked::<FileDataTable>(buf)
+}
+pub const FILE_DATA_TABLE_IDENTIFIER: &str = "FTAB";
+
+#[inline]
+pub fn file_data_table_buffer_has_identifier(buf: &[u8]) -> bool {
+    flatbuffers::buffer_has_identifier(buf, FILE_DATA_TABLE_IDENTIFIER, false)
+}
+
+#[inline]
+pub fn file_data_table_size_prefixed_buffer_has_identifier(buf: &[u8]) -> bool {
+    flatbuffers::buffer_has_identifier(buf, FILE_DATA_TABLE_IDENTIFIER, true)
+}
+
+#[inline]
+pub fn finish_file_data_table_buffer<'a, 'b>(
+    fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    root: flatbuffers::WIPOffset<FileDataTable<'a>>,
+) {
+    fbb.finish(root, Some(FILE_DATA_TABLE_IDENTIFIER));
+}
+
+#[inline]
+pub fn finish_size_prefixed_file_data_table_buffer<'a, 'b>(
+    fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    root: flatbuffers::WIPOffset<FileDataTable<'a>>,
+) {
+    fbb.finish_size_prefixed(root, Some(FILE_DATA_TABLE_IDENTIFIER));
+}
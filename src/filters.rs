@@ -0,0 +1,428 @@
+use crate::base::{Packet, ParseError, Stream, StreamContent};
+use crate::events::Event;
+use crate::{events_generated, frame_generated};
+
+/// Denoising algorithm of a `NoiseFilter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Denoiser {
+    /// Keeps an event if one of its eight neighbours produced an event less than `tau` microseconds before it.
+    ///
+    /// Stores a timestamp per pixel.
+    BackgroundActivity,
+
+    /// Khodamoradi and Kastner's O(N) filter, which keeps an event if the last event of a neighbouring column
+    /// (or row) has the same polarity, is less than `tau` microseconds older, and is adjacent to it.
+    ///
+    /// Stores the last event of each column and each row, hence it is cheaper than the background activity filter
+    /// on large sensors, and lets through less noise in low light, where noise events dominate pixel timestamps.
+    Khodamoradi,
+}
+
+/// Last event of a column (`position` is its y coordinate) or row (`position` is its x coordinate).
+#[derive(Clone, Copy)]
+struct LineEvent {
+    t: i64,
+    position: i64,
+    on: bool,
+}
+
+enum NoiseState {
+    BackgroundActivity(Vec<i64>),
+    Khodamoradi {
+        columns: Vec<LineEvent>,
+        rows: Vec<LineEvent>,
+    },
+}
+
+/// Noise filter, a background activity filter by default (see `Denoiser`).
+///
+/// Events outside the sensor are dropped.
+pub struct NoiseFilter<I> {
+    events: I,
+    width: u16,
+    height: u16,
+    tau: i64,
+    state: NoiseState,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> NoiseFilter<I> {
+    pub fn new(events: I, width: u16, height: u16, tau: i64) -> Self {
+        NoiseFilter {
+            events,
+            width,
+            height,
+            tau,
+            state: NoiseState::BackgroundActivity(vec![i64::MIN; width as usize * height as usize]),
+        }
+    }
+
+    /// Selects the denoising algorithm.
+    pub fn denoiser(mut self, denoiser: Denoiser) -> Self {
+        let empty = LineEvent {
+            t: i64::MIN,
+            position: i64::MIN,
+            on: false,
+        };
+        self.state = match denoiser {
+            Denoiser::BackgroundActivity => {
+                NoiseState::BackgroundActivity(vec![i64::MIN; self.width as usize * self.height as usize])
+            }
+            Denoiser::Khodamoradi => NoiseState::Khodamoradi {
+                columns: vec![empty; self.width as usize],
+                rows: vec![empty; self.height as usize],
+            },
+        };
+        self
+    }
+
+    fn is_supported(&mut self, event: &Event) -> bool {
+        let (x, y) = (event.x() as i64, event.y() as i64);
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return false;
+        }
+        let mut supported = false;
+        match &mut self.state {
+            NoiseState::BackgroundActivity(timestamps) => {
+                for neighbour_y in (y - 1).max(0)..(y + 2).min(self.height as i64) {
+                    for neighbour_x in (x - 1).max(0)..(x + 2).min(self.width as i64) {
+                        if (neighbour_x != x || neighbour_y != y)
+                            && event.t().saturating_sub(
+                                timestamps[(neighbour_y * self.width as i64 + neighbour_x) as usize],
+                            ) <= self.tau
+                        {
+                            supported = true;
+                        }
+                    }
+                }
+                timestamps[(y * self.width as i64 + x) as usize] = event.t();
+            }
+            NoiseState::Khodamoradi { columns, rows } => {
+                // the event's own pixel is not a neighbour
+                let is_neighbour = |same_line: bool, last: &LineEvent, position: i64| {
+                    !(same_line && last.position == position)
+                        && (last.position - position).abs() <= 1
+                        && last.on == event.on()
+                        && event.t().saturating_sub(last.t) <= self.tau
+                };
+                for column in (x - 1).max(0)..(x + 2).min(self.width as i64) {
+                    if is_neighbour(column == x, &columns[column as usize], y) {
+                        supported = true;
+                    }
+                }
+                for row in (y - 1).max(0)..(y + 2).min(self.height as i64) {
+                    if is_neighbour(row == y, &rows[row as usize], x) {
+                        supported = true;
+                    }
+                }
+                let last = LineEvent {
+                    t: event.t(),
+                    position: 0,
+                    on: event.on(),
+                };
+                columns[x as usize] = LineEvent { position: y, ..last };
+                rows[y as usize] = LineEvent { position: x, ..last };
+            }
+        }
+        supported
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for NoiseFilter<I> {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(event) if !self.is_supported(&event) => continue,
+                result => return Some(result),
+            }
+        }
+    }
+}
+
+/// Keeps the events of a region of interest, with coordinates relative to its top-left corner.
+pub struct Crop<I> {
+    events: I,
+    x: i16,
+    y: i16,
+    width: i16,
+    height: i16,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Crop<I> {
+    pub fn new(events: I, x: i16, y: i16, width: i16, height: i16) -> Self {
+        Crop {
+            events,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Crop<I> {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(event) => {
+                    let (x, y) = (event.x() - self.x, event.y() - self.y);
+                    if x >= 0 && y >= 0 && x < self.width && y < self.height {
+                        return Some(Ok(Event::new(event.t(), x, y, event.on())));
+                    }
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// Keeps each event with probability `keep_probability`, for subsampling and load shedding.
+///
+/// The same seed always keeps the same events of a given stream.
+pub struct RandomDrop<I> {
+    events: I,
+    keep_probability: f64,
+    state: u64,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> RandomDrop<I> {
+    pub fn new(events: I, keep_probability: f64, seed: u64) -> Self {
+        RandomDrop {
+            events,
+            keep_probability,
+            state: seed,
+        }
+    }
+
+    /// SplitMix64, returns a number in [0, 1).
+    fn next_random(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for RandomDrop<I> {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(_) if self.next_random() >= self.keep_probability => continue,
+                result => return Some(result),
+            }
+        }
+    }
+}
+
+/// Geometric transformation of the sensor plane, for cameras mounted upside down or sideways.
+///
+/// Rotations are clockwise (the y axis points down).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    FlipX,
+    FlipY,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Transpose,
+}
+
+impl Transform {
+    /// Size of the transformed sensor.
+    pub fn size(self, width: u16, height: u16) -> (u16, u16) {
+        match self {
+            Transform::FlipX | Transform::FlipY | Transform::Rotate180 => (width, height),
+            Transform::Rotate90 | Transform::Rotate270 | Transform::Transpose => (height, width),
+        }
+    }
+
+    /// Maps a pixel of a `width` × `height` sensor.
+    pub fn apply(self, x: i16, y: i16, width: i16, height: i16) -> (i16, i16) {
+        match self {
+            Transform::FlipX => (width - 1 - x, y),
+            Transform::FlipY => (x, height - 1 - y),
+            Transform::Rotate90 => (height - 1 - y, x),
+            Transform::Rotate180 => (width - 1 - x, height - 1 - y),
+            Transform::Rotate270 => (y, width - 1 - x),
+            Transform::Transpose => (y, x),
+        }
+    }
+
+    /// Returns the stream description with the transformed sensor size.
+    pub fn stream(self, stream: &Stream) -> Stream {
+        let (width, height) = self.size(stream.width, stream.height);
+        Stream {
+            content: stream.content.clone(),
+            width,
+            height,
+        }
+    }
+
+    /// Rewrites the events and frame pixels of a packet from a stream with the given (untransformed) geometry.
+    ///
+    /// IMU and trigger packets are returned unchanged.
+    pub fn packet(self, packet: Packet, stream: &Stream) -> Result<Packet, ParseError> {
+        let (width, height) = (stream.width as i16, stream.height as i16);
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        match &stream.content {
+            StreamContent::Events => {
+                let events = packet
+                    .events()?
+                    .iter()
+                    .map(|event| {
+                        let (x, y) = self.apply(event.x(), event.y(), width, height);
+                        Event::new(event.t(), x, y, event.on())
+                    })
+                    .collect::<Vec<Event>>();
+                let elements = builder.create_vector(&events);
+                let root = events_generated::EventPacket::create(
+                    &mut builder,
+                    &events_generated::EventPacketArgs {
+                        elements: Some(elements),
+                    },
+                );
+                events_generated::finish_size_prefixed_event_packet_buffer(&mut builder, root);
+            }
+            StreamContent::Frame => {
+                let frame = packet.frame()?;
+                let channels = match frame.format() {
+                    frame_generated::FrameFormat::Bgr => 3,
+                    frame_generated::FrameFormat::Bgra => 4,
+                    _ => 1,
+                };
+                let (frame_width, frame_height) = (frame.width(), frame.height());
+                let (new_width, new_height) = self.size(frame_width as u16, frame_height as u16);
+                let pixels = frame.pixels().unwrap_or(&[]);
+                let mut new_pixels = vec![0u8; pixels.len()];
+                if pixels.len() == frame_width as usize * frame_height as usize * channels {
+                    for y in 0..frame_height {
+                        for x in 0..frame_width {
+                            let (new_x, new_y) = self.apply(x, y, frame_width, frame_height);
+                            let source =
+                                (y as usize * frame_width as usize + x as usize) * channels;
+                            let target =
+                                (new_y as usize * new_width as usize + new_x as usize) * channels;
+                            new_pixels[target..target + channels]
+                                .copy_from_slice(&pixels[source..source + channels]);
+                        }
+                    }
+                }
+                // the region of interest is mapped with its opposite corners
+                let (first_x, first_y) =
+                    self.apply(frame.offset_x(), frame.offset_y(), width, height);
+                let (last_x, last_y) = self.apply(
+                    frame.offset_x() + frame_width - 1,
+                    frame.offset_y() + frame_height - 1,
+                    width,
+                    height,
+                );
+                let args = frame_generated::FrameArgs {
+                    t: frame.t(),
+                    begin_t: frame.begin_t(),
+                    end_t: frame.end_t(),
+                    exposure_begin_t: frame.exposure_begin_t(),
+                    exposure_end_t: frame.exposure_end_t(),
+                    format: frame.format(),
+                    width: new_width as i16,
+                    height: new_height as i16,
+                    offset_x: first_x.min(last_x),
+                    offset_y: first_y.min(last_y),
+                    pixels: Some(builder.create_vector(&new_pixels)),
+                };
+                let root = frame_generated::Frame::create(&mut builder, &args);
+                frame_generated::finish_size_prefixed_frame_buffer(&mut builder, root);
+            }
+            StreamContent::Imus
+            | StreamContent::Triggers
+            | StreamContent::BoundingBoxes
+            | StreamContent::Pose
+            | StreamContent::Other(_) => return Ok(packet),
+        }
+        Ok(Packet {
+            buffer: builder.finished_data().to_vec(),
+            stream_id: packet.stream_id,
+            arrival_time: packet.arrival_time,
+        })
+    }
+}
+
+/// Applies a `Transform` to the events of a `width` × `height` sensor.
+pub struct Transformed<I> {
+    events: I,
+    transform: Transform,
+    width: i16,
+    height: i16,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Transformed<I> {
+    pub fn new(events: I, transform: Transform, width: u16, height: u16) -> Self {
+        Transformed {
+            events,
+            transform,
+            width: width as i16,
+            height: height as i16,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Transformed<I> {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.events.next()?.map(|event| {
+            let (x, y) = self
+                .transform
+                .apply(event.x(), event.y(), self.width, self.height);
+            Event::new(event.t(), x, y, event.on())
+        }))
+    }
+}
+
+/// Applies a `Transform` to the event and frame packets of a packet stream (see `Transform::packet`).
+pub struct TransformedPackets<I> {
+    /// Streams with the transformed sensor sizes.
+    pub id_to_stream: std::collections::HashMap<u32, Stream>,
+    packets: I,
+    transform: Transform,
+    original_id_to_stream: std::collections::HashMap<u32, Stream>,
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> TransformedPackets<I> {
+    pub fn new(
+        packets: I,
+        id_to_stream: &std::collections::HashMap<u32, Stream>,
+        transform: Transform,
+    ) -> Self {
+        TransformedPackets {
+            id_to_stream: id_to_stream
+                .iter()
+                .map(|(id, stream)| (*id, transform.stream(stream)))
+                .collect(),
+            packets,
+            transform,
+            original_id_to_stream: id_to_stream.clone(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> Iterator for TransformedPackets<I> {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let packet = match self.packets.next()? {
+            Ok(packet) => packet,
+            Err(error) => return Some(Err(error)),
+        };
+        Some(match self.original_id_to_stream.get(&packet.stream_id) {
+            Some(stream) => self.transform.packet(packet, stream),
+            None => Err(ParseError::UnknownStreamId(packet.stream_id)),
+        })
+    }
+}
@@ -0,0 +1,346 @@
+//! Optical flow ground truth, enabled by the `flow` feature (and `flow-hdf5` for HDF5 files).
+//!
+//! Supports the MVSEC companion files:
+//! - `*_gt_flow_dist.npz` with the arrays `timestamps` (N), `x_flow_dist` and `y_flow_dist` (N × height × width)
+//! - `*_gt.hdf5` with the datasets `{group}/flow_dist` (N × 2 × height × width) and `{group}/flow_dist_ts` (N),
+//!   where `group` is typically `davis/left`
+//!
+//! Floating-point timestamps are in seconds, integer timestamps in microseconds.
+//! Field `k` is the displacement (in pixels) between the timestamps `k` and `k + 1`.
+
+use crate::base::ParseError;
+use crate::events::Event;
+use std::io::Read;
+
+/// Row-major optical flow, in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowField {
+    pub t: i64,
+    pub width: usize,
+    pub height: usize,
+    pub vx: Vec<f32>,
+    pub vy: Vec<f32>,
+}
+
+impl FlowField {
+    pub fn at(&self, x: usize, y: usize) -> (f32, f32) {
+        let index = y * self.width + x;
+        (self.vx[index], self.vy[index])
+    }
+}
+
+/// Optical flow fields sorted by timestamp.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowGroundTruth {
+    pub fields: Vec<FlowField>,
+}
+
+impl FlowGroundTruth {
+    pub fn from_npz<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, ParseError> {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+        let mut read = |name: &str| -> Result<Vec<u8>, ParseError> {
+            let mut bytes = Vec::new();
+            archive
+                .by_name(&format!("{}.npy", name))?
+                .read_to_end(&mut bytes)?;
+            Ok(bytes)
+        };
+        let timestamps = read("timestamps")?;
+        let timestamps = Npy::parse(&timestamps)?;
+        let vx = read("x_flow_dist")?;
+        let vx = Npy::parse(&vx)?;
+        let vy = read("y_flow_dist")?;
+        let vy = Npy::parse(&vy)?;
+        if vx.shape.len() != 3 || vx.shape != vy.shape {
+            return Err(ParseError::General(format!(
+                "unexpected flow shapes {:?} and {:?}",
+                vx.shape, vy.shape
+            )));
+        }
+        FlowGroundTruth::new(
+            timestamps.timestamps()?,
+            vx.shape[2],
+            vx.shape[1],
+            vx.values()?,
+            vy.values()?,
+        )
+    }
+
+    #[cfg(feature = "flow-hdf5")]
+    pub fn from_hdf5<P: std::convert::AsRef<std::path::Path>>(
+        path: P,
+        group: &str,
+    ) -> Result<Self, ParseError> {
+        let file = hdf5::File::open(path)?;
+        let flow = file.dataset(&format!("{}/flow_dist", group))?;
+        let shape = flow.shape();
+        if shape.len() != 4 || shape[1] != 2 {
+            return Err(ParseError::General(format!(
+                "unexpected flow shape {:?}",
+                shape
+            )));
+        }
+        let values = if flow.dtype()?.is::<f64>() {
+            flow.read_raw::<f64>()?
+                .into_iter()
+                .map(|value| value as f32)
+                .collect()
+        } else {
+            flow.read_raw::<f32>()?
+        };
+        let timestamps = file.dataset(&format!("{}/flow_dist_ts", group))?;
+        let timestamps = if timestamps.dtype()?.is::<i64>() {
+            timestamps.read_raw::<i64>()?
+        } else {
+            timestamps
+                .read_raw::<f64>()?
+                .into_iter()
+                .map(|t| (t * 1e6).round() as i64)
+                .collect()
+        };
+        let size = shape[2] * shape[3];
+        let (mut vx, mut vy) = (
+            Vec::with_capacity(values.len() / 2),
+            Vec::with_capacity(values.len() / 2),
+        );
+        for field in values.chunks_exact(size * 2) {
+            vx.extend_from_slice(&field[..size]);
+            vy.extend_from_slice(&field[size..]);
+        }
+        FlowGroundTruth::new(timestamps, shape[3], shape[2], vx, vy)
+    }
+
+    fn new(
+        timestamps: Vec<i64>,
+        width: usize,
+        height: usize,
+        vx: Vec<f32>,
+        vy: Vec<f32>,
+    ) -> Result<Self, ParseError> {
+        let size = width * height;
+        if vx.len() != timestamps.len() * size || vy.len() != timestamps.len() * size {
+            return Err(ParseError::General(format!(
+                "{} timestamps do not match {} flow values of size {}x{}",
+                timestamps.len(),
+                vx.len(),
+                width,
+                height
+            )));
+        }
+        let mut fields = timestamps
+            .into_iter()
+            .zip(
+                vx.chunks_exact(size.max(1))
+                    .zip(vy.chunks_exact(size.max(1))),
+            )
+            .map(|(t, (vx, vy))| FlowField {
+                t,
+                width,
+                height,
+                vx: vx.to_vec(),
+                vy: vy.to_vec(),
+            })
+            .collect::<Vec<FlowField>>();
+        fields.sort_by_key(|field| field.t);
+        Ok(FlowGroundTruth { fields })
+    }
+
+    /// Returns the field whose interval contains `t`, if any.
+    pub fn field_at(&self, t: i64) -> Option<&FlowField> {
+        let index = self.fields.partition_point(|field| field.t <= t);
+        if index == 0 || index == self.fields.len() {
+            None
+        } else {
+            Some(&self.fields[index - 1])
+        }
+    }
+
+    /// Pairs each field with the events between its timestamp and the next field's timestamp.
+    pub fn windows<I: Iterator<Item = Result<Event, ParseError>>>(
+        self,
+        events: I,
+    ) -> FlowWindows<I> {
+        FlowWindows {
+            events: events.peekable(),
+            fields: self.fields.into_iter().peekable(),
+        }
+    }
+}
+
+/// Events in `[begin_t, end_t[` and the flow over the same interval.
+#[derive(Debug, Clone)]
+pub struct FlowSample {
+    pub begin_t: i64,
+    pub end_t: i64,
+    pub events: Vec<Event>,
+    pub field: FlowField,
+}
+
+/// See `FlowGroundTruth::windows`.
+///
+/// The last field, whose end is unknown, is skipped.
+pub struct FlowWindows<I: Iterator<Item = Result<Event, ParseError>>> {
+    events: std::iter::Peekable<I>,
+    fields: std::iter::Peekable<std::vec::IntoIter<FlowField>>,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> FlowWindows<I> {
+    fn first_t(&mut self) -> Result<Option<i64>, ParseError> {
+        match self.events.peek() {
+            Some(Ok(event)) => Ok(Some(event.t())),
+            Some(Err(_)) => Err(self.events.next().unwrap().unwrap_err()),
+            None => Ok(None),
+        }
+    }
+
+    fn next_sample(&mut self) -> Result<Option<FlowSample>, ParseError> {
+        let field = match self.fields.next() {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+        let end_t = match self.fields.peek() {
+            Some(next_field) => next_field.t,
+            None => return Ok(None),
+        };
+        let mut events = Vec::new();
+        while let Some(t) = self.first_t()? {
+            if t >= end_t {
+                break;
+            }
+            let event = self.events.next().unwrap()?;
+            if t >= field.t {
+                events.push(event);
+            }
+        }
+        Ok(Some(FlowSample {
+            begin_t: field.t,
+            end_t,
+            events,
+            field,
+        }))
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for FlowWindows<I> {
+    type Item = Result<FlowSample, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_sample().transpose()
+    }
+}
+
+/// NumPy array (.npy) reader, limited to little-endian C-order numbers.
+struct Npy<'a> {
+    descr: String,
+    shape: Vec<usize>,
+    data: &'a [u8],
+}
+
+impl<'a> Npy<'a> {
+    fn parse(bytes: &'a [u8]) -> Result<Self, ParseError> {
+        if bytes.len() < 10 || &bytes[0..6] != b"\x93NUMPY" {
+            return Err(ParseError::General("not a .npy array".to_owned()));
+        }
+        let (header_length, header_begin) = if bytes[6] == 1 {
+            (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10)
+        } else {
+            if bytes.len() < 12 {
+                return Err(ParseError::General("truncated .npy header".to_owned()));
+            }
+            (
+                u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+                12,
+            )
+        };
+        let header = std::str::from_utf8(
+            bytes
+                .get(header_begin..header_begin + header_length)
+                .ok_or_else(|| ParseError::General("truncated .npy header".to_owned()))?,
+        )?;
+        let value = |key: &str| -> Result<&str, ParseError> {
+            let pattern = format!("'{}':", key);
+            header
+                .find(&pattern)
+                .map(|index| header[index + pattern.len()..].trim_start())
+                .ok_or_else(|| ParseError::General(format!("the .npy header has no {}", key)))
+        };
+        if value("fortran_order")?.starts_with("True") {
+            return Err(ParseError::General(
+                "Fortran-order .npy arrays are not supported".to_owned(),
+            ));
+        }
+        let descr = value("descr")?;
+        let descr = descr
+            .get(1..)
+            .and_then(|descr| descr.split('\'').next())
+            .unwrap_or_default()
+            .to_owned();
+        let shape = value("shape")?;
+        let shape = shape
+            .get(1..shape.find(')').unwrap_or(0))
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|size| !size.is_empty())
+            .map(str::parse::<usize>)
+            .collect::<Result<Vec<usize>, std::num::ParseIntError>>()?;
+        Ok(Npy {
+            descr,
+            shape,
+            data: &bytes[header_begin + header_length..],
+        })
+    }
+
+    fn convert<T, const N: usize>(
+        &self,
+        from_le_bytes: fn([u8; N]) -> T,
+    ) -> Result<Vec<T>, ParseError> {
+        let count = self.shape.iter().product::<usize>();
+        if self.data.len() < count * N {
+            return Err(ParseError::General(format!(
+                "the .npy array has {} bytes instead of {}",
+                self.data.len(),
+                count * N
+            )));
+        }
+        Ok(self.data[..count * N]
+            .chunks_exact(N)
+            .map(|bytes| from_le_bytes(bytes.try_into().unwrap()))
+            .collect())
+    }
+
+    fn values(&self) -> Result<Vec<f32>, ParseError> {
+        match self.descr.as_str() {
+            "<f4" => self.convert(f32::from_le_bytes),
+            "<f8" => Ok(self
+                .convert(f64::from_le_bytes)?
+                .into_iter()
+                .map(|value| value as f32)
+                .collect()),
+            descr => Err(ParseError::General(format!(
+                "unsupported flow type `{}`",
+                descr
+            ))),
+        }
+    }
+
+    fn timestamps(&self) -> Result<Vec<i64>, ParseError> {
+        match self.descr.as_str() {
+            "<f8" => Ok(self
+                .convert(f64::from_le_bytes)?
+                .into_iter()
+                .map(|t| (t * 1e6).round() as i64)
+                .collect()),
+            "<i8" => self.convert(i64::from_le_bytes),
+            "<u8" => Ok(self
+                .convert(u64::from_le_bytes)?
+                .into_iter()
+                .map(|t| t as i64)
+                .collect()),
+            descr => Err(ParseError::General(format!(
+                "unsupported timestamp type `{}`",
+                descr
+            ))),
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use crate::base::{Packet, ParseError};
+pub use crate::frame_generated::{Frame, FrameFormat};
+
+/// Timestamps (in microseconds) and geometry of a frame, without its pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameMeta {
+    pub t: i64,
+    /// Start of frame readout (timestampStartOfFrame).
+    pub begin_t: i64,
+    /// End of frame readout (timestampEndOfFrame).
+    pub end_t: i64,
+    pub exposure_begin_t: i64,
+    pub exposure_end_t: i64,
+    pub format: FrameFormat,
+    pub width: i16,
+    pub height: i16,
+    /// Position of the region of interest on the sensor.
+    pub offset_x: i16,
+    pub offset_y: i16,
+}
+
+impl FrameMeta {
+    pub fn exposure_us(&self) -> i64 {
+        self.exposure_end_t - self.exposure_begin_t
+    }
+}
+
+impl<'a> Frame<'a> {
+    pub fn meta(&self) -> FrameMeta {
+        FrameMeta {
+            t: self.t(),
+            begin_t: self.begin_t(),
+            end_t: self.end_t(),
+            exposure_begin_t: self.exposure_begin_t(),
+            exposure_end_t: self.exposure_end_t(),
+            format: self.format(),
+            width: self.width(),
+            height: self.height(),
+            offset_x: self.offset_x(),
+            offset_y: self.offset_y(),
+        }
+    }
+
+    /// Exposure duration in microseconds.
+    pub fn exposure_us(&self) -> i64 {
+        self.exposure_end_t() - self.exposure_begin_t()
+    }
+}
+
+impl Packet {
+    /// Reads the frame of a FRME packet.
+    pub fn frame(&self) -> Result<Frame<'_>, ParseError> {
+        self.expect_identifier(crate::frame_generated::FRAME_IDENTIFIER)?;
+        Ok(crate::frame_generated::size_prefixed_root_as_frame(
+            &self.buffer,
+        )?)
+    }
+
+    /// Reads the metadata of a FRME packet.
+    pub fn frame_meta(&self) -> Result<FrameMeta, ParseError> {
+        Ok(self.frame()?.meta())
+    }
+
+    /// Width and height of a FRME packet, without reading its pixels.
+    pub fn frame_dims(&self) -> Result<(u16, u16), ParseError> {
+        let frame = self.frame()?;
+        Ok((frame.width().max(0) as u16, frame.height().max(0) as u16))
+    }
+}
+
+/// Output pixel format of `convert`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Gray8,
+    /// Interleaved red, green and blue.
+    Rgb8,
+}
+
+impl PixelFormat {
+    pub fn channels(self) -> usize {
+        match self {
+            PixelFormat::Gray8 => 1,
+            PixelFormat::Rgb8 => 3,
+        }
+    }
+}
+
+/// Color filter array of raw frames (color DAVIS sensors), named after the top-left 2 × 2 block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BayerPattern {
+    Rggb,
+    Grbg,
+    Gbrg,
+    Bggr,
+}
+
+impl BayerPattern {
+    /// Channel (0 red, 1 green, 2 blue) of the pixel (x, y).
+    fn channel(self, x: usize, y: usize) -> usize {
+        let block = match self {
+            BayerPattern::Rggb => [0, 1, 1, 2],
+            BayerPattern::Grbg => [1, 0, 2, 1],
+            BayerPattern::Gbrg => [1, 2, 0, 1],
+            BayerPattern::Bggr => [2, 1, 1, 0],
+        };
+        block[(y % 2) * 2 + x % 2]
+    }
+}
+
+/// Row-major pixels in a known format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub format: PixelFormat,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Applies `value = 255 * (value / 255)^(1 / gamma)`, hence gamma values larger than 1 brighten the image.
+    pub fn gamma(&mut self, gamma: f32) {
+        let mut table = [0u8; 256];
+        for (value, mapped) in table.iter_mut().enumerate() {
+            *mapped = (255.0 * (value as f32 / 255.0).powf(1.0 / gamma))
+                .round()
+                .clamp(0.0, 255.0) as u8;
+        }
+        self.apply(&table);
+    }
+
+    /// Stretches the values so that the `low` and `high` percentiles (in [0, 100]) map to 0 and 255.
+    ///
+    /// This compensates for under-exposed frames; (1.0, 99.0) is a reasonable choice.
+    pub fn normalize(&mut self, low: f32, high: f32) {
+        let histogram = histogram(self.pixels.iter());
+        let total = self.pixels.len() as f32;
+        let percentile = |percentile: f32| -> usize {
+            let threshold = total * percentile.clamp(0.0, 100.0) / 100.0;
+            let mut count = 0.0;
+            for (value, value_count) in histogram.iter().enumerate() {
+                count += *value_count as f32;
+                if count >= threshold && count > 0.0 {
+                    return value;
+                }
+            }
+            255
+        };
+        let minimum = percentile(low);
+        let maximum = percentile(high);
+        if maximum <= minimum {
+            return;
+        }
+        let mut table = [0u8; 256];
+        for (value, mapped) in table.iter_mut().enumerate() {
+            *mapped = ((value.clamp(minimum, maximum) - minimum) * 255 + (maximum - minimum) / 2)
+                .checked_div(maximum - minimum)
+                .unwrap_or(0) as u8;
+        }
+        self.apply(&table);
+    }
+
+    /// Contrast-limited adaptive histogram equalization (CLAHE).
+    ///
+    /// The image is divided into `tiles` × `tiles` regions, each equalized with a histogram clipped at `clip_limit`
+    /// times the average bin count (2.0 to 4.0 are typical values), and the regions' mappings are interpolated
+    /// bilinearly. RGB images use the same mapping for the three channels.
+    pub fn equalize(&mut self, tiles: usize, clip_limit: f32) {
+        let channels = self.format.channels();
+        let tiles = tiles.max(1);
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let tile_width = self.width.div_ceil(tiles);
+        let tile_height = self.height.div_ceil(tiles);
+        let tiles_x = self.width.div_ceil(tile_width);
+        let tiles_y = self.height.div_ceil(tile_height);
+        let mut tables = Vec::with_capacity(tiles_x * tiles_y);
+        for tile_y in 0..tiles_y {
+            for tile_x in 0..tiles_x {
+                let rows = tile_y * tile_height..((tile_y + 1) * tile_height).min(self.height);
+                let columns = tile_x * tile_width * channels
+                    ..((tile_x + 1) * tile_width).min(self.width) * channels;
+                let mut histogram = histogram(rows.flat_map(|y| {
+                    let row = y * self.width * channels;
+                    self.pixels[row + columns.start..row + columns.end].iter()
+                }));
+                tables.push(clipped_equalization(&mut histogram, clip_limit));
+            }
+        }
+        // tile centers, in pixels
+        let center = |tile: usize, size: usize, length: usize| -> f32 {
+            (tile * size) as f32 + ((size.min(length - tile * size)) as f32 - 1.0) / 2.0
+        };
+        let neighbours = |position: usize, size: usize, count: usize, length: usize| {
+            let mut first = 0;
+            while first + 1 < count && center(first + 1, size, length) <= position as f32 {
+                first += 1;
+            }
+            let second = (first + 1).min(count - 1);
+            let weight = if second == first {
+                0.0
+            } else {
+                ((position as f32 - center(first, size, length))
+                    / (center(second, size, length) - center(first, size, length)))
+                .clamp(0.0, 1.0)
+            };
+            (first, second, weight)
+        };
+        for y in 0..self.height {
+            let (top, bottom, weight_y) = neighbours(y, tile_height, tiles_y, self.height);
+            for x in 0..self.width {
+                let (left, right, weight_x) = neighbours(x, tile_width, tiles_x, self.width);
+                for channel in 0..channels {
+                    let index = (y * self.width + x) * channels + channel;
+                    let value = self.pixels[index] as usize;
+                    let mapped = |tile_x: usize, tile_y: usize| {
+                        tables[tile_y * tiles_x + tile_x][value] as f32
+                    };
+                    let top_value =
+                        mapped(left, top) * (1.0 - weight_x) + mapped(right, top) * weight_x;
+                    let bottom_value =
+                        mapped(left, bottom) * (1.0 - weight_x) + mapped(right, bottom) * weight_x;
+                    self.pixels[index] = (top_value * (1.0 - weight_y) + bottom_value * weight_y)
+                        .round()
+                        .clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, table: &[u8; 256]) {
+        for value in self.pixels.iter_mut() {
+            *value = table[*value as usize];
+        }
+    }
+}
+
+fn histogram<'a, I: Iterator<Item = &'a u8>>(values: I) -> [u32; 256] {
+    let mut histogram = [0u32; 256];
+    for value in values {
+        histogram[*value as usize] += 1;
+    }
+    histogram
+}
+
+/// Clips the histogram, redistributes the excess uniformly and returns the cumulative mapping.
+fn clipped_equalization(histogram: &mut [u32; 256], clip_limit: f32) -> [u8; 256] {
+    let total = histogram.iter().sum::<u32>();
+    let mut table = [0u8; 256];
+    if total == 0 {
+        return table;
+    }
+    let limit = ((clip_limit.max(1.0) * total as f32 / 256.0).ceil() as u32).max(1);
+    let mut excess = 0;
+    for count in histogram.iter_mut() {
+        if *count > limit {
+            excess += *count - limit;
+            *count = limit;
+        }
+    }
+    for (value, count) in histogram.iter_mut().enumerate() {
+        *count += excess / 256 + u32::from((value as u32) < excess % 256);
+    }
+    let mut cumulative = 0u64;
+    for (value, count) in histogram.iter().enumerate() {
+        cumulative += *count as u64;
+        table[value] = ((cumulative * 255 + total as u64 / 2) / total as u64) as u8;
+    }
+    table
+}
+
+/// Converts the pixels of a frame to 8 bits gray or RGB.
+///
+/// 16 bits frames keep their most significant byte and alpha channels are dropped.
+/// If `bayer` is set, the frame must have a single channel, which is demosaiced with bilinear interpolation.
+pub fn convert(
+    frame: &Frame,
+    format: PixelFormat,
+    bayer: Option<BayerPattern>,
+) -> Result<Image, ParseError> {
+    let (channels, wide) = match frame.format() {
+        FrameFormat::Gray => (1, false),
+        FrameFormat::Gray16 => (1, true),
+        FrameFormat::Bgr => (3, false),
+        FrameFormat::Bgr16 => (3, true),
+        FrameFormat::Bgra => (4, false),
+        FrameFormat::Bgra16 => (4, true),
+        _ => return Err(ParseError::UnknownFrameFormat(frame.format().0)),
+    };
+    let width = frame.width().max(0) as usize;
+    let height = frame.height().max(0) as usize;
+    let bytes = frame.pixels().unwrap_or_default();
+    let expected_length = width * height * channels * if wide { 2 } else { 1 };
+    if bytes.len() != expected_length {
+        return Err(ParseError::FrameSizeMismatch {
+            expected: expected_length,
+            got: bytes.len(),
+        });
+    }
+    let values: Vec<u8> = if wide {
+        bytes.chunks_exact(2).map(|value| value[1]).collect()
+    } else {
+        bytes.to_vec()
+    };
+    let rgb = match (bayer, channels) {
+        (Some(pattern), 1) => demosaic(&values, width, height, pattern),
+        (Some(_), _) => {
+            return Err(ParseError::FrameFormatMismatch {
+                expected: "single-channel (Bayer)",
+                format: format!("{:?}", frame.format()),
+            })
+        }
+        (None, 1) => {
+            if format == PixelFormat::Gray8 {
+                return Ok(Image {
+                    width,
+                    height,
+                    format,
+                    pixels: values,
+                });
+            }
+            values.iter().flat_map(|value| [*value; 3]).collect()
+        }
+        (None, _) => values
+            .chunks_exact(channels)
+            .flat_map(|bgr| [bgr[2], bgr[1], bgr[0]])
+            .collect(),
+    };
+    Ok(Image {
+        width,
+        height,
+        format,
+        pixels: match format {
+            PixelFormat::Rgb8 => rgb,
+            PixelFormat::Gray8 => rgb
+                .chunks_exact(3)
+                .map(|rgb| {
+                    ((299 * rgb[0] as u32 + 587 * rgb[1] as u32 + 114 * rgb[2] as u32 + 500) / 1000)
+                        as u8
+                })
+                .collect(),
+        },
+    })
+}
+
+/// Averages, for each channel, the pixels of that channel in the 3 × 3 neighbourhood (the pixel itself if it matches).
+fn demosaic(values: &[u8], width: usize, height: usize, pattern: BayerPattern) -> Vec<u8> {
+    let mut rgb = vec![0u8; width * height * 3];
+    for y in 0..height {
+        for x in 0..width {
+            let own_channel = pattern.channel(x, y);
+            let mut sums = [0u32; 3];
+            let mut counts = [0u32; 3];
+            for neighbour_y in y.saturating_sub(1)..(y + 2).min(height) {
+                for neighbour_x in x.saturating_sub(1)..(x + 2).min(width) {
+                    let channel = pattern.channel(neighbour_x, neighbour_y);
+                    if channel != own_channel {
+                        sums[channel] += values[neighbour_y * width + neighbour_x] as u32;
+                        counts[channel] += 1;
+                    }
+                }
+            }
+            let pixel = &mut rgb[(y * width + x) * 3..(y * width + x + 1) * 3];
+            for channel in 0..3 {
+                pixel[channel] = if channel == own_channel {
+                    values[y * width + x]
+                } else {
+                    (sums[channel] + counts[channel] / 2)
+                        .checked_div(counts[channel])
+                        .unwrap_or(0) as u8
+                };
+            }
+        }
+    }
+    rgb
+}
@@ -0,0 +1,336 @@
+//! Packet framing of AEDAT4 files and DV network streams, on any `Read` or `Write`.
+//!
+//! Every packet is preceded by its stream id and its length, both little-endian `u32` regardless of the
+//! platform. `PacketFramer` only handles this framing (packets are neither decompressed nor parsed), hence
+//! tools that relay or record the DV protocol can use it without a `Decoder`.
+//!
+//! `Decoder` reads its packet headers with a `PacketFramer`. Unknown stream ids are reported with
+//! `ParseError::MalformedPacketHeader`, which gives the byte offset and the raw header bytes, and lengths
+//! above the limit with `ParseError::PacketTooLarge`. Both suggest swapping bytes if the header looks big-endian.
+//! A stream that ends within a packet returns `ParseError::TruncatedPacket`.
+
+use crate::base::{ParseError, DEFAULT_MAX_PACKET_SIZE, MAXIMUM_PREALLOCATION};
+pub use aedat_core::framing::PacketHeader;
+use std::io::{Read, Write};
+
+/// Reads or writes framed packets (see the module documentation).
+pub struct PacketFramer<S> {
+    stream: S,
+    max_packet_size: u32,
+    stream_ids: Option<std::collections::HashSet<u32>>,
+    position: u64,
+}
+
+impl<S> PacketFramer<S> {
+    /// `stream` must be positioned at a packet header (after the IO header for files and network streams).
+    pub fn new(stream: S) -> Self {
+        PacketFramer {
+            stream,
+            max_packet_size: DEFAULT_MAX_PACKET_SIZE,
+            stream_ids: None,
+            position: 0,
+        }
+    }
+
+    /// Largest accepted packet length, when reading and writing (256 MiB by default).
+    pub fn max_packet_size(mut self, max_packet_size: u32) -> Self {
+        self.max_packet_size = max_packet_size;
+        self
+    }
+
+    /// Rejects headers with other stream ids (all ids are accepted by default).
+    ///
+    /// Since stream ids are small integers, this catches most desynchronized streams early.
+    pub fn stream_ids(mut self, stream_ids: impl IntoIterator<Item = u32>) -> Self {
+        self.stream_ids = Some(stream_ids.into_iter().collect());
+        self
+    }
+
+    /// Position of the stream when the framer is created (for instance the size of the IO header),
+    /// so that `position` and error messages give offsets in the file.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.position = offset;
+        self
+    }
+
+    /// Bytes read or written so far, plus the offset.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    fn check(&self, header: &PacketHeader) -> Result<(), ParseError> {
+        if let Some(stream_ids) = &self.stream_ids {
+            if !stream_ids.contains(&header.stream_id) {
+                return Err(self.malformed(
+                    &header.to_bytes(),
+                    format!(
+                        "unknown stream id {}{}",
+                        header.stream_id,
+                        big_endian_hint(
+                            header.stream_id,
+                            stream_ids.contains(&header.stream_id.swap_bytes())
+                        )
+                    ),
+                ));
+            }
+        }
+        if header.length > self.max_packet_size {
+            return Err(ParseError::PacketTooLarge {
+                size: header.length,
+                limit: self.max_packet_size,
+            });
+        }
+        Ok(())
+    }
+
+    fn malformed(&self, header: &[u8], reason: String) -> ParseError {
+        ParseError::MalformedPacketHeader {
+            position: self.position,
+            header: header
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<Vec<_>>()
+                .join(" "),
+            reason,
+        }
+    }
+}
+
+impl<S: Read> PacketFramer<S> {
+    /// Reads the next header, or returns None if the stream ends cleanly (before the first header byte).
+    ///
+    /// The caller must then read (or skip) exactly `header.length` bytes with `read_body`.
+    pub fn read_header(&mut self) -> Result<Option<PacketHeader>, ParseError> {
+        let mut bytes = [0; PacketHeader::SIZE];
+        let mut read = 0;
+        while read < bytes.len() {
+            match self.stream.read(&mut bytes[read..]) {
+                Ok(0) => break,
+                Ok(count) => read += count,
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        if read == 0 {
+            return Ok(None);
+        }
+        if read < bytes.len() {
+            return Err(ParseError::TruncatedPacket {
+                expected: PacketHeader::SIZE as u64,
+                got: read as u64,
+            });
+        }
+        let header = PacketHeader::from_bytes(&bytes);
+        self.check(&header)?;
+        self.position += PacketHeader::SIZE as u64;
+        Ok(Some(header))
+    }
+
+    /// Reads the (possibly compressed) packet that follows `header`.
+    pub fn read_body(&mut self, header: &PacketHeader) -> Result<Vec<u8>, ParseError> {
+        let mut buffer = Vec::with_capacity((header.length as usize).min(MAXIMUM_PREALLOCATION));
+        let read = Read::by_ref(&mut self.stream)
+            .take(header.length as u64)
+            .read_to_end(&mut buffer)?;
+        if read < header.length as usize {
+            return Err(ParseError::TruncatedPacket {
+                expected: (PacketHeader::SIZE + header.length as usize) as u64,
+                got: (PacketHeader::SIZE + read) as u64,
+            });
+        }
+        self.position += read as u64;
+        Ok(buffer)
+    }
+
+    /// Reads the next packet, or returns None if the stream ends cleanly.
+    pub fn read_packet(&mut self) -> Result<Option<(u32, Vec<u8>)>, ParseError> {
+        match self.read_header()? {
+            Some(header) => Ok(Some((header.stream_id, self.read_body(&header)?))),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<S: Read> Iterator for PacketFramer<S> {
+    type Item = Result<(u32, Vec<u8>), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
+
+impl<S: Write> PacketFramer<S> {
+    /// Writes a header and the (possibly compressed) packet.
+    pub fn write_packet(&mut self, stream_id: u32, buffer: &[u8]) -> Result<(), ParseError> {
+        let header = PacketHeader {
+            stream_id,
+            length: u32::try_from(buffer.len()).unwrap_or(u32::MAX),
+        };
+        self.check(&header)?;
+        self.stream.write_all(&header.to_bytes())?;
+        self.stream.write_all(buffer)?;
+        self.position += (PacketHeader::SIZE + buffer.len()) as u64;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), ParseError> {
+        Ok(self.stream.flush()?)
+    }
+}
+
+/// Message suffix of `ParseError::PacketTooLarge`, for lengths that fit the limit once swapped.
+pub(crate) fn length_hint(size: &u32, limit: &u32) -> String {
+    big_endian_hint(*size, size.swap_bytes() <= *limit)
+}
+
+/// Suggests a byte order problem if the swapped value is smaller and `plausible`.
+fn big_endian_hint(value: u32, plausible: bool) -> String {
+    if plausible && value.swap_bytes() < value {
+        format!(
+            " (the header looks big-endian, swapped value {})",
+            value.swap_bytes()
+        )
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(stream_id: [u8; 4], length: [u8; 4]) -> Vec<u8> {
+        stream_id.iter().chain(length.iter()).copied().collect()
+    }
+
+    fn reason(error: ParseError) -> String {
+        match error {
+            ParseError::MalformedPacketHeader { reason, .. } => reason,
+            error => panic!("unexpected error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut framer = PacketFramer::new(Vec::new()).stream_ids([0, 1]);
+        framer.write_packet(0, b"events").unwrap();
+        framer.write_packet(1, b"frame").unwrap();
+        assert_eq!(framer.position(), 2 * PacketHeader::SIZE as u64 + 11);
+        let bytes = framer.into_inner();
+        let packets = PacketFramer::new(&bytes[..])
+            .stream_ids([0, 1])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            packets,
+            vec![(0, b"events".to_vec()), (1, b"frame".to_vec())]
+        );
+    }
+
+    #[test]
+    fn clean_end_of_stream() {
+        let mut framer = PacketFramer::new(&[][..]);
+        assert!(framer.read_header().unwrap().is_none());
+        assert!(framer.next().is_none());
+    }
+
+    #[test]
+    fn header_cut_short() {
+        let bytes = [1, 0, 0, 0, 4];
+        match PacketFramer::new(&bytes[..]).read_header() {
+            Err(ParseError::TruncatedPacket { expected, got }) => {
+                assert_eq!((expected, got), (8, 5));
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn length_over_limit() {
+        let bytes = header(1u32.to_le_bytes(), 100u32.to_le_bytes());
+        let error = PacketFramer::new(&bytes[..])
+            .max_packet_size(16)
+            .read_header()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the packet length (100 bytes) exceeds the limit (16 bytes)"
+        );
+        assert!(matches!(
+            error,
+            ParseError::PacketTooLarge {
+                size: 100,
+                limit: 16
+            }
+        ));
+    }
+
+    #[test]
+    fn empty_packet() {
+        let bytes = header(1u32.to_le_bytes(), 0u32.to_le_bytes());
+        let mut framer = PacketFramer::new(&bytes[..]);
+        assert_eq!(framer.read_packet().unwrap(), Some((1, Vec::new())));
+        assert!(framer.read_packet().unwrap().is_none());
+    }
+
+    #[test]
+    fn body_cut_short() {
+        let mut bytes = header(1u32.to_le_bytes(), 10u32.to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        match PacketFramer::new(&bytes[..]).read_packet() {
+            Err(ParseError::TruncatedPacket { expected, got }) => {
+                assert_eq!((expected, got), (18, 12));
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn unknown_stream_id() {
+        let bytes = header(7u32.to_le_bytes(), 4u32.to_le_bytes());
+        let error = PacketFramer::new(&bytes[..])
+            .stream_ids([0, 1])
+            .offset(42)
+            .read_header()
+            .unwrap_err();
+        match error {
+            ParseError::MalformedPacketHeader {
+                position,
+                header,
+                reason,
+            } => {
+                assert_eq!(position, 42);
+                assert_eq!(header, "07 00 00 00 04 00 00 00");
+                assert_eq!(reason, "unknown stream id 7");
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn big_endian_hint() {
+        let bytes = header(1u32.to_be_bytes(), 40u32.to_be_bytes());
+        let error = PacketFramer::new(&bytes[..])
+            .stream_ids([0, 1])
+            .read_header()
+            .unwrap_err();
+        assert!(reason(error).ends_with("(the header looks big-endian, swapped value 1)"));
+        let error = PacketFramer::new(&bytes[..]).read_header().unwrap_err();
+        assert!(matches!(error, ParseError::PacketTooLarge { .. }));
+        assert!(error
+            .to_string()
+            .ends_with("(the header looks big-endian, swapped value 40)"));
+    }
+}
@@ -0,0 +1,124 @@
+//! Detection of temporal gaps, such as packets dropped by a network stream or pauses of a recording.
+
+use crate::base::{Decoder, Packet, ParseError, Stream};
+
+/// A packet, or a discontinuity detected before it.
+#[derive(Debug, Clone)]
+pub enum PipelineEvent {
+    Packet(Packet),
+    /// No element of the stream has a timestamp between `start` (the last timestamp of the previous packet)
+    /// and `end` (the first timestamp of the next packet).
+    Gap {
+        stream_id: u32,
+        start: i64,
+        end: i64,
+    },
+}
+
+/// A gap found by `gap_report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    pub stream_id: u32,
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Gap {
+    pub fn duration(&self) -> i64 {
+        self.end - self.start
+    }
+}
+
+/// Inserts a `PipelineEvent::Gap` before every packet that starts more than `threshold` microseconds after
+/// the end of the previous packet of the same stream (see `Decoder::gaps`).
+///
+/// Packets without elements, and packets of streams unknown to this library, do not affect gap detection.
+pub struct Gaps<I> {
+    packets: I,
+    id_to_stream: std::collections::HashMap<u32, Stream>,
+    threshold: i64,
+    end_ts: std::collections::HashMap<u32, i64>,
+    pending: Option<Packet>,
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> Gaps<I> {
+    pub fn new(
+        packets: I,
+        id_to_stream: std::collections::HashMap<u32, Stream>,
+        threshold: i64,
+    ) -> Self {
+        Gaps {
+            packets,
+            id_to_stream,
+            threshold,
+            end_ts: std::collections::HashMap::new(),
+            pending: None,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> Iterator for Gaps<I> {
+    type Item = Result<PipelineEvent, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(packet) = self.pending.take() {
+            return Some(Ok(PipelineEvent::Packet(packet)));
+        }
+        let packet = match self.packets.next()? {
+            Ok(packet) => packet,
+            Err(error) => return Some(Err(error)),
+        };
+        let content = match self.id_to_stream.get(&packet.stream_id) {
+            Some(stream) => packet.content(&stream.content),
+            None => return Some(Ok(PipelineEvent::Packet(packet))),
+        };
+        let (elements, begin_t, end_t) = match packet.summary(&content) {
+            Ok(summary) => summary,
+            Err(error) => return Some(Err(error)),
+        };
+        if elements == 0 {
+            return Some(Ok(PipelineEvent::Packet(packet)));
+        }
+        let stream_id = packet.stream_id;
+        let previous_end_t = self.end_ts.insert(stream_id, end_t);
+        match previous_end_t {
+            Some(previous_end_t) if begin_t - previous_end_t > self.threshold => {
+                self.pending = Some(packet);
+                Some(Ok(PipelineEvent::Gap {
+                    stream_id,
+                    start: previous_end_t,
+                    end: begin_t,
+                }))
+            }
+            _ => Some(Ok(PipelineEvent::Packet(packet))),
+        }
+    }
+}
+
+impl Decoder {
+    /// See `Gaps`.
+    pub fn gaps(self, threshold: i64) -> Gaps<Self> {
+        let id_to_stream = self.id_to_stream.clone();
+        Gaps::new(self, id_to_stream, threshold)
+    }
+}
+
+/// Reads all the packets and lists the gaps longer than `threshold` microseconds.
+pub fn gap_report(decoder: Decoder, threshold: i64) -> Result<Vec<Gap>, ParseError> {
+    let mut gaps = Vec::new();
+    for event in decoder.gaps(threshold) {
+        if let PipelineEvent::Gap {
+            stream_id,
+            start,
+            end,
+        } = event?
+        {
+            gaps.push(Gap {
+                stream_id,
+                start,
+                end,
+            });
+        }
+    }
+    Ok(gaps)
+}
@@ -0,0 +1,200 @@
+//! Per-window inference with ONNX models.
+//!
+//! A `Model` pairs an ONNX model with an event `Representation`. `Model::run` groups any event stream (a file,
+//! a live decoder...) into windows, converts each window into a tensor, stacks windows into batches, and
+//! yields one `Prediction` per window.
+
+use crate::base::ParseError;
+use crate::chunks::{Chunks, Window};
+use crate::events::Event;
+use crate::representations::{EventHistogram, TimeSurface, VoxelGrid};
+
+/// Conversion of a window of events into a (channels, height, width) tensor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Representation {
+    /// OFF and ON event counts (2 channels, see `EventHistogram`).
+    Histogram,
+    /// Event volume with the given number of bins (see `VoxelGrid`).
+    VoxelGrid { bins: usize },
+    /// Exponentially decaying time surface sampled at the end of the window (1 channel, see `TimeSurface`).
+    /// The surface persists across windows. `tau` is in microseconds.
+    TimeSurface { tau: f32 },
+}
+
+impl Representation {
+    pub fn channels(&self) -> usize {
+        match self {
+            Representation::Histogram => 2,
+            Representation::VoxelGrid { bins } => *bins,
+            Representation::TimeSurface { .. } => 1,
+        }
+    }
+}
+
+/// Output of the model for one window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prediction {
+    pub begin_t: i64,
+    pub end_t: i64,
+    /// Shape of the output without the batch dimension.
+    pub shape: Vec<usize>,
+    /// Row-major values of the first output of the model.
+    pub values: Vec<f32>,
+}
+
+/// An ONNX model and its input representation (see the module documentation).
+///
+/// The model must take a (batch, channels, height, width) float tensor as its only input, and its first output
+/// must have the batch size as its first dimension.
+pub struct Model {
+    session: ort::session::Session,
+    representation: Representation,
+    width: u16,
+    height: u16,
+    window: Window,
+    batch_size: usize,
+    time_surface: Option<TimeSurface>,
+}
+
+impl Model {
+    /// Windows contain 10 ms of events and batches contain a single window by default.
+    pub fn new<P: std::convert::AsRef<std::path::Path>>(
+        path: P,
+        representation: Representation,
+        width: u16,
+        height: u16,
+    ) -> Result<Self, ParseError> {
+        Ok(Model {
+            session: ort::session::Session::builder()?.commit_from_file(path)?,
+            representation,
+            width,
+            height,
+            window: Window::Duration(10_000),
+            batch_size: 1,
+            time_surface: match representation {
+                Representation::TimeSurface { tau } => Some(TimeSurface::new(width, height, tau)),
+                _ => None,
+            },
+        })
+    }
+
+    pub fn window(mut self, window: Window) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Number of windows per model call. Larger batches are faster, but delay the predictions of live sources.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Converts a window into a flat (channels, height, width) tensor.
+    pub fn tensor(&mut self, events: &[Event]) -> Vec<f32> {
+        match self.representation {
+            Representation::Histogram => {
+                EventHistogram::new(self.width, self.height).compute_flat(events)
+            }
+            Representation::VoxelGrid { bins } => VoxelGrid::new(bins, self.width, self.height)
+                .compute(events)
+                .into_raw_vec(),
+            Representation::TimeSurface { .. } => {
+                let surface = self.time_surface.as_mut().unwrap();
+                surface.extend(events);
+                surface
+                    .snapshot(events.last().map_or(0, |event| event.t()))
+                    .into_raw_vec()
+            }
+        }
+    }
+
+    /// Runs the model on a batch of windows.
+    pub fn infer(&mut self, windows: &[Vec<Event>]) -> Result<Vec<Prediction>, ParseError> {
+        if windows.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut input = Vec::with_capacity(
+            windows.len()
+                * self.representation.channels()
+                * self.width as usize
+                * self.height as usize,
+        );
+        for events in windows {
+            let tensor = self.tensor(events);
+            input.extend(tensor);
+        }
+        let tensor = ort::value::Tensor::from_array((
+            [
+                windows.len(),
+                self.representation.channels(),
+                self.height as usize,
+                self.width as usize,
+            ],
+            input,
+        ))?;
+        let outputs = self.session.run(ort::inputs![tensor])?;
+        let (shape, values) = outputs[0].try_extract_tensor::<f32>()?;
+        let shape: Vec<usize> = shape
+            .iter()
+            .skip(1)
+            .map(|length| *length as usize)
+            .collect();
+        let length = values.len() / windows.len();
+        Ok(windows
+            .iter()
+            .zip(values.chunks(length.max(1)))
+            .map(|(events, values)| Prediction {
+                begin_t: events.first().map_or(0, |event| event.t()),
+                end_t: events.last().map_or(0, |event| event.t()),
+                shape: shape.clone(),
+                values: values.to_vec(),
+            })
+            .collect())
+    }
+
+    /// Yields the predictions of consecutive windows of an event stream. Empty windows are skipped.
+    pub fn run<I: Iterator<Item = Result<Event, ParseError>>>(self, events: I) -> Inference<I> {
+        Inference {
+            chunks: Chunks::new(events, self.window),
+            model: self,
+            predictions: std::collections::VecDeque::new(),
+        }
+    }
+}
+
+/// Predictions of a `Model` (see `Model::run`).
+pub struct Inference<I> {
+    model: Model,
+    chunks: Chunks<I>,
+    predictions: std::collections::VecDeque<Prediction>,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Inference<I> {
+    type Item = Result<Prediction, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(prediction) = self.predictions.pop_front() {
+            return Some(Ok(prediction));
+        }
+        let mut windows = Vec::with_capacity(self.model.batch_size);
+        while windows.len() < self.model.batch_size {
+            match self.chunks.next() {
+                Some(Ok(events)) => {
+                    if !events.is_empty() {
+                        windows.push(events);
+                    }
+                }
+                Some(Err(error)) => return Some(Err(error)),
+                None => break,
+            }
+        }
+        if windows.is_empty() {
+            return None;
+        }
+        match self.model.infer(&windows) {
+            Ok(predictions) => self.predictions.extend(predictions),
+            Err(error) => return Some(Err(error)),
+        }
+        self.predictions.pop_front().map(Ok)
+    }
+}
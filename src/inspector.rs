@@ -0,0 +1,191 @@
+//! Recording inspector widget for egui applications.
+//!
+//! The application decodes packets and passes them to `Inspector::update`, then calls `Inspector::ui` from its
+//! egui loop. The widget lists the streams, plots the event rate, and shows the events accumulated over the
+//! last rate bin.
+
+use crate::accumulator::Accumulator;
+use crate::base::{Decoder, Packet, ParseError, Stream, StreamContent};
+
+/// See the module documentation.
+pub struct Inspector {
+    streams: Vec<(u32, Stream)>,
+    events_stream: Option<u32>,
+    accumulator: Option<Accumulator>,
+    /// Completed bins (start timestamp, events per second).
+    rates: std::collections::VecDeque<(i64, f32)>,
+    bin: i64,
+    history: usize,
+    bin_begin_t: Option<i64>,
+    bin_events: u64,
+    packets: u64,
+    texture: Option<egui::TextureHandle>,
+    frame_changed: bool,
+}
+
+impl Inspector {
+    /// Reads the stream descriptions of the decoder. The first event stream is accumulated.
+    pub fn new(decoder: &Decoder) -> Self {
+        let mut streams: Vec<(u32, Stream)> = decoder
+            .id_to_stream
+            .iter()
+            .map(|(id, stream)| (*id, stream.clone()))
+            .collect();
+        streams.sort_by_key(|(id, _)| *id);
+        let events_stream = streams.iter().find(|(_, stream)| {
+            stream.content == StreamContent::Events && stream.width > 0 && stream.height > 0
+        });
+        Inspector {
+            events_stream: events_stream.map(|(id, _)| *id),
+            accumulator: events_stream
+                .map(|(_, stream)| Accumulator::new(stream.width, stream.height)),
+            streams,
+            rates: std::collections::VecDeque::new(),
+            bin: 100_000,
+            history: 200,
+            bin_begin_t: None,
+            bin_events: 0,
+            packets: 0,
+            texture: None,
+            frame_changed: false,
+        }
+    }
+
+    /// Duration of a bin of the event rate plot, in microseconds (100 ms by default).
+    pub fn bin(mut self, bin: i64) -> Self {
+        self.bin = bin.max(1);
+        self
+    }
+
+    /// Number of bins in the event rate plot (200 by default).
+    pub fn history(mut self, history: usize) -> Self {
+        self.history = history.max(2);
+        self
+    }
+
+    /// Adds a decoded packet. Packets of other streams than the accumulated event stream are only counted.
+    pub fn update(&mut self, packet: &Packet) -> Result<(), ParseError> {
+        self.packets += 1;
+        if Some(packet.stream_id) != self.events_stream {
+            return Ok(());
+        }
+        for event in packet.events()? {
+            let begin_t = *self.bin_begin_t.get_or_insert(event.t());
+            if event.t() >= begin_t + self.bin {
+                self.rates
+                    .push_back((begin_t, self.bin_events as f32 * 1e6 / self.bin as f32));
+                while self.rates.len() > self.history {
+                    self.rates.pop_front();
+                }
+                // skips empty bins
+                self.bin_begin_t = Some(begin_t + (event.t() - begin_t) / self.bin * self.bin);
+                self.bin_events = 0;
+                if let Some(accumulator) = self.accumulator.as_mut() {
+                    accumulator.reset();
+                }
+            }
+            self.bin_events += 1;
+            if let Some(accumulator) = self.accumulator.as_mut() {
+                accumulator.add(&event);
+            }
+        }
+        self.frame_changed = true;
+        Ok(())
+    }
+
+    /// Draws the widget.
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Streams")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new("aedat_inspector_streams")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("id");
+                        ui.strong("content");
+                        ui.strong("size");
+                        ui.end_row();
+                        for (id, stream) in self.streams.iter() {
+                            ui.label(id.to_string());
+                            ui.label(stream.content.to_string());
+                            ui.label(if stream.width > 0 && stream.height > 0 {
+                                format!("{} × {}", stream.width, stream.height)
+                            } else {
+                                "-".to_owned()
+                            });
+                            ui.end_row();
+                        }
+                    });
+                ui.label(format!("{} packets", self.packets));
+            });
+        egui::CollapsingHeader::new("Event rate")
+            .default_open(true)
+            .show(ui, |ui| self.rate_ui(ui));
+        if let Some(accumulator) = self.accumulator.as_ref() {
+            egui::CollapsingHeader::new("Events")
+                .default_open(true)
+                .show(ui, |ui| {
+                    let image = egui::ColorImage::from_gray(
+                        [accumulator.width() as usize, accumulator.height() as usize],
+                        accumulator.frame(),
+                    );
+                    let texture = match self.texture.as_mut() {
+                        Some(texture) => {
+                            if self.frame_changed {
+                                texture.set(image, egui::TextureOptions::NEAREST);
+                            }
+                            texture
+                        }
+                        None => self.texture.insert(ui.ctx().load_texture(
+                            "aedat_inspector_events",
+                            image,
+                            egui::TextureOptions::NEAREST,
+                        )),
+                    };
+                    self.frame_changed = false;
+                    let scale = (ui.available_width() / accumulator.width() as f32).max(0.1);
+                    ui.image((
+                        texture.id(),
+                        egui::vec2(
+                            accumulator.width() as f32 * scale,
+                            accumulator.height() as f32 * scale,
+                        ),
+                    ));
+                });
+        }
+    }
+
+    fn rate_ui(&self, ui: &mut egui::Ui) {
+        let maximum = self
+            .rates
+            .iter()
+            .map(|(_, rate)| *rate)
+            .fold(0.0f32, f32::max);
+        ui.label(match self.rates.back() {
+            Some((_, rate)) => format!("{:.0} ev/s (peak {:.0} ev/s)", rate, maximum),
+            None => "no events yet".to_owned(),
+        });
+        let (response, painter) =
+            ui.allocate_painter(egui::vec2(ui.available_width(), 80.0), egui::Sense::hover());
+        let rect = response.rect;
+        painter.rect_stroke(rect, 0.0, ui.visuals().widgets.noninteractive.bg_stroke);
+        if self.rates.len() < 2 || maximum <= 0.0 {
+            return;
+        }
+        let points = self
+            .rates
+            .iter()
+            .enumerate()
+            .map(|(index, (_, rate))| {
+                egui::pos2(
+                    rect.left() + rect.width() * index as f32 / (self.history - 1) as f32,
+                    rect.bottom() - rect.height() * rate / maximum,
+                )
+            })
+            .collect();
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new(1.5, ui.visuals().selection.bg_fill),
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+//! Per-packet checksums stored in a sidecar file, to detect corrupted archives.
+//!
+//! The sidecar is a text file: a first line `aedat-checksums <algorithm>`,
+//! then one line `<byte offset> <size> <checksum>` per packet, with the checksum in hexadecimal.
+//! Offsets and sizes follow `FileDataEntry`, the checksum covers the 8 bytes packet header and the packet data.
+
+use crate::base::ParseError;
+use std::io::{BufRead, Write};
+
+const SIDECAR_MAGIC: &str = "aedat-checksums";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    /// CRC-32 (IEEE 802.3), as used by zlib and PNG.
+    Crc32,
+    /// xxHash (32 bits, seed 0).
+    XxHash32,
+}
+
+impl Checksum {
+    pub fn compute(self, bytes: &[u8]) -> u32 {
+        match self {
+            Checksum::Crc32 => crc32(bytes),
+            Checksum::XxHash32 => xxhash32(bytes),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Checksum::Crc32 => "crc32",
+            Checksum::XxHash32 => "xxhash32",
+        }
+    }
+}
+
+/// Expected checksum of a packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketChecksum {
+    pub byte_offset: i64,
+    pub size: u32,
+    pub value: u32,
+}
+
+/// Result of `Decoder::verify_integrity`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityReport {
+    pub packets: usize,
+    /// Packets whose bytes do not match their checksum, or that extend past the end of the file.
+    pub corrupted: Vec<PacketChecksum>,
+}
+
+impl IntegrityReport {
+    pub fn is_intact(&self) -> bool {
+        self.corrupted.is_empty()
+    }
+}
+
+pub(crate) struct SidecarWriter {
+    file: std::io::BufWriter<std::fs::File>,
+    checksum: Checksum,
+}
+
+impl SidecarWriter {
+    pub(crate) fn new<P: std::convert::AsRef<std::path::Path>>(
+        path: P,
+        checksum: Checksum,
+    ) -> Result<Self, ParseError> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(file, "{} {}", SIDECAR_MAGIC, checksum.name())?;
+        Ok(SidecarWriter { file, checksum })
+    }
+
+    /// `bytes` contains the packet header followed by the packet data.
+    pub(crate) fn write(&mut self, byte_offset: i64, bytes: &[u8]) -> Result<(), ParseError> {
+        writeln!(
+            self.file,
+            "{} {} {:08x}",
+            byte_offset,
+            bytes.len() - 8,
+            self.checksum.compute(bytes)
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn flush(&mut self) -> Result<(), ParseError> {
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+/// Reads a checksums sidecar file.
+pub fn read_sidecar<P: std::convert::AsRef<std::path::Path>>(
+    path: P,
+) -> Result<(Checksum, Vec<PacketChecksum>), ParseError> {
+    let mut lines = std::io::BufReader::new(std::fs::File::open(path)?).lines();
+    let header = lines.next().transpose()?.unwrap_or_default();
+    let checksum = match header.split_once(' ') {
+        Some((SIDECAR_MAGIC, "crc32")) => Checksum::Crc32,
+        Some((SIDECAR_MAGIC, "xxhash32")) => Checksum::XxHash32,
+        _ => {
+            return Err(ParseError::General(format!(
+                "unsupported checksums header `{}`",
+                header
+            )))
+        }
+    };
+    let mut checksums = Vec::new();
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let mut next = || {
+            fields
+                .next()
+                .ok_or_else(|| ParseError::General(format!("incomplete checksum line `{}`", line)))
+        };
+        checksums.push(PacketChecksum {
+            byte_offset: next()?.parse()?,
+            size: next()?.parse()?,
+            value: u32::from_str_radix(next()?, 16)?,
+        });
+    }
+    Ok((checksum, checksums))
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut value = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 {
+                (value >> 1) ^ 0xedb88320
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[index] = value;
+        index += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        CRC32_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+const XXHASH32_PRIMES: [u32; 5] = [0x9e3779b1, 0x85ebca77, 0xc2b2ae3d, 0x27d4eb2f, 0x165667b1];
+
+fn xxhash32_round(accumulator: u32, lane: u32) -> u32 {
+    accumulator
+        .wrapping_add(lane.wrapping_mul(XXHASH32_PRIMES[1]))
+        .rotate_left(13)
+        .wrapping_mul(XXHASH32_PRIMES[0])
+}
+
+fn xxhash32(bytes: &[u8]) -> u32 {
+    let lane = |chunk: &[u8]| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    let stripes = bytes.chunks_exact(16);
+    let remainder = stripes.remainder();
+    let mut hash = if bytes.len() >= 16 {
+        let mut accumulators = [
+            XXHASH32_PRIMES[0].wrapping_add(XXHASH32_PRIMES[1]),
+            XXHASH32_PRIMES[1],
+            0,
+            0u32.wrapping_sub(XXHASH32_PRIMES[0]),
+        ];
+        for stripe in stripes {
+            for (index, accumulator) in accumulators.iter_mut().enumerate() {
+                *accumulator = xxhash32_round(*accumulator, lane(&stripe[index * 4..]));
+            }
+        }
+        accumulators[0]
+            .rotate_left(1)
+            .wrapping_add(accumulators[1].rotate_left(7))
+            .wrapping_add(accumulators[2].rotate_left(12))
+            .wrapping_add(accumulators[3].rotate_left(18))
+    } else {
+        XXHASH32_PRIMES[4]
+    };
+    hash = hash.wrapping_add(bytes.len() as u32);
+    let words = remainder.chunks_exact(4);
+    let tail = words.remainder();
+    for word in words {
+        hash = hash
+            .wrapping_add(lane(word).wrapping_mul(XXHASH32_PRIMES[2]))
+            .rotate_left(17)
+            .wrapping_mul(XXHASH32_PRIMES[3]);
+    }
+    for byte in tail {
+        hash = hash
+            .wrapping_add((*byte as u32).wrapping_mul(XXHASH32_PRIMES[4]))
+            .rotate_left(11)
+            .wrapping_mul(XXHASH32_PRIMES[0]);
+    }
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(XXHASH32_PRIMES[1]);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(XXHASH32_PRIMES[2]);
+    hash ^ (hash >> 16)
+}
@@ -0,0 +1,209 @@
+//! Traits shared with other event camera crates.
+//!
+//! Algorithms written against `EventCameraSource` and `CameraEvent` work with this crate's decoder, and with
+//! any other reader (for instance Prophesee RAW or EVT3 files) or live driver that implements the traits.
+//! Sources deliver events in batches, usually one per packet or USB transfer, to keep the per-event cost low.
+
+use crate::base::{Decoder, ParseError, StreamContent};
+use crate::events::Event;
+
+/// Accessors of a polarity event.
+pub trait CameraEvent {
+    /// Timestamp in microseconds.
+    fn t(&self) -> i64;
+    fn x(&self) -> u16;
+    fn y(&self) -> u16;
+    /// True for ON (brightness increase) events.
+    fn polarity(&self) -> bool;
+}
+
+impl CameraEvent for Event {
+    fn t(&self) -> i64 {
+        Event::t(self)
+    }
+
+    fn x(&self) -> u16 {
+        Event::x(self) as u16
+    }
+
+    fn y(&self) -> u16 {
+        Event::y(self) as u16
+    }
+
+    fn polarity(&self) -> bool {
+        self.on()
+    }
+}
+
+impl CameraEvent for aedat_core::Event {
+    fn t(&self) -> i64 {
+        self.t
+    }
+
+    fn x(&self) -> u16 {
+        self.x as u16
+    }
+
+    fn y(&self) -> u16 {
+        self.y as u16
+    }
+
+    fn polarity(&self) -> bool {
+        self.on
+    }
+}
+
+/// (t, x, y, polarity)
+impl CameraEvent for (i64, u16, u16, bool) {
+    fn t(&self) -> i64 {
+        self.0
+    }
+
+    fn x(&self) -> u16 {
+        self.1
+    }
+
+    fn y(&self) -> u16 {
+        self.2
+    }
+
+    fn polarity(&self) -> bool {
+        self.3
+    }
+}
+
+/// Source of polarity events: file reader, network stream or camera driver.
+pub trait EventCameraSource {
+    type Event: CameraEvent;
+    type Error: std::error::Error;
+
+    /// Sensor size (width, height), if known.
+    fn resolution(&self) -> Option<(u16, u16)>;
+
+    /// Appends the next batch of events to `events`, and returns its length.
+    ///
+    /// Zero means that the source has ended. Live sources block until events are available.
+    fn read_batch(&mut self, events: &mut Vec<Self::Event>) -> Result<usize, Self::Error>;
+
+    /// Appends batches until at least `count` events were read or the source ends, and returns the number of
+    /// events read (which may exceed `count`).
+    fn read_at_least(
+        &mut self,
+        count: usize,
+        events: &mut Vec<Self::Event>,
+    ) -> Result<usize, Self::Error> {
+        let mut total = 0;
+        while total < count {
+            match self.read_batch(events)? {
+                0 => break,
+                length => total += length,
+            }
+        }
+        Ok(total)
+    }
+
+    /// Iterates over the events one by one.
+    fn into_events(self) -> SourceEvents<Self>
+    where
+        Self: Sized,
+    {
+        SourceEvents {
+            source: self,
+            events: Vec::new().into_iter(),
+        }
+    }
+}
+
+/// Events of a source, one by one (see `EventCameraSource::into_events`).
+pub struct SourceEvents<S: EventCameraSource> {
+    source: S,
+    events: std::vec::IntoIter<S::Event>,
+}
+
+impl<S: EventCameraSource> Iterator for SourceEvents<S> {
+    type Item = Result<S::Event, S::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.events.next() {
+                return Some(Ok(event));
+            }
+            let mut events = Vec::new();
+            match self.source.read_batch(&mut events) {
+                Ok(0) => return None,
+                Ok(_) => self.events = events.into_iter(),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// Batches are the events of a packet. Events of every event stream are returned, and the resolution is that
+/// of the event stream with the smallest id.
+impl EventCameraSource for Decoder {
+    type Event = Event;
+    type Error = ParseError;
+
+    fn resolution(&self) -> Option<(u16, u16)> {
+        self.id_to_stream
+            .iter()
+            .filter(|(_, stream)| stream.content == StreamContent::Events)
+            .min_by_key(|(stream_id, _)| **stream_id)
+            .and_then(|(_, stream)| stream.resolution())
+    }
+
+    fn read_batch(&mut self, events: &mut Vec<Event>) -> Result<usize, ParseError> {
+        while let Some(packet) = self.next() {
+            let packet = packet?;
+            if self.id_to_stream[&packet.stream_id].content != StreamContent::Events {
+                continue;
+            }
+            let length = events.len();
+            events.extend(packet.events()?);
+            if events.len() > length {
+                return Ok(events.len() - length);
+            }
+        }
+        Ok(0)
+    }
+}
+
+/// Adapts an event iterator (for instance filtered `decoder.events()`) to `EventCameraSource`.
+pub struct IteratorSource<I> {
+    events: I,
+    resolution: Option<(u16, u16)>,
+    batch_size: usize,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> IteratorSource<I> {
+    /// Batches have 4096 events by default.
+    pub fn new(events: I, resolution: Option<(u16, u16)>) -> Self {
+        IteratorSource {
+            events,
+            resolution,
+            batch_size: 4096,
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> EventCameraSource for IteratorSource<I> {
+    type Event = Event;
+    type Error = ParseError;
+
+    fn resolution(&self) -> Option<(u16, u16)> {
+        self.resolution
+    }
+
+    fn read_batch(&mut self, events: &mut Vec<Event>) -> Result<usize, ParseError> {
+        let length = events.len();
+        for event in self.events.by_ref().take(self.batch_size) {
+            events.push(event?);
+        }
+        Ok(events.len() - length)
+    }
+}
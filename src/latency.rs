@@ -0,0 +1,154 @@
+use crate::base::Packet;
+use crate::{events_generated, frame_generated, imus_generated, triggers_generated};
+
+/// Largest number of bins returned by `LatencyHistogram::bins`.
+pub const MAXIMUM_BINS: usize = 1 << 16;
+
+/// Rolling window of packet latencies, in microseconds.
+///
+/// The latency of a packet is its arrival time (wall clock) minus its most recent timestamp.
+/// Camera and host clocks must be synchronised for absolute values to be meaningful,
+/// but jitter does not depend on a constant clock offset.
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    window: usize,
+    samples: std::collections::VecDeque<i64>,
+}
+
+impl LatencyHistogram {
+    pub fn new(window: usize) -> Self {
+        LatencyHistogram {
+            window: window.max(1),
+            samples: std::collections::VecDeque::with_capacity(window.max(1)),
+        }
+    }
+
+    /// Adds a latency sample, discarding the oldest one if the window is full.
+    pub fn record(&mut self, latency: i64) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+    }
+
+    /// Computes and records the latency of a packet with an arrival time.
+    ///
+    /// Returns the latency, or None if the packet has no arrival time or no timestamps.
+    pub fn record_packet(&mut self, packet: &Packet) -> Option<i64> {
+        let arrival_time = packet
+            .arrival_time?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_micros() as i64;
+        let latency = arrival_time - last_timestamp(packet)?;
+        self.record(latency);
+        Some(latency)
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = i64> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        Some(
+            self.samples
+                .iter()
+                .map(|sample| *sample as f64)
+                .sum::<f64>()
+                / self.samples.len() as f64,
+        )
+    }
+
+    /// Standard deviation of the latency.
+    pub fn jitter(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        Some(
+            (self
+                .samples
+                .iter()
+                .map(|sample| (*sample as f64 - mean).powi(2))
+                .sum::<f64>()
+                / self.samples.len() as f64)
+                .sqrt(),
+        )
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.samples.iter().copied().min()
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.samples.iter().copied().max()
+    }
+
+    /// Nearest-rank percentile, with `percentile` in [0, 100].
+    pub fn percentile(&self, percentile: f64) -> Option<i64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<i64> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0) * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    /// Counts samples in bins of `bin_width` microseconds, starting at the minimum sample.
+    ///
+    /// Returns the lower bound of the first bin and the counts. There are at most `MAXIMUM_BINS` bins,
+    /// the last one also counts the samples beyond its upper bound (outliers).
+    pub fn bins(&self, bin_width: i64) -> (i64, Vec<usize>) {
+        let (min, max) = match (self.min(), self.max()) {
+            (Some(min), Some(max)) => (min, max),
+            _ => return (0, Vec::new()),
+        };
+        let bin_width = bin_width.max(1);
+        let bin =
+            |sample: i64| (sample.saturating_sub(min) / bin_width).min(MAXIMUM_BINS as i64 - 1);
+        let mut counts = vec![0; bin(max) as usize + 1];
+        for sample in self.samples.iter() {
+            counts[bin(*sample) as usize] += 1;
+        }
+        (min, counts)
+    }
+}
+
+/// Returns the most recent timestamp of a packet, in microseconds.
+fn last_timestamp(packet: &Packet) -> Option<i64> {
+    let buffer = &packet.buffer;
+    if events_generated::event_packet_size_prefixed_buffer_has_identifier(buffer) {
+        Some(packet.time_range().ok()??.1)
+    } else if frame_generated::frame_size_prefixed_buffer_has_identifier(buffer) {
+        Some(packet.frame().ok()?.t())
+    } else if imus_generated::imu_packet_size_prefixed_buffer_has_identifier(buffer) {
+        imus_generated::size_prefixed_root_as_imu_packet(buffer)
+            .ok()?
+            .elements()?
+            .iter()
+            .map(|imu| imu.t())
+            .max()
+    } else if triggers_generated::trigger_packet_size_prefixed_buffer_has_identifier(buffer) {
+        triggers_generated::size_prefixed_root_as_trigger_packet(buffer)
+            .ok()?
+            .elements()?
+            .iter()
+            .map(|trigger| trigger.t())
+            .max()
+    } else {
+        None
+    }
+}
@@ -1,14 +1,100 @@
+#[cfg(feature = "gstreamer")]
+pub mod aedatsrc;
+#[cfg(feature = "ndarray")]
+pub mod arrays;
+pub mod accumulator;
+pub mod algorithms;
+pub mod align;
+pub mod annotations;
 pub mod base;
+pub mod bench;
+pub mod calibration;
+pub mod camera;
+pub mod chunks;
+pub mod compression;
+#[cfg(feature = "datasets")]
+pub mod datasets;
+pub mod encoder;
+pub mod events;
+pub mod features;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filters;
+#[cfg(feature = "flow")]
+pub mod flow;
+pub mod frames;
+pub mod framing;
+pub mod gaps;
+#[cfg(feature = "inference")]
+pub mod inference;
+#[cfg(feature = "egui")]
+pub mod inspector;
+pub mod integrity;
+pub mod interop;
+pub mod latency;
+#[cfg(feature = "libcaer")]
+pub mod libcaer;
+pub mod loopback;
+#[cfg(feature = "mat")]
+pub mod mat;
+pub mod merge;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod monitor;
+#[cfg(feature = "nwb")]
+pub mod nwb;
+pub mod ordered;
+pub mod packed;
+pub mod parallel;
+pub mod polarity;
+pub mod prefetch;
+pub mod prelude;
+pub mod proxy;
+pub mod repair;
+#[cfg(feature = "ndarray")]
+pub mod representations;
+pub mod ring;
+pub mod runtime;
+pub mod sensors;
+pub mod shared;
+#[cfg(feature = "simulate")]
+pub mod simulate;
+pub mod snn;
+pub mod soa;
+pub mod splitter;
+pub mod statistics;
+pub mod stereo;
+#[cfg(all(feature = "safetensors", feature = "ndarray"))]
+pub mod tensors;
+pub mod testing;
+pub mod throttle;
+pub mod tracking;
+#[cfg(feature = "uvc")]
+pub mod uvc;
+pub mod validate;
+#[cfg(feature = "video")]
+pub mod video;
+#[cfg(feature = "viewer")]
+pub mod viewer;
 
-#[allow(dead_code, unused_imports)]
+#[allow(warnings, clippy::all)]
+#[path = "./bounding_boxes_generated.rs"]
+pub mod bounding_boxes_generated;
+#[allow(warnings, clippy::all)]
 #[path = "./events_generated.rs"]
 pub mod events_generated;
-#[allow(dead_code, unused_imports)]
+#[allow(warnings, clippy::all)]
+#[path = "./file_data_table_generated.rs"]
+pub mod file_data_table_generated;
+#[allow(warnings, clippy::all)]
 #[path = "./frame_generated.rs"]
 pub mod frame_generated;
-#[allow(dead_code, unused_imports)]
+#[allow(warnings, clippy::all)]
 #[path = "./imus_generated.rs"]
 pub mod imus_generated;
-#[allow(dead_code, unused_imports)]
+#[allow(warnings, clippy::all)]
+#[path = "./pose_generated.rs"]
+pub mod pose_generated;
+#[allow(warnings, clippy::all)]
 #[path = "./triggers_generated.rs"]
 pub mod triggers_generated;
@@ -0,0 +1,445 @@
+//! Live DAVIS and DVXplorer cameras through libcaer, without the DV runtime.
+//!
+//! `Decoder::new_from_device` opens a USB camera with its default configuration, and converts the libcaer packets
+//! to AEDAT4 packets with the stream ids of DV's capture module: 0 polarity events, 1 APS frames (DAVIS only),
+//! 2 IMU samples and 3 external input signals. Hence every decoder adapter works with live data.
+//!
+//! Timestamps are Unix times in microseconds: the device clock, offset by the host clock when the acquisition
+//! starts (and after timestamp resets). Only grayscale frames are converted, to 8 bits per pixel.
+//!
+//! The `libcaer` feature links to the system library (libcaer 3).
+
+use crate::base::{ioheader_generated, Decoder, Packet, ParseError, Source, Stream, StreamContent};
+use crate::compression::DefaultBackend;
+use crate::encoder::{encode_io_header, encode_packet};
+use crate::events::Event;
+use crate::{frame_generated, imus_generated, triggers_generated};
+use std::collections::HashMap;
+use std::os::raw::{c_char, c_void};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// device types (libcaer/devices/device.h)
+const CAER_DEVICE_DAVIS: u16 = 4;
+const CAER_DEVICE_DVXPLORER: u16 = 8;
+
+// DAVIS_CONFIG_DVS and DVX_DVS, DAVIS_CONFIG_APS (libcaer/devices/davis.h and dvxplorer.h)
+const DVS_MODULE: i8 = 1;
+const APS_MODULE: i8 = 2;
+const SIZE_COLUMNS: u8 = 0;
+const SIZE_ROWS: u8 = 1;
+
+// event types (libcaer/events/common.h)
+const SPECIAL_EVENT: i16 = 0;
+const POLARITY_EVENT: i16 = 1;
+const FRAME_EVENT: i16 = 2;
+const IMU6_EVENT: i16 = 3;
+
+// special event types (libcaer/events/special.h)
+const TIMESTAMP_RESET: u32 = 1;
+const EXTERNAL_INPUT_RISING_EDGE: u32 = 2;
+const EXTERNAL_INPUT_FALLING_EDGE: u32 = 3;
+const EXTERNAL_INPUT_PULSE: u32 = 4;
+
+// packed headers of packet containers and event packets (libcaer/events/packetContainer.h and common.h)
+const CONTAINER_HEADER_SIZE: usize = 28;
+const PACKET_HEADER_SIZE: usize = 28;
+
+#[link(name = "caer")]
+extern "C" {
+    fn caerDeviceOpen(
+        device_id: u16,
+        device_type: u16,
+        bus_number_restrict: u8,
+        device_address_restrict: u8,
+        serial_number_restrict: *const c_char,
+    ) -> *mut c_void;
+    fn caerDeviceClose(handle: *mut *mut c_void) -> bool;
+    fn caerDeviceSendDefaultConfig(handle: *mut c_void) -> bool;
+    fn caerDeviceConfigGet(handle: *mut c_void, module: i8, parameter: u8, value: *mut u32)
+        -> bool;
+    fn caerDeviceDataStart(
+        handle: *mut c_void,
+        data_notify_increase: Option<extern "C" fn(*mut c_void)>,
+        data_notify_decrease: Option<extern "C" fn(*mut c_void)>,
+        data_notify_user_pointer: *mut c_void,
+        data_shutdown_notify: Option<extern "C" fn(*mut c_void)>,
+        data_shutdown_user_pointer: *mut c_void,
+    ) -> bool;
+    fn caerDeviceDataStop(handle: *mut c_void) -> bool;
+    fn caerDeviceDataGet(handle: *mut c_void) -> *mut c_void;
+}
+
+// packet containers are allocated by libcaer with malloc, and released by inline functions of its headers
+extern "C" {
+    fn free(pointer: *mut c_void);
+}
+
+extern "C" fn on_shutdown(pointer: *mut c_void) {
+    // the pointer comes from Arc::as_ptr, and the device keeps the Arc alive until the acquisition stops
+    unsafe { &*(pointer as *const AtomicBool) }.store(true, Ordering::Release);
+}
+
+struct Device {
+    handle: *mut c_void,
+    started: bool,
+    shutdown: Arc<AtomicBool>,
+}
+
+// libcaer handles can be used from any thread, one at a time
+unsafe impl Send for Device {}
+
+impl Device {
+    fn open(serial: Option<&str>) -> Result<(Self, u16), ParseError> {
+        let serial = serial
+            .map(std::ffi::CString::new)
+            .transpose()
+            .map_err(|_| ParseError::Device("the serial number contains a null byte".to_owned()))?;
+        let serial = serial
+            .as_ref()
+            .map_or(std::ptr::null(), |serial| serial.as_ptr());
+        [CAER_DEVICE_DAVIS, CAER_DEVICE_DVXPLORER]
+            .into_iter()
+            .find_map(|device_type| {
+                let handle = unsafe { caerDeviceOpen(1, device_type, 0, 0, serial) };
+                (!handle.is_null()).then(|| {
+                    (
+                        Device {
+                            handle,
+                            started: false,
+                            shutdown: Arc::new(AtomicBool::new(false)),
+                        },
+                        device_type,
+                    )
+                })
+            })
+            .ok_or_else(|| ParseError::Device("no DAVIS or DVXplorer camera found".to_owned()))
+    }
+
+    fn size(&self, module: i8) -> Option<(u16, u16)> {
+        let mut width = 0;
+        let mut height = 0;
+        let found = unsafe {
+            caerDeviceConfigGet(self.handle, module, SIZE_COLUMNS, &mut width)
+                && caerDeviceConfigGet(self.handle, module, SIZE_ROWS, &mut height)
+        };
+        (found && width > 0 && height > 0).then_some((width as u16, height as u16))
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        unsafe {
+            if self.started {
+                caerDeviceDataStop(self.handle);
+            }
+            caerDeviceClose(&mut self.handle);
+        }
+    }
+}
+
+impl Decoder {
+    /// Reads a DAVIS or DVXplorer camera connected over USB (see `libcaer`). `serial` restricts the search
+    /// to the camera with this serial number, otherwise the first camera found is used.
+    ///
+    /// The decoder blocks until data is available, and ends when the camera is disconnected.
+    pub fn new_from_device(serial: Option<&str>) -> Result<Self, ParseError> {
+        let (mut device, device_type) = Device::open(serial)?;
+        if !unsafe { caerDeviceSendDefaultConfig(device.handle) } {
+            return Err(ParseError::Device(
+                "the default configuration could not be sent".to_owned(),
+            ));
+        }
+        let (width, height) = device
+            .size(DVS_MODULE)
+            .ok_or_else(|| ParseError::Device("the sensor size is not available".to_owned()))?;
+        let mut id_to_stream = HashMap::from([
+            (0, Stream::new(StreamContent::Events, width, height)),
+            (2, Stream::new(StreamContent::Imus, 0, 0)),
+            (3, Stream::new(StreamContent::Triggers, 0, 0)),
+        ]);
+        if device_type == CAER_DEVICE_DAVIS {
+            if let Some((width, height)) = device.size(APS_MODULE) {
+                id_to_stream.insert(1, Stream::new(StreamContent::Frame, width, height));
+            }
+        }
+        if !unsafe {
+            caerDeviceDataStart(
+                device.handle,
+                None,
+                None,
+                std::ptr::null_mut(),
+                Some(on_shutdown),
+                Arc::as_ptr(&device.shutdown) as *mut c_void,
+            )
+        } {
+            return Err(ParseError::Device(
+                "the acquisition could not be started".to_owned(),
+            ));
+        }
+        device.started = true;
+        let buffer = encode_io_header(
+            &id_to_stream,
+            ioheader_generated::Compression::None,
+            -1,
+            None,
+            None,
+        )?;
+        Decoder::new_from_source(
+            Box::new(DeviceSource {
+                device,
+                id_to_stream,
+                time_offset: now(),
+                buffer,
+                position: 0,
+            }),
+            false,
+            false,
+        )
+    }
+}
+
+/// Unix time in microseconds.
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_micros() as i64)
+}
+
+/// Reads a value from a packed libcaer structure.
+///
+/// # Safety
+/// `pointer + offset` must be valid for reads of `T`.
+unsafe fn read<T: Copy>(pointer: *const u8, offset: usize) -> T {
+    std::ptr::read_unaligned(pointer.add(offset) as *const T)
+}
+
+/// Serves the device data as an AEDAT4 stream (IOHeader, then uncompressed packets).
+struct DeviceSource {
+    device: Device,
+    id_to_stream: HashMap<u32, Stream>,
+    time_offset: i64,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl DeviceSource {
+    /// Converts and releases a libcaer packet container.
+    ///
+    /// # Safety
+    /// `container` must be a packet container returned by `caerDeviceDataGet`.
+    unsafe fn convert(&mut self, container: *mut c_void) -> Vec<Packet> {
+        let container = container as *const u8;
+        let mut events = Vec::new();
+        let mut imus = Vec::new();
+        let mut triggers = Vec::new();
+        let mut frames = Vec::new();
+        let packets_number = read::<i32>(container, 24).max(0) as usize;
+        for index in 0..packets_number {
+            let packet: *const u8 = read(
+                container,
+                CONTAINER_HEADER_SIZE + index * std::mem::size_of::<*const u8>(),
+            );
+            if packet.is_null() {
+                continue;
+            }
+            let event_type: i16 = read(packet, 0);
+            let event_size = read::<i32>(packet, 4).max(0) as usize;
+            let overflow = (read::<i32>(packet, 12) as i64) << 31;
+            let event_number = read::<i32>(packet, 20).max(0) as usize;
+            for event_index in 0..event_number {
+                let event = packet.add(PACKET_HEADER_SIZE + event_index * event_size);
+                let data: u32 = read(event, 0);
+                if data & 1 == 0 {
+                    continue;
+                }
+                let t = self.time_offset + (overflow | read::<i32>(event, 4) as i64);
+                match event_type {
+                    POLARITY_EVENT => events.push(Event::new(
+                        t,
+                        ((data >> 17) & 0x7fff) as i16,
+                        ((data >> 2) & 0x7fff) as i16,
+                        (data >> 1) & 1 == 1,
+                    )),
+                    IMU6_EVENT => imus.push(imus_generated::ImuArgs {
+                        t,
+                        accelerometer_x: read(event, 8),
+                        accelerometer_y: read(event, 12),
+                        accelerometer_z: read(event, 16),
+                        gyroscope_x: read(event, 20),
+                        gyroscope_y: read(event, 24),
+                        gyroscope_z: read(event, 28),
+                        temperature: read(event, 32),
+                        ..Default::default()
+                    }),
+                    SPECIAL_EVENT => {
+                        let source = match (data >> 1) & 0x7f {
+                            TIMESTAMP_RESET => {
+                                // the device clock restarts from zero
+                                self.time_offset = now();
+                                triggers_generated::TriggerSource::TimestampReset
+                            }
+                            EXTERNAL_INPUT_RISING_EDGE => {
+                                triggers_generated::TriggerSource::ExternalSignalRisingEdge
+                            }
+                            EXTERNAL_INPUT_FALLING_EDGE => {
+                                triggers_generated::TriggerSource::ExternalSignalFallingEdge
+                            }
+                            EXTERNAL_INPUT_PULSE => {
+                                triggers_generated::TriggerSource::ExternalSignalPulse
+                            }
+                            _ => continue,
+                        };
+                        triggers.push((t, source));
+                    }
+                    FRAME_EVENT if self.id_to_stream.contains_key(&1) => {
+                        if let Some(frame) = self.convert_frame(event, data, overflow) {
+                            frames.push(frame);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            free(packet as *mut c_void);
+        }
+        free(container as *mut c_void);
+        let mut packets = Vec::new();
+        if !events.is_empty() {
+            packets.push(Packet::from_events(0, &events));
+        }
+        packets.extend(frames);
+        if !imus.is_empty() {
+            packets.push(imu_packet(&imus));
+        }
+        if !triggers.is_empty() {
+            packets.push(trigger_packet(&triggers));
+        }
+        packets
+    }
+
+    /// Converts a grayscale frame to an 8-bit AEDAT4 frame.
+    ///
+    /// # Safety
+    /// `event` must point to a frame event of a libcaer packet.
+    unsafe fn convert_frame(&self, event: *const u8, info: u32, overflow: i64) -> Option<Packet> {
+        if (info >> 1) & 0x7 != 1 {
+            return None;
+        }
+        let timestamp = |offset| self.time_offset + (overflow | read::<i32>(event, offset) as i64);
+        let (begin_t, end_t) = (timestamp(4), timestamp(8));
+        let (exposure_begin_t, exposure_end_t) = (timestamp(12), timestamp(16));
+        let width = read::<i32>(event, 20).max(0);
+        let height = read::<i32>(event, 24).max(0);
+        let pixels: Vec<u8> = (0..(width * height) as usize)
+            .map(|index| (read::<u16>(event, 36 + index * 2) >> 8) as u8)
+            .collect();
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        let pixels = builder.create_vector(&pixels);
+        let root = frame_generated::Frame::create(
+            &mut builder,
+            &frame_generated::FrameArgs {
+                t: exposure_begin_t + (exposure_end_t - exposure_begin_t) / 2,
+                begin_t,
+                end_t,
+                exposure_begin_t,
+                exposure_end_t,
+                format: frame_generated::FrameFormat::Gray,
+                width: width as i16,
+                height: height as i16,
+                offset_x: read::<i32>(event, 28) as i16,
+                offset_y: read::<i32>(event, 32) as i16,
+                pixels: Some(pixels),
+            },
+        );
+        frame_generated::finish_size_prefixed_frame_buffer(&mut builder, root);
+        Some(Packet {
+            buffer: builder.finished_data().to_vec(),
+            stream_id: 1,
+            arrival_time: None,
+        })
+    }
+}
+
+fn imu_packet(samples: &[imus_generated::ImuArgs]) -> Packet {
+    let mut builder = flatbuffers::FlatBufferBuilder::new();
+    let samples: Vec<_> = samples
+        .iter()
+        .map(|sample| imus_generated::Imu::create(&mut builder, sample))
+        .collect();
+    let elements = builder.create_vector(&samples);
+    let root = imus_generated::ImuPacket::create(
+        &mut builder,
+        &imus_generated::ImuPacketArgs {
+            elements: Some(elements),
+        },
+    );
+    imus_generated::finish_size_prefixed_imu_packet_buffer(&mut builder, root);
+    Packet {
+        buffer: builder.finished_data().to_vec(),
+        stream_id: 2,
+        arrival_time: None,
+    }
+}
+
+fn trigger_packet(triggers: &[(i64, triggers_generated::TriggerSource)]) -> Packet {
+    let mut builder = flatbuffers::FlatBufferBuilder::new();
+    let triggers: Vec<_> = triggers
+        .iter()
+        .map(|(t, source)| {
+            triggers_generated::Trigger::create(
+                &mut builder,
+                &triggers_generated::TriggerArgs {
+                    t: *t,
+                    source: *source,
+                },
+            )
+        })
+        .collect();
+    let elements = builder.create_vector(&triggers);
+    let root = triggers_generated::TriggerPacket::create(
+        &mut builder,
+        &triggers_generated::TriggerPacketArgs {
+            elements: Some(elements),
+        },
+    );
+    triggers_generated::finish_size_prefixed_trigger_packet_buffer(&mut builder, root);
+    Packet {
+        buffer: builder.finished_data().to_vec(),
+        stream_id: 3,
+        arrival_time: None,
+    }
+}
+
+impl std::io::Read for DeviceSource {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.buffer.len() {
+            let container = unsafe { caerDeviceDataGet(self.device.handle) };
+            if container.is_null() {
+                if self.device.shutdown.load(Ordering::Acquire) {
+                    return Ok(0);
+                }
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                continue;
+            }
+            self.buffer.clear();
+            self.position = 0;
+            for packet in unsafe { self.convert(container) } {
+                let bytes = encode_packet(
+                    &self.id_to_stream,
+                    ioheader_generated::Compression::None,
+                    &DefaultBackend,
+                    &packet,
+                    false,
+                )
+                .map_err(std::io::Error::other)?;
+                self.buffer.extend_from_slice(&bytes);
+            }
+        }
+        let length = buffer.len().min(self.buffer.len() - self.position);
+        buffer[..length].copy_from_slice(&self.buffer[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+impl Source for DeviceSource {}
@@ -0,0 +1,160 @@
+//! In-memory source for packets generated by the application.
+//!
+//! `channel_decoder` returns a `PacketSender` and a `Decoder` connected by a channel. Packets sent by the
+//! sender are encoded (without compression) and decoded by the decoder, hence they go through the same
+//! checks and adapters as file and network packets. The decoder blocks until a packet is available, and ends
+//! when every sender has been dropped.
+
+use crate::base::{ioheader_generated, Decoder, Packet, ParseError, Source, Stream};
+use crate::compression::DefaultBackend;
+use crate::encoder::{encode_io_header, encode_packet};
+use crate::events::Event;
+use std::collections::HashMap;
+use std::sync::mpsc;
+
+/// Sending half of a loopback (see `channel_decoder`). Senders can be cloned to write from several threads.
+#[derive(Clone)]
+pub struct PacketSender {
+    id_to_stream: std::sync::Arc<HashMap<u32, Stream>>,
+    sender: mpsc::Sender<Vec<u8>>,
+}
+
+impl PacketSender {
+    /// Sends a packet to the decoder.
+    ///
+    /// Fails if the stream id is not declared, if the packet does not match its stream type, or if the decoder
+    /// was dropped.
+    pub fn send(&self, packet: &Packet) -> Result<(), ParseError> {
+        let bytes = encode_packet(
+            &self.id_to_stream,
+            ioheader_generated::Compression::None,
+            &DefaultBackend,
+            packet,
+            false,
+        )?;
+        self.sender.send(bytes).map_err(|_| {
+            ParseError::Io(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "the loopback decoder was dropped",
+            ))
+        })
+    }
+
+    /// Sends events as an EVTS packet.
+    pub fn send_events(&self, stream_id: u32, events: &[Event]) -> Result<(), ParseError> {
+        self.send(&Packet::from_events(stream_id, events))
+    }
+
+    pub fn id_to_stream(&self) -> &HashMap<u32, Stream> {
+        &self.id_to_stream
+    }
+}
+
+/// Creates a connected sender and decoder for the given streams.
+pub fn channel_decoder(
+    id_to_stream: HashMap<u32, Stream>,
+) -> Result<(PacketSender, Decoder), ParseError> {
+    if id_to_stream.is_empty() {
+        return Err(ParseError::NoStreams);
+    }
+    let (sender, receiver) = mpsc::channel();
+    // the header is queued before the decoder reads it
+    sender
+        .send(encode_io_header(
+            &id_to_stream,
+            ioheader_generated::Compression::None,
+            -1,
+            None,
+            None,
+        )?)
+        .expect("the receiver is alive");
+    let decoder = Decoder::new_from_source(
+        Box::new(ChannelSource {
+            receiver,
+            buffer: Vec::new(),
+            position: 0,
+        }),
+        false,
+        false,
+    )?;
+    Ok((
+        PacketSender {
+            id_to_stream: std::sync::Arc::new(id_to_stream),
+            sender,
+        },
+        decoder,
+    ))
+}
+
+struct ChannelSource {
+    receiver: mpsc::Receiver<Vec<u8>>,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl std::io::Read for ChannelSource {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.buffer.len() {
+            match self.receiver.recv() {
+                Ok(bytes) => {
+                    self.buffer = bytes;
+                    self.position = 0;
+                }
+                // every sender was dropped
+                Err(_) => return Ok(0),
+            }
+        }
+        let length = buffer.len().min(self.buffer.len() - self.position);
+        buffer[..length].copy_from_slice(&self.buffer[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+impl Source for ChannelSource {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::PacketIterator;
+    use crate::testing::Fixture;
+
+    #[test]
+    fn round_trip() {
+        let fixture = Fixture::new().frames(true).imus(true).triggers(true);
+        let (sender, decoder) = channel_decoder(fixture.streams()).unwrap();
+        let packets = fixture.packets();
+        let writer = std::thread::spawn(move || {
+            for packet in packets.iter() {
+                sender.send(packet).unwrap();
+            }
+        });
+        let decoded = decoder
+            .collect::<Result<Vec<Packet>, ParseError>>()
+            .unwrap();
+        writer.join().unwrap();
+        let key = |packet: &Packet| (packet.stream_id, packet.buffer.clone());
+        assert_eq!(
+            decoded.iter().map(key).collect::<Vec<_>>(),
+            fixture.packets().iter().map(key).collect::<Vec<_>>()
+        );
+        let events = decoded
+            .into_iter()
+            .map(Ok)
+            .events()
+            .collect::<Result<Vec<Event>, ParseError>>()
+            .unwrap();
+        assert_eq!(events, fixture.events());
+    }
+
+    #[test]
+    fn undeclared_stream() {
+        let fixture = Fixture::new();
+        let (sender, mut decoder) = channel_decoder(fixture.streams()).unwrap();
+        assert!(sender.send_events(5, &fixture.events()).is_err());
+        sender.send_events(0, &fixture.events()[..10]).unwrap();
+        drop(sender);
+        assert_eq!(decoder.next().unwrap().unwrap().stream_id, 0);
+        assert!(decoder.next().is_none());
+    }
+}
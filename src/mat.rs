@@ -0,0 +1,299 @@
+//! MATLAB (v7.3) export.
+//!
+//! The file contains four structs with column vectors, named after the fields of the AedatTools importers:
+//! - `events`: `timeStamp`, `x`, `y`, `polarity`
+//! - `frames`: `timeStampStart`, `timeStampEnd`, `timeStampExposureStart`, `timeStampExposureEnd`,
+//!   and `samples`, a height × width × frames (Gray) or height × width × 3 × frames (BGR) array
+//! - `imu`: `timeStamp`, `temperature`, `accelX`, `accelY`, `accelZ`, `gyroX`, `gyroY`, `gyroZ`,
+//!   `magX`, `magY`, `magZ`
+//! - `triggers`: `timeStamp`, `source`
+//!
+//! MAT v7.3 files are HDF5 files with a 512 bytes header, they can be loaded with `load('recording.mat')`.
+
+use crate::base::{Decoder, ParseError, StreamContent};
+use crate::{frame_generated, imus_generated, triggers_generated};
+use hdf5::types::FixedAscii;
+use std::io::{Seek, Write};
+
+/// Writes the packets of a decoder to a MAT file.
+///
+/// All the frames must have the same size and format. BGRA frames are stored without the alpha channel.
+/// Packets from streams of unknown type are skipped.
+pub fn export<P: std::convert::AsRef<std::path::Path>>(
+    decoder: Decoder,
+    path: P,
+) -> Result<(), ParseError> {
+    let id_to_stream = decoder.id_to_stream.clone();
+    let mut events = Events::default();
+    let mut frames = Frames::default();
+    let mut imu = Imu::default();
+    let mut triggers = Triggers::default();
+    for packet in decoder {
+        let packet = packet?;
+        match id_to_stream[&packet.stream_id].content {
+            StreamContent::Events => {
+                for event in packet.events()? {
+                    events.t.push(event.t());
+                    events.x.push(event.x() as u16);
+                    events.y.push(event.y() as u16);
+                    events.polarity.push(event.on() as u8);
+                }
+            }
+            StreamContent::Frame => frames.push(packet.frame()?)?,
+            StreamContent::Imus => {
+                if let Some(elements) =
+                    imus_generated::size_prefixed_root_as_imu_packet(&packet.buffer)?.elements()
+                {
+                    for element in elements.iter() {
+                        imu.t.push(element.t());
+                        imu.values[0].push(element.temperature());
+                        imu.values[1].push(element.accelerometer_x());
+                        imu.values[2].push(element.accelerometer_y());
+                        imu.values[3].push(element.accelerometer_z());
+                        imu.values[4].push(element.gyroscope_x());
+                        imu.values[5].push(element.gyroscope_y());
+                        imu.values[6].push(element.gyroscope_z());
+                        imu.values[7].push(element.magnetometer_x());
+                        imu.values[8].push(element.magnetometer_y());
+                        imu.values[9].push(element.magnetometer_z());
+                    }
+                }
+            }
+            StreamContent::Triggers => {
+                if let Some(elements) =
+                    triggers_generated::size_prefixed_root_as_trigger_packet(&packet.buffer)?
+                        .elements()
+                {
+                    for element in elements.iter() {
+                        triggers.t.push(element.t());
+                        triggers.source.push(element.source().0 as u8);
+                    }
+                }
+            }
+            StreamContent::BoundingBoxes | StreamContent::Pose | StreamContent::Other(_) => {}
+        }
+    }
+
+    {
+        let file = hdf5::File::with_options()
+            .with_fcpl(|properties| properties.userblock(512))
+            .create(path.as_ref())?;
+
+        let group = create_struct(&file, "events")?;
+        write_vector(&group, "timeStamp", "int64", &events.t)?;
+        write_vector(&group, "x", "uint16", &events.x)?;
+        write_vector(&group, "y", "uint16", &events.y)?;
+        write_vector(&group, "polarity", "logical", &events.polarity)?;
+
+        let group = create_struct(&file, "frames")?;
+        write_vector(&group, "timeStampStart", "int64", &frames.begin_t)?;
+        write_vector(&group, "timeStampEnd", "int64", &frames.end_t)?;
+        write_vector(
+            &group,
+            "timeStampExposureStart",
+            "int64",
+            &frames.exposure_begin_t,
+        )?;
+        write_vector(
+            &group,
+            "timeStampExposureEnd",
+            "int64",
+            &frames.exposure_end_t,
+        )?;
+        let count = frames.begin_t.len();
+        let shape = match frames.channels {
+            0 | 1 => vec![frames.height, frames.width, count],
+            channels => vec![frames.height, frames.width, channels, count],
+        };
+        write_array(&group, "samples", "uint8", &shape, &frames.samples)?;
+
+        let group = create_struct(&file, "imu")?;
+        write_vector(&group, "timeStamp", "int64", &imu.t)?;
+        for (name, values) in [
+            "temperature",
+            "accelX",
+            "accelY",
+            "accelZ",
+            "gyroX",
+            "gyroY",
+            "gyroZ",
+            "magX",
+            "magY",
+            "magZ",
+        ]
+        .iter()
+        .zip(imu.values.iter())
+        {
+            write_vector(&group, name, "single", values)?;
+        }
+
+        let group = create_struct(&file, "triggers")?;
+        write_vector(&group, "timeStamp", "int64", &triggers.t)?;
+        write_vector(&group, "source", "uint8", &triggers.source)?;
+    }
+
+    let mut header = format!(
+        "MATLAB 7.3 MAT-file, Platform: {}, Created by: aedat {} HDF5 schema 1.00 .",
+        std::env::consts::OS,
+        env!("CARGO_PKG_VERSION")
+    )
+    .into_bytes();
+    header.resize(116, b' ');
+    header.extend_from_slice(&[0; 8]);
+    header.extend_from_slice(&[0x00, 0x02]);
+    header.extend_from_slice(b"IM");
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    file.seek(std::io::SeekFrom::Start(0))?;
+    file.write_all(&header)?;
+    Ok(())
+}
+
+#[derive(Default)]
+struct Events {
+    t: Vec<i64>,
+    x: Vec<u16>,
+    y: Vec<u16>,
+    polarity: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Frames {
+    begin_t: Vec<i64>,
+    end_t: Vec<i64>,
+    exposure_begin_t: Vec<i64>,
+    exposure_end_t: Vec<i64>,
+    width: usize,
+    height: usize,
+    channels: usize,
+    /// Column-major (MATLAB order).
+    samples: Vec<u8>,
+}
+
+impl Frames {
+    fn push(&mut self, frame: frame_generated::Frame<'_>) -> Result<(), ParseError> {
+        let (input_channels, channels) = match frame.format() {
+            frame_generated::FrameFormat::Gray => (1, 1),
+            frame_generated::FrameFormat::Bgr => (3, 3),
+            frame_generated::FrameFormat::Bgra => (4, 3),
+            format => {
+                return Err(ParseError::General(format!(
+                    "unsupported frame format {}",
+                    format.0
+                )))
+            }
+        };
+        let (width, height) = (frame.width() as usize, frame.height() as usize);
+        if self.begin_t.is_empty() {
+            self.width = width;
+            self.height = height;
+            self.channels = channels;
+        } else if (self.width, self.height, self.channels) != (width, height, channels) {
+            return Err(ParseError::General(format!(
+                "the frames have different sizes or formats ({}x{}x{} and {}x{}x{})",
+                self.width, self.height, self.channels, width, height, channels
+            )));
+        }
+        let pixels = frame.pixels().unwrap_or(&[]);
+        if pixels.len() != width * height * input_channels {
+            return Err(ParseError::General(format!(
+                "the frame has {} bytes instead of {}",
+                pixels.len(),
+                width * height * input_channels
+            )));
+        }
+        self.begin_t.push(frame.begin_t());
+        self.end_t.push(frame.end_t());
+        self.exposure_begin_t.push(frame.exposure_begin_t());
+        self.exposure_end_t.push(frame.exposure_end_t());
+        // MATLAB stores images as RGB (the input is BGR)
+        for channel in 0..channels {
+            let input_channel = if channels == 3 { 2 - channel } else { 0 };
+            for x in 0..width {
+                for y in 0..height {
+                    self.samples
+                        .push(pixels[(y * width + x) * input_channels + input_channel]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Imu {
+    t: Vec<i64>,
+    values: [Vec<f32>; 10],
+}
+
+#[derive(Default)]
+struct Triggers {
+    t: Vec<i64>,
+    source: Vec<u8>,
+}
+
+fn write_class(location: &hdf5::Location, class: &str) -> Result<(), ParseError> {
+    location
+        .new_attr::<FixedAscii<16>>()
+        .shape(())
+        .create("MATLAB_class")?
+        .write_scalar(
+            &FixedAscii::<16>::from_ascii(class.as_bytes())
+                .map_err(|error| ParseError::General(error.to_string()))?,
+        )?;
+    Ok(())
+}
+
+fn create_struct(file: &hdf5::File, name: &str) -> Result<hdf5::Group, ParseError> {
+    let group = file.create_group(name)?;
+    write_class(&group, "struct")?;
+    Ok(group)
+}
+
+fn write_vector<T: hdf5::H5Type>(
+    group: &hdf5::Group,
+    name: &str,
+    class: &str,
+    data: &[T],
+) -> Result<(), ParseError> {
+    write_array(group, name, class, &[data.len(), 1], data)
+}
+
+/// Writes a column-major array, `shape` is in MATLAB order.
+fn write_array<T: hdf5::H5Type>(
+    group: &hdf5::Group,
+    name: &str,
+    class: &str,
+    shape: &[usize],
+    data: &[T],
+) -> Result<(), ParseError> {
+    let dataset = if data.is_empty() {
+        // MATLAB stores empty arrays as their dimensions
+        let dataset = group
+            .new_dataset::<u64>()
+            .shape([shape.len()])
+            .create(name)?;
+        dataset.write_raw(&shape.iter().map(|size| *size as u64).collect::<Vec<u64>>())?;
+        dataset
+            .new_attr::<u8>()
+            .shape(())
+            .create("MATLAB_empty")?
+            .write_scalar(&1u8)?;
+        dataset
+    } else {
+        let dataset = group
+            .new_dataset::<T>()
+            .shape(shape.iter().rev().copied().collect::<Vec<usize>>())
+            .create(name)?;
+        dataset.write_raw(data)?;
+        dataset
+    };
+    write_class(&dataset, class)?;
+    if class == "logical" {
+        dataset
+            .new_attr::<i32>()
+            .shape(())
+            .create("MATLAB_int_decode")?
+            .write_scalar(&1i32)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use crate::base::{ioheader_generated, Decoder, DecoderBuilder, Packet, ParseError};
+use crate::encoder::Encoder;
+
+/// A recording to merge, with its synchronisation offset.
+pub struct MergeInput {
+    path: std::path::PathBuf,
+    timestamp_offset: i64,
+    stream_ids: std::collections::HashMap<u32, u32>,
+}
+
+impl MergeInput {
+    pub fn new<P: std::convert::AsRef<std::path::Path>>(path: P) -> Self {
+        MergeInput {
+            path: path.as_ref().to_path_buf(),
+            timestamp_offset: 0,
+            stream_ids: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Offset (in microseconds) added to the timestamps of this input.
+    pub fn timestamp_offset(mut self, timestamp_offset: i64) -> Self {
+        self.timestamp_offset = timestamp_offset;
+        self
+    }
+
+    /// Writes the stream `from` of this input as the stream `to` of the output.
+    ///
+    /// Streams without an explicit id are assigned the lowest free ids, in input order.
+    pub fn stream_id(mut self, from: u32, to: u32) -> Self {
+        self.stream_ids.insert(from, to);
+        self
+    }
+}
+
+/// Interleaves the packets of several recordings (for instance two cameras recorded separately) into one file.
+///
+/// Packets are written in the order of their first timestamp, after applying the input offsets.
+/// Returns the output stream id of each input stream.
+pub fn merge<P: std::convert::AsRef<std::path::Path>>(
+    inputs: &[MergeInput],
+    output: P,
+    compression: ioheader_generated::Compression,
+) -> Result<Vec<std::collections::HashMap<u32, u32>>, ParseError> {
+    if inputs.is_empty() {
+        return Err(ParseError::NoFiles);
+    }
+    let mut decoders = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        decoders.push(
+            DecoderBuilder::file(&input.path)
+                .timestamp_offset(input.timestamp_offset)
+                .build()?,
+        );
+    }
+    let mut used: std::collections::HashSet<u32> = std::collections::HashSet::new();
+    for input in inputs {
+        for to in input.stream_ids.values() {
+            if !used.insert(*to) {
+                return Err(ParseError::DuplicatedStreamId(*to));
+            }
+        }
+    }
+    let mut mappings = Vec::with_capacity(inputs.len());
+    let mut id_to_stream = std::collections::HashMap::new();
+    let mut next_id = 0;
+    for (input, decoder) in inputs.iter().zip(decoders.iter()) {
+        let mut ids: Vec<u32> = decoder.id_to_stream.keys().copied().collect();
+        ids.sort();
+        let mut mapping = std::collections::HashMap::new();
+        for id in ids {
+            let to = match input.stream_ids.get(&id) {
+                Some(to) => *to,
+                None => {
+                    while used.contains(&next_id) {
+                        next_id += 1;
+                    }
+                    used.insert(next_id);
+                    next_id
+                }
+            };
+            mapping.insert(id, to);
+            id_to_stream.insert(to, decoder.id_to_stream[&id].clone());
+        }
+        mappings.push(mapping);
+    }
+    let mut encoder = Encoder::new_to_file(output, id_to_stream, compression)?;
+    let mut heads: Vec<Option<(i64, Packet)>> = Vec::with_capacity(decoders.len());
+    for decoder in decoders.iter_mut() {
+        heads.push(next_packet(decoder)?);
+    }
+    while let Some((_, index)) = heads
+        .iter()
+        .enumerate()
+        .filter_map(|(index, head)| head.as_ref().map(|(t, _)| (*t, index)))
+        .min()
+    {
+        let (_, mut packet) = heads[index].take().unwrap();
+        packet.stream_id = mappings[index][&packet.stream_id];
+        encoder.write(&packet)?;
+        heads[index] = next_packet(&mut decoders[index])?;
+    }
+    encoder.finish()?;
+    Ok(mappings)
+}
+
+/// Returns the next packet of a decoder and its first timestamp.
+fn next_packet(decoder: &mut Decoder) -> Result<Option<(i64, Packet)>, ParseError> {
+    match decoder.next() {
+        Some(packet) => {
+            let packet = packet?;
+            let (elements, timestamp_start, _) =
+                packet.summary(&decoder.id_to_stream[&packet.stream_id].content)?;
+            Ok(Some((
+                if elements == 0 {
+                    i64::MIN
+                } else {
+                    timestamp_start
+                },
+                packet,
+            )))
+        }
+        None => Ok(None),
+    }
+}
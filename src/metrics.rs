@@ -0,0 +1,48 @@
+//! Counters and histograms emitted through the `metrics` facade.
+//!
+//! The decoder records the metrics below when the `metrics` feature is enabled. They go nowhere until the
+//! application installs a recorder, for instance `metrics-exporter-prometheus`. Throughputs (events per second,
+//! bytes per second) are the rates of the counters.
+
+use crate::base::{Packet, ParseError, StreamContent};
+
+/// Packets decoded, labelled by stream content (`content="EVTS"`...).
+pub const PACKETS_DECODED: &str = "aedat_packets_decoded_total";
+
+/// Bytes read from the source, including packet headers.
+pub const BYTES_READ: &str = "aedat_bytes_read_total";
+
+/// Events decoded.
+pub const EVENTS_DECODED: &str = "aedat_events_decoded_total";
+
+/// Duration (in seconds) of the decompression of each packet.
+pub const DECOMPRESSION_SECONDS: &str = "aedat_decompression_seconds";
+
+/// Errors, labelled by `ParseError` variant (`kind="UnknownStreamId"`...). Includes skipped packets.
+pub const ERRORS: &str = "aedat_errors_total";
+
+pub(crate) fn record_read(length: u32, decompression: std::time::Duration) {
+    ::metrics::counter!(BYTES_READ).increment(8 + length as u64);
+    ::metrics::histogram!(DECOMPRESSION_SECONDS).record(decompression.as_secs_f64());
+}
+
+pub(crate) fn record_packet(packet: &Packet, content: &StreamContent) {
+    ::metrics::counter!(PACKETS_DECODED, "content" => content.to_string()).increment(1);
+    if *content == StreamContent::Events {
+        if let Some(elements) =
+            crate::events_generated::size_prefixed_root_as_event_packet(&packet.buffer)
+                .ok()
+                .and_then(|events| events.elements())
+        {
+            ::metrics::counter!(EVENTS_DECODED).increment(elements.len() as u64);
+        }
+    }
+}
+
+pub(crate) fn record_error(error: &ParseError) {
+    let kind: String = format!("{:?}", error)
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect();
+    ::metrics::counter!(ERRORS, "kind" => kind).increment(1);
+}
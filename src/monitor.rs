@@ -0,0 +1,168 @@
+//! Callbacks on activity, triggers and frames, for monitoring daemons.
+//!
+//! Callbacks are registered with the builder methods of `Monitor`, then `Monitor::run` consumes a decoder
+//! (or any packet iterator), which replaces the read loop of the application.
+
+use crate::base::{Packet, ParseError};
+use crate::events::Event;
+use crate::frames::Frame;
+use crate::ordered::Trigger;
+use crate::{events_generated, frame_generated, triggers_generated};
+
+/// Events of a window whose rate exceeded the threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventWindow {
+    pub begin_t: i64,
+    pub end_t: i64,
+    /// Events that passed the polarity filter.
+    pub events: Vec<Event>,
+    /// Events per second in the window.
+    pub rate: f64,
+}
+
+struct RateCallback<'a> {
+    rate: f64,
+    window: i64,
+    polarity: Option<bool>,
+    begin_t: Option<i64>,
+    events: Vec<Event>,
+    callback: Box<dyn FnMut(&EventWindow) + 'a>,
+}
+
+impl RateCallback<'_> {
+    fn add(&mut self, event: Event) {
+        if self.polarity.is_some_and(|on| on != event.on()) {
+            return;
+        }
+        let begin_t = *self.begin_t.get_or_insert(event.t());
+        if event.t() >= begin_t + self.window {
+            self.complete(begin_t);
+            // windows are aligned on the first event, and empty windows are skipped
+            self.begin_t = Some(begin_t + (event.t() - begin_t) / self.window * self.window);
+        }
+        self.events.push(event);
+    }
+
+    fn complete(&mut self, begin_t: i64) {
+        let rate = self.events.len() as f64 * 1e6 / self.window as f64;
+        if rate > self.rate {
+            (self.callback)(&EventWindow {
+                begin_t,
+                end_t: begin_t + self.window,
+                events: std::mem::take(&mut self.events),
+                rate,
+            });
+        }
+        self.events.clear();
+    }
+}
+
+type TriggerCallback<'a> = Box<dyn FnMut(&Trigger) + 'a>;
+type FrameCallback<'a> = Box<dyn FnMut(&Frame<'_>) + 'a>;
+
+/// Dispatches the packets of a decoder to callbacks (see the module documentation).
+#[derive(Default)]
+pub struct Monitor<'a> {
+    rates: Vec<RateCallback<'a>>,
+    triggers: Vec<TriggerCallback<'a>>,
+    frames: Vec<FrameCallback<'a>>,
+}
+
+impl<'a> Monitor<'a> {
+    pub fn new() -> Self {
+        Monitor::default()
+    }
+
+    /// Calls `callback` for every window of `window` microseconds with more than `rate` events per second.
+    ///
+    /// Windows are consecutive (they do not overlap). If `polarity` is set, only ON (true) or OFF (false)
+    /// events are counted.
+    pub fn on_event_rate_above(
+        mut self,
+        rate: f64,
+        window: i64,
+        polarity: Option<bool>,
+        callback: impl FnMut(&EventWindow) + 'a,
+    ) -> Self {
+        self.rates.push(RateCallback {
+            rate,
+            window: window.max(1),
+            polarity,
+            begin_t: None,
+            events: Vec::new(),
+            callback: Box::new(callback),
+        });
+        self
+    }
+
+    /// Calls `callback` for every trigger.
+    pub fn on_trigger(mut self, callback: impl FnMut(&Trigger) + 'a) -> Self {
+        self.triggers.push(Box::new(callback));
+        self
+    }
+
+    /// Calls `callback` for every APS frame.
+    pub fn on_frame(mut self, callback: impl FnMut(&Frame<'_>) + 'a) -> Self {
+        self.frames.push(Box::new(callback));
+        self
+    }
+
+    /// Dispatches a packet. Packets are recognized by their identifier, hence any stream is supported.
+    pub fn process(&mut self, packet: &Packet) -> Result<(), ParseError> {
+        if packet.has_identifier(events_generated::EVENT_PACKET_IDENTIFIER) {
+            if !self.rates.is_empty() {
+                for event in packet.events()? {
+                    for rate in self.rates.iter_mut() {
+                        rate.add(event);
+                    }
+                }
+            }
+        } else if packet.has_identifier(triggers_generated::TRIGGER_PACKET_IDENTIFIER) {
+            if !self.triggers.is_empty() {
+                if let Some(elements) =
+                    triggers_generated::size_prefixed_root_as_trigger_packet(&packet.buffer)?
+                        .elements()
+                {
+                    for trigger in elements.iter() {
+                        let trigger = Trigger {
+                            t: trigger.t(),
+                            source: trigger.source(),
+                        };
+                        for callback in self.triggers.iter_mut() {
+                            callback(&trigger);
+                        }
+                    }
+                }
+            }
+        } else if packet.has_identifier(frame_generated::FRAME_IDENTIFIER)
+            && !self.frames.is_empty()
+        {
+            let frame = packet.frame()?;
+            for callback in self.frames.iter_mut() {
+                callback(&frame);
+            }
+        }
+        Ok(())
+    }
+
+    /// Dispatches the packets until the source ends, then completes the last rate windows.
+    pub fn run<I: IntoIterator<Item = Result<Packet, ParseError>>>(
+        mut self,
+        packets: I,
+    ) -> Result<(), ParseError> {
+        for packet in packets {
+            self.process(&packet?)?;
+        }
+        self.finish();
+        Ok(())
+    }
+
+    /// Completes the last rate windows (which may be shorter than their duration).
+    pub fn finish(&mut self) {
+        for rate in self.rates.iter_mut() {
+            if let Some(begin_t) = rate.begin_t.take() {
+                rate.complete(begin_t);
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Neurodata Without Borders (NWB 2) export.
+//!
+//! Events are written to a single `events` table in `acquisition` (an hdmf-common `DynamicTable`),
+//! with `timestamp` (seconds since the first event), `x`, `y` and `polarity` columns.
+//! The columns are resizable datasets, filled chunk by chunk, hence recordings need not fit in memory.
+
+use crate::base::ParseError;
+use crate::chunks::Chunks;
+use crate::events::Event;
+use hdf5::types::VarLenUnicode;
+use std::hash::{BuildHasher, Hasher};
+
+const NWB_VERSION: &str = "2.7.0";
+
+/// Number of events written at once, and HDF5 chunk size of the columns.
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// Writes an event stream (for instance `Events`) to an NWB file.
+///
+/// The session starts at the first event (AEDAT4 timestamps are microseconds since the Unix epoch).
+/// Returns the number of events.
+pub fn export<I, P>(mut events: I, path: P, session_description: &str) -> Result<usize, ParseError>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+    P: std::convert::AsRef<std::path::Path>,
+{
+    let first = events.next().transpose()?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_micros() as i64);
+    let first_t = first.as_ref().map_or(now, |event| event.t());
+    let session_start_time = iso8601(first_t);
+
+    let file = hdf5::File::create(path)?;
+    write_string_attribute(&file, "namespace", "core")?;
+    write_string_attribute(&file, "neurodata_type", "NWBFile")?;
+    write_string_attribute(&file, "nwb_version", NWB_VERSION)?;
+    write_string_attribute(&file, "object_id", &object_id())?;
+    file.new_dataset_builder()
+        .with_data(&[string(&iso8601(now))?][..])
+        .create("file_create_date")?;
+    write_string_dataset(&file, "identifier", &object_id())?;
+    write_string_dataset(&file, "session_description", session_description)?;
+    write_string_dataset(&file, "session_start_time", &session_start_time)?;
+    write_string_dataset(&file, "timestamps_reference_time", &session_start_time)?;
+    for name in ["analysis", "general", "processing"] {
+        file.create_group(name)?;
+    }
+    let stimulus = file.create_group("stimulus")?;
+    stimulus.create_group("presentation")?;
+    stimulus.create_group("templates")?;
+
+    let table = file.create_group("acquisition")?.create_group("events")?;
+    write_string_attribute(&table, "namespace", "hdmf-common")?;
+    write_string_attribute(&table, "neurodata_type", "DynamicTable")?;
+    write_string_attribute(&table, "object_id", &object_id())?;
+    write_string_attribute(&table, "description", "DVS events, one row per event")?;
+    table
+        .new_attr_builder()
+        .with_data(
+            &[
+                string("timestamp")?,
+                string("x")?,
+                string("y")?,
+                string("polarity")?,
+            ][..],
+        )
+        .create("colnames")?;
+    let id = create_column::<i64>(&table, "id", "ElementIdentifiers", None)?;
+    let timestamps = create_column::<f64>(
+        &table,
+        "timestamp",
+        "VectorData",
+        Some("event times in seconds"),
+    )?;
+    timestamps
+        .new_attr::<f64>()
+        .shape(())
+        .create("resolution")?
+        .write_scalar(&1e-6)?;
+    let xs = create_column::<i16>(&table, "x", "VectorData", Some("pixel column"))?;
+    let ys = create_column::<i16>(&table, "y", "VectorData", Some("pixel row"))?;
+    let polarities = create_column::<u8>(
+        &table,
+        "polarity",
+        "VectorData",
+        Some("1 for ON events, 0 for OFF events"),
+    )?;
+
+    let mut count = 0;
+    for chunk in Chunks::by_count(first.map(Ok).into_iter().chain(events), CHUNK_SIZE) {
+        let chunk = chunk?;
+        append(
+            &id,
+            &(count as i64..(count + chunk.len()) as i64).collect::<Vec<i64>>(),
+        )?;
+        append(
+            &timestamps,
+            &chunk
+                .iter()
+                .map(|event| (event.t() - first_t) as f64 / 1e6)
+                .collect::<Vec<f64>>(),
+        )?;
+        append(
+            &xs,
+            &chunk.iter().map(|event| event.x()).collect::<Vec<i16>>(),
+        )?;
+        append(
+            &ys,
+            &chunk.iter().map(|event| event.y()).collect::<Vec<i16>>(),
+        )?;
+        append(
+            &polarities,
+            &chunk
+                .iter()
+                .map(|event| event.on() as u8)
+                .collect::<Vec<u8>>(),
+        )?;
+        count += chunk.len();
+    }
+    Ok(count)
+}
+
+fn string(value: &str) -> Result<VarLenUnicode, ParseError> {
+    value
+        .parse::<VarLenUnicode>()
+        .map_err(|error| ParseError::General(error.to_string()))
+}
+
+fn write_string_attribute(
+    location: &hdf5::Location,
+    name: &str,
+    value: &str,
+) -> Result<(), ParseError> {
+    location
+        .new_attr::<VarLenUnicode>()
+        .shape(())
+        .create(name)?
+        .write_scalar(&string(value)?)?;
+    Ok(())
+}
+
+fn write_string_dataset(group: &hdf5::Group, name: &str, value: &str) -> Result<(), ParseError> {
+    group
+        .new_dataset::<VarLenUnicode>()
+        .shape(())
+        .create(name)?
+        .write_scalar(&string(value)?)?;
+    Ok(())
+}
+
+/// Creates an empty resizable column of a `DynamicTable`, filled by `append`.
+fn create_column<T: hdf5::H5Type>(
+    table: &hdf5::Group,
+    name: &str,
+    neurodata_type: &str,
+    description: Option<&str>,
+) -> Result<hdf5::Dataset, ParseError> {
+    let dataset = table
+        .new_dataset::<T>()
+        .chunk(CHUNK_SIZE)
+        .shape(0..)
+        .create(name)?;
+    write_string_attribute(&dataset, "namespace", "hdmf-common")?;
+    write_string_attribute(&dataset, "neurodata_type", neurodata_type)?;
+    write_string_attribute(&dataset, "object_id", &object_id())?;
+    if let Some(description) = description {
+        write_string_attribute(&dataset, "description", description)?;
+    }
+    Ok(dataset)
+}
+
+fn append<T: hdf5::H5Type>(dataset: &hdf5::Dataset, data: &[T]) -> Result<(), ParseError> {
+    let start = dataset.size();
+    dataset.resize(start + data.len())?;
+    dataset.write_slice(data, start..start + data.len())?;
+    Ok(())
+}
+
+/// Returns a random (version 4) UUID.
+fn object_id() -> String {
+    let state = std::collections::hash_map::RandomState::new();
+    let mut bytes = [0u8; 16];
+    for (index, chunk) in bytes.chunks_mut(8).enumerate() {
+        let mut hasher = state.build_hasher();
+        hasher.write_usize(index);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Formats a Unix timestamp in microseconds as an ISO 8601 UTC date.
+fn iso8601(t: i64) -> String {
+    let days = t.div_euclid(86_400_000_000);
+    let microseconds = t.rem_euclid(86_400_000_000);
+    // civil_from_days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}+00:00",
+        year,
+        month,
+        day,
+        microseconds / 3_600_000_000,
+        (microseconds / 60_000_000) % 60,
+        (microseconds / 1_000_000) % 60,
+        microseconds % 1_000_000
+    )
+}
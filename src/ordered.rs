@@ -0,0 +1,276 @@
+use crate::annotations::{BoundingBox, Pose};
+use crate::base::{Decoder, Packet, ParseError, StreamContent};
+use crate::events::Event;
+use crate::{imus_generated, triggers_generated};
+
+/// An IMU sample, with accelerations in g, angular velocities in °/s and magnetic fields in µT.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Imu {
+    pub t: i64,
+    pub temperature: f32,
+    pub accelerometer: [f32; 3],
+    pub gyroscope: [f32; 3],
+    pub magnetometer: [f32; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trigger {
+    pub t: i64,
+    pub source: triggers_generated::TriggerSource,
+}
+
+/// An element of any stream.
+#[derive(Debug, Clone)]
+pub enum Item {
+    Event(Event),
+    /// A FRME packet (see `Packet::frame`).
+    Frame(Packet),
+    Imu(Imu),
+    Trigger(Trigger),
+    BoundingBox(BoundingBox),
+    Pose(Pose),
+    /// A packet of a stream unknown to this library, ordered by its position in the file.
+    Other(Packet),
+}
+
+impl Decoder {
+    /// Interleaves the packets of all the streams in the order of their first timestamp, instead of file order.
+    ///
+    /// Writers interleave streams with some delay (for instance, frames are written after their exposure),
+    /// hence packets are buffered until the decoder has read `TimeOrdered::lookahead` microseconds further
+    /// (one second by default).
+    pub fn iter_time_ordered(self) -> TimeOrdered {
+        TimeOrdered {
+            decoder: self,
+            pending: std::collections::BinaryHeap::new(),
+            lookahead: 1_000_000,
+            frontier: i64::MIN,
+            index: 0,
+            done: false,
+        }
+    }
+}
+
+/// Sorts by timestamp, then by reading order.
+struct Pending<T> {
+    t: i64,
+    index: u64,
+    value: T,
+}
+
+impl<T> PartialEq for Pending<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.t, self.index) == (other.t, other.index)
+    }
+}
+
+impl<T> Eq for Pending<T> {}
+
+impl<T> PartialOrd for Pending<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Pending<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (other.t, other.index).cmp(&(self.t, self.index))
+    }
+}
+
+/// See `Decoder::iter_time_ordered`.
+pub struct TimeOrdered {
+    decoder: Decoder,
+    pending: std::collections::BinaryHeap<Pending<Packet>>,
+    lookahead: i64,
+    frontier: i64,
+    index: u64,
+    done: bool,
+}
+
+impl TimeOrdered {
+    /// Delay (in microseconds) between the latest timestamp read and the packets returned.
+    pub fn lookahead(mut self, lookahead: i64) -> Self {
+        self.lookahead = lookahead;
+        self
+    }
+
+    /// Splits the packets into their elements, in timestamp order.
+    pub fn items(self) -> TimeOrderedItems {
+        TimeOrderedItems {
+            packets: self,
+            pending: std::collections::BinaryHeap::new(),
+            horizon: i64::MIN,
+            index: 0,
+        }
+    }
+
+    /// Returns the next packet and its sorting timestamp.
+    fn next_pending(&mut self) -> Option<Result<(i64, Packet), ParseError>> {
+        loop {
+            if let Some(head) = self.pending.peek() {
+                if self.done || head.t <= self.frontier.saturating_sub(self.lookahead) {
+                    let head = self.pending.pop().unwrap();
+                    return Some(Ok((head.t, head.value)));
+                }
+            } else if self.done {
+                return None;
+            }
+            match self.decoder.next() {
+                Some(Ok(packet)) => {
+                    let content =
+                        packet.content(&self.decoder.id_to_stream[&packet.stream_id].content);
+                    let (elements, begin_t, end_t) = match packet.summary(&content) {
+                        Ok(summary) => summary,
+                        Err(error) => return Some(Err(error)),
+                    };
+                    // empty and unknown packets keep their position relative to their neighbours
+                    let t = if elements == 0 {
+                        self.frontier
+                    } else {
+                        self.frontier = self.frontier.max(end_t);
+                        begin_t
+                    };
+                    self.pending.push(Pending {
+                        t,
+                        index: self.index,
+                        value: packet,
+                    });
+                    self.index += 1;
+                }
+                Some(Err(error)) => return Some(Err(error)),
+                None => self.done = true,
+            }
+        }
+    }
+}
+
+impl Iterator for TimeOrdered {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_pending()
+            .map(|result| result.map(|(_, packet)| packet))
+    }
+}
+
+/// See `TimeOrdered::items`. Yields the stream id and the element.
+pub struct TimeOrderedItems {
+    packets: TimeOrdered,
+    pending: std::collections::BinaryHeap<Pending<(u32, Item)>>,
+    horizon: i64,
+    index: u64,
+}
+
+impl TimeOrderedItems {
+    fn push(&mut self, t: i64, stream_id: u32, item: Item) {
+        self.pending.push(Pending {
+            t,
+            index: self.index,
+            value: (stream_id, item),
+        });
+        self.index += 1;
+    }
+
+    fn split(&mut self, t: i64, packet: Packet) -> Result<(), ParseError> {
+        let stream_id = packet.stream_id;
+        match packet.content(&self.packets.decoder.id_to_stream[&stream_id].content) {
+            StreamContent::Events => {
+                for event in packet.events()? {
+                    self.push(event.t(), stream_id, Item::Event(event));
+                }
+            }
+            StreamContent::Frame => {
+                let frame_t = packet.frame()?.t();
+                self.push(frame_t, stream_id, Item::Frame(packet));
+            }
+            StreamContent::Imus => {
+                if let Some(elements) =
+                    imus_generated::size_prefixed_root_as_imu_packet(&packet.buffer)?.elements()
+                {
+                    for imu in elements.iter() {
+                        self.push(
+                            imu.t(),
+                            stream_id,
+                            Item::Imu(Imu {
+                                t: imu.t(),
+                                temperature: imu.temperature(),
+                                accelerometer: [
+                                    imu.accelerometer_x(),
+                                    imu.accelerometer_y(),
+                                    imu.accelerometer_z(),
+                                ],
+                                gyroscope: [
+                                    imu.gyroscope_x(),
+                                    imu.gyroscope_y(),
+                                    imu.gyroscope_z(),
+                                ],
+                                magnetometer: [
+                                    imu.magnetometer_x(),
+                                    imu.magnetometer_y(),
+                                    imu.magnetometer_z(),
+                                ],
+                            }),
+                        );
+                    }
+                }
+            }
+            StreamContent::Triggers => {
+                if let Some(elements) =
+                    triggers_generated::size_prefixed_root_as_trigger_packet(&packet.buffer)?
+                        .elements()
+                {
+                    for trigger in elements.iter() {
+                        self.push(
+                            trigger.t(),
+                            stream_id,
+                            Item::Trigger(Trigger {
+                                t: trigger.t(),
+                                source: trigger.source(),
+                            }),
+                        );
+                    }
+                }
+            }
+            StreamContent::BoundingBoxes => {
+                for bounding_box in packet.bounding_boxes()? {
+                    self.push(bounding_box.t, stream_id, Item::BoundingBox(bounding_box));
+                }
+            }
+            StreamContent::Pose => {
+                let pose = packet.pose()?;
+                self.push(pose.t, stream_id, Item::Pose(pose));
+            }
+            StreamContent::Other(_) => self.push(t, stream_id, Item::Other(packet)),
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for TimeOrderedItems {
+    type Item = Result<(u32, Item), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // packets come in order of their first timestamp, hence elements before the last one are final
+            if let Some(head) = self.pending.peek() {
+                if head.t < self.horizon {
+                    return self.pending.pop().map(|head| Ok(head.value));
+                }
+            }
+            match self.packets.next_pending() {
+                Some(Ok((t, packet))) => {
+                    self.horizon = self.horizon.max(t);
+                    if let Err(error) = self.split(t, packet) {
+                        return Some(Err(error));
+                    }
+                }
+                Some(Err(error)) => return Some(Err(error)),
+                None => {
+                    self.horizon = i64::MAX;
+                    return self.pending.pop().map(|head| Ok(head.value));
+                }
+            }
+        }
+    }
+}
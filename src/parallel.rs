@@ -0,0 +1,125 @@
+//! Parallel offline processing of a recording.
+//!
+//! `process_parallel` splits a file into contiguous ranges of packets of similar size (in bytes) with the file
+//! data table, and gives each range, as a `PacketRange`, to a closure running on its own thread. Each thread
+//! has its own decoder, hence reading, decompression and processing all scale with the number of threads.
+//! Results are returned in file order, ready to be merged.
+
+use crate::base::{Decoder, FileDataEntry, Packet, ParseError, Stream};
+
+/// Packets of a part of a file (see `process_parallel`). Iterating yields the packets in file order.
+pub struct PacketRange {
+    decoder: Decoder,
+    index: usize,
+    end: i64,
+    packets: usize,
+    time_range: Option<(i64, i64)>,
+}
+
+impl PacketRange {
+    /// Position of the range in the file (0 for the first range).
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Number of packets in the range (all streams).
+    pub fn packets(&self) -> usize {
+        self.packets
+    }
+
+    /// Smallest and largest timestamps of the range according to the file data table, or None if its packets
+    /// are empty.
+    pub fn time_range(&self) -> Option<(i64, i64)> {
+        self.time_range
+    }
+
+    pub fn id_to_stream(&self) -> &std::collections::HashMap<u32, Stream> {
+        &self.decoder.id_to_stream
+    }
+}
+
+impl Iterator for PacketRange {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.decoder.position() >= self.end {
+            return None;
+        }
+        self.decoder.next()
+    }
+}
+
+/// Splits the file data table into at most `count` contiguous ranges of similar size.
+fn split(entries: &[FileDataEntry], count: usize) -> Vec<&[FileDataEntry]> {
+    let total: u64 = entries.iter().map(|entry| entry.size as u64 + 8).sum();
+    let count = count.clamp(1, entries.len().max(1));
+    let mut ranges = Vec::with_capacity(count);
+    let mut begin = 0;
+    let mut size = 0u64;
+    for (index, entry) in entries.iter().enumerate() {
+        size += entry.size as u64 + 8;
+        if size * count as u64 >= total * (ranges.len() as u64 + 1) && ranges.len() + 1 < count {
+            ranges.push(&entries[begin..index + 1]);
+            begin = index + 1;
+        }
+    }
+    if begin < entries.len() {
+        ranges.push(&entries[begin..]);
+    }
+    ranges
+}
+
+/// Runs `process` on `workers` ranges of the file in parallel, and returns the results in file order.
+///
+/// The file must have a file data table. The first error (opening the file or reading the table) is returned;
+/// errors in the packets are given to `process` by the ranges.
+pub fn process_parallel<P, T, F>(path: P, workers: usize, process: F) -> Result<Vec<T>, ParseError>
+where
+    P: std::convert::AsRef<std::path::Path>,
+    T: Send,
+    F: Fn(PacketRange) -> T + Sync,
+{
+    let path = path.as_ref();
+    let mut entries = Decoder::new_from_file(path)?.file_data_table()?;
+    entries.sort_by_key(|entry| entry.byte_offset);
+    let ranges = split(&entries, workers);
+    std::thread::scope(|scope| {
+        let workers = ranges
+            .iter()
+            .enumerate()
+            .map(|(index, range)| {
+                let process = &process;
+                scope.spawn(move || -> Result<T, ParseError> {
+                    let mut decoder = Decoder::new_from_file(path)?;
+                    decoder.seek_to_packet(range[0].byte_offset)?;
+                    let last = range.last().unwrap();
+                    let time_range = range.iter().filter(|entry| entry.num_elements > 0).fold(
+                        None,
+                        |time_range: Option<(i64, i64)>, entry| {
+                            Some(time_range.map_or(
+                                (entry.timestamp_start, entry.timestamp_end),
+                                |(begin, end)| {
+                                    (
+                                        begin.min(entry.timestamp_start),
+                                        end.max(entry.timestamp_end),
+                                    )
+                                },
+                            ))
+                        },
+                    );
+                    Ok(process(PacketRange {
+                        decoder,
+                        index,
+                        end: last.byte_offset + last.size as i64,
+                        packets: range.len(),
+                        time_range,
+                    }))
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("a worker panicked"))
+            .collect()
+    })
+}
@@ -0,0 +1,71 @@
+use crate::base::ParseError;
+use crate::events::Event;
+
+/// Polarity conventions used by downstream tools.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PolarityEncoding {
+    /// OFF is 0, ON is 1.
+    ZeroOne,
+    /// OFF is -1, ON is 1.
+    MinusOnePlusOne,
+    /// OFF is 0, ON is any other value.
+    Bool,
+}
+
+impl PolarityEncoding {
+    pub fn encode(self, on: bool) -> i8 {
+        match (self, on) {
+            (PolarityEncoding::MinusOnePlusOne, false) => -1,
+            (_, false) => 0,
+            (_, true) => 1,
+        }
+    }
+
+    pub fn decode(self, value: i8) -> Result<bool, ParseError> {
+        match (self, value) {
+            (PolarityEncoding::ZeroOne, 0) | (PolarityEncoding::MinusOnePlusOne, -1) => Ok(false),
+            (PolarityEncoding::ZeroOne, 1) | (PolarityEncoding::MinusOnePlusOne, 1) => Ok(true),
+            (PolarityEncoding::Bool, value) => Ok(value != 0),
+            (_, value) => Err(ParseError::InvalidPolarity(value)),
+        }
+    }
+}
+
+/// Converts a polarity value from one encoding to another.
+pub fn remap(value: i8, from: PolarityEncoding, to: PolarityEncoding) -> Result<i8, ParseError> {
+    Ok(to.encode(from.decode(value)?))
+}
+
+/// Splits events into (ON events, OFF events), preserving their order.
+pub fn split<I: IntoIterator<Item = Event>>(events: I) -> (Vec<Event>, Vec<Event>) {
+    events.into_iter().partition(|event| event.on())
+}
+
+/// Keeps the events with the given polarity from an event stream (for instance `Events`).
+pub struct Polarity<I> {
+    events: I,
+    on: bool,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Polarity<I> {
+    pub fn on(events: I) -> Self {
+        Polarity { events, on: true }
+    }
+
+    pub fn off(events: I) -> Self {
+        Polarity { events, on: false }
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Polarity<I> {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(event) if event.on() != self.on => continue,
+                result => return Some(result),
+            }
+        }
+    }
+}
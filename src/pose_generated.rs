@@ -0,0 +1,608 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+
+use std::cmp::Ordering;
+use std::mem;
+
+extern crate flatbuffers;
+use self::flatbuffers::{EndianScalar, Follow};
+
+// struct Vec3f, aligned to 4
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct Vec3f(pub [u8; 12]);
+impl Default for Vec3f {
+    fn default() -> Self {
+        Self([0; 12])
+    }
+}
+impl std::fmt::Debug for Vec3f {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Vec3f")
+            .field("x", &self.x())
+            .field("y", &self.y())
+            .field("z", &self.z())
+            .finish()
+    }
+}
+
+impl flatbuffers::SimpleToVerifyInSlice for Vec3f {}
+impl flatbuffers::SafeSliceAccess for Vec3f {}
+impl<'a> flatbuffers::Follow<'a> for Vec3f {
+    type Inner = &'a Vec3f;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        <&'a Vec3f>::follow(buf, loc)
+    }
+}
+impl<'a> flatbuffers::Follow<'a> for &'a Vec3f {
+    type Inner = &'a Vec3f;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        flatbuffers::follow_cast_ref::<Vec3f>(buf, loc)
+    }
+}
+impl<'b> flatbuffers::Push for Vec3f {
+    type Output = Vec3f;
+    #[inline]
+    fn push(&self, dst: &mut [u8], _rest: &[u8]) {
+        let src = unsafe {
+            ::std::slice::from_raw_parts(self as *const Vec3f as *const u8, Self::size())
+        };
+        dst.copy_from_slice(src);
+    }
+}
+impl<'b> flatbuffers::Push for &'b Vec3f {
+    type Output = Vec3f;
+
+    #[inline]
+    fn push(&self, dst: &mut [u8], _rest: &[u8]) {
+        let src = unsafe {
+            ::std::slice::from_raw_parts(*self as *const Vec3f as *const u8, Self::size())
+        };
+        dst.copy_from_slice(src);
+    }
+}
+
+impl<'a> flatbuffers::Verifiable for Vec3f {
+    #[inline]
+    fn run_verifier(
+        v: &mut flatbuffers::Verifier,
+        pos: usize,
+    ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+        use self::flatbuffers::Verifiable;
+        v.in_buffer::<Self>(pos)
+    }
+}
+impl<'a> Vec3f {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        let mut s = Self([0; 12]);
+        s.set_x(x);
+        s.set_y(y);
+        s.set_z(z);
+        s
+    }
+
+    pub fn x(&self) -> f32 {
+        let mut mem = core::mem::MaybeUninit::<f32>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.0[0..].as_ptr(),
+                mem.as_mut_ptr() as *mut u8,
+                core::mem::size_of::<f32>(),
+            );
+            mem.assume_init()
+        }
+        .from_little_endian()
+    }
+
+    pub fn set_x(&mut self, x: f32) {
+        let x_le = x.to_little_endian();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                &x_le as *const f32 as *const u8,
+                self.0[0..].as_mut_ptr(),
+                core::mem::size_of::<f32>(),
+            );
+        }
+    }
+
+    pub fn y(&self) -> f32 {
+        let mut mem = core::mem::MaybeUninit::<f32>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.0[4..].as_ptr(),
+                mem.as_mut_ptr() as *mut u8,
+                core::mem::size_of::<f32>(),
+            );
+            mem.assume_init()
+        }
+        .from_little_endian()
+    }
+
+    pub fn set_y(&mut self, x: f32) {
+        let x_le = x.to_little_endian();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                &x_le as *const f32 as *const u8,
+                self.0[4..].as_mut_ptr(),
+                core::mem::size_of::<f32>(),
+            );
+        }
+    }
+
+    pub fn z(&self) -> f32 {
+        let mut mem = core::mem::MaybeUninit::<f32>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.0[8..].as_ptr(),
+                mem.as_mut_ptr() as *mut u8,
+                core::mem::size_of::<f32>(),
+            );
+            mem.assume_init()
+        }
+        .from_little_endian()
+    }
+
+    pub fn set_z(&mut self, x: f32) {
+        let x_le = x.to_little_endian();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                &x_le as *const f32 as *const u8,
+                self.0[8..].as_mut_ptr(),
+                core::mem::size_of::<f32>(),
+            );
+        }
+    }
+}
+
+// struct Quaternion, aligned to 4
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct Quaternion(pub [u8; 16]);
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self([0; 16])
+    }
+}
+impl std::fmt::Debug for Quaternion {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Quaternion")
+            .field("w", &self.w())
+            .field("x", &self.x())
+            .field("y", &self.y())
+            .field("z", &self.z())
+            .finish()
+    }
+}
+
+impl flatbuffers::SimpleToVerifyInSlice for Quaternion {}
+impl flatbuffers::SafeSliceAccess for Quaternion {}
+impl<'a> flatbuffers::Follow<'a> for Quaternion {
+    type Inner = &'a Quaternion;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        <&'a Quaternion>::follow(buf, loc)
+    }
+}
+impl<'a> flatbuffers::Follow<'a> for &'a Quaternion {
+    type Inner = &'a Quaternion;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        flatbuffers::follow_cast_ref::<Quaternion>(buf, loc)
+    }
+}
+impl<'b> flatbuffers::Push for Quaternion {
+    type Output = Quaternion;
+    #[inline]
+    fn push(&self, dst: &mut [u8], _rest: &[u8]) {
+        let src = unsafe {
+            ::std::slice::from_raw_parts(self as *const Quaternion as *const u8, Self::size())
+        };
+        dst.copy_from_slice(src);
+    }
+}
+impl<'b> flatbuffers::Push for &'b Quaternion {
+    type Output = Quaternion;
+
+    #[inline]
+    fn push(&self, dst: &mut [u8], _rest: &[u8]) {
+        let src = unsafe {
+            ::std::slice::from_raw_parts(*self as *const Quaternion as *const u8, Self::size())
+        };
+        dst.copy_from_slice(src);
+    }
+}
+
+impl<'a> flatbuffers::Verifiable for Quaternion {
+    #[inline]
+    fn run_verifier(
+        v: &mut flatbuffers::Verifier,
+        pos: usize,
+    ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+        use self::flatbuffers::Verifiable;
+        v.in_buffer::<Self>(pos)
+    }
+}
+impl<'a> Quaternion {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(w: f32, x: f32, y: f32, z: f32) -> Self {
+        let mut s = Self([0; 16]);
+        s.set_w(w);
+        s.set_x(x);
+        s.set_y(y);
+        s.set_z(z);
+        s
+    }
+
+    pub fn w(&self) -> f32 {
+        let mut mem = core::mem::MaybeUninit::<f32>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.0[0..].as_ptr(),
+                mem.as_mut_ptr() as *mut u8,
+                core::mem::size_of::<f32>(),
+            );
+            mem.assume_init()
+        }
+        .from_little_endian()
+    }
+
+    pub fn set_w(&mut self, x: f32) {
+        let x_le = x.to_little_endian();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                &x_le as *const f32 as *const u8,
+                self.0[0..].as_mut_ptr(),
+                core::mem::size_of::<f32>(),
+            );
+        }
+    }
+
+    pub fn x(&self) -> f32 {
+        let mut mem = core::mem::MaybeUninit::<f32>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.0[4..].as_ptr(),
+                mem.as_mut_ptr() as *mut u8,
+                core::mem::size_of::<f32>(),
+            );
+            mem.assume_init()
+        }
+        .from_little_endian()
+    }
+
+    pub fn set_x(&mut self, x: f32) {
+        let x_le = x.to_little_endian();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                &x_le as *const f32 as *const u8,
+                self.0[4..].as_mut_ptr(),
+                core::mem::size_of::<f32>(),
+            );
+        }
+    }
+
+    pub fn y(&self) -> f32 {
+        let mut mem = core::mem::MaybeUninit::<f32>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.0[8..].as_ptr(),
+                mem.as_mut_ptr() as *mut u8,
+                core::mem::size_of::<f32>(),
+            );
+            mem.assume_init()
+        }
+        .from_little_endian()
+    }
+
+    pub fn set_y(&mut self, x: f32) {
+        let x_le = x.to_little_endian();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                &x_le as *const f32 as *const u8,
+                self.0[8..].as_mut_ptr(),
+                core::mem::size_of::<f32>(),
+            );
+        }
+    }
+
+    pub fn z(&self) -> f32 {
+        let mut mem = core::mem::MaybeUninit::<f32>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.0[12..].as_ptr(),
+                mem.as_mut_ptr() as *mut u8,
+                core::mem::size_of::<f32>(),
+            );
+            mem.assume_init()
+        }
+        .from_little_endian()
+    }
+
+    pub fn set_z(&mut self, x: f32) {
+        let x_le = x.to_little_endian();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                &x_le as *const f32 as *const u8,
+                self.0[12..].as_mut_ptr(),
+                core::mem::size_of::<f32>(),
+            );
+        }
+    }
+}
+
+pub enum PoseOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct Pose<'a> {
+    pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for Pose<'a> {
+    type Inner = Pose<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf, loc },
+        }
+    }
+}
+
+impl<'a> Pose<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        Pose { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args PoseArgs<'args>,
+    ) -> flatbuffers::WIPOffset<Pose<'bldr>> {
+        let mut builder = PoseBuilder::new(_fbb);
+        builder.add_timestamp(args.timestamp);
+        if let Some(x) = args.target_frame {
+            builder.add_target_frame(x);
+        }
+        if let Some(x) = args.reference_frame {
+            builder.add_reference_frame(x);
+        }
+        if let Some(x) = args.rotation {
+            builder.add_rotation(x);
+        }
+        if let Some(x) = args.translation {
+            builder.add_translation(x);
+        }
+        builder.finish()
+    }
+
+    pub const VT_TIMESTAMP: flatbuffers::VOffsetT = 4;
+    pub const VT_TRANSLATION: flatbuffers::VOffsetT = 6;
+    pub const VT_ROTATION: flatbuffers::VOffsetT = 8;
+    pub const VT_REFERENCE_FRAME: flatbuffers::VOffsetT = 10;
+    pub const VT_TARGET_FRAME: flatbuffers::VOffsetT = 12;
+
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self._tab.get::<i64>(Pose::VT_TIMESTAMP, Some(0)).unwrap()
+    }
+    #[inline]
+    pub fn translation(&self) -> Option<&'a Vec3f> {
+        self._tab.get::<Vec3f>(Pose::VT_TRANSLATION, None)
+    }
+    #[inline]
+    pub fn rotation(&self) -> Option<&'a Quaternion> {
+        self._tab.get::<Quaternion>(Pose::VT_ROTATION, None)
+    }
+    #[inline]
+    pub fn reference_frame(&self) -> Option<&'a str> {
+        self._tab
+            .get::<flatbuffers::ForwardsUOffset<&str>>(Pose::VT_REFERENCE_FRAME, None)
+    }
+    #[inline]
+    pub fn target_frame(&self) -> Option<&'a str> {
+        self._tab
+            .get::<flatbuffers::ForwardsUOffset<&str>>(Pose::VT_TARGET_FRAME, None)
+    }
+}
+
+impl flatbuffers::Verifiable for Pose<'_> {
+    #[inline]
+    fn run_verifier(
+        v: &mut flatbuffers::Verifier,
+        pos: usize,
+    ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+        use self::flatbuffers::Verifiable;
+        v.visit_table(pos)?
+            .visit_field::<i64>(&"timestamp", Self::VT_TIMESTAMP, false)?
+            .visit_field::<Vec3f>(&"translation", Self::VT_TRANSLATION, false)?
+            .visit_field::<Quaternion>(&"rotation", Self::VT_ROTATION, false)?
+            .visit_field::<flatbuffers::ForwardsUOffset<&str>>(
+                &"reference_frame",
+                Self::VT_REFERENCE_FRAME,
+                false,
+            )?
+            .visit_field::<flatbuffers::ForwardsUOffset<&str>>(
+                &"target_frame",
+                Self::VT_TARGET_FRAME,
+                false,
+            )?
+            .finish();
+        Ok(())
+    }
+}
+pub struct PoseArgs<'a> {
+    pub timestamp: i64,
+    pub translation: Option<&'a Vec3f>,
+    pub rotation: Option<&'a Quaternion>,
+    pub reference_frame: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub target_frame: Option<flatbuffers::WIPOffset<&'a str>>,
+}
+impl<'a> Default for PoseArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        PoseArgs {
+            timestamp: 0,
+            translation: None,
+            rotation: None,
+            reference_frame: None,
+            target_frame: None,
+        }
+    }
+}
+pub struct PoseBuilder<'a: 'b, 'b> {
+    fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> PoseBuilder<'a, 'b> {
+    #[inline]
+    pub fn add_timestamp(&mut self, timestamp: i64) {
+        self.fbb_.push_slot::<i64>(Pose::VT_TIMESTAMP, timestamp, 0);
+    }
+    #[inline]
+    pub fn add_translation(&mut self, translation: &Vec3f) {
+        self.fbb_
+            .push_slot_always::<&Vec3f>(Pose::VT_TRANSLATION, translation);
+    }
+    #[inline]
+    pub fn add_rotation(&mut self, rotation: &Quaternion) {
+        self.fbb_
+            .push_slot_always::<&Quaternion>(Pose::VT_ROTATION, rotation);
+    }
+    #[inline]
+    pub fn add_reference_frame(&mut self, reference_frame: flatbuffers::WIPOffset<&'b str>) {
+        self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(
+            Pose::VT_REFERENCE_FRAME,
+            reference_frame,
+        );
+    }
+    #[inline]
+    pub fn add_target_frame(&mut self, target_frame: flatbuffers::WIPOffset<&'b str>) {
+        self.fbb_
+            .push_slot_always::<flatbuffers::WIPOffset<_>>(Pose::VT_TARGET_FRAME, target_frame);
+    }
+    #[inline]
+    pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> PoseBuilder<'a, 'b> {
+        let start = _fbb.start_table();
+        PoseBuilder {
+            fbb_: _fbb,
+            start_: start,
+        }
+    }
+    #[inline]
+    pub fn finish(self) -> flatbuffers::WIPOffset<Pose<'a>> {
+        let o = self.fbb_.end_table(self.start_);
+        flatbuffers::WIPOffset::new(o.value())
+    }
+}
+
+impl std::fmt::Debug for Pose<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("Pose");
+        ds.field("timestamp", &self.timestamp());
+        ds.field("translation", &self.translation());
+        ds.field("rotation", &self.rotation());
+        ds.field("reference_frame", &self.reference_frame());
+        ds.field("target_frame", &self.target_frame());
+        ds.finish()
+    }
+}
+#[inline]
+#[deprecated(since = "2.0.0", note = "Deprecated in favor of `root_as...` methods.")]
+pub fn get_root_as_pose<'a>(buf: &'a [u8]) -> Pose<'a> {
+    unsafe { flatbuffers::root_unchecked::<Pose<'a>>(buf) }
+}
+
+#[inline]
+#[deprecated(since = "2.0.0", note = "Deprecated in favor of `root_as...` methods.")]
+pub fn get_size_prefixed_root_as_pose<'a>(buf: &'a [u8]) -> Pose<'a> {
+    unsafe { flatbuffers::size_prefixed_root_unchecked::<Pose<'a>>(buf) }
+}
+
+#[inline]
+/// Verifies that a buffer of bytes contains a `Pose`
+/// and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_pose_unchecked`.
+pub fn root_as_pose(buf: &[u8]) -> Result<Pose, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::root::<Pose>(buf)
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a size prefixed
+/// `Pose` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `size_prefixed_root_as_pose_unchecked`.
+pub fn size_prefixed_root_as_pose(buf: &[u8]) -> Result<Pose, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::size_prefixed_root::<Pose>(buf)
+}
+#[inline]
+/// Verifies, with the given options, that a buffer of bytes
+/// contains a `Pose` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_pose_unchecked`.
+pub fn root_as_pose_with_opts<'b, 'o>(
+    opts: &'o flatbuffers::VerifierOptions,
+    buf: &'b [u8],
+) -> Result<Pose<'b>, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::root_with_opts::<Pose<'b>>(opts, buf)
+}
+#[inline]
+/// Verifies, with the given verifier options, that a buffer of
+/// bytes contains a size prefixed `Pose` and returns
+/// it. Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_pose_unchecked`.
+pub fn size_prefixed_root_as_pose_with_opts<'b, 'o>(
+    opts: &'o flatbuffers::VerifierOptions,
+    buf: &'b [u8],
+) -> Result<Pose<'b>, flatbuffers::InvalidFlatbuffer> {
+    flatbuffers::size_prefixed_root_with_opts::<Pose<'b>>(opts, buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a Pose and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid `Pose`.
+pub unsafe fn root_as_pose_unchecked(buf: &[u8]) -> Pose {
+    flatbuffers::root_unchecked::<Pose>(buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a size prefixed Pose and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid size prefixed `Pose`.
+pub unsafe fn size_prefixed_root_as_pose_unchecked(buf: &[u8]) -> Pose {
+    flatbuffers::size_prefixed_root_unchecked::<Pose>(buf)
+}
+pub const POSE_IDENTIFIER: &str = "POSE";
+
+#[inline]
+pub fn pose_buffer_has_identifier(buf: &[u8]) -> bool {
+    flatbuffers::buffer_has_identifier(buf, POSE_IDENTIFIER, false)
+}
+
+#[inline]
+pub fn pose_size_prefixed_buffer_has_identifier(buf: &[u8]) -> bool {
+    flatbuffers::buffer_has_identifier(buf, POSE_IDENTIFIER, true)
+}
+
+#[inline]
+pub fn finish_pose_buffer<'a, 'b>(
+    fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    root: flatbuffers::WIPOffset<Pose<'a>>,
+) {
+    fbb.finish(root, Some(POSE_IDENTIFIER));
+}
+
+#[inline]
+pub fn finish_size_prefixed_pose_buffer<'a, 'b>(
+    fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    root: flatbuffers::WIPOffset<Pose<'a>>,
+) {
+    fbb.finish_size_prefixed(root, Some(POSE_IDENTIFIER));
+}
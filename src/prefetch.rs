@@ -0,0 +1,37 @@
+use crate::base::{Decoder, Packet, ParseError, Stream};
+
+/// Reads and decompresses packets on a background thread (see `Decoder::prefetch`).
+pub struct Prefetch {
+    pub id_to_stream: std::collections::HashMap<u32, Stream>,
+    receiver: std::sync::mpsc::Receiver<Result<Packet, ParseError>>,
+}
+
+impl Decoder {
+    /// Decodes the next packet on a background thread while the caller processes the current one.
+    ///
+    /// This hides the latency of network sources and slow disks. At most one packet is decoded ahead.
+    /// The thread stops after the last packet, or once the returned iterator is dropped and its current read completes.
+    pub fn prefetch(self) -> Prefetch {
+        let id_to_stream = self.id_to_stream.clone();
+        let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+        std::thread::spawn(move || {
+            for packet in self {
+                if sender.send(packet).is_err() {
+                    break;
+                }
+            }
+        });
+        Prefetch {
+            id_to_stream,
+            receiver,
+        }
+    }
+}
+
+impl Iterator for Prefetch {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
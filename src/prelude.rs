@@ -0,0 +1,5 @@
+//! Imports the decoder and the iterator traits, for instance with `use aedat::prelude::*;`.
+
+pub use crate::base::{Decoder, DecoderBuilder, Packet, ParseError, StreamContent};
+pub use crate::events::{Event, EventIterator, PacketIterator};
+pub use crate::interop::{CameraEvent, EventCameraSource};
@@ -0,0 +1,96 @@
+//! Record-and-replay proxy for live sources.
+//!
+//! A DV runtime (or camera) output serves a single socket, hence recording a stream and processing it live
+//! usually requires two outputs in the runtime. The proxy reads the source once, writes it to an AEDAT4 file,
+//! and re-serves it to any number of downstream TCP clients, which connect to the proxy as they would connect
+//! to the runtime.
+
+use crate::base::{ioheader_generated::Compression, Decoder, Packet, ParseError};
+use crate::encoder::Encoder;
+
+/// See the module documentation.
+pub struct Proxy {
+    decoder: Decoder,
+    server: Encoder,
+    recorder: Option<Encoder>,
+    packets: u64,
+}
+
+impl Proxy {
+    /// Serves the packets of `decoder` on `address`, with the compression of the source.
+    pub fn new<A: std::net::ToSocketAddrs>(
+        decoder: Decoder,
+        address: A,
+    ) -> Result<Self, ParseError> {
+        let server =
+            Encoder::new_to_tcp(address, decoder.id_to_stream.clone(), decoder.compression())?;
+        Ok(Proxy {
+            decoder,
+            server,
+            recorder: None,
+            packets: 0,
+        })
+    }
+
+    /// Also writes the packets to an AEDAT4 file.
+    pub fn record<P: std::convert::AsRef<std::path::Path>>(
+        mut self,
+        path: P,
+        compression: Compression,
+    ) -> Result<Self, ParseError> {
+        self.recorder = Some(Encoder::new_to_file(
+            path,
+            self.decoder.id_to_stream.clone(),
+            compression,
+        )?);
+        Ok(self)
+    }
+
+    /// Number of connected downstream clients.
+    pub fn clients(&self) -> usize {
+        self.server.clients()
+    }
+
+    /// Number of packets forwarded so far.
+    pub fn packets(&self) -> u64 {
+        self.packets
+    }
+
+    /// Forwards packets until the source closes, completes the file, and returns the number of packets.
+    pub fn run(mut self) -> Result<u64, ParseError> {
+        for packet in self.by_ref() {
+            packet?;
+        }
+        self.finish()
+    }
+
+    /// Completes the file. The downstream connections are closed when the proxy is dropped.
+    pub fn finish(mut self) -> Result<u64, ParseError> {
+        if let Some(recorder) = self.recorder.take() {
+            recorder.finish()?;
+        }
+        Ok(self.packets)
+    }
+}
+
+/// Reads, records and serves the next packet, and returns it for live processing in the same process.
+impl Iterator for Proxy {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let packet = match self.decoder.next()? {
+            Ok(packet) => packet,
+            Err(error) => return Some(Err(error)),
+        };
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(error) = recorder.write(&packet) {
+                return Some(Err(error));
+            }
+        }
+        if let Err(error) = self.server.write(&packet) {
+            return Some(Err(error));
+        }
+        self.packets += 1;
+        Some(Ok(packet))
+    }
+}
@@ -0,0 +1,51 @@
+use crate::base::{DecoderBuilder, ParseError};
+use crate::encoder::Encoder;
+
+/// Outcome of `repair`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepairReport {
+    /// Number of packets written to the output.
+    pub packets: usize,
+    /// Number of malformed packets dropped.
+    pub skipped_packets: usize,
+    /// Whether the input ended in the middle of a packet (or its packet headers are damaged).
+    pub truncated: bool,
+}
+
+/// Copies the valid packets of a damaged recording (for instance a recording interrupted by a crash)
+/// to a new file with a regenerated file data table.
+///
+/// Malformed packets are dropped, and salvaging stops at the first unreadable packet header. The complete events
+/// of a truncated event packet are kept.
+pub fn repair<P: std::convert::AsRef<std::path::Path>, Q: std::convert::AsRef<std::path::Path>>(
+    input: P,
+    output: Q,
+) -> Result<RepairReport, ParseError> {
+    let mut decoder = DecoderBuilder::file(input)
+        .verification(true)
+        .recovery(true)
+        .salvage_truncated(true)
+        .build()?;
+    let mut encoder =
+        Encoder::new_to_file(output, decoder.id_to_stream.clone(), decoder.compression())?;
+    let mut packets = 0;
+    let mut truncated = false;
+    for packet in decoder.by_ref() {
+        match packet {
+            Ok(packet) => {
+                encoder.write(&packet)?;
+                packets += 1;
+            }
+            Err(_) => {
+                truncated = true;
+                break;
+            }
+        }
+    }
+    encoder.finish()?;
+    Ok(RepairReport {
+        packets,
+        skipped_packets: decoder.skipped_packets(),
+        truncated: truncated || decoder.is_truncated(),
+    })
+}
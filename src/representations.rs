@@ -0,0 +1,192 @@
+use crate::base::ParseError;
+use crate::chunks::Chunks;
+pub use crate::chunks::Window;
+use crate::events::Event;
+
+/// Per-pixel surface of active events (SAE) with exponential decay.
+pub struct TimeSurface {
+    width: u16,
+    height: u16,
+    tau: f32,
+    timestamps: Vec<Option<i64>>,
+}
+
+impl TimeSurface {
+    /// `tau` is the decay time constant, in microseconds.
+    pub fn new(width: u16, height: u16, tau: f32) -> Self {
+        TimeSurface {
+            width,
+            height,
+            tau,
+            timestamps: vec![None; width as usize * height as usize],
+        }
+    }
+
+    /// Stores the event timestamp. Events outside the sensor are ignored.
+    pub fn update(&mut self, event: &Event) {
+        if event.x() >= 0
+            && event.y() >= 0
+            && (event.x() as u16) < self.width
+            && (event.y() as u16) < self.height
+        {
+            self.timestamps[event.y() as usize * self.width as usize + event.x() as usize] =
+                Some(event.t());
+        }
+    }
+
+    pub fn extend<'a, I: IntoIterator<Item = &'a Event>>(&mut self, events: I) {
+        for event in events {
+            self.update(event);
+        }
+    }
+
+    /// Latest timestamp at each pixel, if the pixel received an event.
+    pub fn timestamps(&self) -> &[Option<i64>] {
+        &self.timestamps
+    }
+
+    pub fn clear(&mut self) {
+        self.timestamps
+            .iter_mut()
+            .for_each(|timestamp| *timestamp = None);
+    }
+
+    /// Evaluates exp(-(t - t_pixel) / tau) at every pixel, with shape (height, width).
+    ///
+    /// Pixels without events, or whose latest event is after t, are zero.
+    pub fn snapshot(&self, t: i64) -> ndarray::Array2<f32> {
+        ndarray::Array2::from_shape_fn((self.height as usize, self.width as usize), |(y, x)| {
+            match self.timestamps[y * self.width as usize + x] {
+                Some(timestamp) if timestamp <= t => (-((t - timestamp) as f32) / self.tau).exp(),
+                _ => 0.0,
+            }
+        })
+    }
+}
+
+/// Event volume with bilinear interpolation along time, as used by E2VID.
+pub struct VoxelGrid {
+    bins: usize,
+    width: u16,
+    height: u16,
+}
+
+impl VoxelGrid {
+    pub fn new(bins: usize, width: u16, height: u16) -> Self {
+        VoxelGrid {
+            bins,
+            width,
+            height,
+        }
+    }
+
+    /// Bins events into a (bins, height, width) tensor, using the first and last event timestamps as the window.
+    pub fn compute(&self, events: &[Event]) -> ndarray::Array3<f32> {
+        match (events.first(), events.last()) {
+            (Some(first), Some(last)) => self.compute_window(events, first.t(), last.t()),
+            _ => ndarray::Array3::zeros((self.bins, self.height as usize, self.width as usize)),
+        }
+    }
+
+    /// Bins events into a (bins, height, width) tensor spanning [begin_t, end_t].
+    ///
+    /// ON events add +1 and OFF events add -1, split between the two nearest bins.
+    /// Events outside the window or the sensor are ignored.
+    pub fn compute_window(
+        &self,
+        events: &[Event],
+        begin_t: i64,
+        end_t: i64,
+    ) -> ndarray::Array3<f32> {
+        let mut grid =
+            ndarray::Array3::zeros((self.bins, self.height as usize, self.width as usize));
+        if self.bins == 0 {
+            return grid;
+        }
+        let duration = (end_t - begin_t).max(1) as f32;
+        for event in events {
+            if event.t() < begin_t
+                || event.t() > end_t
+                || event.x() < 0
+                || event.y() < 0
+                || event.x() as u16 >= self.width
+                || event.y() as u16 >= self.height
+            {
+                continue;
+            }
+            let polarity = if event.on() { 1.0 } else { -1.0 };
+            let t = (self.bins - 1) as f32 * (event.t() - begin_t) as f32 / duration;
+            let lower = t.floor();
+            let weight = t - lower;
+            let (y, x) = (event.y() as usize, event.x() as usize);
+            grid[[lower as usize, y, x]] += polarity * (1.0 - weight);
+            if weight > 0.0 && (lower as usize) + 1 < self.bins {
+                grid[[lower as usize + 1, y, x]] += polarity * weight;
+            }
+        }
+        grid
+    }
+}
+
+/// Per-pixel event counts with separate OFF (channel 0) and ON (channel 1) channels.
+pub struct EventHistogram {
+    width: u16,
+    height: u16,
+}
+
+impl EventHistogram {
+    pub fn new(width: u16, height: u16) -> Self {
+        EventHistogram { width, height }
+    }
+
+    /// Counts events into a (2, height, width) tensor. Events outside the sensor are ignored.
+    pub fn compute(&self, events: &[Event]) -> ndarray::Array3<f32> {
+        let mut histogram = ndarray::Array3::zeros((2, self.height as usize, self.width as usize));
+        for event in events {
+            if event.x() >= 0
+                && event.y() >= 0
+                && (event.x() as u16) < self.width
+                && (event.y() as u16) < self.height
+            {
+                histogram[[event.on() as usize, event.y() as usize, event.x() as usize]] += 1.0;
+            }
+        }
+        histogram
+    }
+
+    /// Same as `compute`, flattened in (channel, y, x) order.
+    pub fn compute_flat(&self, events: &[Event]) -> Vec<f32> {
+        self.compute(events).into_raw_vec()
+    }
+
+    /// Converts an event stream into one histogram per window.
+    ///
+    /// Duration windows are aligned on the first event and empty windows yield empty histograms.
+    pub fn windows<I: Iterator<Item = Result<Event, ParseError>>>(
+        self,
+        events: I,
+        window: Window,
+    ) -> Histograms<I> {
+        Histograms {
+            histogram: self,
+            chunks: Chunks::new(events, window),
+        }
+    }
+}
+
+pub struct Histograms<I> {
+    histogram: EventHistogram,
+    chunks: Chunks<I>,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Histograms<I> {
+    type Item = Result<ndarray::Array3<f32>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.chunks
+                .next()?
+                .map(|events| self.histogram.compute(&events)),
+        )
+    }
+}
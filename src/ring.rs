@@ -0,0 +1,206 @@
+//! Pre-trigger recording: keeps the last seconds of a live source in memory and writes them on demand.
+
+use crate::base::{ioheader_generated::Compression, Decoder, Packet, ParseError, Stream};
+use crate::encoder::Encoder;
+
+/// Packets of the last `retention` microseconds (recording time), in arrival order.
+pub struct RingRecorder {
+    pub id_to_stream: std::collections::HashMap<u32, Stream>,
+    retention: i64,
+    max_bytes: Option<usize>,
+    /// Packets with the latest timestamp at the time they were pushed.
+    packets: std::collections::VecDeque<(i64, Packet)>,
+    latest_t: Option<i64>,
+    bytes: usize,
+}
+
+impl RingRecorder {
+    pub fn new(id_to_stream: std::collections::HashMap<u32, Stream>, retention: i64) -> Self {
+        RingRecorder {
+            id_to_stream,
+            retention,
+            max_bytes: None,
+            packets: std::collections::VecDeque::new(),
+            latest_t: None,
+            bytes: 0,
+        }
+    }
+
+    /// Also drops the oldest packets once the decompressed packets exceed `max_bytes` (unlimited by default).
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Adds a packet and drops the packets older than the retention.
+    pub fn push(&mut self, packet: Packet) -> Result<(), ParseError> {
+        let content = match self.id_to_stream.get(&packet.stream_id) {
+            Some(stream) => &stream.content,
+            None => return Err(ParseError::UnknownStreamId(packet.stream_id)),
+        };
+        let (elements, _, end_t) = packet.summary(content)?;
+        if elements > 0 {
+            self.latest_t = Some(self.latest_t.map_or(end_t, |latest_t| latest_t.max(end_t)));
+        }
+        let t = self.latest_t.unwrap_or(i64::MIN);
+        self.bytes += packet.buffer.len();
+        self.packets.push_back((t, packet));
+        if let Some(latest_t) = self.latest_t {
+            while let Some((t, _)) = self.packets.front() {
+                if *t >= latest_t.saturating_sub(self.retention) {
+                    break;
+                }
+                self.pop_front();
+            }
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            while self.bytes > max_bytes && self.packets.len() > 1 {
+                self.pop_front();
+            }
+        }
+        Ok(())
+    }
+
+    fn pop_front(&mut self) {
+        if let Some((_, packet)) = self.packets.pop_front() {
+            self.bytes -= packet.buffer.len();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    /// Size of the retained (decompressed) packets in bytes.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn clear(&mut self) {
+        self.packets.clear();
+        self.bytes = 0;
+    }
+
+    /// Writes the retained packets to an AEDAT4 file and returns the number of packets.
+    ///
+    /// The packets are kept, hence consecutive dumps may overlap.
+    pub fn dump<P: std::convert::AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        compression: Compression,
+    ) -> Result<usize, ParseError> {
+        write(
+            path,
+            self.id_to_stream.clone(),
+            compression,
+            self.packets.iter().map(|(_, packet)| packet),
+        )?;
+        Ok(self.packets.len())
+    }
+}
+
+fn write<'a, P: std::convert::AsRef<std::path::Path>>(
+    path: P,
+    id_to_stream: std::collections::HashMap<u32, Stream>,
+    compression: Compression,
+    packets: impl Iterator<Item = &'a Packet>,
+) -> Result<(), ParseError> {
+    let mut encoder = Encoder::new_to_file(path, id_to_stream, compression)?;
+    for packet in packets {
+        encoder.write(packet)?;
+    }
+    encoder.finish()
+}
+
+struct Shared {
+    recorder: std::sync::Mutex<RingRecorder>,
+    stop: std::sync::atomic::AtomicBool,
+}
+
+/// A ring recorder fed by a decoder on a background thread (see `Decoder::ring_recorder`).
+pub struct RingRecorderHandle {
+    shared: std::sync::Arc<Shared>,
+    thread: std::thread::JoinHandle<Result<(), ParseError>>,
+}
+
+impl Decoder {
+    /// Reads the packets on a background thread and keeps the last `retention` microseconds in memory.
+    pub fn ring_recorder(self, retention: i64) -> RingRecorderHandle {
+        let shared = std::sync::Arc::new(Shared {
+            recorder: std::sync::Mutex::new(RingRecorder::new(
+                self.id_to_stream.clone(),
+                retention,
+            )),
+            stop: std::sync::atomic::AtomicBool::new(false),
+        });
+        let thread = {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                for packet in self {
+                    if shared.stop.load(std::sync::atomic::Ordering::Relaxed) {
+                        break;
+                    }
+                    shared.recorder.lock().unwrap().push(packet?)?;
+                }
+                Ok(())
+            })
+        };
+        RingRecorderHandle { shared, thread }
+    }
+}
+
+impl RingRecorderHandle {
+    /// Writes the retained packets to an AEDAT4 file and returns the number of packets.
+    ///
+    /// The packets are copied first, hence the source is not blocked while the file is written.
+    pub fn dump<P: std::convert::AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        compression: Compression,
+    ) -> Result<usize, ParseError> {
+        let (id_to_stream, packets): (_, Vec<Packet>) = {
+            let recorder = self.shared.recorder.lock().unwrap();
+            (
+                recorder.id_to_stream.clone(),
+                recorder
+                    .packets
+                    .iter()
+                    .map(|(_, packet)| packet.clone())
+                    .collect(),
+            )
+        };
+        write(path, id_to_stream, compression, packets.iter())?;
+        Ok(packets.len())
+    }
+
+    /// Calls `function` with the recorder, for instance to change its content or read its size.
+    pub fn with<R>(&self, function: impl FnOnce(&mut RingRecorder) -> R) -> R {
+        function(&mut self.shared.recorder.lock().unwrap())
+    }
+
+    /// Whether the background thread is still reading packets.
+    pub fn is_running(&self) -> bool {
+        !self.thread.is_finished()
+    }
+
+    /// Stops reading after the next packet, and returns the recorder (or the error that ended the source).
+    ///
+    /// The call blocks until the next packet arrives (or the source closes).
+    pub fn stop(self) -> Result<RingRecorder, ParseError> {
+        self.shared
+            .stop
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        let result = self
+            .thread
+            .join()
+            .expect("the ring recorder thread panicked");
+        let shared = std::sync::Arc::try_unwrap(self.shared)
+            .ok()
+            .expect("the ring recorder thread holds a reference");
+        result.map(|_| shared.recorder.into_inner().unwrap())
+    }
+}
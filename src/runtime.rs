@@ -0,0 +1,209 @@
+//! Configuration tree of a live DV runtime.
+//!
+//! The IOHeader of a network stream only describes the output streams. The runtime exposes everything else
+//! (camera biases, device information, module settings) through its config server, a separate TCP service
+//! (port 4040 by default). `RuntimeConfig::fetch` performs the dump handshake of the config server:
+//! the client sends a `DUMP_TREE` action, and the server answers with one message per node and attribute,
+//! followed by a final `DUMP_TREE` message. Messages are size-prefixed `ConfigActionData` flatbuffers.
+//!
+//! `RuntimeConfig::from_xml` reads the same tree from the XML format of DV configuration files and
+//! IOHeader descriptions.
+
+use crate::base::ParseError;
+use aedat_core::flatbuffer;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+/// Default port of the DV runtime config server.
+pub const DEFAULT_CONFIG_PORT: u16 = 4040;
+
+const ACTION_ERROR: i8 = 0;
+const ACTION_DUMP_TREE: i8 = 17;
+const ACTION_DUMP_TREE_NODE: i8 = 18;
+const ACTION_DUMP_TREE_ATTR: i8 = 19;
+
+// ConfigActionData fields
+const FIELD_ACTION: usize = 0;
+const FIELD_NODE: usize = 4;
+const FIELD_KEY: usize = 5;
+const FIELD_VALUE: usize = 7;
+
+/// Attributes of every node, indexed by node path (for instance `/mainloop/capture/bias/`) and key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    nodes: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl RuntimeConfig {
+    /// Connects to a config server and downloads its tree.
+    ///
+    /// `timeout` applies to the connection and to each read.
+    pub fn fetch<A: std::net::ToSocketAddrs>(
+        address: A,
+        timeout: std::time::Duration,
+    ) -> Result<Self, ParseError> {
+        let mut last_error = None;
+        for address in address.to_socket_addrs()? {
+            match std::net::TcpStream::connect_timeout(&address, timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(timeout))?;
+                    stream.set_write_timeout(Some(timeout))?;
+                    return RuntimeConfig::dump(stream);
+                }
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(match last_error {
+            Some(error) => error.into(),
+            None => ParseError::General("the config server address did not resolve".to_owned()),
+        })
+    }
+
+    /// Runs the dump handshake on a connected stream.
+    pub fn dump<S: Read + Write>(mut stream: S) -> Result<Self, ParseError> {
+        stream.write_all(&dump_request())?;
+        stream.flush()?;
+        let mut config = RuntimeConfig::default();
+        loop {
+            let mut length = [0; 4];
+            stream.read_exact(&mut length)?;
+            let size = u32::from_le_bytes(length);
+            if size > crate::base::DEFAULT_MAX_PACKET_SIZE {
+                return Err(ParseError::PacketTooLarge {
+                    size,
+                    limit: crate::base::DEFAULT_MAX_PACKET_SIZE,
+                });
+            }
+            let mut buffer = length.to_vec();
+            crate::base::read_length(&mut stream, &mut buffer, size)?;
+            let root = flatbuffer::root(&buffer)?;
+            let action = match flatbuffer::field(&buffer, root, FIELD_ACTION)? {
+                Some(position) => *buffer.get(position).ok_or(aedat_core::Error::OutOfBounds {
+                    position,
+                    length: buffer.len(),
+                })? as i8,
+                None => ACTION_ERROR,
+            };
+            let string = |field: usize| -> Result<String, ParseError> {
+                Ok(match flatbuffer::field(&buffer, root, field)? {
+                    Some(position) => flatbuffer::string(&buffer, position)?.to_owned(),
+                    None => String::new(),
+                })
+            };
+            match action {
+                ACTION_DUMP_TREE => return Ok(config),
+                ACTION_DUMP_TREE_NODE => {
+                    config.nodes.entry(string(FIELD_NODE)?).or_default();
+                }
+                ACTION_DUMP_TREE_ATTR => {
+                    config
+                        .nodes
+                        .entry(string(FIELD_NODE)?)
+                        .or_default()
+                        .insert(string(FIELD_KEY)?, string(FIELD_VALUE)?);
+                }
+                ACTION_ERROR => {
+                    return Err(ParseError::General(format!(
+                        "the config server returned an error: {}",
+                        string(FIELD_VALUE)?
+                    )))
+                }
+                // push messages from other clients may be interleaved
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads a DV XML tree (`<dv>` root, nested `node` elements with a `path`, and `attr` elements).
+    pub fn from_xml(xml: &str) -> Result<Self, ParseError> {
+        let document = roxmltree::Document::parse(xml)?;
+        let mut config = RuntimeConfig::default();
+        for node in document
+            .descendants()
+            .filter(|node| node.is_element() && node.has_tag_name("node"))
+        {
+            let path = match node.attribute("path") {
+                Some(path) => path.to_owned(),
+                None => continue,
+            };
+            let attributes = config.nodes.entry(path).or_default();
+            for attribute in node
+                .children()
+                .filter(|child| child.is_element() && child.has_tag_name("attr"))
+            {
+                if let Some(key) = attribute.attribute("key") {
+                    attributes.insert(key.to_owned(), attribute.text().unwrap_or("").to_owned());
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Adds the nodes and attributes of another tree, which take precedence.
+    pub fn merge(&mut self, other: RuntimeConfig) {
+        for (path, attributes) in other.nodes {
+            self.nodes.entry(path).or_default().extend(attributes);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Node paths in lexicographic order (parents before children).
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.nodes.keys().map(|path| path.as_str())
+    }
+
+    pub fn node(&self, path: &str) -> Option<&BTreeMap<String, String>> {
+        self.nodes.get(path)
+    }
+
+    pub fn get(&self, path: &str, key: &str) -> Option<&str> {
+        self.nodes.get(path)?.get(key).map(|value| value.as_str())
+    }
+
+    /// Attributes of the `bias` nodes, as (name relative to the bias node, value).
+    ///
+    /// DAVIS cameras have one node per bias (for instance `DiffBn/coarseValue`), and DVXplorer cameras
+    /// have attributes directly in the bias node.
+    pub fn biases(&self) -> Vec<(String, String)> {
+        let mut biases = Vec::new();
+        for (path, attributes) in self.nodes.iter() {
+            let relative = match path.find("/bias/") {
+                Some(index) => &path[index + "/bias/".len()..],
+                None => continue,
+            };
+            for (key, value) in attributes.iter() {
+                biases.push((format!("{}{}", relative, key), value.clone()));
+            }
+        }
+        biases
+    }
+
+    /// Camera model reported by the capture module (for instance `DAVIS346`), read from the `source` attribute
+    /// of stream information nodes, which has the form `MODEL_SERIAL`.
+    pub fn sensor_model(&self) -> Option<&str> {
+        self.nodes
+            .values()
+            .filter_map(|attributes| attributes.get("source"))
+            .map(|source| {
+                source
+                    .rsplit_once('_')
+                    .map_or(source.as_str(), |(model, _)| model)
+            })
+            .next()
+    }
+}
+
+fn dump_request() -> Vec<u8> {
+    let mut builder = flatbuffers::FlatBufferBuilder::new();
+    let start = builder.start_table();
+    builder.push_slot_always::<i8>(
+        4 + 2 * FIELD_ACTION as flatbuffers::VOffsetT,
+        ACTION_DUMP_TREE,
+    );
+    let root = builder.end_table(start);
+    builder.finish_size_prefixed(root, None);
+    builder.finished_data().to_vec()
+}
@@ -0,0 +1,154 @@
+//! Geometry and properties of common event cameras.
+//!
+//! `Decoder::camera_model` identifies the sensor of a recording from the `source` attribute of the
+//! IOHeader description (for instance `DAVIS346_00000002`), or from the runtime configuration, and falls
+//! back to the resolution of the event stream when it matches a single known sensor.
+
+use crate::base::{Decoder, StreamContent};
+use crate::runtime::RuntimeConfig;
+
+/// A known sensor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorModel {
+    pub name: &'static str,
+    pub vendor: &'static str,
+    pub width: u16,
+    pub height: u16,
+    /// Pixel pitch in micrometres.
+    pub pixel_pitch: f64,
+    /// Whether the sensor also produces intensity frames (APS).
+    pub frames: bool,
+    /// Whether cameras with this sensor usually embed an IMU.
+    pub imu: bool,
+    /// Uppercase prefixes of the model names reported by drivers.
+    pub aliases: &'static [&'static str],
+}
+
+impl SensorModel {
+    /// Size of the pixel array in millimetres.
+    pub fn size_mm(&self) -> (f64, f64) {
+        (
+            self.width as f64 * self.pixel_pitch * 1e-3,
+            self.height as f64 * self.pixel_pitch * 1e-3,
+        )
+    }
+
+    /// Focal length in pixels of a lens with the given focal length in millimetres, a common calibration prior.
+    pub fn focal_length_pixels(&self, focal_length_mm: f64) -> f64 {
+        focal_length_mm * 1e3 / self.pixel_pitch
+    }
+
+    fn matches(&self, model: &str) -> bool {
+        let model = model.to_uppercase();
+        self.aliases.iter().any(|alias| model.starts_with(alias))
+    }
+}
+
+pub const DAVIS240: SensorModel = SensorModel {
+    name: "DAVIS240",
+    vendor: "iniVation",
+    width: 240,
+    height: 180,
+    pixel_pitch: 18.5,
+    frames: true,
+    imu: true,
+    aliases: &["DAVIS240"],
+};
+
+pub const DAVIS346: SensorModel = SensorModel {
+    name: "DAVIS346",
+    vendor: "iniVation",
+    width: 346,
+    height: 260,
+    pixel_pitch: 18.5,
+    frames: true,
+    imu: true,
+    aliases: &["DAVIS346"],
+};
+
+pub const DVXPLORER: SensorModel = SensorModel {
+    name: "DVXplorer",
+    vendor: "iniVation",
+    width: 640,
+    height: 480,
+    pixel_pitch: 9.0,
+    frames: false,
+    imu: true,
+    aliases: &["DVXPLORER", "DVXPLR"],
+};
+
+pub const PROPHESEE_GEN3: SensorModel = SensorModel {
+    name: "Prophesee Gen3",
+    vendor: "Prophesee",
+    width: 640,
+    height: 480,
+    pixel_pitch: 15.0,
+    frames: false,
+    imu: false,
+    aliases: &["GEN3", "PPS3MVCD", "EVK3"],
+};
+
+pub const PROPHESEE_GEN4: SensorModel = SensorModel {
+    name: "Prophesee Gen4",
+    vendor: "Prophesee",
+    width: 1280,
+    height: 720,
+    pixel_pitch: 4.86,
+    frames: false,
+    imu: false,
+    aliases: &["GEN4", "IMX636", "EVK4"],
+};
+
+/// Every known sensor.
+pub const SENSORS: &[SensorModel] = &[
+    DAVIS240,
+    DAVIS346,
+    DVXPLORER,
+    PROPHESEE_GEN3,
+    PROPHESEE_GEN4,
+];
+
+/// Finds a sensor by model name, for instance `DAVIS346` or `DVXplorer` (case insensitive).
+pub fn by_name(model: &str) -> Option<&'static SensorModel> {
+    SENSORS.iter().find(|sensor| sensor.matches(model))
+}
+
+/// Finds the sensor with the given resolution, if exactly one sensor matches.
+pub fn by_resolution(width: u16, height: u16) -> Option<&'static SensorModel> {
+    let mut sensors = SENSORS
+        .iter()
+        .filter(|sensor| sensor.width == width && sensor.height == height);
+    match (sensors.next(), sensors.next()) {
+        (Some(sensor), None) => Some(sensor),
+        _ => None,
+    }
+}
+
+/// Identifies a sensor from the model name reported by the driver, then from the resolution.
+pub fn detect(model: Option<&str>, width: u16, height: u16) -> Option<&'static SensorModel> {
+    model
+        .and_then(by_name)
+        .or_else(|| by_resolution(width, height))
+}
+
+impl Decoder {
+    /// Sensor of the recording (see the module documentation), for calibration defaults.
+    pub fn camera_model(&self) -> Option<&'static SensorModel> {
+        let model = match self
+            .runtime_config()
+            .and_then(|config| config.sensor_model())
+        {
+            Some(model) => Some(model.to_owned()),
+            None => RuntimeConfig::from_xml(self.description())
+                .ok()
+                .and_then(|config| config.sensor_model().map(|model| model.to_owned())),
+        };
+        let (width, height) = self
+            .id_to_stream
+            .values()
+            .filter(|stream| stream.content == StreamContent::Events)
+            .find_map(|stream| stream.resolution())
+            .unwrap_or((0, 0));
+        detect(model.as_deref(), width, height)
+    }
+}
@@ -0,0 +1,190 @@
+use crate::base::{Decoder, Decompressor, Packet, ParseError, RawPacket, Stream};
+
+struct State {
+    decoder: Decoder,
+    /// Streams with a packet being decompressed or processed, in per-stream order mode.
+    busy: std::collections::HashSet<u32>,
+    /// Compressed packets read while their stream was busy, in file order.
+    deferred: std::collections::VecDeque<RawPacket>,
+    done: bool,
+}
+
+struct Shared {
+    state: std::sync::Mutex<State>,
+    released: std::sync::Condvar,
+    decompressor: Decompressor,
+    per_stream_order: bool,
+    max_deferred: usize,
+}
+
+/// A decoder that several threads pull packets from (see `Decoder::shared`).
+///
+/// Clones share the same decoder. Each packet is given to a single thread.
+#[derive(Clone)]
+pub struct SharedDecoder {
+    pub id_to_stream: std::collections::HashMap<u32, Stream>,
+    shared: std::sync::Arc<Shared>,
+}
+
+impl Decoder {
+    /// Shares the decoder between threads, which get the next packet as soon as they are idle.
+    ///
+    /// If `per_stream_order` is set, a thread gets a packet only once the previous packet of the same stream
+    /// has been dropped, hence the packets of each stream are processed one at a time and in file order.
+    /// Packets are decompressed by the threads that get them, outside the lock on the decoder.
+    pub fn shared(self, per_stream_order: bool) -> SharedDecoder {
+        SharedDecoder {
+            id_to_stream: self.id_to_stream.clone(),
+            shared: std::sync::Arc::new(Shared {
+                decompressor: self.decompressor(),
+                state: std::sync::Mutex::new(State {
+                    decoder: self,
+                    busy: std::collections::HashSet::new(),
+                    deferred: std::collections::VecDeque::new(),
+                    done: false,
+                }),
+                released: std::sync::Condvar::new(),
+                per_stream_order,
+                max_deferred: 64,
+            }),
+        }
+    }
+}
+
+impl SharedDecoder {
+    /// Returns the next packet, or None once all the packets have been given out.
+    ///
+    /// Blocks while packets are deferred by the per-stream order. In this mode, a thread must drop its
+    /// packet before asking for the next one: otherwise it blocks forever once 64 packets are deferred
+    /// and all of them belong to its stream.
+    pub fn next_packet(&self) -> Option<Result<SharedPacket, ParseError>> {
+        let shared = &self.shared;
+        let mut state = shared.state.lock().expect("a worker panicked");
+        loop {
+            let mut packet = loop {
+                if let Some(index) = state
+                    .deferred
+                    .iter()
+                    .position(|packet| !state.busy.contains(&packet.stream_id))
+                {
+                    break state.deferred.remove(index).unwrap();
+                }
+                if state.done || state.deferred.len() >= shared.max_deferred {
+                    if state.deferred.is_empty() {
+                        return None;
+                    }
+                    state = shared.released.wait(state).expect("a worker panicked");
+                    continue;
+                }
+                match state.decoder.read_raw() {
+                    Some(Ok(packet)) => {
+                        if shared.per_stream_order && state.busy.contains(&packet.stream_id) {
+                            state.deferred.push_back(packet);
+                        } else {
+                            break packet;
+                        }
+                    }
+                    Some(Err(error)) => return Some(Err(error)),
+                    None => state.done = true,
+                }
+            };
+            let stream_id = packet.stream_id;
+            if shared.per_stream_order {
+                state.busy.insert(stream_id);
+            }
+            drop(state);
+            let (buffer, result) = shared.decompressor.decompress(&mut packet);
+            state = shared.state.lock().expect("a worker panicked");
+            match state.decoder.finish_raw(packet, buffer, result) {
+                Some(Ok(packet)) => {
+                    return Some(Ok(SharedPacket {
+                        packet,
+                        shared: shared.clone(),
+                    }))
+                }
+                Some(Err(error)) => {
+                    self.release(&mut state, stream_id);
+                    return Some(Err(error));
+                }
+                None => self.release(&mut state, stream_id),
+            }
+        }
+    }
+
+    /// Calls `process` on every packet from `threads` threads, and returns the first error.
+    ///
+    /// Errors returned by `process` stop all the threads after their current packet.
+    pub fn for_each<F>(&self, threads: usize, process: F) -> Result<(), ParseError>
+    where
+        F: Fn(&Packet) -> Result<(), ParseError> + Sync,
+    {
+        let failed = std::sync::atomic::AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            let workers = (0..threads.max(1))
+                .map(|_| {
+                    scope.spawn(|| -> Result<(), ParseError> {
+                        while !failed.load(std::sync::atomic::Ordering::Relaxed) {
+                            let packet = match self.next_packet() {
+                                Some(packet) => packet,
+                                None => return Ok(()),
+                            };
+                            if let Err(error) = packet.and_then(|packet| process(&packet)) {
+                                failed.store(true, std::sync::atomic::Ordering::Relaxed);
+                                return Err(error);
+                            }
+                        }
+                        Ok(())
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("a worker panicked"))
+                .collect::<Result<Vec<()>, ParseError>>()
+                .map(|_| ())
+        })
+    }
+
+    fn release(&self, state: &mut State, stream_id: u32) {
+        if self.shared.per_stream_order {
+            state.busy.remove(&stream_id);
+            self.shared.released.notify_all();
+        }
+    }
+}
+
+/// A packet given to one thread, which releases its stream when dropped (see `Decoder::shared`).
+pub struct SharedPacket {
+    packet: Packet,
+    shared: std::sync::Arc<Shared>,
+}
+
+impl SharedPacket {
+    /// Takes the packet out and releases its stream.
+    pub fn into_packet(mut self) -> Packet {
+        Packet {
+            buffer: std::mem::take(&mut self.packet.buffer),
+            stream_id: self.packet.stream_id,
+            arrival_time: self.packet.arrival_time,
+        }
+    }
+}
+
+impl std::ops::Deref for SharedPacket {
+    type Target = Packet;
+
+    fn deref(&self) -> &Packet {
+        &self.packet
+    }
+}
+
+impl Drop for SharedPacket {
+    fn drop(&mut self) {
+        if self.shared.per_stream_order {
+            if let Ok(mut state) = self.shared.state.lock() {
+                state.busy.remove(&self.packet.stream_id);
+            }
+            self.shared.released.notify_all();
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! ESIM-style event simulation from intensity frames.
+//!
+//! Each pixel keeps the log intensity of its last event (the reference). Between two frames, the log intensity
+//! of every pixel is interpolated linearly, and an event is generated each time it moves away from the reference
+//! by a contrast threshold, at the interpolated crossing time. The reference then moves by one threshold.
+//!
+//! See Rebecq et al., "ESIM: an Open Event Camera Simulator", CoRL 2018.
+
+use crate::base::{ioheader_generated, Packet, ParseError, Stream, StreamContent};
+use crate::encoder::Encoder;
+use crate::events::Event;
+
+/// Event camera model fed with intensity frames.
+pub struct Simulator {
+    width: u16,
+    height: u16,
+    positive_threshold: f32,
+    negative_threshold: f32,
+    refractory_period: i64,
+    epsilon: f32,
+    reference: Vec<f32>,
+    previous: Option<(i64, Vec<f32>)>,
+    last_event_t: Vec<i64>,
+}
+
+impl Simulator {
+    /// The contrast thresholds are 0.2, the refractory period is zero and `epsilon` is 1e-3 by default.
+    pub fn new(width: u16, height: u16) -> Self {
+        let pixels = width as usize * height as usize;
+        Simulator {
+            width,
+            height,
+            positive_threshold: 0.2,
+            negative_threshold: 0.2,
+            refractory_period: 0,
+            epsilon: 1e-3,
+            reference: vec![0.0; pixels],
+            previous: None,
+            last_event_t: vec![i64::MIN; pixels],
+        }
+    }
+
+    /// Log intensity changes that trigger ON and OFF events (both positive).
+    pub fn thresholds(mut self, positive: f32, negative: f32) -> Self {
+        self.positive_threshold = positive.max(f32::EPSILON);
+        self.negative_threshold = negative.max(f32::EPSILON);
+        self
+    }
+
+    /// Minimum time between two events of the same pixel in microseconds. Suppressed crossings still move
+    /// the reference.
+    pub fn refractory_period(mut self, refractory_period: i64) -> Self {
+        self.refractory_period = refractory_period;
+        self
+    }
+
+    /// Offset added to intensities (in [0, 1]) before taking the logarithm, to avoid log(0).
+    pub fn epsilon(mut self, epsilon: f32) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Adds a frame of log intensities (row-major) at time `t`, and returns the events generated since
+    /// the previous frame, sorted by timestamp. The first frame initializes the references.
+    pub fn push_log_frame(&mut self, t: i64, frame: &[f32]) -> Result<Vec<Event>, ParseError> {
+        if frame.len() != self.reference.len() {
+            return Err(ParseError::Usage(format!(
+                "the frame has {} pixels, the simulator expects {}x{}",
+                frame.len(),
+                self.width,
+                self.height
+            )));
+        }
+        let mut events = Vec::new();
+        let (previous_t, previous) = match self.previous.take() {
+            Some(previous) => previous,
+            None => {
+                self.reference.copy_from_slice(frame);
+                self.previous = Some((t, frame.to_vec()));
+                return Ok(events);
+            }
+        };
+        let duration = (t - previous_t) as f64;
+        for (index, (begin, end)) in previous.iter().zip(frame.iter()).enumerate() {
+            if begin == end {
+                continue;
+            }
+            let (x, y) = (
+                (index % self.width as usize) as i16,
+                (index / self.width as usize) as i16,
+            );
+            let reference = &mut self.reference[index];
+            loop {
+                let (level, on) = if *end > *begin && *reference + self.positive_threshold <= *end {
+                    (*reference + self.positive_threshold, true)
+                } else if *end < *begin && *reference - self.negative_threshold >= *end {
+                    (*reference - self.negative_threshold, false)
+                } else {
+                    break;
+                };
+                *reference = level;
+                let event_t = previous_t
+                    + (((level - begin) / (end - begin)).clamp(0.0, 1.0) as f64 * duration) as i64;
+                if self.last_event_t[index] == i64::MIN
+                    || event_t - self.last_event_t[index] >= self.refractory_period
+                {
+                    self.last_event_t[index] = event_t;
+                    events.push(Event::new(event_t, x, y, on));
+                }
+            }
+        }
+        events.sort_by_key(|event| event.t());
+        self.previous = Some((t, frame.to_vec()));
+        Ok(events)
+    }
+
+    /// Adds an image at time `t` (see `push_log_frame`). Color images are converted to luma.
+    pub fn push(&mut self, t: i64, image: &image::DynamicImage) -> Result<Vec<Event>, ParseError> {
+        if image.width() != self.width as u32 || image.height() != self.height as u32 {
+            return Err(ParseError::Usage(format!(
+                "the image is {}x{}, the simulator expects {}x{}",
+                image.width(),
+                image.height(),
+                self.width,
+                self.height
+            )));
+        }
+        let frame: Vec<f32> = image
+            .to_luma32f()
+            .into_raw()
+            .into_iter()
+            .map(|intensity| (intensity.clamp(0.0, 1.0) + self.epsilon).ln())
+            .collect();
+        self.push_log_frame(t, &frame)
+    }
+}
+
+/// Simulates the events of a sequence of timestamped images (in microseconds) and writes them to an AEDAT4
+/// file, with one packet per interval between frames. Returns the number of events.
+pub fn simulate_to_file<P, I>(
+    frames: I,
+    mut simulator: Simulator,
+    path: P,
+    compression: ioheader_generated::Compression,
+) -> Result<u64, ParseError>
+where
+    P: std::convert::AsRef<std::path::Path>,
+    I: IntoIterator<Item = (i64, image::DynamicImage)>,
+{
+    let mut encoder = Encoder::new_to_file(
+        path,
+        std::collections::HashMap::from([(
+            0,
+            Stream::new(StreamContent::Events, simulator.width, simulator.height),
+        )]),
+        compression,
+    )?;
+    let mut count = 0;
+    for (t, image) in frames {
+        let events = simulator.push(t, &image)?;
+        if !events.is_empty() {
+            count += events.len() as u64;
+            encoder.write(&Packet::from_events(0, &events))?;
+        }
+    }
+    encoder.finish()?;
+    Ok(count)
+}
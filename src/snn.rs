@@ -0,0 +1,246 @@
+//! Export of event windows to spiking neural network frameworks (Norse, snnTorch...).
+//!
+//! A `SpikeTensor` is a sparse (time bins, polarity, height, width) tensor in coordinate (COO) format, the layout
+//! expected by `torch.sparse_coo_tensor`. With the `safetensors` feature, `write_safetensors` saves a batch of
+//! windows as a single file that PyTorch loads with `safetensors.torch.load_file`:
+//! - `indices`: (5, nnz) int64 tensor of (sample, bin, polarity, y, x) coordinates
+//! - `values`: (nnz) float32 tensor of spike counts
+//! - `begin_t` and `end_t`: (samples) int64 tensors with the timestamps of the first and last event of each window
+//!
+//! The metadata contains the dense `shape` (`samples,bins,2,height,width`), hence
+//! `torch.sparse_coo_tensor(tensors["indices"], tensors["values"], shape).to_dense()` recovers the input of
+//! the network.
+
+use crate::base::ParseError;
+use crate::chunks::{Chunks, Window};
+use crate::events::Event;
+
+/// Sparse (bins, 2, height, width) spike tensor of a window, with coordinates sorted in row-major order
+/// and without duplicates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpikeTensor {
+    pub begin_t: i64,
+    pub end_t: i64,
+    /// Dense shape, (bins, 2, height, width).
+    pub shape: [usize; 4],
+    /// (bin, polarity, y, x) coordinates. The polarity is 0 for OFF events and 1 for ON events.
+    pub indices: Vec<[i64; 4]>,
+    /// Number of spikes at each coordinate (1 for binary tensors).
+    pub values: Vec<f32>,
+}
+
+impl SpikeTensor {
+    /// Number of non-zero elements.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Dense tensor, flattened in (bin, polarity, y, x) order.
+    pub fn to_dense(&self) -> Vec<f32> {
+        let mut dense = vec![0.0; self.shape.iter().product()];
+        for (index, value) in self.indices.iter().zip(self.values.iter()) {
+            let offset = ((index[0] as usize * self.shape[1] + index[1] as usize) * self.shape[2]
+                + index[2] as usize)
+                * self.shape[3]
+                + index[3] as usize;
+            dense[offset] = *value;
+        }
+        dense
+    }
+}
+
+/// Converts windows of events into spike tensors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpikeEncoder {
+    bins: usize,
+    width: u16,
+    height: u16,
+    binary: bool,
+}
+
+impl SpikeEncoder {
+    pub fn new(bins: usize, width: u16, height: u16) -> Self {
+        SpikeEncoder {
+            bins: bins.max(1),
+            width,
+            height,
+            binary: false,
+        }
+    }
+
+    /// Clamps counts to 1, as expected by networks trained on binary spikes (snnTorch `spikegen`).
+    pub fn binary(mut self, binary: bool) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    /// Splits [first event timestamp, last event timestamp] into `bins` time bins of equal duration.
+    /// Events outside the sensor are ignored.
+    pub fn encode(&self, events: &[Event]) -> SpikeTensor {
+        let begin_t = events.first().map_or(0, |event| event.t());
+        let end_t = events.last().map_or(0, |event| event.t());
+        let shape = [self.bins, 2, self.height as usize, self.width as usize];
+        let duration = (end_t - begin_t + 1).max(1) as i128;
+        let mut offsets: Vec<usize> = events
+            .iter()
+            .filter(|event| {
+                event.x() >= 0
+                    && event.y() >= 0
+                    && (event.x() as u16) < self.width
+                    && (event.y() as u16) < self.height
+            })
+            .map(|event| {
+                let bin = ((event.t() - begin_t).max(0) as i128 * self.bins as i128 / duration)
+                    .min(self.bins as i128 - 1) as usize;
+                ((bin * 2 + event.on() as usize) * shape[2] + event.y() as usize) * shape[3]
+                    + event.x() as usize
+            })
+            .collect();
+        offsets.sort_unstable();
+        let mut indices: Vec<[i64; 4]> = Vec::new();
+        let mut values: Vec<f32> = Vec::new();
+        let mut previous = None;
+        for offset in offsets {
+            if previous == Some(offset) {
+                if !self.binary {
+                    *values.last_mut().unwrap() += 1.0;
+                }
+                continue;
+            }
+            previous = Some(offset);
+            indices.push([
+                (offset / (shape[1] * shape[2] * shape[3])) as i64,
+                (offset / (shape[2] * shape[3]) % shape[1]) as i64,
+                (offset / shape[3] % shape[2]) as i64,
+                (offset % shape[3]) as i64,
+            ]);
+            values.push(1.0);
+        }
+        SpikeTensor {
+            begin_t,
+            end_t,
+            shape,
+            indices,
+            values,
+        }
+    }
+
+    /// Converts an event stream into one spike tensor per window. Empty windows are skipped.
+    pub fn windows<I: Iterator<Item = Result<Event, ParseError>>>(
+        self,
+        events: I,
+        window: Window,
+    ) -> SpikeTensors<I> {
+        SpikeTensors {
+            encoder: self,
+            chunks: Chunks::new(events, window),
+        }
+    }
+}
+
+/// Spike tensors of consecutive windows (see `SpikeEncoder::windows`).
+pub struct SpikeTensors<I> {
+    encoder: SpikeEncoder,
+    chunks: Chunks<I>,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for SpikeTensors<I> {
+    type Item = Result<SpikeTensor, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.chunks.next()? {
+                Ok(events) => {
+                    if !events.is_empty() {
+                        return Some(Ok(self.encoder.encode(&events)));
+                    }
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// Writes a batch of spike tensors with the same shape to a safetensors file (see the module documentation).
+#[cfg(feature = "safetensors")]
+pub fn write_safetensors<P: std::convert::AsRef<std::path::Path>>(
+    tensors: &[SpikeTensor],
+    path: P,
+) -> Result<(), ParseError> {
+    use safetensors::tensor::TensorView;
+    use safetensors::Dtype;
+
+    let shape = tensors.first().map_or([0, 0, 2, 0, 0], |tensor| {
+        [
+            tensors.len(),
+            tensor.shape[0],
+            tensor.shape[1],
+            tensor.shape[2],
+            tensor.shape[3],
+        ]
+    });
+    if let Some(tensor) = tensors.iter().find(|tensor| tensor.shape[..] != shape[1..]) {
+        return Err(ParseError::Usage(format!(
+            "all the spike tensors must have the same shape ({:?} and {:?})",
+            &shape[1..],
+            tensor.shape
+        )));
+    }
+    let nnz: usize = tensors.iter().map(SpikeTensor::nnz).sum();
+    let mut indices = vec![0i64; 5 * nnz];
+    let mut values = Vec::with_capacity(nnz * 4);
+    let mut position = 0;
+    for (sample, tensor) in tensors.iter().enumerate() {
+        for (index, value) in tensor.indices.iter().zip(tensor.values.iter()) {
+            indices[position] = sample as i64;
+            for dimension in 0..4 {
+                indices[(dimension + 1) * nnz + position] = index[dimension];
+            }
+            values.extend_from_slice(&value.to_le_bytes());
+            position += 1;
+        }
+    }
+    let indices: Vec<u8> = indices
+        .iter()
+        .flat_map(|index| index.to_le_bytes())
+        .collect();
+    let begin_t: Vec<u8> = tensors
+        .iter()
+        .flat_map(|tensor| tensor.begin_t.to_le_bytes())
+        .collect();
+    let end_t: Vec<u8> = tensors
+        .iter()
+        .flat_map(|tensor| tensor.end_t.to_le_bytes())
+        .collect();
+    let metadata = std::collections::HashMap::from([
+        ("format".to_owned(), "sparse_coo".to_owned()),
+        (
+            "shape".to_owned(),
+            shape
+                .iter()
+                .map(|length| length.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+    ]);
+    safetensors::tensor::serialize_to_file(
+        [
+            (
+                "indices",
+                TensorView::new(Dtype::I64, vec![5, nnz], &indices)?,
+            ),
+            ("values", TensorView::new(Dtype::F32, vec![nnz], &values)?),
+            (
+                "begin_t",
+                TensorView::new(Dtype::I64, vec![tensors.len()], &begin_t)?,
+            ),
+            (
+                "end_t",
+                TensorView::new(Dtype::I64, vec![tensors.len()], &end_t)?,
+            ),
+        ],
+        &Some(metadata),
+        path.as_ref(),
+    )?;
+    Ok(())
+}
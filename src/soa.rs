@@ -0,0 +1,331 @@
+//! Structure-of-arrays events, for vectorized processing.
+//!
+//! `EventSoA` keeps the signed types of the AEDAT format, and `EventBatch` uses unsigned types, which are
+//! what most numeric code expects. Valid AEDAT events have non-negative timestamps and coordinates,
+//! hence both represent the same values.
+//!
+//! On x86_64, unpacking uses SSE2 (part of the baseline instruction set) and handles four events per iteration.
+//! Other architectures use a scalar loop.
+
+use crate::base::{Packet, ParseError};
+use crate::events::Event;
+use crate::events_generated;
+
+/// Events stored as one array per field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventSoA {
+    pub t: Vec<i64>,
+    pub x: Vec<i16>,
+    pub y: Vec<i16>,
+    pub on: Vec<bool>,
+}
+
+impl EventSoA {
+    pub fn new() -> Self {
+        EventSoA::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        EventSoA {
+            t: Vec::with_capacity(capacity),
+            x: Vec::with_capacity(capacity),
+            y: Vec::with_capacity(capacity),
+            on: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn from_events(events: &[Event]) -> Self {
+        let mut soa = EventSoA::with_capacity(events.len());
+        soa.extend_from_events(events);
+        soa
+    }
+
+    pub fn len(&self) -> usize {
+        self.t.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.t.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.t.clear();
+        self.x.clear();
+        self.y.clear();
+        self.on.clear();
+    }
+
+    pub fn event(&self, index: usize) -> Event {
+        Event::new(self.t[index], self.x[index], self.y[index], self.on[index])
+    }
+
+    /// Appends events.
+    pub fn extend_from_events(&mut self, events: &[Event]) {
+        let begin = self.len();
+        let end = begin + events.len();
+        self.t.resize(end, 0);
+        self.x.resize(end, 0);
+        self.y.resize(end, 0);
+        self.on.resize(end, false);
+        unpack(
+            events,
+            &mut self.t[begin..],
+            &mut self.x[begin..],
+            &mut self.y[begin..],
+            &mut self.on[begin..],
+        );
+    }
+}
+
+/// Events stored as one array per field, with unsigned timestamps and coordinates.
+///
+/// Negative timestamps and coordinates (invalid in AEDAT files) wrap around.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventBatch {
+    pub t: Vec<u64>,
+    pub x: Vec<u16>,
+    pub y: Vec<u16>,
+    pub p: Vec<bool>,
+}
+
+impl EventBatch {
+    pub fn new() -> Self {
+        EventBatch::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        EventBatch {
+            t: Vec::with_capacity(capacity),
+            x: Vec::with_capacity(capacity),
+            y: Vec::with_capacity(capacity),
+            p: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn from_events(events: &[Event]) -> Self {
+        let mut batch = EventBatch::with_capacity(events.len());
+        batch.extend_from_events(events);
+        batch
+    }
+
+    pub fn len(&self) -> usize {
+        self.t.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.t.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.t.clear();
+        self.x.clear();
+        self.y.clear();
+        self.p.clear();
+    }
+
+    pub fn event(&self, index: usize) -> Event {
+        Event::new(
+            self.t[index] as i64,
+            self.x[index] as i16,
+            self.y[index] as i16,
+            self.p[index],
+        )
+    }
+
+    /// Appends events.
+    pub fn extend_from_events(&mut self, events: &[Event]) {
+        let begin = self.len();
+        let end = begin + events.len();
+        self.t.resize(end, 0);
+        self.x.resize(end, 0);
+        self.y.resize(end, 0);
+        self.p.resize(end, false);
+        let (t, x, y) = (
+            &mut self.t[begin..],
+            &mut self.x[begin..],
+            &mut self.y[begin..],
+        );
+        // SAFETY: signed and unsigned integers of the same size have the same layout, and every bit pattern is valid
+        unsafe {
+            unpack(
+                events,
+                std::slice::from_raw_parts_mut(t.as_mut_ptr() as *mut i64, t.len()),
+                std::slice::from_raw_parts_mut(x.as_mut_ptr() as *mut i16, x.len()),
+                std::slice::from_raw_parts_mut(y.as_mut_ptr() as *mut i16, y.len()),
+                &mut self.p[begin..],
+            );
+        }
+    }
+}
+
+impl From<&[Event]> for EventBatch {
+    fn from(events: &[Event]) -> Self {
+        EventBatch::from_events(events)
+    }
+}
+
+impl From<EventSoA> for EventBatch {
+    fn from(soa: EventSoA) -> Self {
+        EventBatch {
+            t: soa.t.into_iter().map(|t| t as u64).collect(),
+            x: soa.x.into_iter().map(|x| x as u16).collect(),
+            y: soa.y.into_iter().map(|y| y as u16).collect(),
+            p: soa.on,
+        }
+    }
+}
+
+impl Packet {
+    /// Reads the events of an EVTS packet as arrays, without an intermediate `Vec<Event>`.
+    pub fn events_soa(&self) -> Result<EventSoA, ParseError> {
+        let mut soa = EventSoA::new();
+        self.extend_soa(&mut soa)?;
+        Ok(soa)
+    }
+
+    /// Appends the events of an EVTS packet, which lets callers reuse the arrays between packets.
+    pub fn extend_soa(&self, soa: &mut EventSoA) -> Result<(), ParseError> {
+        self.expect_identifier(events_generated::EVENT_PACKET_IDENTIFIER)?;
+        if let Some(events) =
+            events_generated::size_prefixed_root_as_event_packet(&self.buffer)?.elements()
+        {
+            soa.extend_from_events(events);
+        }
+        Ok(())
+    }
+
+    /// Reads the events of an EVTS packet as unsigned arrays, without an intermediate `Vec<Event>`.
+    pub fn event_batch(&self) -> Result<EventBatch, ParseError> {
+        let mut batch = EventBatch::new();
+        self.extend_batch(&mut batch)?;
+        Ok(batch)
+    }
+
+    /// Appends the events of an EVTS packet, which lets callers reuse the arrays between packets.
+    pub fn extend_batch(&self, batch: &mut EventBatch) -> Result<(), ParseError> {
+        self.expect_identifier(events_generated::EVENT_PACKET_IDENTIFIER)?;
+        if let Some(events) =
+            events_generated::size_prefixed_root_as_event_packet(&self.buffer)?.elements()
+        {
+            batch.extend_from_events(events);
+        }
+        Ok(())
+    }
+}
+
+/// Converts event packets into batches, one per EVTS packet (see `PacketIterator::event_batches`).
+///
+/// Packets that do not belong to an event stream are skipped.
+pub struct EventBatches<I> {
+    packets: I,
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> EventBatches<I> {
+    pub fn new(packets: I) -> Self {
+        EventBatches { packets }
+    }
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> Iterator for EventBatches<I> {
+    type Item = Result<EventBatch, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let packet = match self.packets.next()? {
+                Ok(packet) => packet,
+                Err(error) => return Some(Err(error)),
+            };
+            if packet.has_identifier(events_generated::EVENT_PACKET_IDENTIFIER) {
+                return Some(packet.event_batch());
+            }
+        }
+    }
+}
+
+/// Converts chunks of events into batches (see `Chunks::batches`).
+pub struct Batched<I> {
+    chunks: I,
+}
+
+impl<I: Iterator<Item = Result<Vec<Event>, ParseError>>> Batched<I> {
+    pub fn new(chunks: I) -> Self {
+        Batched { chunks }
+    }
+}
+
+impl<I: Iterator<Item = Result<Vec<Event>, ParseError>>> Iterator for Batched<I> {
+    type Item = Result<EventBatch, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.chunks
+                .next()?
+                .map(|chunk| EventBatch::from_events(&chunk)),
+        )
+    }
+}
+
+fn unpack_scalar(events: &[Event], t: &mut [i64], x: &mut [i16], y: &mut [i16], on: &mut [bool]) {
+    for (index, event) in events.iter().enumerate() {
+        t[index] = event.t();
+        x[index] = event.x();
+        y[index] = event.y();
+        on[index] = event.0[12] != 0;
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn unpack(events: &[Event], t: &mut [i64], x: &mut [i16], y: &mut [i16], on: &mut [bool]) {
+    use std::arch::x86_64::*;
+    let length = events.len() / 4 * 4;
+    assert!(t.len() >= length && x.len() >= length && y.len() >= length && on.len() >= length);
+    for base in (0..length).step_by(4) {
+        // SAFETY: SSE2 is available on every x86_64 processor, and the unaligned loads and stores are in bounds
+        unsafe {
+            let e0 = _mm_loadu_si128(events[base].0.as_ptr() as *const __m128i);
+            let e1 = _mm_loadu_si128(events[base + 1].0.as_ptr() as *const __m128i);
+            let e2 = _mm_loadu_si128(events[base + 2].0.as_ptr() as *const __m128i);
+            let e3 = _mm_loadu_si128(events[base + 3].0.as_ptr() as *const __m128i);
+            // the low halves are the timestamps
+            _mm_storeu_si128(
+                t.as_mut_ptr().add(base) as *mut __m128i,
+                _mm_unpacklo_epi64(e0, e1),
+            );
+            _mm_storeu_si128(
+                t.as_mut_ptr().add(base + 2) as *mut __m128i,
+                _mm_unpacklo_epi64(e2, e3),
+            );
+            // the high halves are [x, y, on, padding] as 16-bit words, transposed with two unpack rounds
+            let high01 = _mm_unpackhi_epi64(e0, e1);
+            let high23 = _mm_unpackhi_epi64(e2, e3);
+            let low = _mm_unpacklo_epi16(high01, high23);
+            let high = _mm_unpackhi_epi16(high01, high23);
+            let xy = _mm_unpacklo_epi16(low, high);
+            let polarities = _mm_unpackhi_epi16(low, high);
+            _mm_storel_epi64(x.as_mut_ptr().add(base) as *mut __m128i, xy);
+            _mm_storel_epi64(
+                y.as_mut_ptr().add(base) as *mut __m128i,
+                _mm_unpackhi_epi64(xy, xy),
+            );
+            let polarities = _mm_and_si128(polarities, _mm_set1_epi16(0xff));
+            let polarities =
+                _mm_min_epu8(_mm_packus_epi16(polarities, polarities), _mm_set1_epi8(1));
+            std::ptr::write_unaligned(
+                on.as_mut_ptr().add(base) as *mut i32,
+                _mm_cvtsi128_si32(polarities),
+            );
+        }
+    }
+    unpack_scalar(
+        &events[length..],
+        &mut t[length..],
+        &mut x[length..],
+        &mut y[length..],
+        &mut on[length..],
+    );
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn unpack(events: &[Event], t: &mut [i64], x: &mut [i16], y: &mut [i16], on: &mut [bool]) {
+    unpack_scalar(events, t, x, y, on);
+}
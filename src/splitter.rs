@@ -0,0 +1,192 @@
+//! Writes a long recording or a live stream to a sequence of files.
+//!
+//! Files are closed when they exceed a duration or a size, and new files are opened on demand. With an
+//! activity threshold, the splitter only records while the event rate is high, which avoids filling disks
+//! with static scenes during unattended deployments.
+
+use crate::base::{ioheader_generated::Compression, Packet, ParseError, Stream, StreamContent};
+use crate::encoder::Encoder;
+
+struct Activity {
+    /// Events per second.
+    rate: f64,
+    window: i64,
+    hold: i64,
+}
+
+/// See the module documentation.
+pub struct Splitter {
+    directory: std::path::PathBuf,
+    prefix: String,
+    id_to_stream: std::collections::HashMap<u32, Stream>,
+    compression: Compression,
+    max_duration: Option<i64>,
+    max_size: Option<u64>,
+    activity: Option<Activity>,
+    encoder: Option<Encoder>,
+    file_begin_t: Option<i64>,
+    files: Vec<std::path::PathBuf>,
+    /// End timestamp and number of events of the event packets in the activity window.
+    window: std::collections::VecDeque<(i64, u64)>,
+    window_events: u64,
+    last_active_t: Option<i64>,
+    latest_t: Option<i64>,
+}
+
+impl Splitter {
+    /// Files are written to `directory`, named `recording-00000.aedat4`, `recording-00001.aedat4`...
+    pub fn new<P: std::convert::AsRef<std::path::Path>>(
+        directory: P,
+        id_to_stream: std::collections::HashMap<u32, Stream>,
+        compression: Compression,
+    ) -> Self {
+        Splitter {
+            directory: directory.as_ref().to_path_buf(),
+            prefix: "recording".to_owned(),
+            id_to_stream,
+            compression,
+            max_duration: None,
+            max_size: None,
+            activity: None,
+            encoder: None,
+            file_begin_t: None,
+            files: Vec::new(),
+            window: std::collections::VecDeque::new(),
+            window_events: 0,
+            last_active_t: None,
+            latest_t: None,
+        }
+    }
+
+    /// Prefix of the file names ("recording" by default).
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_owned();
+        self
+    }
+
+    /// Starts a new file once a packet begins `max_duration` microseconds after the first packet of the file.
+    pub fn max_duration(mut self, max_duration: i64) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Closes the file once it reaches `max_size` bytes (files may exceed the limit by one packet).
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Only records while the event rate, averaged over `window` microseconds, exceeds `rate` events per second.
+    ///
+    /// Recording continues for `hold` microseconds after the rate drops below the threshold, and each active
+    /// period starts a new file.
+    pub fn activity(mut self, rate: f64, window: i64, hold: i64) -> Self {
+        self.activity = Some(Activity {
+            rate,
+            window: window.max(1),
+            hold,
+        });
+        self
+    }
+
+    /// Writes a packet to the current file, opening or closing files as needed.
+    pub fn write(&mut self, packet: &Packet) -> Result<(), ParseError> {
+        let content = match self.id_to_stream.get(&packet.stream_id) {
+            Some(stream) => stream.content.clone(),
+            None => return Err(ParseError::UnknownStreamId(packet.stream_id)),
+        };
+        let (elements, begin_t, end_t) = packet.summary(&content)?;
+        let t = (elements > 0).then_some(begin_t);
+        if elements > 0 {
+            self.latest_t = Some(self.latest_t.map_or(end_t, |latest_t| latest_t.max(end_t)));
+        }
+        if self.activity.is_some() {
+            if content == StreamContent::Events && elements > 0 {
+                self.update_activity(end_t, elements as u64);
+            }
+            if !self.is_active() {
+                return self.close();
+            }
+        }
+        if let (Some(max_duration), Some(file_begin_t), Some(t)) =
+            (self.max_duration, self.file_begin_t, t)
+        {
+            if t >= file_begin_t + max_duration {
+                self.close()?;
+            }
+        }
+        if self.encoder.is_none() {
+            let path =
+                self.directory
+                    .join(format!("{}-{:05}.aedat4", self.prefix, self.files.len()));
+            self.encoder = Some(Encoder::new_to_file(
+                &path,
+                self.id_to_stream.clone(),
+                self.compression,
+            )?);
+            self.files.push(path);
+        }
+        if self.file_begin_t.is_none() {
+            self.file_begin_t = t;
+        }
+        let encoder = self.encoder.as_mut().unwrap();
+        encoder.write(packet)?;
+        if let Some(max_size) = self.max_size {
+            if encoder.bytes_written() >= max_size {
+                self.close()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn update_activity(&mut self, end_t: i64, events: u64) {
+        let activity = self.activity.as_ref().unwrap();
+        self.window.push_back((end_t, events));
+        self.window_events += events;
+        while let Some((t, events)) = self.window.front().copied() {
+            if t > end_t - activity.window {
+                break;
+            }
+            self.window.pop_front();
+            self.window_events -= events;
+        }
+        if self.window_events as f64 * 1e6 / activity.window as f64 >= activity.rate {
+            self.last_active_t = Some(end_t);
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        match (self.activity.as_ref(), self.last_active_t, self.latest_t) {
+            (None, _, _) => true,
+            (Some(activity), Some(last_active_t), Some(latest_t)) => {
+                latest_t - last_active_t <= activity.hold
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a file is open.
+    pub fn is_recording(&self) -> bool {
+        self.encoder.is_some()
+    }
+
+    /// Closes the current file, if any. The next packet opens a new file.
+    pub fn close(&mut self) -> Result<(), ParseError> {
+        self.file_begin_t = None;
+        match self.encoder.take() {
+            Some(encoder) => encoder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Files created so far, including the current one.
+    pub fn files(&self) -> &[std::path::PathBuf] {
+        &self.files
+    }
+
+    /// Closes the current file and returns the list of files.
+    pub fn finish(mut self) -> Result<Vec<std::path::PathBuf>, ParseError> {
+        self.close()?;
+        Ok(std::mem::take(&mut self.files))
+    }
+}
@@ -0,0 +1,156 @@
+//! Per-stream statistics embedded in file descriptions.
+//!
+//! File encoders write a `statistics` node for each stream in the IOHeader description (packet and element
+//! counts, time range and mean rate). The values are computed from the file data table when the file is
+//! finished, and the header is rewritten in place, hence the numbers have a fixed width. Unfinished files
+//! (and network streams) have no statistics.
+//!
+//! `Decoder::statistics` reads them without scanning the file, and `summarize` computes the same values from
+//! a file data table for files written by other tools.
+
+use crate::base::{Decoder, FileDataEntry, ParseError, Stream};
+use std::collections::HashMap;
+
+/// Summary of the packets of a stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamStatistics {
+    pub packets: u64,
+    /// Number of events, frames, IMU samples... depending on the stream type.
+    pub elements: u64,
+    /// Timestamp of the first element in microseconds (zero if the stream is empty).
+    pub begin_t: i64,
+    /// Timestamp of the last element in microseconds (zero if the stream is empty).
+    pub end_t: i64,
+    /// Elements per second between the first and last elements.
+    pub mean_rate: f64,
+}
+
+impl StreamStatistics {
+    /// Time between the first and last elements in microseconds.
+    pub fn duration(&self) -> i64 {
+        self.end_t - self.begin_t
+    }
+}
+
+/// Computes the statistics of every stream from a file data table.
+pub fn summarize(
+    id_to_stream: &HashMap<u32, Stream>,
+    entries: &[FileDataEntry],
+) -> HashMap<u32, StreamStatistics> {
+    let empty = StreamStatistics {
+        packets: 0,
+        elements: 0,
+        begin_t: 0,
+        end_t: 0,
+        mean_rate: 0.0,
+    };
+    let mut id_to_statistics: HashMap<u32, StreamStatistics> = id_to_stream
+        .keys()
+        .map(|stream_id| (*stream_id, empty))
+        .collect();
+    for entry in entries {
+        let statistics = id_to_statistics.entry(entry.stream_id).or_insert(empty);
+        statistics.packets += 1;
+        if entry.num_elements > 0 {
+            if statistics.elements == 0 {
+                statistics.begin_t = entry.timestamp_start;
+                statistics.end_t = entry.timestamp_end;
+            } else {
+                statistics.begin_t = statistics.begin_t.min(entry.timestamp_start);
+                statistics.end_t = statistics.end_t.max(entry.timestamp_end);
+            }
+            statistics.elements += entry.num_elements as u64;
+        }
+    }
+    for statistics in id_to_statistics.values_mut() {
+        if statistics.duration() > 0 {
+            statistics.mean_rate = statistics.elements as f64 * 1e6 / statistics.duration() as f64;
+        }
+    }
+    id_to_statistics
+}
+
+/// Largest mean rate that fits in the reserved width.
+const MAXIMUM_RATE: f64 = 9.99e15;
+
+/// Writes the statistics node of a stream. `None` writes placeholders of the same size, which mark an
+/// unfinished file.
+pub(crate) fn encode(path: &str, statistics: Option<&StreamStatistics>) -> String {
+    let (packets, elements, begin_t, end_t, mean_rate) = match statistics {
+        Some(statistics) => (
+            statistics.packets as i64,
+            statistics.elements as i64,
+            statistics.begin_t,
+            statistics.end_t,
+            statistics.mean_rate.clamp(0.0, MAXIMUM_RATE),
+        ),
+        None => (-1, -1, 0, 0, 0.0),
+    };
+    let mut node = format!(
+        "            <node name=\"statistics\" path=\"{}statistics/\">\n",
+        path
+    );
+    // numbers are padded to 20 characters, the header size must not change when it is rewritten
+    for (key, value) in [
+        ("packetCount", packets),
+        ("elementCount", elements),
+        ("firstTimestamp", begin_t),
+        ("lastTimestamp", end_t),
+    ] {
+        node.push_str(&format!(
+            "                <attr key=\"{}\" type=\"long\">{:020}</attr>\n",
+            key, value
+        ));
+    }
+    node.push_str(&format!(
+        "                <attr key=\"meanRate\" type=\"double\">{:020.3}</attr>\n",
+        mean_rate
+    ));
+    node.push_str("            </node>\n");
+    node
+}
+
+impl Decoder {
+    /// Statistics embedded in the description by the encoder, or None if the file was written by another
+    /// tool or was not finished.
+    pub fn statistics(&self) -> Result<Option<HashMap<u32, StreamStatistics>>, ParseError> {
+        let document = roxmltree::Document::parse(self.description())?;
+        let mut id_to_statistics = HashMap::new();
+        for stream_id in self.id_to_stream.keys() {
+            let node = match document.descendants().find(|node| {
+                node.has_tag_name("node")
+                    && node.attribute("name") == Some("statistics")
+                    && node.parent().and_then(|parent| parent.attribute("name"))
+                        == Some(stream_id.to_string().as_str())
+            }) {
+                Some(node) => node,
+                None => return Ok(None),
+            };
+            let attribute = |key: &'static str| -> Result<&str, ParseError> {
+                node.children()
+                    .find(|child| child.attribute("key") == Some(key))
+                    .and_then(|child| child.text())
+                    .map(str::trim)
+                    .ok_or(ParseError::MissingAttribute { attribute: key })
+            };
+            let packets: i64 = attribute("packetCount")?.parse()?;
+            let elements: i64 = attribute("elementCount")?.parse()?;
+            if packets < 0 || elements < 0 {
+                return Ok(None);
+            }
+            id_to_statistics.insert(
+                *stream_id,
+                StreamStatistics {
+                    packets: packets as u64,
+                    elements: elements as u64,
+                    begin_t: attribute("firstTimestamp")?.parse()?,
+                    end_t: attribute("lastTimestamp")?.parse()?,
+                    mean_rate: attribute("meanRate")?.parse().map_err(|_| {
+                        ParseError::General("the mean rate is not a number".to_owned())
+                    })?,
+                },
+            );
+        }
+        Ok(Some(id_to_statistics))
+    }
+}
@@ -0,0 +1,237 @@
+use crate::base::{Decoder, DecoderBuilder, Packet, ParseError, StreamContent};
+use crate::events::{Event, Events};
+use crate::triggers_generated;
+
+/// One camera of a stereo pair: a recording, or some of the streams of a multi-stream recording.
+pub struct StereoInput {
+    path: std::path::PathBuf,
+    stream_ids: Option<Vec<u32>>,
+}
+
+impl StereoInput {
+    pub fn new<P: std::convert::AsRef<std::path::Path>>(path: P) -> Self {
+        StereoInput {
+            path: path.as_ref().to_path_buf(),
+            stream_ids: None,
+        }
+    }
+
+    /// Only uses the given streams of the recording (for instance the streams of one camera in a multi-stream file).
+    pub fn stream_ids(mut self, stream_ids: &[u32]) -> Self {
+        self.stream_ids = Some(stream_ids.to_vec());
+        self
+    }
+
+    fn decoder(
+        &self,
+        content: StreamContent,
+        timestamp_offset: i64,
+    ) -> Result<Decoder, ParseError> {
+        let mut builder = DecoderBuilder::file(&self.path)
+            .only_streams(&[content])
+            .timestamp_offset(timestamp_offset);
+        if let Some(stream_ids) = &self.stream_ids {
+            builder = builder.stream_ids(stream_ids);
+        }
+        builder.build()
+    }
+
+    fn first_trigger(&self) -> Result<Option<i64>, ParseError> {
+        for packet in self.decoder(StreamContent::Triggers, 0)? {
+            let packet = packet?;
+            if let Some(elements) =
+                triggers_generated::size_prefixed_root_as_trigger_packet(&packet.buffer)?.elements()
+            {
+                if !elements.is_empty() {
+                    return Ok(Some(elements.get(0).t()));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Synchronization of the right camera with the left camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    /// Offset (in microseconds) added to the timestamps of the right camera.
+    Offset(i64),
+    /// Shifts the right camera so that the first triggers of both cameras (of any source) coincide.
+    Triggers,
+}
+
+/// A pair of synchronized cameras.
+pub struct Stereo {
+    left: StereoInput,
+    right: StereoInput,
+    offset: i64,
+}
+
+impl Stereo {
+    pub fn new(
+        left: StereoInput,
+        right: StereoInput,
+        alignment: Alignment,
+    ) -> Result<Self, ParseError> {
+        let offset = match alignment {
+            Alignment::Offset(offset) => offset,
+            Alignment::Triggers => match (left.first_trigger()?, right.first_trigger()?) {
+                (Some(left_t), Some(right_t)) => left_t - right_t,
+                _ => {
+                    return Err(ParseError::Usage(
+                        "both cameras must record triggers to align them".to_owned(),
+                    ))
+                }
+            },
+        };
+        Ok(Stereo {
+            left,
+            right,
+            offset,
+        })
+    }
+
+    /// Offset (in microseconds) added to the timestamps of the right camera.
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    /// Left and right events in consecutive windows of `duration` microseconds with common boundaries.
+    pub fn event_windows(&self, duration: i64) -> Result<EventWindows, ParseError> {
+        Ok(EventWindows {
+            left: Events::new(self.left.decoder(StreamContent::Events, 0)?).peekable(),
+            right: Events::new(self.right.decoder(StreamContent::Events, self.offset)?).peekable(),
+            duration,
+            begin: None,
+        })
+    }
+
+    /// Left and right frames whose timestamps differ by at most `tolerance` microseconds.
+    /// Frames without a match are skipped.
+    pub fn frame_pairs(&self, tolerance: i64) -> Result<FramePairs, ParseError> {
+        Ok(FramePairs {
+            left: self.left.decoder(StreamContent::Frame, 0)?,
+            right: self.right.decoder(StreamContent::Frame, self.offset)?,
+            tolerance,
+            left_frame: None,
+            right_frame: None,
+        })
+    }
+}
+
+/// See `Stereo::event_windows`.
+pub struct EventWindows {
+    left: std::iter::Peekable<Events<Decoder>>,
+    right: std::iter::Peekable<Events<Decoder>>,
+    duration: i64,
+    begin: Option<i64>,
+}
+
+impl EventWindows {
+    fn first_t(
+        events: &mut std::iter::Peekable<Events<Decoder>>,
+    ) -> Result<Option<i64>, ParseError> {
+        match events.peek() {
+            Some(Ok(event)) => Ok(Some(event.t())),
+            Some(Err(_)) => Err(events.next().unwrap().unwrap_err()),
+            None => Ok(None),
+        }
+    }
+
+    fn take_until(
+        events: &mut std::iter::Peekable<Events<Decoder>>,
+        end: i64,
+    ) -> Result<Vec<Event>, ParseError> {
+        let mut window = Vec::new();
+        while let Some(t) = EventWindows::first_t(events)? {
+            if t >= end {
+                break;
+            }
+            window.push(events.next().unwrap()?);
+        }
+        Ok(window)
+    }
+}
+
+impl Iterator for EventWindows {
+    type Item = Result<(Vec<Event>, Vec<Event>), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let left_t = match EventWindows::first_t(&mut self.left) {
+            Ok(t) => t,
+            Err(error) => return Some(Err(error)),
+        };
+        let right_t = match EventWindows::first_t(&mut self.right) {
+            Ok(t) => t,
+            Err(error) => return Some(Err(error)),
+        };
+        if left_t.is_none() && right_t.is_none() {
+            return None;
+        }
+        let begin = *self
+            .begin
+            .get_or_insert_with(|| left_t.unwrap_or(i64::MAX).min(right_t.unwrap_or(i64::MAX)));
+        let end = begin + self.duration;
+        self.begin = Some(end);
+        Some(
+            EventWindows::take_until(&mut self.left, end).and_then(|left| {
+                EventWindows::take_until(&mut self.right, end).map(|right| (left, right))
+            }),
+        )
+    }
+}
+
+/// See `Stereo::frame_pairs`.
+pub struct FramePairs {
+    left: Decoder,
+    right: Decoder,
+    tolerance: i64,
+    left_frame: Option<(i64, Packet)>,
+    right_frame: Option<(i64, Packet)>,
+}
+
+fn next_frame(decoder: &mut Decoder) -> Result<Option<(i64, Packet)>, ParseError> {
+    match decoder.next() {
+        Some(packet) => {
+            let packet = packet?;
+            Ok(Some((packet.frame()?.t(), packet)))
+        }
+        None => Ok(None),
+    }
+}
+
+impl FramePairs {
+    fn next_pair(&mut self) -> Result<Option<(Packet, Packet)>, ParseError> {
+        loop {
+            if self.left_frame.is_none() {
+                self.left_frame = next_frame(&mut self.left)?;
+            }
+            if self.right_frame.is_none() {
+                self.right_frame = next_frame(&mut self.right)?;
+            }
+            let (left_t, right_t) = match (&self.left_frame, &self.right_frame) {
+                (Some((left_t, _)), Some((right_t, _))) => (*left_t, *right_t),
+                _ => return Ok(None),
+            };
+            if (left_t - right_t).abs() <= self.tolerance {
+                return Ok(Some((
+                    self.left_frame.take().unwrap().1,
+                    self.right_frame.take().unwrap().1,
+                )));
+            }
+            if left_t < right_t {
+                self.left_frame = None;
+            } else {
+                self.right_frame = None;
+            }
+        }
+    }
+}
+
+impl Iterator for FramePairs {
+    type Item = Result<(Packet, Packet), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_pair().transpose()
+    }
+}
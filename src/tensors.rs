@@ -0,0 +1,188 @@
+//! safetensors export of event representations.
+//!
+//! A `RepresentationBatch` stacks representations with the same shape (voxel grids, histograms...) and saves
+//! them to a file that Python loads with `safetensors.numpy.load_file` or `safetensors.torch.load_file`:
+//! - `data`: (samples, ...) float32 tensor
+//! - `begin_t` and `end_t`: (samples) int64 tensors with the bounds of each window in microseconds
+//!   (`begin_t` inclusive, `end_t` exclusive)
+//!
+//! The metadata always contains the number of samples (`samples`), and any key added with
+//! `RepresentationBatch::metadata`.
+
+use crate::base::ParseError;
+use crate::chunks::{Chunks, Window};
+use crate::events::Event;
+use std::collections::HashMap;
+
+/// Representations and their window bounds, saved as a single safetensors file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepresentationBatch {
+    shape: Option<Vec<usize>>,
+    data: Vec<f32>,
+    begin_t: Vec<i64>,
+    end_t: Vec<i64>,
+    metadata: HashMap<String, String>,
+}
+
+impl RepresentationBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a metadata entry, for instance the representation name or the source recording.
+    pub fn metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Adds a representation. All the representations of a batch must have the same shape.
+    pub fn push<D: ndarray::Dimension>(
+        &mut self,
+        begin_t: i64,
+        end_t: i64,
+        representation: &ndarray::Array<f32, D>,
+    ) -> Result<(), ParseError> {
+        match &self.shape {
+            Some(shape) if shape[..] != *representation.shape() => {
+                return Err(ParseError::Usage(format!(
+                    "all the representations of a batch must have the same shape ({:?} and {:?})",
+                    shape,
+                    representation.shape()
+                )));
+            }
+            Some(_) => {}
+            None => self.shape = Some(representation.shape().to_vec()),
+        }
+        self.data.extend(representation.iter());
+        self.begin_t.push(begin_t);
+        self.end_t.push(end_t);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.begin_t.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.begin_t.is_empty()
+    }
+
+    /// Removes the representations and keeps the metadata.
+    pub fn clear(&mut self) {
+        self.shape = None;
+        self.data.clear();
+        self.begin_t.clear();
+        self.end_t.clear();
+    }
+
+    /// Serializes the batch in the safetensors format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ParseError> {
+        let (data, begin_t, end_t, metadata) = self.prepare();
+        Ok(safetensors::tensor::serialize(
+            self.views(&data, &begin_t, &end_t)?,
+            &Some(metadata),
+        )?)
+    }
+
+    pub fn save<P: std::convert::AsRef<std::path::Path>>(&self, path: P) -> Result<(), ParseError> {
+        let (data, begin_t, end_t, metadata) = self.prepare();
+        safetensors::tensor::serialize_to_file(
+            self.views(&data, &begin_t, &end_t)?,
+            &Some(metadata),
+            path.as_ref(),
+        )?;
+        Ok(())
+    }
+
+    fn prepare(&self) -> (Vec<u8>, Vec<u8>, Vec<u8>, HashMap<String, String>) {
+        let mut metadata = self.metadata.clone();
+        metadata.insert("samples".to_owned(), self.len().to_string());
+        (
+            self.data
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect(),
+            self.begin_t.iter().flat_map(|t| t.to_le_bytes()).collect(),
+            self.end_t.iter().flat_map(|t| t.to_le_bytes()).collect(),
+            metadata,
+        )
+    }
+
+    fn views<'a>(
+        &self,
+        data: &'a [u8],
+        begin_t: &'a [u8],
+        end_t: &'a [u8],
+    ) -> Result<[(&'static str, safetensors::tensor::TensorView<'a>); 3], ParseError> {
+        use safetensors::tensor::TensorView;
+        use safetensors::Dtype;
+
+        let mut shape = vec![self.len()];
+        shape.extend(self.shape.iter().flatten());
+        Ok([
+            ("data", TensorView::new(Dtype::F32, shape, data)?),
+            (
+                "begin_t",
+                TensorView::new(Dtype::I64, vec![self.len()], begin_t)?,
+            ),
+            (
+                "end_t",
+                TensorView::new(Dtype::I64, vec![self.len()], end_t)?,
+            ),
+        ])
+    }
+}
+
+/// Computes a representation per window of an event stream, and saves them in files of `batch_size`
+/// windows named `{prefix}_00000.safetensors`, `{prefix}_00001.safetensors`...
+///
+/// Duration windows are aligned on the first event and empty windows are kept, so that samples are evenly
+/// spaced. Count windows span from their first event to their last event (included).
+/// `batch` provides the metadata of every file. Returns the paths of the files.
+pub fn export_windows<I, F>(
+    events: I,
+    window: Window,
+    batch_size: usize,
+    prefix: &str,
+    mut batch: RepresentationBatch,
+    mut representation: F,
+) -> Result<Vec<std::path::PathBuf>, ParseError>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+    F: FnMut(&[Event]) -> ndarray::Array3<f32>,
+{
+    batch.clear();
+    let mut paths = Vec::new();
+    let mut save = |batch: &mut RepresentationBatch| -> Result<(), ParseError> {
+        let path = std::path::PathBuf::from(format!("{}_{:05}.safetensors", prefix, paths.len()));
+        batch.save(&path)?;
+        batch.clear();
+        paths.push(path);
+        Ok(())
+    };
+    let mut first_t = None;
+    for (index, events) in Chunks::new(events, window).enumerate() {
+        let events = events?;
+        let (begin_t, end_t) = match window {
+            Window::Duration(duration) => {
+                let duration = duration.max(1);
+                let first_t =
+                    *first_t.get_or_insert_with(|| events.first().map_or(0, |event| event.t()));
+                let begin_t = first_t + index as i64 * duration;
+                (begin_t, begin_t + duration)
+            }
+            Window::Count(_) => (
+                events.first().map_or(0, |event| event.t()),
+                events.last().map_or(0, |event| event.t() + 1),
+            ),
+        };
+        batch.push(begin_t, end_t, &representation(&events))?;
+        if batch.len() >= batch_size.max(1) {
+            save(&mut batch)?;
+        }
+    }
+    if !batch.is_empty() {
+        save(&mut batch)?;
+    }
+    Ok(paths)
+}
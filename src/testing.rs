@@ -0,0 +1,620 @@
+//! Deterministic synthetic recordings for tests.
+//!
+//! `Fixture` generates small valid AEDAT4 files, as byte buffers or on disk, so that tests do not depend on
+//! committed binary recordings. The same configuration always produces the same bytes.
+//!
+//! Stream ids follow DV's capture module: 0 events, 1 frames, 2 IMU samples, 3 triggers. Packets are interleaved
+//! by time: each period of `packet_duration` microseconds contains one packet of each enabled stream.
+
+use crate::base::{ioheader_generated, Decoder, Packet, ParseError, Stream, StreamContent};
+use crate::compression::DefaultBackend;
+use crate::events::Event;
+use crate::{frame_generated, imus_generated, triggers_generated};
+use std::collections::HashMap;
+
+/// Positions and polarities of the generated events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventPattern {
+    /// Uniformly distributed positions and polarities, from a seeded generator.
+    Random { seed: u64 },
+    /// Pixels in raster order (row by row), alternating between ON and OFF after each full sensor.
+    Raster,
+    /// A vertical bar crossing the sensor from left to right once per packet, with ON events on its leading
+    /// edge and OFF events on its trailing edge.
+    Sweep,
+}
+
+/// Configuration of a synthetic recording (see the module documentation).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixture {
+    width: u16,
+    height: u16,
+    compression: ioheader_generated::Compression,
+    packets: usize,
+    events_per_packet: usize,
+    packet_duration: i64,
+    begin_t: i64,
+    pattern: EventPattern,
+    frames: bool,
+    imus: bool,
+    triggers: bool,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Fixture::new()
+    }
+}
+
+impl Fixture {
+    /// 32 × 24 events stream, 4 packets of 100 events spanning 1 ms each, uncompressed, random pattern.
+    pub fn new() -> Self {
+        Fixture {
+            width: 32,
+            height: 24,
+            compression: ioheader_generated::Compression::None,
+            packets: 4,
+            events_per_packet: 100,
+            packet_duration: 1000,
+            begin_t: 1_000_000,
+            pattern: EventPattern::Random { seed: 0 },
+            frames: false,
+            imus: false,
+            triggers: false,
+        }
+    }
+
+    pub fn resolution(mut self, width: u16, height: u16) -> Self {
+        self.width = width.max(1);
+        self.height = height.max(1);
+        self
+    }
+
+    pub fn compression(mut self, compression: ioheader_generated::Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Number of packets per stream.
+    pub fn packet_count(mut self, packets: usize) -> Self {
+        self.packets = packets;
+        self
+    }
+
+    pub fn events_per_packet(mut self, events_per_packet: usize) -> Self {
+        self.events_per_packet = events_per_packet;
+        self
+    }
+
+    /// Time span of each packet in microseconds.
+    pub fn packet_duration(mut self, packet_duration: i64) -> Self {
+        self.packet_duration = packet_duration.max(1);
+        self
+    }
+
+    /// Timestamp of the first event in microseconds.
+    pub fn begin_t(mut self, begin_t: i64) -> Self {
+        self.begin_t = begin_t;
+        self
+    }
+
+    pub fn pattern(mut self, pattern: EventPattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Adds a frames stream with one gray frame per packet (a horizontal gradient shifted by the packet index).
+    pub fn frames(mut self, frames: bool) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// Adds an IMU stream with ten samples per packet (gravity along y and a constant rotation about z).
+    pub fn imus(mut self, imus: bool) -> Self {
+        self.imus = imus;
+        self
+    }
+
+    /// Adds a triggers stream with one rising edge at the beginning of each packet.
+    pub fn triggers(mut self, triggers: bool) -> Self {
+        self.triggers = triggers;
+        self
+    }
+
+    pub fn streams(&self) -> HashMap<u32, Stream> {
+        let mut streams = HashMap::from([(
+            0,
+            Stream::new(StreamContent::Events, self.width, self.height),
+        )]);
+        if self.frames {
+            streams.insert(
+                1,
+                Stream::new(StreamContent::Frame, self.width, self.height),
+            );
+        }
+        if self.imus {
+            streams.insert(2, Stream::new(StreamContent::Imus, 0, 0));
+        }
+        if self.triggers {
+            streams.insert(3, Stream::new(StreamContent::Triggers, 0, 0));
+        }
+        streams
+    }
+
+    /// Events of every packet, in order.
+    pub fn events(&self) -> Vec<Event> {
+        let mut generator = Generator::new(match self.pattern {
+            EventPattern::Random { seed } => seed,
+            _ => 0,
+        });
+        let mut events = Vec::with_capacity(self.packets * self.events_per_packet);
+        let (width, height) = (self.width as u64, self.height as u64);
+        for packet in 0..self.packets {
+            let packet_t = self.begin_t + packet as i64 * self.packet_duration;
+            for index in 0..self.events_per_packet {
+                let t = packet_t
+                    + (index as i64 * self.packet_duration) / self.events_per_packet.max(1) as i64;
+                let (x, y, on) = match self.pattern {
+                    EventPattern::Random { .. } => {
+                        let value = generator.next();
+                        (
+                            value % width,
+                            (value >> 16) % height,
+                            (value >> 32) & 1 == 1,
+                        )
+                    }
+                    EventPattern::Raster => {
+                        let pixel = (packet * self.events_per_packet + index) as u64;
+                        (
+                            pixel % width,
+                            (pixel / width) % height,
+                            (pixel / (width * height)).is_multiple_of(2),
+                        )
+                    }
+                    EventPattern::Sweep => {
+                        let bar = index as u64 * width / self.events_per_packet.max(1) as u64;
+                        let on = index.is_multiple_of(2);
+                        let x = if on { bar } else { bar.saturating_sub(2) };
+                        (x, (index as u64 / 2) % height, on)
+                    }
+                };
+                events.push(Event::new(t, x as i16, y as i16, on));
+            }
+        }
+        events
+    }
+
+    /// Packets of every stream, interleaved by time.
+    pub fn packets(&self) -> Vec<Packet> {
+        let events = self.events();
+        let mut packets = Vec::new();
+        for packet in 0..self.packets {
+            let packet_t = self.begin_t + packet as i64 * self.packet_duration;
+            if self.triggers {
+                packets.push(trigger_packet(3, packet_t));
+            }
+            packets.push(Packet::from_events(
+                0,
+                &events[packet * self.events_per_packet..(packet + 1) * self.events_per_packet],
+            ));
+            if self.imus {
+                packets.push(imu_packet(2, packet_t, self.packet_duration));
+            }
+            if self.frames {
+                packets.push(frame_packet(
+                    1,
+                    self.width,
+                    self.height,
+                    packet,
+                    packet_t,
+                    packet_t + self.packet_duration - 1,
+                ));
+            }
+        }
+        packets
+    }
+
+    /// Encodes the recording, with a file data table.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ParseError> {
+        crate::encoder::encode_to_bytes(
+            &self.streams(),
+            self.compression,
+            &DefaultBackend,
+            &self.packets(),
+        )
+    }
+
+    pub fn write<P: std::convert::AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), ParseError> {
+        std::fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Decoder reading the encoded recording from memory.
+    pub fn decoder(&self) -> Result<Decoder, ParseError> {
+        Decoder::from_bytes(&self.to_bytes()?)
+    }
+}
+
+/// SplitMix64, small and stable across platforms and versions.
+struct Generator(u64);
+
+impl Generator {
+    fn new(seed: u64) -> Self {
+        Generator(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut value = self.0;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+        value ^ (value >> 31)
+    }
+}
+
+fn trigger_packet(stream_id: u32, t: i64) -> Packet {
+    let mut builder = flatbuffers::FlatBufferBuilder::new();
+    let trigger = triggers_generated::Trigger::create(
+        &mut builder,
+        &triggers_generated::TriggerArgs {
+            t,
+            source: triggers_generated::TriggerSource::ExternalSignalRisingEdge,
+        },
+    );
+    let elements = builder.create_vector(&[trigger]);
+    let root = triggers_generated::TriggerPacket::create(
+        &mut builder,
+        &triggers_generated::TriggerPacketArgs {
+            elements: Some(elements),
+        },
+    );
+    triggers_generated::finish_size_prefixed_trigger_packet_buffer(&mut builder, root);
+    Packet {
+        buffer: builder.finished_data().to_vec(),
+        stream_id,
+        arrival_time: None,
+    }
+}
+
+fn imu_packet(stream_id: u32, begin_t: i64, duration: i64) -> Packet {
+    let mut builder = flatbuffers::FlatBufferBuilder::new();
+    let samples: Vec<_> = (0..10)
+        .map(|index| {
+            imus_generated::Imu::create(
+                &mut builder,
+                &imus_generated::ImuArgs {
+                    t: begin_t + index * duration / 10,
+                    temperature: 25.0,
+                    accelerometer_y: -1.0,
+                    gyroscope_z: 10.0,
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+    let elements = builder.create_vector(&samples);
+    let root = imus_generated::ImuPacket::create(
+        &mut builder,
+        &imus_generated::ImuPacketArgs {
+            elements: Some(elements),
+        },
+    );
+    imus_generated::finish_size_prefixed_imu_packet_buffer(&mut builder, root);
+    Packet {
+        buffer: builder.finished_data().to_vec(),
+        stream_id,
+        arrival_time: None,
+    }
+}
+
+fn frame_packet(
+    stream_id: u32,
+    width: u16,
+    height: u16,
+    index: usize,
+    begin_t: i64,
+    end_t: i64,
+) -> Packet {
+    let mut builder = flatbuffers::FlatBufferBuilder::new();
+    let pixels: Vec<u8> = (0..height as usize)
+        .flat_map(|_| {
+            (0..width as usize)
+                .map(move |x| ((x + index) % width as usize * 255 / width as usize) as u8)
+        })
+        .collect();
+    let pixels = builder.create_vector(&pixels);
+    let root = frame_generated::Frame::create(
+        &mut builder,
+        &frame_generated::FrameArgs {
+            t: begin_t,
+            begin_t,
+            end_t,
+            exposure_begin_t: begin_t,
+            exposure_end_t: end_t,
+            format: frame_generated::FrameFormat::Gray,
+            width: width as i16,
+            height: height as i16,
+            pixels: Some(pixels),
+            ..Default::default()
+        },
+    );
+    frame_generated::finish_size_prefixed_frame_buffer(&mut builder, root);
+    Packet {
+        buffer: builder.finished_data().to_vec(),
+        stream_id,
+        arrival_time: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::DecoderBuilder;
+    use crate::events::PacketIterator;
+
+    /// File removed on drop, since `DecoderBuilder` and `repair` read files.
+    struct TemporaryFile(std::path::PathBuf);
+
+    impl TemporaryFile {
+        fn new(name: &str, bytes: &[u8]) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("aedat-{}-{}.aedat4", name, std::process::id()));
+            std::fs::write(&path, bytes).unwrap();
+            TemporaryFile(path)
+        }
+    }
+
+    impl Drop for TemporaryFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn collect<I: Iterator<Item = Result<Event, ParseError>>>(events: I) -> Vec<Event> {
+        events.collect::<Result<Vec<Event>, ParseError>>().unwrap()
+    }
+
+    /// Bytes of an events-only recording cut in the middle of its last packet.
+    fn truncated(fixture: &Fixture) -> Vec<u8> {
+        let bytes = fixture.to_bytes().unwrap();
+        let last = *fixture
+            .decoder()
+            .unwrap()
+            .file_data_table()
+            .unwrap()
+            .last()
+            .unwrap();
+        bytes[..(last.byte_offset + last.size as i64 / 2) as usize].to_vec()
+    }
+
+    #[test]
+    fn timestamp_offset() {
+        let fixture = Fixture::new().begin_t(5_000_000).triggers(true);
+        let file = TemporaryFile::new("timestamp-offset", &fixture.to_bytes().unwrap());
+        let events = collect(
+            DecoderBuilder::file(&file.0)
+                .timestamp_offset(-1000)
+                .build()
+                .unwrap()
+                .events(),
+        );
+        assert_eq!(
+            events.iter().map(Event::t).collect::<Vec<i64>>(),
+            fixture
+                .events()
+                .iter()
+                .map(|event| event.t() - 1000)
+                .collect::<Vec<i64>>()
+        );
+    }
+
+    #[test]
+    fn zero_timestamps() {
+        // the earliest element is a trigger stored after the first events packet
+        let fixture = Fixture::new().begin_t(5_000_000).triggers(true);
+        let mut packets = fixture
+            .packets()
+            .into_iter()
+            .filter(|packet| packet.stream_id != 3)
+            .collect::<Vec<Packet>>();
+        packets.insert(1, trigger_packet(3, 4_000_000));
+        let bytes = crate::encoder::encode_to_bytes(
+            &fixture.streams(),
+            ioheader_generated::Compression::None,
+            &DefaultBackend,
+            &packets,
+        )
+        .unwrap();
+        let file = TemporaryFile::new("zero-timestamps", &bytes);
+        let decoder = DecoderBuilder::file(&file.0)
+            .zero_timestamps(true)
+            .timestamp_offset(10)
+            .build()
+            .unwrap();
+        assert_eq!(decoder.first_timestamp(), Some(4_000_000));
+        let events = collect(decoder.events());
+        assert_eq!(
+            events.iter().map(Event::t).collect::<Vec<i64>>(),
+            fixture
+                .events()
+                .iter()
+                .map(|event| event.t() - 4_000_000 + 10)
+                .collect::<Vec<i64>>()
+        );
+    }
+
+    #[test]
+    fn reverse_iteration() {
+        let fixture = Fixture::new().packet_count(5).frames(true).imus(true);
+        let forward = fixture
+            .decoder()
+            .unwrap()
+            .collect::<Result<Vec<Packet>, ParseError>>()
+            .unwrap();
+        let mut backward = fixture
+            .decoder()
+            .unwrap()
+            .iter_rev()
+            .unwrap()
+            .collect::<Result<Vec<Packet>, ParseError>>()
+            .unwrap();
+        backward.reverse();
+        let key = |packet: &Packet| (packet.stream_id, packet.buffer.clone());
+        assert_eq!(
+            backward.iter().map(key).collect::<Vec<_>>(),
+            forward.iter().map(key).collect::<Vec<_>>()
+        );
+        let last = fixture.decoder().unwrap().last_packets(4).unwrap();
+        assert_eq!(
+            last.iter().map(key).collect::<Vec<_>>(),
+            forward[forward.len() - 4..]
+                .iter()
+                .map(key)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn file_data_table() {
+        let fixture = Fixture::new().triggers(true).frames(true);
+        let entries = fixture.decoder().unwrap().file_data_table().unwrap();
+        let packets = fixture.packets();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| entry.stream_id)
+                .collect::<Vec<u32>>(),
+            packets
+                .iter()
+                .map(|packet| packet.stream_id)
+                .collect::<Vec<u32>>()
+        );
+        let events = fixture.events();
+        for (index, entry) in entries
+            .iter()
+            .filter(|entry| entry.stream_id == 0)
+            .enumerate()
+        {
+            let packet_events = &events[index * 100..(index + 1) * 100];
+            assert_eq!(entry.num_elements, 100);
+            assert_eq!(entry.timestamp_start, packet_events[0].t());
+            assert_eq!(entry.timestamp_end, packet_events[99].t());
+        }
+    }
+
+    #[test]
+    fn repair() {
+        let fixture = Fixture::new();
+        let input = TemporaryFile::new("repair-input", &truncated(&fixture));
+        let output = TemporaryFile::new("repair-output", &[]);
+        let report = crate::repair::repair(&input.0, &output.0).unwrap();
+        assert_eq!(report.packets, 4);
+        assert!(report.truncated);
+        let mut decoder = DecoderBuilder::file(&output.0).build().unwrap();
+        assert_eq!(decoder.file_data_table().unwrap().len(), 4);
+        let events = collect(decoder.events());
+        assert!(events.len() > 300 && events.len() < 400);
+        assert_eq!(events[..], fixture.events()[..events.len()]);
+    }
+
+    #[test]
+    fn hostile_lengths() {
+        let fixture = Fixture::new();
+        let bytes = fixture.to_bytes().unwrap();
+        let mut header = bytes.clone();
+        header[14..18].copy_from_slice(&crate::base::DEFAULT_MAX_PACKET_SIZE.to_le_bytes());
+        assert!(Decoder::from_bytes(&header).is_err());
+        let first = fixture.decoder().unwrap().file_data_table().unwrap()[0];
+        let mut packet = bytes.clone();
+        let offset = first.byte_offset as usize - 4;
+        packet[offset..offset + 4].copy_from_slice(&0xf000_0000u32.to_le_bytes());
+        let mut decoder = Decoder::from_bytes(&packet)
+            .unwrap()
+            .with_max_packet_size(u32::MAX);
+        assert!(matches!(
+            decoder.next(),
+            Some(Err(ParseError::TruncatedPacket { .. }))
+        ));
+        let file = TemporaryFile::new("hostile-lengths", &bytes);
+        let mut decoder = DecoderBuilder::file(&file.0)
+            .max_packet_size(100)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            decoder.next(),
+            Some(Err(ParseError::PacketTooLarge { limit: 100, .. }))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn decompression_limits() {
+        let fixture = Fixture::new()
+            .compression(ioheader_generated::Compression::Lz4)
+            .events_per_packet(1000);
+        let events = collect(fixture.decoder().unwrap().events());
+        assert_eq!(events, fixture.events());
+        let mut decoder = fixture.decoder().unwrap().with_max_decompressed_size(1000);
+        assert!(matches!(
+            decoder.next(),
+            Some(Err(ParseError::DecompressedTooLarge { limit: 1000 }))
+        ));
+        let file = TemporaryFile::new("decompression-limits", &fixture.to_bytes().unwrap());
+        let mut decoder = DecoderBuilder::file(&file.0)
+            .max_decompressed_size(1000)
+            .recovery(true)
+            .build()
+            .unwrap();
+        assert!(decoder.next().is_none());
+        assert_eq!(decoder.skipped_packets(), 4);
+    }
+
+    #[test]
+    fn truncated_packets() {
+        let fixture = Fixture::new();
+        let file = TemporaryFile::new("truncated-packets", &truncated(&fixture));
+        let packets = DecoderBuilder::file(&file.0)
+            .build()
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(packets.len(), 4);
+        assert!(matches!(
+            packets[3],
+            Err(ParseError::TruncatedPacket { .. })
+        ));
+        let mut decoder = DecoderBuilder::file(&file.0)
+            .salvage_truncated(true)
+            .build()
+            .unwrap();
+        let events = collect(decoder.by_ref().events());
+        assert!(decoder.is_truncated());
+        assert!(events.len() > 300 && events.len() < 400);
+        assert_eq!(events[..], fixture.events()[..events.len()]);
+    }
+
+    #[test]
+    fn chained_files() {
+        let first = Fixture::new();
+        let second = Fixture::new().begin_t(2_000_000);
+        let files = [
+            TemporaryFile::new("chained-first", &first.to_bytes().unwrap()),
+            TemporaryFile::new("chained-second", &second.to_bytes().unwrap()),
+        ];
+        let decoder =
+            crate::base::ChainedDecoder::new_from_files(&[&files[0].0, &files[1].0]).unwrap();
+        let mut expected = first.events();
+        expected.extend(second.events());
+        assert_eq!(collect(decoder.events()), expected);
+        let other = TemporaryFile::new(
+            "chained-other",
+            &Fixture::new().frames(true).to_bytes().unwrap(),
+        );
+        assert!(matches!(
+            crate::base::ChainedDecoder::new_from_files(&[&files[0].0, &other.0]),
+            Err(ParseError::InconsistentStreams { .. })
+        ));
+    }
+}
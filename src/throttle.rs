@@ -0,0 +1,153 @@
+//! Load shedding for real-time consumers.
+//!
+//! `Throttle` maps event timestamps to the wall clock when the first event is read. The lag of an event
+//! is the wall-clock time elapsed since then minus the event time elapsed since the first event, that is,
+//! how late the consumer receives the event compared with a real-time consumer. When the lag exceeds a bound,
+//! events are dropped according to a `ThrottlePolicy` so that the consumer catches up.
+
+use crate::base::{Packet, ParseError};
+use crate::events_generated::{Event, EVENT_PACKET_IDENTIFIER};
+
+/// Which events are dropped when the consumer lags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottlePolicy {
+    /// Drops the events whose lag exceeds the bound, hence the consumer skips to recent events.
+    OldestFirst,
+    /// Keeps the events of a regular subset of pixels (one pixel every `stride` pixels in each direction),
+    /// with `stride` chosen from the lag.
+    SpatialSubsample,
+    /// Keeps a fraction of the events chosen from the lag, evenly spaced in time and separately for each
+    /// polarity, so that the ratio of ON and OFF events is preserved.
+    PolarityBalanced,
+}
+
+/// Number of events kept and dropped by a `Throttle`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThrottleStats {
+    pub kept: u64,
+    pub dropped: u64,
+    /// Event packets read while the consumer lagged.
+    pub lagging_packets: u64,
+    /// Lag of the last event packet in microseconds.
+    pub lag: i64,
+}
+
+/// Drops events from event packets when the consumer lags by more than `max_lag` microseconds
+/// (see the module documentation).
+///
+/// Other packets are passed through, and packets that lose all their events are skipped.
+pub struct Throttle<I> {
+    packets: I,
+    max_lag: i64,
+    policy: ThrottlePolicy,
+    origin: Option<(std::time::Instant, i64)>,
+    /// Fractional events owed to each polarity (OFF, ON) by `PolarityBalanced`.
+    credits: [f64; 2],
+    stats: ThrottleStats,
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> Throttle<I> {
+    pub fn new(packets: I, max_lag: i64, policy: ThrottlePolicy) -> Self {
+        Throttle {
+            packets,
+            max_lag: max_lag.max(1),
+            policy,
+            origin: None,
+            credits: [0.0; 2],
+            stats: ThrottleStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> ThrottleStats {
+        self.stats
+    }
+
+    fn lag(&self, t: i64) -> i64 {
+        match self.origin {
+            Some((instant, origin_t)) => {
+                (instant.elapsed().as_micros() as i64 - (t - origin_t)).max(0)
+            }
+            None => 0,
+        }
+    }
+
+    fn shed(&mut self, events: Vec<Event>) -> Vec<Event> {
+        let first_t = match events.first() {
+            Some(event) => event.t(),
+            None => return events,
+        };
+        if self.origin.is_none() {
+            self.origin = Some((std::time::Instant::now(), first_t));
+        }
+        let lag = self.lag(first_t);
+        self.stats.lag = lag;
+        if lag <= self.max_lag {
+            self.credits = [0.0; 2];
+            return events;
+        }
+        self.stats.lagging_packets += 1;
+        let keep = self.max_lag as f64 / lag as f64;
+        match self.policy {
+            ThrottlePolicy::OldestFirst => {
+                // event timestamp matching the current wall-clock time
+                let now_t = first_t + lag;
+                events
+                    .into_iter()
+                    .filter(|event| now_t - event.t() <= self.max_lag)
+                    .collect()
+            }
+            ThrottlePolicy::SpatialSubsample => {
+                let stride = (1.0 / keep).sqrt().ceil() as i16;
+                events
+                    .into_iter()
+                    .filter(|event| event.x() % stride == 0 && event.y() % stride == 0)
+                    .collect()
+            }
+            ThrottlePolicy::PolarityBalanced => events
+                .into_iter()
+                .filter(|event| {
+                    let credit = &mut self.credits[event.on() as usize];
+                    *credit += keep;
+                    if *credit >= 1.0 {
+                        *credit -= 1.0;
+                        true
+                    } else {
+                        false
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Packet, ParseError>>> Iterator for Throttle<I> {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let packet = match self.packets.next()? {
+                Ok(packet) => packet,
+                Err(error) => return Some(Err(error)),
+            };
+            if !packet.has_identifier(EVENT_PACKET_IDENTIFIER) {
+                return Some(Ok(packet));
+            }
+            let events = match packet.events() {
+                Ok(events) => events,
+                Err(error) => return Some(Err(error)),
+            };
+            let length = events.len();
+            let events = self.shed(events);
+            self.stats.kept += events.len() as u64;
+            self.stats.dropped += (length - events.len()) as u64;
+            if events.len() == length {
+                return Some(Ok(packet));
+            }
+            if !events.is_empty() {
+                let mut throttled = Packet::from_events(packet.stream_id, &events);
+                throttled.arrival_time = packet.arrival_time;
+                return Some(Ok(throttled));
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Streaming event clustering, for tracking a few moving objects.
+//!
+//! Each event joins the nearest cluster whose centroid is closer than the tracker radius, and pulls the centroid
+//! towards itself (an online mean-shift). Events far from every cluster start a new one, clusters that receive
+//! no events for a while are closed, and clusters whose centroids meet are merged into the oldest.
+
+use crate::base::ParseError;
+use crate::events::Event;
+
+/// A group of nearby events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    pub id: u64,
+    /// Centroid in pixels.
+    pub x: f32,
+    pub y: f32,
+    pub events: u64,
+    pub first_t: i64,
+    pub last_t: i64,
+    /// Centroid positions (t, x, y), sampled every `ClusterTracker::trajectory_period` microseconds.
+    pub trajectory: Vec<(i64, f32, f32)>,
+}
+
+impl Cluster {
+    fn distance_squared(&self, x: f32, y: f32) -> f32 {
+        (self.x - x).powi(2) + (self.y - y).powi(2)
+    }
+}
+
+/// See the module documentation.
+pub struct ClusterTracker {
+    radius: f32,
+    timeout: i64,
+    mixing: f32,
+    min_events: u64,
+    trajectory_period: i64,
+    next_id: u64,
+    active: Vec<Cluster>,
+    finished: Vec<Cluster>,
+}
+
+impl ClusterTracker {
+    /// `radius` is in pixels, and `timeout` (in microseconds) is the inactivity that closes a cluster.
+    pub fn new(radius: f32, timeout: i64) -> Self {
+        ClusterTracker {
+            radius,
+            timeout,
+            mixing: 0.05,
+            min_events: 20,
+            trajectory_period: 10_000,
+            next_id: 0,
+            active: Vec::new(),
+            finished: Vec::new(),
+        }
+    }
+
+    /// Weight of each event in the centroid update (0.05 by default). Larger values follow faster objects.
+    pub fn mixing(mut self, mixing: f32) -> Self {
+        self.mixing = mixing;
+        self
+    }
+
+    /// Number of events before a cluster is reported (20 by default), which hides isolated noise.
+    pub fn min_events(mut self, min_events: u64) -> Self {
+        self.min_events = min_events;
+        self
+    }
+
+    /// Sampling period of the trajectories, in microseconds (10 ms by default).
+    pub fn trajectory_period(mut self, trajectory_period: i64) -> Self {
+        self.trajectory_period = trajectory_period;
+        self
+    }
+
+    /// Adds an event and returns the id of its cluster, or None if the cluster has too few events to be reported.
+    pub fn update(&mut self, event: &Event) -> Option<u64> {
+        let t = event.t();
+        self.expire(t);
+        let (x, y) = (event.x() as f32, event.y() as f32);
+        let nearest = self
+            .active
+            .iter()
+            .enumerate()
+            .map(|(index, cluster)| (index, cluster.distance_squared(x, y)))
+            .filter(|(_, distance)| *distance <= self.radius * self.radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index);
+        let index = match nearest {
+            Some(index) => {
+                let cluster = &mut self.active[index];
+                cluster.x += (x - cluster.x) * self.mixing;
+                cluster.y += (y - cluster.y) * self.mixing;
+                cluster.events += 1;
+                cluster.last_t = t;
+                if cluster.events >= self.min_events
+                    && cluster
+                        .trajectory
+                        .last()
+                        .is_none_or(|(last_t, _, _)| t - last_t >= self.trajectory_period)
+                {
+                    cluster.trajectory.push((t, cluster.x, cluster.y));
+                }
+                self.merge(index)
+            }
+            None => {
+                self.active.push(Cluster {
+                    id: self.next_id,
+                    x,
+                    y,
+                    events: 1,
+                    first_t: t,
+                    last_t: t,
+                    trajectory: Vec::new(),
+                });
+                self.next_id += 1;
+                self.active.len() - 1
+            }
+        };
+        let cluster = &self.active[index];
+        (cluster.events >= self.min_events).then_some(cluster.id)
+    }
+
+    /// Merges the cluster into an older one if their centroids are closer than half the radius,
+    /// and returns the index of the remaining cluster.
+    fn merge(&mut self, index: usize) -> usize {
+        let (x, y) = (self.active[index].x, self.active[index].y);
+        let other = self.active.iter().enumerate().position(|(other, cluster)| {
+            other != index && cluster.distance_squared(x, y) <= (self.radius / 2.0).powi(2)
+        });
+        let other = match other {
+            Some(other) => other,
+            None => return index,
+        };
+        let (kept, removed) = if self.active[other].id < self.active[index].id {
+            (other, index)
+        } else {
+            (index, other)
+        };
+        let removed_cluster = self.active.swap_remove(removed);
+        let kept = if kept == self.active.len() {
+            removed
+        } else {
+            kept
+        };
+        let cluster = &mut self.active[kept];
+        let total = (cluster.events + removed_cluster.events) as f32;
+        cluster.x = (cluster.x * cluster.events as f32
+            + removed_cluster.x * removed_cluster.events as f32)
+            / total;
+        cluster.y = (cluster.y * cluster.events as f32
+            + removed_cluster.y * removed_cluster.events as f32)
+            / total;
+        cluster.events += removed_cluster.events;
+        cluster.first_t = cluster.first_t.min(removed_cluster.first_t);
+        cluster.last_t = cluster.last_t.max(removed_cluster.last_t);
+        kept
+    }
+
+    fn expire(&mut self, t: i64) {
+        let mut index = 0;
+        while index < self.active.len() {
+            if t.saturating_sub(self.active[index].last_t) > self.timeout {
+                let cluster = self.active.swap_remove(index);
+                if cluster.events >= self.min_events {
+                    self.finished.push(cluster);
+                }
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// Clusters with enough events that are still receiving events.
+    pub fn clusters(&self) -> impl Iterator<Item = &Cluster> {
+        self.active
+            .iter()
+            .filter(move |cluster| cluster.events >= self.min_events)
+    }
+
+    /// Returns the clusters closed since the last call, with their complete trajectories.
+    pub fn take_finished(&mut self) -> Vec<Cluster> {
+        std::mem::take(&mut self.finished)
+    }
+
+    /// Closes all the clusters and returns those with enough events.
+    pub fn finish(mut self) -> Vec<Cluster> {
+        self.expire(i64::MAX);
+        self.finished
+    }
+}
+
+/// Labels each event with its cluster id (see `ClusterTracker::update`).
+pub struct Tracked<I> {
+    events: I,
+    tracker: ClusterTracker,
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Tracked<I> {
+    pub fn new(events: I, tracker: ClusterTracker) -> Self {
+        Tracked { events, tracker }
+    }
+
+    pub fn tracker(&mut self) -> &mut ClusterTracker {
+        &mut self.tracker
+    }
+
+    /// Closes all the clusters and returns those with enough events.
+    pub fn finish(self) -> Vec<Cluster> {
+        self.tracker.finish()
+    }
+}
+
+impl<I: Iterator<Item = Result<Event, ParseError>>> Iterator for Tracked<I> {
+    type Item = Result<(Event, Option<u64>), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.events.next()?.map(|event| {
+            let label = self.tracker.update(&event);
+            (event, label)
+        }))
+    }
+}
@@ -0,0 +1,472 @@
+//! UVC camera frames synchronised with an event stream, for hybrid RGB and event camera rigs.
+//!
+//! `Hybrid` captures frames from a V4L2 device (most USB webcams and machine vision cameras follow the UVC
+//! class) in a background thread, converts them to AEDAT4 frame packets, and interleaves them with the packets of
+//! a decoder. The frames are assigned a new stream, hence `Hybrid::id_to_stream` can be passed to an encoder to
+//! record both cameras in one file.
+//!
+//! Frame timestamps are the kernel capture times (CLOCK_MONOTONIC) converted to Unix times in microseconds, the
+//! time base of live decoders (`Decoder::new_from_device`, or DV streams over TCP). `UvcInput::time_offset`
+//! compensates for a known delay between the cameras, for instance measured with a flash visible to both.
+//!
+//! YUYV frames are converted to BGR, GREY, BGR3 and RGB3 frames are copied. Compressed formats (MJPEG, H.264)
+//! are not supported. The `uvc` feature requires Linux.
+
+use crate::base::{Decoder, Packet, ParseError, Stream, StreamContent};
+use crate::frame_generated;
+use std::collections::{HashMap, VecDeque};
+use std::os::raw::{c_int, c_long};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use v4l::io::traits::CaptureStream;
+use v4l::video::Capture;
+
+/// Frames waiting for the decoder, about two seconds at 30 fps. Older frames are released, which bounds the
+/// memory if the decoder lags or does not use Unix timestamps.
+const MAXIMUM_PENDING: usize = 64;
+
+/// Period at which the capture thread checks that `Hybrid` was not dropped.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+// clock ids (linux/time.h)
+const CLOCK_REALTIME: c_int = 0;
+const CLOCK_MONOTONIC: c_int = 1;
+
+#[repr(C)]
+struct Timespec {
+    seconds: c_long,
+    nanoseconds: c_long,
+}
+
+extern "C" {
+    fn clock_gettime(clock: c_int, timespec: *mut Timespec) -> c_int;
+}
+
+/// Reads a system clock in microseconds.
+fn clock(clock: c_int) -> i64 {
+    let mut timespec = Timespec {
+        seconds: 0,
+        nanoseconds: 0,
+    };
+    unsafe { clock_gettime(clock, &mut timespec) };
+    microseconds(timespec.seconds, timespec.nanoseconds / 1_000)
+}
+
+// c_long has 32 bits on 32-bit platforms
+#[allow(clippy::unnecessary_cast)]
+fn microseconds(seconds: c_long, microseconds: c_long) -> i64 {
+    seconds as i64 * 1_000_000 + microseconds as i64
+}
+
+/// A V4L2 capture device and its settings.
+pub struct UvcInput {
+    path: std::path::PathBuf,
+    resolution: Option<(u32, u32)>,
+    stream_id: Option<u32>,
+    time_offset: i64,
+    buffers: u32,
+}
+
+impl UvcInput {
+    /// `path` is usually "/dev/video0".
+    pub fn new<P: std::convert::AsRef<std::path::Path>>(path: P) -> Self {
+        UvcInput {
+            path: path.as_ref().to_path_buf(),
+            resolution: None,
+            stream_id: None,
+            time_offset: 0,
+            buffers: 4,
+        }
+    }
+
+    /// Requested frame size. The driver picks the closest supported size, the current size is used by default.
+    pub fn resolution(mut self, width: u32, height: u32) -> Self {
+        self.resolution = Some((width, height));
+        self
+    }
+
+    /// Stream id of the frames. Defaults to the lowest id that is not used by the decoder.
+    pub fn stream_id(mut self, stream_id: u32) -> Self {
+        self.stream_id = Some(stream_id);
+        self
+    }
+
+    /// Offset (in microseconds) added to the frame timestamps.
+    pub fn time_offset(mut self, time_offset: i64) -> Self {
+        self.time_offset = time_offset;
+        self
+    }
+
+    /// Number of kernel buffers (4 by default). The driver drops frames when every buffer is full.
+    pub fn buffers(mut self, buffers: u32) -> Self {
+        self.buffers = buffers.max(2);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PixelFormat {
+    Yuyv,
+    Grey,
+    Bgr,
+    Rgb,
+}
+
+/// Supported formats, in order of preference.
+const PIXEL_FORMATS: [(&[u8; 4], PixelFormat); 4] = [
+    (b"YUYV", PixelFormat::Yuyv),
+    (b"GREY", PixelFormat::Grey),
+    (b"BGR3", PixelFormat::Bgr),
+    (b"RGB3", PixelFormat::Rgb),
+];
+
+fn pixel_format(fourcc: v4l::FourCC) -> Option<PixelFormat> {
+    PIXEL_FORMATS
+        .iter()
+        .find(|(repr, _)| v4l::FourCC::new(repr) == fourcc)
+        .map(|(_, format)| *format)
+}
+
+fn device_error(context: &str, error: std::io::Error) -> ParseError {
+    ParseError::Device(format!("{}: {}", context, error))
+}
+
+/// Configured capture, moved to the capture thread.
+struct Camera {
+    device: v4l::Device,
+    format: PixelFormat,
+    width: u32,
+    height: u32,
+    stride: usize,
+    row_size: usize,
+    stream_id: u32,
+    time_offset: i64,
+    buffers: u32,
+}
+
+impl Camera {
+    fn open(input: &UvcInput, stream_id: u32) -> Result<Self, ParseError> {
+        let device = v4l::Device::with_path(&input.path)
+            .map_err(|error| device_error(&format!("opening {}", input.path.display()), error))?;
+        let mut format = device
+            .format()
+            .map_err(|error| device_error("reading the format", error))?;
+        if let Some((width, height)) = input.resolution {
+            format.width = width;
+            format.height = height;
+        }
+        if pixel_format(format.fourcc).is_none() {
+            let descriptions = device
+                .enum_formats()
+                .map_err(|error| device_error("listing the formats", error))?;
+            format.fourcc = PIXEL_FORMATS
+                .iter()
+                .map(|(repr, _)| v4l::FourCC::new(repr))
+                .find(|fourcc| {
+                    descriptions
+                        .iter()
+                        .any(|description| description.fourcc == *fourcc)
+                })
+                .ok_or_else(|| {
+                    ParseError::Device(format!(
+                        "{} has no uncompressed format (YUYV, GREY, BGR3 or RGB3)",
+                        input.path.display()
+                    ))
+                })?;
+        }
+        let format = device
+            .set_format(&v4l::Format::new(
+                format.width,
+                format.height,
+                format.fourcc,
+            ))
+            .map_err(|error| device_error("setting the format", error))?;
+        let pixel_format = pixel_format(format.fourcc).ok_or_else(|| {
+            ParseError::Device(format!("the driver selected the format {}", format.fourcc))
+        })?;
+        if format.width > i16::MAX as u32 || format.height > i16::MAX as u32 {
+            return Err(ParseError::Device(format!(
+                "the frame size {}x{} is too large",
+                format.width, format.height
+            )));
+        }
+        let bytes_per_pixel = match pixel_format {
+            PixelFormat::Yuyv => 2,
+            PixelFormat::Grey => 1,
+            PixelFormat::Bgr | PixelFormat::Rgb => 3,
+        };
+        Ok(Camera {
+            device,
+            format: pixel_format,
+            width: format.width,
+            height: format.height,
+            stride: (format.stride as usize).max(format.width as usize * bytes_per_pixel),
+            row_size: format.width as usize * bytes_per_pixel,
+            stream_id,
+            time_offset: input.time_offset,
+            buffers: input.buffers,
+        })
+    }
+
+    /// Captures frames until `running` is cleared or the receiver is dropped.
+    fn run(
+        self,
+        running: Arc<AtomicBool>,
+        started: mpsc::Sender<Result<(), ParseError>>,
+        sender: mpsc::SyncSender<Result<(i64, Packet), ParseError>>,
+    ) {
+        let mut stream = match v4l::prelude::MmapStream::with_buffers(
+            &self.device,
+            v4l::buffer::Type::VideoCapture,
+            self.buffers,
+        ) {
+            Ok(stream) => stream,
+            Err(error) => {
+                let _ = started.send(Err(device_error("allocating the buffers", error)));
+                return;
+            }
+        };
+        stream.set_timeout(POLL_INTERVAL);
+        let _ = started.send(Ok(()));
+        while running.load(Ordering::Acquire) {
+            let packet = match stream.next() {
+                Ok((bytes, metadata)) => {
+                    let captured_t = microseconds(metadata.timestamp.sec, metadata.timestamp.usec);
+                    // the age of the frame on the monotonic clock, subtracted from the Unix time
+                    let t = clock(CLOCK_REALTIME) - (clock(CLOCK_MONOTONIC) - captured_t)
+                        + self.time_offset;
+                    let bytes = &bytes[..(metadata.bytesused as usize).min(bytes.len())];
+                    match self.frame(bytes, t) {
+                        Some(packet) => Ok((t, packet)),
+                        // incomplete frame, usually a USB transfer error
+                        None => continue,
+                    }
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::TimedOut => continue,
+                Err(error) => Err(device_error("capturing a frame", error)),
+            };
+            let failed = packet.is_err();
+            if sender.send(packet).is_err() || failed {
+                return;
+            }
+        }
+    }
+
+    /// Converts a V4L2 buffer to an AEDAT4 frame.
+    fn frame(&self, bytes: &[u8], t: i64) -> Option<Packet> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        if height == 0 || bytes.len() < self.stride * (height - 1) + self.row_size {
+            return None;
+        }
+        let rows = (0..height).map(|y| &bytes[y * self.stride..]);
+        let (format, pixels): (frame_generated::FrameFormat, Vec<u8>) = match self.format {
+            PixelFormat::Yuyv => (
+                frame_generated::FrameFormat::Bgr,
+                rows.flat_map(|row| row[..self.row_size].chunks_exact(4).flat_map(yuyv_to_bgr))
+                    .collect(),
+            ),
+            PixelFormat::Grey => (
+                frame_generated::FrameFormat::Gray,
+                rows.flat_map(|row| row[..self.row_size].iter().copied())
+                    .collect(),
+            ),
+            PixelFormat::Bgr => (
+                frame_generated::FrameFormat::Bgr,
+                rows.flat_map(|row| row[..self.row_size].iter().copied())
+                    .collect(),
+            ),
+            PixelFormat::Rgb => (
+                frame_generated::FrameFormat::Bgr,
+                rows.flat_map(|row| {
+                    row[..self.row_size]
+                        .chunks_exact(3)
+                        .flat_map(|pixel| [pixel[2], pixel[1], pixel[0]])
+                })
+                .collect(),
+            ),
+        };
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        let pixels = builder.create_vector(&pixels);
+        let root = frame_generated::Frame::create(
+            &mut builder,
+            &frame_generated::FrameArgs {
+                t,
+                begin_t: t,
+                end_t: t,
+                exposure_begin_t: t,
+                exposure_end_t: t,
+                format,
+                width: width as i16,
+                height: height as i16,
+                offset_x: 0,
+                offset_y: 0,
+                pixels: Some(pixels),
+            },
+        );
+        frame_generated::finish_size_prefixed_frame_buffer(&mut builder, root);
+        Some(Packet {
+            buffer: builder.finished_data().to_vec(),
+            stream_id: self.stream_id,
+            arrival_time: None,
+        })
+    }
+}
+
+/// Converts two YUYV pixels (BT.601, limited range) to BGR.
+fn yuyv_to_bgr(chunk: &[u8]) -> [u8; 6] {
+    let u = chunk[1] as i32 - 128;
+    let v = chunk[3] as i32 - 128;
+    let mut bgr = [0u8; 6];
+    for (index, y) in [chunk[0], chunk[2]].into_iter().enumerate() {
+        let c = 298 * (y as i32 - 16) + 128;
+        bgr[index * 3] = ((c + 516 * u) >> 8).clamp(0, 255) as u8;
+        bgr[index * 3 + 1] = ((c - 100 * u - 208 * v) >> 8).clamp(0, 255) as u8;
+        bgr[index * 3 + 2] = ((c + 409 * v) >> 8).clamp(0, 255) as u8;
+    }
+    bgr
+}
+
+/// Packets of a decoder interleaved with the frames of a UVC camera.
+///
+/// Frames are yielded before the first decoder packet that starts after them. Frames that arrive after such a
+/// packet (the USB latencies of the cameras differ) are yielded as soon as possible, hence the order is only
+/// approximate at the scale of a packet. Frames captured after the decoder ended are discarded.
+pub struct Hybrid {
+    decoder: Decoder,
+    id_to_stream: HashMap<u32, Stream>,
+    stream_id: u32,
+    frames: mpsc::Receiver<Result<(i64, Packet), ParseError>>,
+    pending: VecDeque<(i64, Packet)>,
+    ready: VecDeque<Result<Packet, ParseError>>,
+    running: Arc<AtomicBool>,
+    done: bool,
+}
+
+impl Hybrid {
+    /// Opens the camera and starts the capture.
+    pub fn new(decoder: Decoder, input: UvcInput) -> Result<Self, ParseError> {
+        let stream_id = match input.stream_id {
+            Some(stream_id) => {
+                if decoder.id_to_stream.contains_key(&stream_id) {
+                    return Err(ParseError::DuplicatedStreamId(stream_id));
+                }
+                stream_id
+            }
+            None => (0..)
+                .find(|stream_id| !decoder.id_to_stream.contains_key(stream_id))
+                .expect("a stream id is free"),
+        };
+        let capture = Camera::open(&input, stream_id)?;
+        let mut id_to_stream = decoder.id_to_stream.clone();
+        id_to_stream.insert(
+            stream_id,
+            Stream::new(
+                StreamContent::Frame,
+                capture.width as u16,
+                capture.height as u16,
+            ),
+        );
+        let running = Arc::new(AtomicBool::new(true));
+        let (started_sender, started) = mpsc::channel();
+        let (sender, frames) = mpsc::sync_channel(input.buffers as usize);
+        {
+            let running = running.clone();
+            std::thread::spawn(move || capture.run(running, started_sender, sender));
+        }
+        started.recv().map_err(|_| {
+            ParseError::Device("the capture thread stopped unexpectedly".to_owned())
+        })??;
+        Ok(Hybrid {
+            decoder,
+            id_to_stream,
+            stream_id,
+            frames,
+            pending: VecDeque::new(),
+            ready: VecDeque::new(),
+            running,
+            done: false,
+        })
+    }
+
+    /// Streams of the decoder and the frame stream.
+    pub fn id_to_stream(&self) -> &HashMap<u32, Stream> {
+        &self.id_to_stream
+    }
+
+    /// Stream id of the camera frames.
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    pub fn decoder(&self) -> &Decoder {
+        &self.decoder
+    }
+
+    /// Moves the frames received so far to the pending queue, sorted by timestamp.
+    fn receive(&mut self) {
+        while let Ok(frame) = self.frames.try_recv() {
+            match frame {
+                Ok((t, packet)) => {
+                    let index = self
+                        .pending
+                        .partition_point(|(pending_t, _)| *pending_t <= t);
+                    self.pending.insert(index, (t, packet));
+                }
+                Err(error) => self.ready.push_back(Err(error)),
+            }
+        }
+        while self.pending.len() > MAXIMUM_PENDING {
+            let (_, packet) = self.pending.pop_front().expect("pending is not empty");
+            self.ready.push_back(Ok(packet));
+        }
+    }
+
+    fn stop(&mut self) {
+        self.running.store(false, Ordering::Release);
+    }
+}
+
+impl Iterator for Hybrid {
+    type Item = Result<Packet, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.ready.pop_front() {
+                return Some(item);
+            }
+            if self.done {
+                return self.pending.pop_front().map(|(_, packet)| Ok(packet));
+            }
+            match self.decoder.next() {
+                Some(Ok(packet)) => {
+                    self.receive();
+                    let begin_t = self
+                        .id_to_stream
+                        .get(&packet.stream_id)
+                        .and_then(|stream| packet.summary(&stream.content).ok())
+                        .and_then(|(elements, begin_t, _)| (elements > 0).then_some(begin_t));
+                    if let Some(begin_t) = begin_t {
+                        while self.pending.front().is_some_and(|(t, _)| *t < begin_t) {
+                            let (_, frame) =
+                                self.pending.pop_front().expect("pending is not empty");
+                            self.ready.push_back(Ok(frame));
+                        }
+                    }
+                    self.ready.push_back(Ok(packet));
+                }
+                Some(Err(error)) => return Some(Err(error)),
+                None => {
+                    self.stop();
+                    self.receive();
+                    self.done = true;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Hybrid {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
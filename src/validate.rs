@@ -0,0 +1,408 @@
+use crate::base::{describe, DecoderBuilder, FileDataEntry, Packet, ParseError, StreamContent};
+use crate::{imus_generated, triggers_generated};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// The check that produced an issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    /// Magic number, header and XML description.
+    Header,
+    /// Packets must belong to a stream declared in the description, with a matching content type.
+    StreamId,
+    /// Packets must decompress to valid FlatBuffers.
+    FlatBuffer,
+    /// Timestamps must not decrease within a stream.
+    Timestamps,
+    /// The file data table must list every packet with its size, number of elements and time range.
+    FileDataTable,
+    /// The file must not end in the middle of a packet.
+    Truncated,
+}
+
+impl std::fmt::Display for Check {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            formatter,
+            "{}",
+            match self {
+                Check::Header => "header",
+                Check::StreamId => "stream_id",
+                Check::FlatBuffer => "flatbuffer",
+                Check::Timestamps => "timestamps",
+                Check::FileDataTable => "file_data_table",
+                Check::Truncated => "truncated",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub check: Check,
+    /// Index of the packet in file order, if the issue concerns a packet.
+    pub packet: Option<usize>,
+    pub stream_id: Option<u32>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            formatter,
+            "{} [{}]",
+            match self.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            },
+            self.check
+        )?;
+        if let Some(packet) = self.packet {
+            write!(formatter, " packet {}", packet)?;
+        }
+        if let Some(stream_id) = self.stream_id {
+            write!(formatter, " stream {}", stream_id)?;
+        }
+        write!(formatter, ": {}", self.message)
+    }
+}
+
+/// Outcome of `validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Number of packets read, including malformed packets.
+    pub packets: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    /// Whether the file has no errors (warnings are allowed).
+    pub fn is_valid(&self) -> bool {
+        self.issues
+            .iter()
+            .all(|issue| issue.severity == Severity::Warning)
+    }
+
+    /// Serializes the report as a JSON object.
+    pub fn to_json(&self) -> String {
+        let mut json = format!(
+            "{{\"valid\":{},\"packets\":{},\"issues\":[",
+            self.is_valid(),
+            self.packets
+        );
+        for (index, issue) in self.issues.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            json.push_str(&format!(
+                "{{\"severity\":\"{}\",\"check\":\"{}\",\"packet\":{},\"stream_id\":{},\"message\":\"{}\"}}",
+                match issue.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                },
+                issue.check,
+                issue
+                    .packet
+                    .map_or_else(|| "null".to_owned(), |packet| packet.to_string()),
+                issue
+                    .stream_id
+                    .map_or_else(|| "null".to_owned(), |stream_id| stream_id.to_string()),
+                escape(&issue.message)
+            ));
+        }
+        json.push_str("]}");
+        json
+    }
+
+    fn error(
+        &mut self,
+        check: Check,
+        packet: Option<usize>,
+        stream_id: Option<u32>,
+        message: String,
+    ) {
+        self.issues.push(Issue {
+            severity: Severity::Error,
+            check,
+            packet,
+            stream_id,
+            message,
+        });
+    }
+
+    fn warning(
+        &mut self,
+        check: Check,
+        packet: Option<usize>,
+        stream_id: Option<u32>,
+        message: String,
+    ) {
+        self.issues.push(Issue {
+            severity: Severity::Warning,
+            check,
+            packet,
+            stream_id,
+            message,
+        });
+    }
+}
+
+/// Checks the structure of an AEDAT4 file: magic number, XML description, stream ids,
+/// FlatBuffer validity of every packet, timestamp monotonicity, and agreement with the file data table.
+///
+/// Problems with the file are listed in the report. Errors are only returned if the file cannot be opened.
+/// The file data table is not checked if the file is truncated, since it is written at the end.
+pub fn validate<P: std::convert::AsRef<std::path::Path>>(
+    path: P,
+) -> Result<ValidationReport, ParseError> {
+    std::fs::File::open(path.as_ref())?;
+    let mut report = ValidationReport {
+        packets: 0,
+        issues: Vec::new(),
+    };
+    let mut decoder = match DecoderBuilder::file(path.as_ref())
+        .verification(true)
+        .build()
+    {
+        Ok(decoder) => decoder,
+        Err(error) => {
+            report.error(Check::Header, None, None, describe(&error));
+            return Ok(report);
+        }
+    };
+    let mut last_timestamps = std::collections::HashMap::new();
+    let mut packets = std::collections::HashMap::new();
+    let mut truncated = false;
+    while let Some(packet) = decoder.next() {
+        let index = report.packets;
+        report.packets += 1;
+        let packet = match packet {
+            Ok(packet) => packet,
+            Err(error) => {
+                packets.insert(decoder.position(), None);
+                match error {
+                    ParseError::UnknownStreamId(stream_id) => report.error(
+                        Check::StreamId,
+                        Some(index),
+                        Some(stream_id),
+                        "the stream is not declared in the description".to_owned(),
+                    ),
+                    ParseError::IdentifierMismatch { stream_id, .. } => report.error(
+                        Check::StreamId,
+                        Some(index),
+                        Some(stream_id),
+                        describe(&error),
+                    ),
+                    ParseError::TruncatedPacket { .. } => {
+                        truncated = true;
+                        report.error(Check::Truncated, Some(index), None, describe(&error));
+                        break;
+                    }
+                    error => report.error(Check::FlatBuffer, Some(index), None, describe(&error)),
+                }
+                continue;
+            }
+        };
+        let timestamps = match timestamps(&packet, &decoder.id_to_stream[&packet.stream_id].content)
+        {
+            Ok(timestamps) => timestamps,
+            Err(error) => {
+                packets.insert(decoder.position(), None);
+                report.error(
+                    Check::FlatBuffer,
+                    Some(index),
+                    Some(packet.stream_id),
+                    describe(&error),
+                );
+                continue;
+            }
+        };
+        let mut last = last_timestamps.get(&packet.stream_id).copied();
+        for t in timestamps.iter() {
+            if let Some(last) = last {
+                if *t < last {
+                    report.error(
+                        Check::Timestamps,
+                        Some(index),
+                        Some(packet.stream_id),
+                        format!("timestamp {} follows timestamp {}", t, last),
+                    );
+                    break;
+                }
+            }
+            last = Some(*t);
+        }
+        if let Some(last) = last {
+            last_timestamps.insert(packet.stream_id, last);
+        }
+        packets.insert(
+            decoder.position(),
+            Some((
+                index,
+                packet.stream_id,
+                timestamps.len() as i64,
+                timestamps.first().copied().unwrap_or(0),
+                timestamps.last().copied().unwrap_or(0),
+            )),
+        );
+    }
+    let mut stream_ids = decoder
+        .id_to_stream
+        .iter()
+        // the elements of unknown packets cannot be counted
+        .filter(|(stream_id, stream)| {
+            !last_timestamps.contains_key(stream_id)
+                && !matches!(stream.content, StreamContent::Other(_))
+        })
+        .map(|(stream_id, _)| *stream_id)
+        .collect::<Vec<u32>>();
+    stream_ids.sort();
+    for stream_id in stream_ids {
+        report.warning(
+            Check::StreamId,
+            None,
+            Some(stream_id),
+            "the stream has no elements".to_owned(),
+        );
+    }
+    if truncated {
+        return Ok(report);
+    }
+    match decoder.file_data_table() {
+        Ok(entries) => check_file_data_table(&mut report, &entries, &packets),
+        Err(ParseError::NoFileDataTable) => report.warning(
+            Check::FileDataTable,
+            None,
+            None,
+            "the file has no file data table".to_owned(),
+        ),
+        Err(error) => report.error(Check::FileDataTable, None, None, describe(&error)),
+    }
+    Ok(report)
+}
+
+/// Compares the table with the packets read, indexed by the position of their end.
+#[allow(clippy::type_complexity)]
+fn check_file_data_table(
+    report: &mut ValidationReport,
+    entries: &[FileDataEntry],
+    packets: &std::collections::HashMap<i64, Option<(usize, u32, i64, i64, i64)>>,
+) {
+    let mut listed = std::collections::HashSet::new();
+    for entry in entries {
+        let end = entry.byte_offset + entry.size as i64;
+        listed.insert(end);
+        let (index, stream_id, num_elements, timestamp_start, timestamp_end) =
+            match packets.get(&end) {
+                Some(Some(packet)) => *packet,
+                Some(None) => continue,
+                None => {
+                    report.error(
+                        Check::FileDataTable,
+                        None,
+                        Some(entry.stream_id),
+                        format!(
+                            "the table lists a packet at byte {} that does not exist",
+                            entry.byte_offset
+                        ),
+                    );
+                    continue;
+                }
+            };
+        if entry.stream_id != stream_id {
+            report.error(
+                Check::FileDataTable,
+                Some(index),
+                Some(stream_id),
+                format!("the table lists the packet in stream {}", entry.stream_id),
+            );
+        } else if entry.num_elements != num_elements {
+            report.error(
+                Check::FileDataTable,
+                Some(index),
+                Some(stream_id),
+                format!(
+                    "the table lists {} elements but the packet has {}",
+                    entry.num_elements, num_elements
+                ),
+            );
+        } else if num_elements > 0
+            && (entry.timestamp_start != timestamp_start || entry.timestamp_end != timestamp_end)
+        {
+            report.error(
+                Check::FileDataTable,
+                Some(index),
+                Some(stream_id),
+                format!(
+                    "the table lists the time range [{}, {}] but the packet spans [{}, {}]",
+                    entry.timestamp_start, entry.timestamp_end, timestamp_start, timestamp_end
+                ),
+            );
+        }
+    }
+    let mut missing = packets
+        .iter()
+        .filter(|(end, _)| !listed.contains(end))
+        .filter_map(|(_, packet)| packet.map(|(index, stream_id, ..)| (index, stream_id)))
+        .collect::<Vec<(usize, u32)>>();
+    missing.sort();
+    for (index, stream_id) in missing {
+        report.error(
+            Check::FileDataTable,
+            Some(index),
+            Some(stream_id),
+            "the packet is not listed in the table".to_owned(),
+        );
+    }
+}
+
+/// Returns the timestamps of the elements of a packet, in storage order.
+fn timestamps(packet: &Packet, content: &StreamContent) -> Result<Vec<i64>, ParseError> {
+    Ok(match content {
+        StreamContent::Events => packet.events()?.iter().map(|event| event.t()).collect(),
+        StreamContent::Frame => vec![packet.frame()?.t()],
+        StreamContent::Imus => imus_generated::size_prefixed_root_as_imu_packet(&packet.buffer)?
+            .elements()
+            .map_or_else(Vec::new, |elements| {
+                elements.iter().map(|imu| imu.t()).collect()
+            }),
+        StreamContent::Triggers => {
+            triggers_generated::size_prefixed_root_as_trigger_packet(&packet.buffer)?
+                .elements()
+                .map_or_else(Vec::new, |elements| {
+                    elements.iter().map(|trigger| trigger.t()).collect()
+                })
+        }
+        StreamContent::BoundingBoxes => packet
+            .bounding_boxes()?
+            .iter()
+            .map(|bounding_box| bounding_box.t)
+            .collect(),
+        StreamContent::Pose => vec![packet.pose()?.t],
+        // the layout of unknown packets is unknown
+        StreamContent::Other(_) => Vec::new(),
+    })
+}
+
+fn escape(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len());
+    for character in string.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
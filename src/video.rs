@@ -0,0 +1,86 @@
+use crate::accumulator::Accumulator;
+use crate::base::{Decoder, ParseError, StreamContent};
+use crate::events::Events;
+use std::io::Write;
+
+pub struct VideoOptions {
+    pub frame_rate: f64,
+    /// ffmpeg video codec, for instance "libx264".
+    pub codec: String,
+    /// ffmpeg executable.
+    pub ffmpeg: std::path::PathBuf,
+}
+
+impl Default for VideoOptions {
+    fn default() -> Self {
+        VideoOptions {
+            frame_rate: 30.0,
+            codec: "libx264".to_string(),
+            ffmpeg: std::path::PathBuf::from("ffmpeg"),
+        }
+    }
+}
+
+/// Accumulates the events of the decoder at a fixed frame rate and encodes the frames with ffmpeg.
+///
+/// The ffmpeg executable must be installed. The output container is inferred from the file extension.
+pub fn render_video<P: AsRef<std::path::Path>>(
+    decoder: Decoder,
+    output: P,
+    options: &VideoOptions,
+) -> Result<(), ParseError> {
+    let (width, height) = match decoder
+        .id_to_stream
+        .values()
+        .find(|stream| stream.content == StreamContent::Events)
+    {
+        Some(content) => (content.width, content.height),
+        None => {
+            return Err(ParseError::MissingDescriptionNode {
+                node: "event stream",
+            })
+        }
+    };
+    let mut ffmpeg = std::process::Command::new(&options.ffmpeg)
+        .args([
+            "-y",
+            "-loglevel",
+            "error",
+            "-f",
+            "rawvideo",
+            "-pix_fmt",
+            "gray",
+            "-s",
+        ])
+        .arg(format!("{}x{}", width, height))
+        .arg("-r")
+        .arg(options.frame_rate.to_string())
+        .args([
+            "-i",
+            "-",
+            "-vf",
+            "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+            "-pix_fmt",
+            "yuv420p",
+            "-c:v",
+        ])
+        .arg(&options.codec)
+        .arg(output.as_ref())
+        .stdin(std::process::Stdio::piped())
+        .spawn()?;
+    let result = {
+        let mut stdin = ffmpeg.stdin.take().unwrap();
+        Accumulator::new(width, height)
+            .frames(
+                Events::new(decoder),
+                (1e6 / options.frame_rate).round() as i64,
+            )
+            .try_for_each(|frame| Ok::<(), ParseError>(stdin.write_all(&frame?)?))
+    };
+    let status = ffmpeg.wait()?;
+    result?;
+    if !status.success() {
+        return Err(ParseError::General(format!("ffmpeg failed ({})", status)));
+    }
+    Ok(())
+}
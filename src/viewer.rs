@@ -0,0 +1,230 @@
+//! Native window that plays a recording or a live stream.
+//!
+//! The left panel shows the events accumulated over the last period (ON white, OFF black), and the right panel
+//! the latest APS frame. Space pauses, the left and right arrows seek by one second (files with a data table),
+//! and Escape closes the window.
+
+use crate::accumulator::Accumulator;
+use crate::base::{Decoder, FileDataEntry, Packet, ParseError, StreamContent};
+use crate::frames::{convert, PixelFormat};
+
+/// See the module documentation.
+pub struct Viewer {
+    decoder: Decoder,
+    period: i64,
+    speed: f64,
+    scale: usize,
+    title: String,
+}
+
+enum Source {
+    /// Seekable file, read as fast as the playback clock requires.
+    File(Box<Decoder>, Vec<FileDataEntry>),
+    /// Live stream (or file without a data table) read on a background thread.
+    Stream(std::sync::mpsc::Receiver<Result<Packet, ParseError>>),
+}
+
+impl Source {
+    /// Returns the next packet without blocking on live streams.
+    fn next(&mut self) -> Option<Result<Packet, ParseError>> {
+        match self {
+            Source::File(decoder, _) => decoder.next(),
+            Source::Stream(receiver) => receiver.try_recv().ok(),
+        }
+    }
+}
+
+impl Viewer {
+    pub fn new(decoder: Decoder) -> Self {
+        Viewer {
+            decoder,
+            period: 33_000,
+            speed: 1.0,
+            scale: 2,
+            title: "AEDAT".to_owned(),
+        }
+    }
+
+    /// Accumulation period of the events panel, in microseconds (33 ms by default).
+    pub fn period(mut self, period: i64) -> Self {
+        self.period = period.max(1);
+        self
+    }
+
+    /// Playback speed of files (1 by default, real time).
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Size of a sensor pixel on screen (1, 2, 4 or 8, 2 by default).
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_owned();
+        self
+    }
+
+    /// Opens the window and blocks until it is closed. Playback pauses at the end of files.
+    pub fn run(mut self) -> Result<(), ParseError> {
+        let find = |content: StreamContent| {
+            let mut streams: Vec<_> = self
+                .decoder
+                .id_to_stream
+                .iter()
+                .filter(|(_, stream)| stream.content == content)
+                .map(|(id, stream)| (*id, stream.width as usize, stream.height as usize))
+                .collect();
+            streams.sort();
+            streams.first().copied()
+        };
+        let events_stream = find(StreamContent::Events);
+        let frame_stream = find(StreamContent::Frame);
+        let (events_width, events_height) =
+            events_stream.map_or((0, 0), |(_, width, height)| (width, height));
+        let (frame_width, frame_height) =
+            frame_stream.map_or((0, 0), |(_, width, height)| (width, height));
+        let (width, height) = (events_width + frame_width, events_height.max(frame_height));
+        if width == 0 || height == 0 {
+            return Err(ParseError::Usage(
+                "the decoder has no event or frame stream with a known size".to_owned(),
+            ));
+        }
+        let mut window = minifb::Window::new(
+            &self.title,
+            width,
+            height,
+            minifb::WindowOptions {
+                scale: match self.scale {
+                    1 => minifb::Scale::X1,
+                    4 => minifb::Scale::X4,
+                    8 => minifb::Scale::X8,
+                    _ => minifb::Scale::X2,
+                },
+                ..minifb::WindowOptions::default()
+            },
+        )
+        .map_err(|error| ParseError::General(error.to_string()))?;
+        window.set_target_fps(60);
+        let mut source = match self.decoder.file_data_table() {
+            Ok(entries) if !entries.is_empty() => Source::File(Box::new(self.decoder), entries),
+            _ => Source::Stream(self.decoder.spawn(64)),
+        };
+        let mut accumulator = Accumulator::new(events_width as u16, events_height as u16);
+        let mut buffer = vec![0u32; width * height];
+        let mut pending: Option<(i64, Packet)> = None;
+        // recording time shown on screen, set by the first packet
+        let mut clock: Option<i64> = None;
+        let mut period_end = i64::MIN;
+        let mut paused = false;
+        let mut finished = false;
+        let mut last_update = std::time::Instant::now();
+        while window.is_open() && !window.is_key_down(minifb::Key::Escape) {
+            let elapsed = last_update.elapsed();
+            last_update = std::time::Instant::now();
+            if window.is_key_pressed(minifb::Key::Space, minifb::KeyRepeat::No) {
+                paused = !paused;
+            }
+            let seek = if window.is_key_pressed(minifb::Key::Right, minifb::KeyRepeat::Yes) {
+                1_000_000
+            } else if window.is_key_pressed(minifb::Key::Left, minifb::KeyRepeat::Yes) {
+                -1_000_000
+            } else {
+                0
+            };
+            if let (Source::File(decoder, entries), Some(t), true) = (&mut source, clock, seek != 0)
+            {
+                let target = t + seek;
+                if let Some(entry) = entries
+                    .iter()
+                    .find(|entry| entry.timestamp_end >= target)
+                    .or(entries.last())
+                {
+                    decoder.seek_to_packet(entry.byte_offset)?;
+                    clock = Some(target.max(entries[0].timestamp_start));
+                    period_end = i64::MIN;
+                    pending = None;
+                    finished = false;
+                    accumulator.reset();
+                }
+            }
+            if !paused {
+                if let Some(t) = clock.as_mut() {
+                    *t += (elapsed.as_secs_f64() * 1e6 * self.speed) as i64;
+                }
+            }
+            loop {
+                if pending.is_none() && !finished {
+                    match source.next() {
+                        Some(Ok(packet)) => {
+                            let begin_t = if Some(packet.stream_id)
+                                == events_stream.map(|(id, _, _)| id)
+                            {
+                                match packet.time_range()? {
+                                    Some((begin_t, _)) => begin_t,
+                                    None => continue,
+                                }
+                            } else if Some(packet.stream_id) == frame_stream.map(|(id, _, _)| id) {
+                                packet.frame()?.t()
+                            } else {
+                                continue;
+                            };
+                            pending = Some((begin_t, packet));
+                        }
+                        Some(Err(error)) => return Err(error),
+                        None => finished = matches!(source, Source::File(..)),
+                    }
+                }
+                let (begin_t, packet) = match pending.take() {
+                    Some(pending) => pending,
+                    None => break,
+                };
+                let clock = *clock.get_or_insert(begin_t);
+                // live streams are shown as soon as they arrive
+                if begin_t > clock && matches!(source, Source::File(..)) {
+                    pending = Some((begin_t, packet));
+                    break;
+                }
+                if packet.has_identifier(crate::events_generated::EVENT_PACKET_IDENTIFIER) {
+                    for event in packet.events()? {
+                        if event.t() >= period_end {
+                            accumulator.reset();
+                            period_end = event.t() + self.period;
+                        }
+                        accumulator.add(&event);
+                    }
+                } else {
+                    let image = convert(&packet.frame()?, PixelFormat::Gray8, None)?;
+                    for y in 0..image.height.min(height) {
+                        for x in 0..image.width.min(frame_width) {
+                            let value = image.pixels[y * image.width + x] as u32;
+                            buffer[y * width + events_width + x] =
+                                (value << 16) | (value << 8) | value;
+                        }
+                    }
+                }
+            }
+            if finished && pending.is_none() {
+                paused = true;
+            }
+            for (index, value) in accumulator.frame().iter().enumerate() {
+                let value = *value as u32;
+                buffer[(index / events_width) * width + index % events_width] =
+                    (value << 16) | (value << 8) | value;
+            }
+            window.set_title(&format!(
+                "{} - {:.3} s{}",
+                self.title,
+                clock.unwrap_or(0) as f64 / 1e6,
+                if paused { " (paused)" } else { "" }
+            ));
+            window
+                .update_with_buffer(&buffer, width, height)
+                .map_err(|error| ParseError::General(error.to_string()))?;
+        }
+        Ok(())
+    }
+}